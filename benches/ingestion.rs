@@ -0,0 +1,74 @@
+//! The benchmarks of registering events from several threads, run with `cargo bench --features threaded`.
+//! See the README next to this file for what each benchmark measures.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use simple_event_bus::{EventBus, NullLogger};
+
+const PRODUCERS: usize = 8;
+const EVENTS_PER_PRODUCER: usize = 10_000;
+const TOPICS: usize = 10;
+
+fn topic_names() -> Vec<String> {
+    (0..TOPICS).map(|topic| format!("sensors/{}/temperature", topic)).collect()
+}
+
+fn event_bus() -> EventBus {
+    let event_bus = EventBus::new();
+    event_bus.set_logger(NullLogger);
+    event_bus
+}
+
+/// Runs the producers to completion, each registering its events spread over the event names.
+fn produce(register: impl Fn(&str, u64) + Send + Sync + Clone + 'static) {
+    let names = Arc::new(topic_names());
+    let producers: Vec<_> = (0..PRODUCERS)
+        .map(|producer| {
+            let names = names.clone();
+            let register = register.clone();
+            thread::spawn(move || {
+                for event in 0..EVENTS_PER_PRODUCER {
+                    register(&names[event % TOPICS], (producer * EVENTS_PER_PRODUCER + event) as u64);
+                }
+            })
+        })
+        .collect();
+    producers.into_iter().for_each(|producer| producer.join().unwrap());
+}
+
+/// Registers 80k events from 8 producer threads, and drains them into an event bus.
+/// `mutex_map` pushes them into a map of event names behind a mutex, `thread_sink` onto the lock-free queue of a `ThreadSink`.
+fn ingestion(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ingestion");
+    group.throughput(Throughput::Elements((PRODUCERS * EVENTS_PER_PRODUCER) as u64));
+    group.bench_function(BenchmarkId::from_parameter("mutex_map"), |b| {
+        b.iter(|| {
+            let queues: Arc<Mutex<HashMap<String, Vec<u64>>>> = Arc::new(Mutex::new(HashMap::new()));
+            let producer_queues = queues.clone();
+            produce(move |name, event| producer_queues.lock().unwrap().entry(name.to_string()).or_default().push(event));
+            let event_bus = event_bus();
+            let queues = std::mem::take(&mut *queues.lock().unwrap());
+            event_bus.register_all(queues.into_iter()
+                .flat_map(|(name, events)| events.into_iter().map(move |event| (name.clone(), event))));
+            event_bus.publish().unwrap();
+            event_bus
+        });
+    });
+    group.bench_function(BenchmarkId::from_parameter("thread_sink"), |b| {
+        b.iter(|| {
+            let event_bus = event_bus();
+            let sink = event_bus.thread_sink();
+            produce(move |name, event| {
+                sink.register(name, event);
+            });
+            event_bus.publish().unwrap();
+            event_bus
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, ingestion);
+criterion_main!(benches);
@@ -0,0 +1,155 @@
+//! Measures the time spent per delivered message by `publish`, and the registering and reading of events around it.
+//! Run with `cargo bench`, see the README next to this file for what each benchmark measures.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use simple_event_bus::{Event, EventBus, NullLogger, Subscriber};
+
+const EVENTS: usize = 100_000;
+
+/// Counts the allocations, so the payload benchmark can report them next to its timings.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+struct SummingSubscriber {
+    sum: u64,
+}
+
+impl Subscriber for SummingSubscriber {
+    fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+        self.sum += *event.try_get_data::<u64>()?;
+        black_box(self.sum);
+        Ok(())
+    }
+}
+
+fn topic_names(topics: usize) -> Vec<String> {
+    (0..topics).map(|topic| format!("sensors/{}/temperature", topic)).collect()
+}
+
+fn event_bus() -> EventBus {
+    let event_bus = EventBus::new();
+    event_bus.set_logger(NullLogger);
+    event_bus
+}
+
+/// Registers 100k events spread over 1, 10 and 100 event names.
+fn register(c: &mut Criterion) {
+    let mut group = c.benchmark_group("register");
+    group.throughput(Throughput::Elements(EVENTS as u64));
+    for topics in [1, 10, 100] {
+        let names = topic_names(topics);
+        group.bench_with_input(BenchmarkId::from_parameter(topics), &names, |b, names| {
+            b.iter_batched(
+                event_bus,
+                |event_bus| {
+                    for event in 0..EVENTS {
+                        event_bus.register(names[event % names.len()].as_str(), event as u64);
+                    }
+                    event_bus
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+/// Publishes 10k events spread over the event names, each with the same number of subscribers.
+fn publish(c: &mut Criterion) {
+    const PUBLISHED: usize = 10_000;
+    let mut group = c.benchmark_group("publish");
+    for (topics, subscribers) in [(1, 1), (1, 10), (1, 100), (10, 10), (100, 1)] {
+        let names = topic_names(topics);
+        group.throughput(Throughput::Elements((PUBLISHED * subscribers) as u64));
+        group.bench_with_input(BenchmarkId::new(format!("{}_topics", topics), subscribers), &subscribers, |b, &subscribers| {
+            let event_bus = event_bus();
+            for name in &names {
+                for _ in 0..subscribers {
+                    event_bus.subscribe_listener(name.as_str(), SummingSubscriber { sum: 0 });
+                }
+            }
+            b.iter_batched(
+                || {
+                    for event in 0..PUBLISHED {
+                        event_bus.register(names[event % names.len()].as_str(), event as u64);
+                    }
+                },
+                |_| event_bus.publish().unwrap(),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+/// Compares reading the data of an event as the type it holds, and as another type.
+fn downcast(c: &mut Criterion) {
+    let mut group = c.benchmark_group("downcast");
+    let event = Event::new(42u64);
+    group.bench_function("get_data", |b| b.iter(|| black_box(&event).get_data::<u64>().copied()));
+    group.bench_function("get_data_mismatch", |b| b.iter(|| black_box(&event).get_data::<u32>().copied()));
+    group.bench_function("try_get_data", |b| b.iter(|| black_box(&event).try_get_data::<u64>().copied()));
+    group.finish();
+}
+
+/// Registers a burst of 100k `u32` events on a `u8` event name, so neither the event name nor the data allocates,
+/// and prints the number of allocations the burst made.
+fn payload(c: &mut Criterion) {
+    let burst = |event_bus: EventBus<u8>| {
+        for event in 0..EVENTS {
+            event_bus.register(0, event as u32);
+        }
+        event_bus
+    };
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    drop(burst(EventBus::default()));
+    println!("payload/u32_burst: {} allocations for {} events", ALLOCATIONS.load(Ordering::Relaxed) - before, EVENTS);
+
+    let mut group = c.benchmark_group("payload");
+    group.throughput(Throughput::Elements(EVENTS as u64));
+    group.bench_function("u32_burst", |b| b.iter_batched(EventBus::default, burst, BatchSize::LargeInput));
+    group.finish();
+}
+
+/// Publishes 10k events spread over 100 event names to a single wildcard subscriber.
+fn pattern(c: &mut Criterion) {
+    const PUBLISHED: usize = 10_000;
+    let names = topic_names(100);
+    let mut group = c.benchmark_group("pattern");
+    group.throughput(Throughput::Elements(PUBLISHED as u64));
+    group.bench_function("single_level_wildcard", |b| {
+        let event_bus = event_bus();
+        event_bus.subscribe_pattern("sensors/+/temperature", SummingSubscriber { sum: 0 }).unwrap();
+        b.iter_batched(
+            || {
+                for event in 0..PUBLISHED {
+                    event_bus.register(names[event % names.len()].as_str(), event as u64);
+                }
+            },
+            |_| event_bus.publish().unwrap(),
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, register, publish, downcast, payload, pattern);
+criterion_main!(benches);
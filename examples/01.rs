@@ -42,18 +42,12 @@ impl NumberSubscriber {
 
 impl Subscriber for NumberSubscriber {
     fn on_before(&mut self, event: &mut Event) -> Result<(), String> {
-        match event.get_data::<u32>() {
-            Some(value) => {
-                let new_data = *&value + 1;
-                debug!("Changing {} into {}", value, new_data);
-                event.set_data::<u32>(new_data);
-                Ok(())
-            }
-            None => {
-                let message = format!("{} received invalid message", NumberSubscriber::NAME);
-                Err(message)
-            }
-        }
+        event
+            .map_data(|value: u32| {
+                debug!("Changing {} into {}", value, value + 1);
+                value + 1
+            })
+            .map_err(|_| format!("{} received invalid message", NumberSubscriber::NAME))
     }
 
     fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
@@ -74,7 +68,7 @@ impl Subscriber for NumberSubscriber {
 fn main() {
     env_logger::Builder::from_env(Env::default().default_filter_or("debug")).init();
 
-    let mut event_bus = EventBus::new();
+    let event_bus = EventBus::new();
 
     // We have to manually create and add each subscriber to the event bus.
     event_bus
@@ -83,8 +77,8 @@ fn main() {
 
     // We can manually register an event to the event bus.
     match event_bus
-        .register("foo", Event::new(42u32))
-        .register("bar", Event::new("hello".to_string()))
+        .register("foo", 42u32)
+        .register("bar", "hello".to_string())
         .register("foo", Event::new("hello".to_string()))
         .register("hello", Event::new("hello".to_string()))
         .publish() // Publishes each event, and calls each listener's on_* methods.
@@ -0,0 +1,54 @@
+use std::rc::Rc;
+use simple_event_bus::{Event, EventBus, Subscriber};
+use env_logger::Env;
+use log::info;
+
+// A button only holds a shared reference to the event bus, and fires an event when it is clicked.
+struct Button {
+    event_bus: Rc<EventBus>,
+}
+
+impl Button {
+    fn click(&self) {
+        self.event_bus.register("clicked", "ok".to_string());
+    }
+}
+
+// A status bar listens for clicks, and fires an event of its own from within its subscriber.
+struct StatusBar {
+    event_bus: Rc<EventBus>,
+}
+
+impl Subscriber for StatusBar {
+    fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+        let button = event.get_data::<String>().ok_or("Expected the name of a button")?;
+        info!("Button {} was clicked", button);
+        self.event_bus.register("status", format!("Clicked {}", button));
+        Ok(())
+    }
+}
+
+struct StatusLogger;
+
+impl Subscriber for StatusLogger {
+    fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+        info!("Status: {}", event.get_data::<String>().ok_or("Expected a status")?);
+        Ok(())
+    }
+}
+
+fn main() {
+    env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
+
+    let event_bus = Rc::new(EventBus::new());
+    event_bus
+        .subscribe_listener("clicked", StatusBar { event_bus: event_bus.clone() })
+        .subscribe_listener("status", StatusLogger);
+
+    let button = Button { event_bus: event_bus.clone() };
+    button.click();
+
+    // The first publish delivers the click, the event fired by the status bar is delivered by the second.
+    event_bus.publish().unwrap();
+    event_bus.publish().unwrap();
+}
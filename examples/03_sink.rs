@@ -0,0 +1,34 @@
+use simple_event_bus::{Event, EventBus, Subscriber};
+use env_logger::Env;
+use log::info;
+
+// The producer only sees the sink, it can register events
+// but cannot manage the subscribers or publish.
+mod producer {
+    use simple_event_bus::EventSink;
+
+    pub fn produce(sink: &EventSink) {
+        sink.register("reading", 21u32)
+            .register_all((22..25u32).map(|value| ("reading".to_string(), value)));
+    }
+}
+
+struct ReadingSubscriber;
+
+impl Subscriber for ReadingSubscriber {
+    fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+        info!("Reading: {}", event.get_data::<u32>().ok_or("Expected a reading")?);
+        Ok(())
+    }
+}
+
+fn main() {
+    env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
+
+    // The owner of the loop holds the event bus itself.
+    let event_bus = EventBus::new();
+    event_bus.subscribe_listener("reading", ReadingSubscriber);
+
+    producer::produce(&event_bus.sink());
+    event_bus.publish().unwrap();
+}
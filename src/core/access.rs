@@ -0,0 +1,216 @@
+use super::{EventBus, TopicKey};
+use super::state::BusState;
+use super::topic::topic_str;
+
+/// # Topic Policy
+///
+/// Which event names can be used on an event bus, set with `EventBus::set_topic_policy`.
+/// An entry of a list is an event name, or a prefix ending with `*` like `"debug.*"`, which matches every
+/// event name starting with it. The policy only applies to event names that are strings.
+///
+/// ## Variants
+///
+/// * `AllowAll` - Every event name can be used, the default.
+///
+/// * `Allowlist` - Only the event names the entries match can be used.
+///
+/// * `Denylist` - The event names the entries match cannot be used.
+///
+/// * `WarnUnknown` - Every event name can be used, and the ones the entries do not match are logged.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum TopicPolicy {
+    /// Every event name can be used.
+    #[default]
+    AllowAll,
+    /// Only the event names the entries match can be used.
+    Allowlist(Vec<String>),
+    /// The event names the entries match cannot be used.
+    Denylist(Vec<String>),
+    /// Every event name can be used, and the ones the entries do not match are logged.
+    WarnUnknown(Vec<String>),
+}
+
+/// What the topic policy says about an event name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TopicAccess {
+    Allowed,
+    /// Allowed, but not matched by the entries of `TopicPolicy::WarnUnknown`.
+    Unlisted,
+    Denied,
+}
+
+/// Returns whether an entry, an event name or a prefix ending with `*`, matches the event name.
+pub(crate) fn lists(entries: &[String], topic: &str) -> bool {
+    entries.iter().any(|entry| match entry.strip_suffix('*') {
+        Some(prefix) => topic.starts_with(prefix),
+        None => entry == topic,
+    })
+}
+
+impl TopicPolicy {
+    fn access(&self, topic: &str) -> TopicAccess {
+        match self {
+            TopicPolicy::AllowAll => TopicAccess::Allowed,
+            TopicPolicy::Allowlist(entries) if !lists(entries, topic) => TopicAccess::Denied,
+            TopicPolicy::Denylist(entries) if lists(entries, topic) => TopicAccess::Denied,
+            TopicPolicy::WarnUnknown(entries) if !lists(entries, topic) => TopicAccess::Unlisted,
+            _ => TopicAccess::Allowed,
+        }
+    }
+
+    fn entries_mut(&mut self) -> Option<&mut Vec<String>> {
+        match self {
+            TopicPolicy::AllowAll => None,
+            TopicPolicy::Allowlist(entries) | TopicPolicy::Denylist(entries) | TopicPolicy::WarnUnknown(entries) => Some(entries),
+        }
+    }
+}
+
+impl<K: TopicKey> BusState<K> {
+    /// Rewrites the entries of a list of event names with the normalizer of the event bus.
+    pub(crate) fn normalize_entries(&self, entries: &mut [String]) {
+        if let Some(normalizer) = &self.normalizer {
+            entries.iter_mut().for_each(|entry| *entry = normalizer(entry));
+        }
+    }
+
+    /// Returns what the topic policy says about the normalized event name.
+    pub(crate) fn topic_access(&self, event_name: &K) -> TopicAccess {
+        topic_str(event_name).map_or(TopicAccess::Allowed, |topic| self.topic_policy.access(topic))
+    }
+
+    /// Returns whether the normalized event name can be used, and logs it when it cannot or is unlisted.
+    pub(crate) fn admits_topic(&self, event_name: &K) -> bool {
+        match self.topic_access(event_name) {
+            TopicAccess::Allowed => true,
+            TopicAccess::Unlisted => {
+                self.logger.unlisted_topic(event_name);
+                true
+            }
+            TopicAccess::Denied => {
+                self.logger.topic_denied(event_name);
+                false
+            }
+        }
+    }
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Set Topic Policy
+    ///
+    /// Sets which event names can be used, see `TopicPolicy`. The policy replaces the previous one,
+    /// and is checked against the event names after the normalizer rewrote them, as are its entries.
+    /// Events registered on a denied event name are dropped, also when they arrive through a bridge,
+    /// and listeners subscribed to one are not subscribed, which is logged. `try_register`,
+    /// `try_subscribe_listener` and `add_route` return an error instead. Meta events are not checked.
+    pub fn set_topic_policy(&self, mut policy: TopicPolicy) -> &Self {
+        let mut state = self.state.borrow_mut();
+        if let Some(entries) = policy.entries_mut() {
+            state.normalize_entries(entries);
+        }
+        state.topic_policy = policy;
+        self
+    }
+
+    /// # Topic Policy
+    ///
+    /// Returns the policy of the event names that can be used, see `set_topic_policy`.
+    pub fn topic_policy(&self) -> TopicPolicy {
+        self.state.borrow().topic_policy.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TopicPolicy;
+    use crate::{EventBus, RegisterError, RouteError, RouteMode, UnknownTopic};
+    use crate::subscribers::CollectingSubscriber;
+
+    #[test]
+    fn test_allowlist_rejects_register_of_unlisted_topic() {
+        let event_bus = EventBus::new();
+        let (collector, received) = CollectingSubscriber::<u32>::new();
+        event_bus
+            .set_topic_policy(TopicPolicy::Allowlist(vec!["orders.*".to_string()]))
+            .subscribe_listener("orders.created", collector);
+
+        let result = event_bus.try_register("payments.created", 1u32);
+        assert_eq!(Some(RegisterError::Denied { topic: "payments.created".to_string() }), result.err());
+        assert!(event_bus.try_register("orders.created", 2u32).is_ok());
+        event_bus.register("payments.created", 3u32);
+        assert_eq!(0, event_bus.pending(&"payments.created".to_string()));
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![2], *received.borrow());
+    }
+
+    #[test]
+    fn test_allowlist_rejects_subscribe_after_normalization() {
+        let event_bus = EventBus::new();
+        event_bus
+            .set_topic_normalizer(|topic| topic.to_lowercase())
+            .set_topic_policy(TopicPolicy::Allowlist(vec!["Orders.Created".to_string()]));
+
+        let (collector, received) = CollectingSubscriber::<u32>::new();
+        assert!(event_bus.try_subscribe_listener("ORDERS.CREATED", collector).is_ok());
+        let (rejected, _) = CollectingSubscriber::<u32>::new();
+        let result = event_bus.try_subscribe_listener("orders.cancelled", rejected);
+        assert_eq!(Some(UnknownTopic { topic: "orders.cancelled".to_string() }), result.err());
+
+        event_bus.set_topic_policy(TopicPolicy::AllowAll);
+        let (accepted, _) = CollectingSubscriber::<u32>::new();
+        assert!(event_bus.try_subscribe_listener("orders.cancelled", accepted).is_ok());
+        event_bus.register("orders.created", 1u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![1], *received.borrow());
+    }
+
+    #[test]
+    fn test_denylist_pattern_denies_matching_topics() {
+        let event_bus = EventBus::new();
+        let (collector, received) = CollectingSubscriber::<u32>::new();
+        event_bus
+            .set_topic_policy(TopicPolicy::Denylist(vec!["debug.*".to_string()]))
+            .subscribe_listener("debug.frames", collector)
+            .register("debug.frames", 1u32);
+
+        assert_eq!(0, event_bus.pending(&"debug.frames".to_string()));
+        assert_eq!(Ok(()), event_bus.publish());
+        assert!(received.borrow().is_empty());
+        assert_eq!(
+            Some(RouteError::Denied { topic: "debug.copy".to_string() }),
+            event_bus.add_route("frames", "debug.copy", RouteMode::Copy).err(),
+        );
+        assert!(event_bus.add_route("frames", "release.frames", RouteMode::Copy).is_ok());
+    }
+
+    #[test]
+    fn test_denylist_applies_to_bulk_registration() {
+        let mut event_bus = EventBus::new();
+        event_bus
+            .set_topic_policy(TopicPolicy::Denylist(vec!["debug.*".to_string()]))
+            .register_all(vec![("debug.frames".to_string(), 1u32), ("frames".to_string(), 2u32)]);
+        event_bus.extend(vec![("debug.frames".to_string(), 3u32), ("frames".to_string(), 4u32)]);
+
+        assert_eq!(0, event_bus.pending(&"debug.frames".to_string()));
+        assert_eq!(2, event_bus.pending(&"frames".to_string()));
+    }
+
+    #[cfg(feature = "threaded")]
+    #[test]
+    fn test_denylist_applies_to_thread_sinks() {
+        let event_bus = EventBus::new();
+        let (collector, received) = CollectingSubscriber::<u32>::new();
+        event_bus
+            .set_topic_policy(TopicPolicy::Denylist(vec!["debug.*".to_string()]))
+            .subscribe_listener("debug.frames", collector);
+        let sink = event_bus.thread_sink();
+        std::thread::spawn(move || {
+            sink.register("debug.frames", 1u32);
+        }).join().unwrap();
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert!(received.borrow().is_empty());
+        assert_eq!(0, event_bus.metrics().topic(&"debug.frames".to_string()).registered);
+    }
+}
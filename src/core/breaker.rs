@@ -0,0 +1,353 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+use super::{BreakerStateChanged, EventBus, Instant, Subscriber, TopicKey};
+use super::state::{meta_topic, BusState};
+use super::subscription::Subscription;
+
+/// # Circuit Breaker
+///
+/// Stops delivering events to a subscriber that keeps failing, like one calling a dependency that is down,
+/// and tries it again once the dependency may have recovered. Set with `subscribe_with_breaker`.
+///
+/// When `on_event` fails `failure_threshold` times within the window, the breaker opens and the subscriber
+/// is skipped for the cool-down. The first event after the cool-down is delivered as a trial: when it succeeds
+/// the breaker closes, when it fails the breaker opens again for twice the cool-down, up to the maximum.
+/// Only one trial is delivered at a time; when it does not reach the subscriber, like after a `Stop`
+/// of an earlier subscriber, the next event is delivered as the trial.
+///
+/// ## Fields
+///
+/// * `failure_threshold` - The number of failures within the window that opens the breaker.
+///
+/// * `window` - How long a failure is counted.
+///
+/// * `cool_down` - How long the subscriber is skipped the first time the breaker opens.
+///
+/// * `max_cool_down` - The longest the subscriber is skipped, when trials keep failing.
+///
+/// * `dead_letter` - Whether the events the subscriber skips are dead-lettered, which clones them,
+///   so only events whose payload type is registered with `register_cloneable` are dead-lettered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreaker {
+    /// The number of failures within the window that opens the breaker.
+    pub failure_threshold: u32,
+    /// How long a failure is counted.
+    pub window: Duration,
+    /// How long the subscriber is skipped the first time the breaker opens.
+    pub cool_down: Duration,
+    /// The longest the subscriber is skipped, when trials keep failing.
+    pub max_cool_down: Duration,
+    /// Whether the events the subscriber skips are dead-lettered.
+    pub dead_letter: bool,
+}
+
+impl CircuitBreaker {
+    /// # New
+    ///
+    /// Creates a breaker that opens after the number of failures within the window, for the cool-down.
+    /// The cool-down grows up to 16 times its length, and skipped events are not dead-lettered.
+    pub fn new(failure_threshold: u32, window: Duration, cool_down: Duration) -> CircuitBreaker {
+        CircuitBreaker { failure_threshold, window, cool_down, max_cool_down: cool_down.saturating_mul(16), dead_letter: false }
+    }
+}
+
+/// # Breaker State
+///
+/// The state of the circuit breaker of a subscription, see `CircuitBreaker`.
+///
+/// ## Variants
+///
+/// * `Closed` - The subscriber receives the events.
+///
+/// * `Open` - The subscriber is skipped until the cool-down passed.
+///
+/// * `HalfOpen` - The cool-down passed, and the next event is delivered as a trial.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerState {
+    /// The subscriber receives the events.
+    Closed,
+    /// The subscriber is skipped until the cool-down passed.
+    Open,
+    /// The next event is delivered as a trial.
+    HalfOpen,
+}
+
+/// Whether an event is delivered to a subscription with a breaker.
+pub(crate) enum Admission {
+    Deliver,
+    /// The event is delivered as the trial, right after the cool-down passed when the breaker `reopened`,
+    /// or again because the event of the previous trial did not reach the subscriber.
+    Trial { reopened: bool },
+    Skip,
+}
+
+/// The circuit breaker of a subscription.
+#[derive(Clone)]
+pub(crate) struct Breaker {
+    pub(crate) config: CircuitBreaker,
+    state: BreakerState,
+    /// When the failures that are still counted happened.
+    failures: VecDeque<Instant>,
+    cool_down: Duration,
+    reopens_at: Option<Instant>,
+    /// Whether the trial of the half-open breaker was admitted, and its result is not recorded yet.
+    trial_pending: bool,
+}
+
+impl Breaker {
+    fn new(config: CircuitBreaker) -> Breaker {
+        Breaker {
+            config,
+            state: BreakerState::Closed,
+            failures: VecDeque::new(),
+            cool_down: config.cool_down,
+            reopens_at: None,
+            trial_pending: false,
+        }
+    }
+
+    pub(crate) fn state(&self) -> BreakerState {
+        self.state
+    }
+
+    /// Returns whether an event is delivered, turning the breaker half-open once the cool-down passed.
+    /// A half-open breaker admits a single trial, and skips the events until its result is recorded.
+    pub(crate) fn admit(&mut self, now: Instant) -> Admission {
+        match self.state {
+            BreakerState::Closed => Admission::Deliver,
+            BreakerState::HalfOpen if self.trial_pending => Admission::Skip,
+            BreakerState::HalfOpen => {
+                self.trial_pending = true;
+                Admission::Trial { reopened: false }
+            }
+            BreakerState::Open if self.reopens_at.is_some_and(|reopens_at| reopens_at <= now) => {
+                self.state = BreakerState::HalfOpen;
+                self.trial_pending = true;
+                Admission::Trial { reopened: true }
+            }
+            BreakerState::Open => Admission::Skip,
+        }
+    }
+
+    /// Admits a new trial with the next event, when the event of the trial was settled
+    /// without reaching the subscriber, like after a `Stop` of an earlier subscriber.
+    pub(crate) fn rearm(&mut self) {
+        self.trial_pending = false;
+    }
+
+    /// Records the result of a call of `on_event`, returns the new state when it changed.
+    pub(crate) fn record(&mut self, failed: bool, now: Instant) -> Option<BreakerState> {
+        self.trial_pending = false;
+        match (self.state, failed) {
+            (BreakerState::Closed, true) => {
+                self.failures.push_back(now);
+                while self.failures.front().is_some_and(|failed_at| *failed_at + self.config.window <= now) {
+                    self.failures.pop_front();
+                }
+                (self.failures.len() >= self.config.failure_threshold as usize).then(|| self.open(now))
+            }
+            (BreakerState::HalfOpen, true) => {
+                self.cool_down = self.cool_down.saturating_mul(2).min(self.config.max_cool_down);
+                Some(self.open(now))
+            }
+            (BreakerState::HalfOpen, false) => {
+                self.state = BreakerState::Closed;
+                self.cool_down = self.config.cool_down;
+                self.reopens_at = None;
+                Some(BreakerState::Closed)
+            }
+            _ => None,
+        }
+    }
+
+    fn open(&mut self, now: Instant) -> BreakerState {
+        self.state = BreakerState::Open;
+        self.failures.clear();
+        self.reopens_at = Some(now + self.cool_down);
+        BreakerState::Open
+    }
+}
+
+impl<K: TopicKey> BusState<K> {
+    /// Emits the meta event of a breaker that changed its state.
+    pub(crate) fn breaker_changed(&mut self, event_name: &K, subscriber: &str, state: BreakerState) {
+        let changed = BreakerStateChanged { topic: meta_topic(event_name), subscriber: subscriber.to_string(), state };
+        self.emit_meta(BreakerStateChanged::TOPIC, changed);
+    }
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Subscribe With Breaker
+    ///
+    /// Subscribes a listener behind a circuit breaker, which skips it for a while when it keeps failing,
+    /// see `CircuitBreaker`. The failures are handled by the error policy as usual, so a breaker is combined
+    /// with `ErrorPolicy::Continue` to keep delivering the events to the other subscribers.
+    /// Every change of the state of the breaker is announced with a `BreakerStateChanged` meta event.
+    pub fn subscribe_with_breaker<R: Subscriber + 'static>(&self, event_name: impl Into<K>, listener: R, breaker: CircuitBreaker) -> &Self {
+        let mut subscription = Subscription::new(listener);
+        subscription.breaker = Some(Breaker::new(breaker));
+        self.subscribe(self.topic_key(event_name), subscription)
+    }
+
+    /// # Breaker States
+    ///
+    /// Returns the names of the subscribers of the event name with a circuit breaker, in subscription order,
+    /// with the state of their breaker. An open breaker whose cool-down passed turns half-open with the next event.
+    /// The subscribers are not known while the event name is being published.
+    pub fn breaker_states(&self, event_name: &K) -> Vec<(String, BreakerState)> {
+        let state = self.state.borrow();
+        state.subscribers.get(&state.normalize(event_name.clone())).into_iter().flatten()
+            .filter_map(|subscription| Some((subscription.listener.name().to_string(), subscription.breaker.as_ref()?.state())))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+    use std::time::Duration;
+    use std::time::Instant;
+    use super::{Admission, Breaker, BreakerState, CircuitBreaker};
+    use crate::{BreakerStateChanged, DeadLetterReason, ErrorPolicy, Event, EventBus, Outcome, Subscriber};
+    use crate::subscribers::CollectingSubscriber;
+    use crate::testing::ManualClock;
+
+    /// Stops the delivery of the events to the later subscribers while `stopping` is set.
+    struct Gate {
+        stopping: Rc<Cell<bool>>,
+    }
+
+    impl Subscriber for Gate {
+        fn on_event_outcome(&mut self, _event: &mut Event) -> Outcome {
+            match self.stopping.get() {
+                true => Outcome::Stop,
+                false => Outcome::Ack,
+            }
+        }
+    }
+
+    /// An event bus with a subscriber named `gateway` on `payments` that fails while `failing` is set,
+    /// behind a `Gate`.
+    struct Flaky {
+        event_bus: EventBus,
+        failing: Rc<Cell<bool>>,
+        stopping: Rc<Cell<bool>>,
+        calls: Rc<Cell<u32>>,
+        changes: Rc<RefCell<Vec<BreakerStateChanged>>>,
+    }
+
+    fn flaky_bus(clock: &ManualClock, breaker: CircuitBreaker) -> Flaky {
+        let event_bus: EventBus = EventBus::with_clock(clock.clone());
+        let (failing, calls, stopping) = (Rc::new(Cell::new(true)), Rc::new(Cell::new(0)), Rc::new(Cell::new(false)));
+        let (collector, changes) = CollectingSubscriber::<BreakerStateChanged>::new();
+        let (is_failing, called) = (failing.clone(), calls.clone());
+        event_bus
+            .set_error_policy(ErrorPolicy::Continue)
+            .enable_meta_events()
+            .subscribe_listener(BreakerStateChanged::TOPIC, collector)
+            .subscribe_listener("payments", Gate { stopping: stopping.clone() })
+            .subscribe_with_breaker("payments", <dyn Subscriber>::builder().name("gateway").on_event(move |_| {
+                called.set(called.get() + 1);
+                match is_failing.get() {
+                    true => Err("gateway down".to_string()),
+                    false => Ok(()),
+                }
+            }).build(), breaker);
+        Flaky { event_bus, failing, stopping, calls, changes }
+    }
+
+    fn states(changes: &RefCell<Vec<BreakerStateChanged>>) -> Vec<BreakerState> {
+        changes.borrow().iter().map(|change| change.state).collect()
+    }
+
+    #[test]
+    fn test_breaker_opens_then_recovers_through_half_open() {
+        let clock = ManualClock::new();
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(10), Duration::from_secs(5));
+        let Flaky { event_bus, failing, calls, changes, .. } = flaky_bus(&clock, breaker);
+
+        event_bus.register("payments", 1u32).register("payments", 2u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![("gateway".to_string(), BreakerState::Open)], event_bus.breaker_states(&"payments".to_string()));
+        assert_eq!("gateway", changes.borrow()[0].subscriber);
+
+        event_bus.register("payments", 3u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(2, calls.get());
+
+        clock.advance(Duration::from_secs(5));
+        failing.set(false);
+        event_bus.register("payments", 4u32).register("payments", 5u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(4, calls.get());
+        assert_eq!(vec![BreakerState::Open, BreakerState::HalfOpen, BreakerState::Closed], states(&changes));
+        assert_eq!(vec![("gateway".to_string(), BreakerState::Closed)], event_bus.breaker_states(&"payments".to_string()));
+    }
+
+    #[test]
+    fn test_failed_trial_reopens_with_longer_cool_down() {
+        let clock = ManualClock::new();
+        let breaker = CircuitBreaker { dead_letter: true, ..CircuitBreaker::new(1, Duration::from_secs(10), Duration::from_secs(5)) };
+        let Flaky { event_bus, calls, changes, .. } = flaky_bus(&clock, breaker);
+        event_bus.register_cloneable::<u32>();
+
+        event_bus.register("payments", 1u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        clock.advance(Duration::from_secs(5));
+        event_bus.register("payments", 2u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![BreakerState::Open, BreakerState::HalfOpen, BreakerState::Open], states(&changes));
+
+        clock.advance(Duration::from_secs(5));
+        event_bus.register("payments", 3u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(2, calls.get());
+        let dead_letters = event_bus.take_dead_letters();
+        assert_eq!(1, dead_letters.len());
+        assert_eq!(DeadLetterReason::CircuitOpen { subscriber: "gateway".to_string() }, dead_letters[0].reason);
+        assert_eq!(Some(&3), dead_letters[0].event.get_data::<u32>());
+
+        clock.advance(Duration::from_secs(5));
+        event_bus.register("payments", 4u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(3, calls.get());
+    }
+
+    #[test]
+    fn test_half_open_breaker_admits_one_trial_at_a_time() {
+        let now = Instant::now();
+        let mut breaker = Breaker::new(CircuitBreaker::new(1, Duration::from_secs(10), Duration::from_secs(5)));
+        breaker.record(true, now);
+        let later = now + Duration::from_secs(5);
+        assert!(matches!(breaker.admit(later), Admission::Trial { reopened: true }));
+        assert!(matches!(breaker.admit(later), Admission::Skip));
+        breaker.rearm();
+        assert!(matches!(breaker.admit(later), Admission::Trial { reopened: false }));
+        assert_eq!(Some(BreakerState::Closed), breaker.record(false, later));
+        assert!(matches!(breaker.admit(later), Admission::Deliver));
+    }
+
+    #[test]
+    fn test_trial_stopped_by_earlier_subscriber_is_rearmed() {
+        let clock = ManualClock::new();
+        let breaker = CircuitBreaker::new(1, Duration::from_secs(10), Duration::from_secs(5));
+        let Flaky { event_bus, failing, stopping, calls, changes } = flaky_bus(&clock, breaker);
+
+        event_bus.register("payments", 1u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        clock.advance(Duration::from_secs(5));
+        stopping.set(true);
+        event_bus.register("payments", 2u32).register("payments", 3u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(1, calls.get());
+        assert_eq!(vec![("gateway".to_string(), BreakerState::HalfOpen)], event_bus.breaker_states(&"payments".to_string()));
+
+        stopping.set(false);
+        failing.set(false);
+        event_bus.register("payments", 4u32).register("payments", 5u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(3, calls.get());
+        assert_eq!(vec![BreakerState::Open, BreakerState::HalfOpen, BreakerState::Closed], states(&changes));
+    }
+}
@@ -0,0 +1,202 @@
+use super::{Event, EventContext, Outcome, Subscriber};
+
+type BeforeFn = Box<dyn FnMut(&mut Event) -> Result<(), String>>;
+type EventFn = Box<dyn FnMut(&mut Event) -> Result<(), String>>;
+type AfterFn = Box<dyn Fn(&Event) -> Result<(), String>>;
+
+/// # Subscriber Builder
+///
+/// Assembles a subscriber from closures, one per phase, created with `<dyn Subscriber>::builder()`.
+/// The phases without a closure succeed without doing anything.
+/// To share state between the closures, let each of them capture a clone of an `Rc<RefCell<..>>`.
+///
+/// ## Methods
+///
+/// * `name` - Sets the name of the subscriber in errors.
+///
+/// * `on_before` - Sets the closure called before the event.
+///
+/// * `on_event` - Sets the closure called with the event.
+///
+/// * `on_after` - Sets the closure called after the event.
+///
+/// * `build` - Returns the subscriber.
+#[derive(Default)]
+pub struct SubscriberBuilder {
+    name: Option<String>,
+    before: Option<BeforeFn>,
+    event: Option<EventFn>,
+    after: Option<AfterFn>,
+}
+
+impl dyn Subscriber {
+    /// # Builder
+    ///
+    /// Returns a builder to assemble a subscriber from closures.
+    pub fn builder() -> SubscriberBuilder {
+        SubscriberBuilder::default()
+    }
+}
+
+impl SubscriberBuilder {
+    /// # Name
+    ///
+    /// Sets the name of the subscriber in errors, `"subscriber"` by default.
+    pub fn name(mut self, name: impl Into<String>) -> SubscriberBuilder {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// # On Before
+    ///
+    /// Sets the closure called before the event is delivered.
+    pub fn on_before(mut self, before: impl FnMut(&mut Event) -> Result<(), String> + 'static) -> SubscriberBuilder {
+        self.before = Some(Box::new(before));
+        self
+    }
+
+    /// # On Event
+    ///
+    /// Sets the closure the event is delivered to.
+    pub fn on_event(mut self, event: impl FnMut(&mut Event) -> Result<(), String> + 'static) -> SubscriberBuilder {
+        self.event = Some(Box::new(event));
+        self
+    }
+
+    /// # On After
+    ///
+    /// Sets the closure called after the event is delivered.
+    pub fn on_after(mut self, after: impl Fn(&Event) -> Result<(), String> + 'static) -> SubscriberBuilder {
+        self.after = Some(Box::new(after));
+        self
+    }
+
+    /// # Build
+    ///
+    /// Returns the subscriber, ready to be subscribed with `subscribe_listener`.
+    pub fn build(self) -> Box<dyn Subscriber> {
+        Box::new(Built {
+            name: self.name.unwrap_or_else(|| "subscriber".to_string()),
+            before: self.before,
+            event: self.event,
+            after: self.after,
+        })
+    }
+}
+
+/// A subscriber assembled by a `SubscriberBuilder`.
+struct Built {
+    name: String,
+    before: Option<BeforeFn>,
+    event: Option<EventFn>,
+    after: Option<AfterFn>,
+}
+
+impl Subscriber for Built {
+    fn on_before(&mut self, event: &mut Event) -> Result<(), String> {
+        self.before.as_mut().map_or(Ok(()), |before| before(event))
+    }
+
+    fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+        self.event.as_mut().map_or(Ok(()), |on_event| on_event(event))
+    }
+
+    fn on_after(&self, event: &Event) -> Result<(), String> {
+        self.after.as_ref().map_or(Ok(()), |after| after(event))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<S: Subscriber + ?Sized> Subscriber for Box<S> {
+    fn on_subscribe(&mut self, topic: &str) {
+        (**self).on_subscribe(topic)
+    }
+
+    fn on_unsubscribe(&mut self, topic: &str) {
+        (**self).on_unsubscribe(topic)
+    }
+
+    fn on_before(&mut self, event: &mut Event) -> Result<(), String> {
+        (**self).on_before(event)
+    }
+
+    fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+        (**self).on_event(event)
+    }
+
+    fn on_event_outcome(&mut self, event: &mut Event) -> Outcome {
+        (**self).on_event_outcome(event)
+    }
+
+    fn on_event_with_context(&mut self, event: &mut Event, context: &mut EventContext) -> Outcome {
+        (**self).on_event_with_context(event, context)
+    }
+
+    fn on_after(&self, event: &Event) -> Result<(), String> {
+        (**self).on_after(event)
+    }
+
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use crate::{ErrorPolicy, EventBus, Phase, Subscriber, SubscriberFailure};
+    use crate::subscribers::CollectingSubscriber;
+
+    #[test]
+    fn test_phases_are_called_in_order() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let (before, event, after) = (calls.clone(), calls.clone(), calls.clone());
+        let subscriber = <dyn Subscriber>::builder()
+            .on_before(move |e| {
+                before.borrow_mut().push(format!("before {}", e.get_data::<u32>().unwrap()));
+                Ok(())
+            })
+            .on_event(move |e| {
+                event.borrow_mut().push(format!("event {}", e.get_data::<u32>().unwrap()));
+                Ok(())
+            })
+            .on_after(move |e| {
+                after.borrow_mut().push(format!("after {}", e.get_data::<u32>().unwrap()));
+                Ok(())
+            })
+            .build();
+        let event_bus = EventBus::new();
+        event_bus
+            .subscribe_listener("tick", subscriber)
+            .subscribe_listener("tick", <dyn Subscriber>::builder().build())
+            .register("tick", 1u32)
+            .register("tick", 2u32);
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec!["before 1", "event 1", "after 1", "before 2", "event 2", "after 2"], *calls.borrow());
+    }
+
+    #[test]
+    fn test_name_is_used_in_errors() {
+        let (collector, failures) = CollectingSubscriber::<SubscriberFailure>::new();
+        let event_bus = EventBus::new();
+        event_bus
+            .set_error_policy(ErrorPolicy::Continue)
+            .route_errors_to("errors")
+            .subscribe_listener("errors", collector)
+            .subscribe_listener("order", <dyn Subscriber>::builder()
+                .name("validator")
+                .on_after(|_| Err("Missing customer".to_string()))
+                .build())
+            .register("order", 1u32);
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(Ok(()), event_bus.publish());
+        let failures = failures.borrow();
+        assert_eq!(("validator", Phase::After), (failures[0].subscriber.as_str(), failures[0].phase));
+    }
+}
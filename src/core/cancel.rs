@@ -0,0 +1,172 @@
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use super::{Clock, EventBus, Instant, TopicKey};
+
+/// # Cancel Token
+///
+/// Cancels a publish started with `publish_with_cancellation`, also from another thread.
+/// Clones share the same state, so a subscriber or another thread can keep a clone to cancel with.
+///
+/// ## Methods
+///
+/// * `new` - Creates a token that is not cancelled.
+///
+/// * `cancel` - Cancels the publish that checks the token.
+///
+/// * `is_cancelled` - Returns whether the token was cancelled.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    /// # New
+    ///
+    /// Creates a token that is not cancelled.
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    /// # Cancel
+    ///
+    /// Cancels the publish that checks the token, before its next message.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// # Is Cancelled
+    ///
+    /// Returns whether the token was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Stops a publish before every queued event is published: when its token is cancelled,
+/// or when its budget of messages or time is spent.
+#[derive(Default)]
+pub(crate) struct Halt<'a> {
+    token: Option<&'a CancelToken>,
+    /// The number of messages the publish delivers at most.
+    max_messages: Option<usize>,
+    /// The number of messages the publish delivered.
+    delivered: Cell<usize>,
+    /// When the publish stops, on the clock of the event bus.
+    deadline: Option<(Instant, Arc<dyn Clock>)>,
+    /// Whether the deadline passed, so the publish stops consistently once it noticed.
+    expired: Cell<bool>,
+}
+
+impl<'a> Halt<'a> {
+    pub(crate) fn token(token: &'a CancelToken) -> Halt<'a> {
+        Halt { token: Some(token), ..Halt::default() }
+    }
+
+    pub(crate) fn budget(max_messages: Option<usize>, deadline: Option<(Instant, Arc<dyn Clock>)>) -> Halt<'a> {
+        Halt { max_messages, deadline, ..Halt::default() }
+    }
+
+    /// Whether the publish is limited by a budget, rather than unlimited or cancelled by a token.
+    pub(crate) fn is_budgeted(&self) -> bool {
+        self.max_messages.is_some() || self.deadline.is_some()
+    }
+
+    /// Returns whether the publish stops.
+    pub(crate) fn is_halted(&self) -> bool {
+        if self.token.is_some_and(CancelToken::is_cancelled) || self.max_messages.is_some_and(|max| self.delivered.get() >= max) {
+            return true;
+        }
+        if !self.expired.get() && self.deadline.as_ref().is_some_and(|(deadline, clock)| clock.now() >= *deadline) {
+            self.expired.set(true);
+        }
+        self.expired.get()
+    }
+
+    /// Returns whether the publish stops before the next message, or counts the message as delivered.
+    pub(crate) fn before_message(&self) -> bool {
+        if self.is_halted() {
+            return true;
+        }
+        self.delivered.set(self.delivered.get() + 1);
+        false
+    }
+
+    /// Returns whether the publish stopped because its deadline passed.
+    pub(crate) fn is_expired(&self) -> bool {
+        self.expired.get()
+    }
+
+    /// Returns the number of messages the publish delivered.
+    pub(crate) fn delivered(&self) -> usize {
+        self.delivered.get()
+    }
+}
+
+/// # Publish Status
+///
+/// Whether a publish with a cancel token published every queued event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PublishStatus {
+    /// Every queued event was published.
+    Completed,
+    /// The publish was cancelled, the events that were not published yet stay queued.
+    Cancelled { remaining: usize },
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Publish With Cancellation
+    ///
+    /// Publishes each event like `publish`, until the token is cancelled.
+    /// The token is checked before each message, so a subscriber is never interrupted.
+    /// Once cancelled, the events that were not published stay queued for the next publish,
+    /// and debounced deliveries wait for the next publish as well.
+    pub fn publish_with_cancellation(&self, token: &CancelToken) -> Result<PublishStatus, String> {
+        self.publish_cycle(&Halt::token(token))?;
+        if !token.is_cancelled() {
+            return Ok(PublishStatus::Completed);
+        }
+        let remaining = self.state.borrow().events.values().map(Vec::len).sum();
+        Ok(PublishStatus::Cancelled { remaining })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use super::{CancelToken, PublishStatus};
+    use crate::{Event, EventBus, Subscriber};
+
+    struct CancellingSubscriber {
+        token: CancelToken,
+        received: Rc<RefCell<Vec<u32>>>,
+    }
+
+    impl Subscriber for CancellingSubscriber {
+        fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+            self.received.borrow_mut().push(*event.get_data::<u32>().unwrap());
+            self.token.cancel();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_cancel_leaves_the_rest_queued() {
+        let token = CancelToken::new();
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .subscribe_listener("frames", CancellingSubscriber { token: token.clone(), received: received.clone() })
+            .subscribe_listener("audio", CancellingSubscriber { token: token.clone(), received: received.clone() })
+            .register_all([("frames", 1u32), ("frames", 2), ("frames", 3), ("audio", 4)].map(|(topic, n)| (topic.to_string(), n)));
+
+        assert_eq!(Ok(PublishStatus::Cancelled { remaining: 3 }), event_bus.publish_with_cancellation(&token));
+        assert_eq!(1, received.borrow().len());
+
+        assert_eq!(Ok(()), event_bus.publish());
+        let mut received = received.borrow().clone();
+        received.sort();
+        assert_eq!(vec![1, 2, 3, 4], received);
+    }
+}
@@ -0,0 +1,143 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use super::{EventBus, TopicKey};
+use super::pattern::PatternSubscription;
+use super::state::BusState;
+use super::subscription::{release, Subscription};
+
+/// The subscriptions of an ancestor of a child event bus to an event name,
+/// taken out of the ancestor while the child publishes the event name.
+pub(crate) struct Inherited<K: TopicKey> {
+    state: Rc<RefCell<BusState<K>>>,
+    pub(crate) subscriptions: Vec<Subscription>,
+    pub(crate) patterns: Vec<PatternSubscription>,
+}
+
+impl<K: TopicKey> BusState<K> {
+    /// Takes the subscriptions of the ancestors to the event name out, the parent first.
+    pub(crate) fn take_inherited(&self, event_name: &K) -> Vec<Inherited<K>> {
+        let mut inherited = Vec::new();
+        let mut parent = self.parent.clone();
+        while let Some(state) = parent {
+            let (subscriptions, patterns, grandparent) = {
+                let mut ancestor = state.borrow_mut();
+                let subscriptions = ancestor.subscribers.remove(event_name).unwrap_or_default();
+                (subscriptions, std::mem::take(&mut ancestor.pattern_subscriptions), ancestor.parent.clone())
+            };
+            inherited.push(Inherited { state, subscriptions, patterns });
+            parent = grandparent;
+        }
+        inherited
+    }
+}
+
+/// Puts the subscriptions taken out of the ancestors back, removing the ones that unsubscribed.
+pub(crate) fn restore_inherited<K: TopicKey>(event_name: &K, inherited: Vec<Inherited<K>>) {
+    for Inherited { state, mut subscriptions, mut patterns } in inherited {
+        let mut ancestor = state.borrow_mut();
+        let removed = ancestor.remove_unsubscribed(event_name, &mut subscriptions, &mut patterns);
+        ancestor.restore_subscriptions(event_name.clone(), subscriptions, patterns);
+        drop(ancestor);
+        release(removed);
+    }
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Child Of
+    ///
+    /// Creates an event bus whose events are delivered to its own subscribers first,
+    /// and then to the subscribers of the parent for the same event name, and so on up to the root.
+    /// The events of the parent are not delivered to the subscribers of the child.
+    /// The child starts with the clock and the logger of the parent, its other settings are its own:
+    /// the error policy of the child decides what happens when a subscriber of the parent fails.
+    ///
+    /// While the parent publishes an event name, its subscribers to that event name
+    /// are not reached by a child publishing from within one of them, and the other way around.
+    pub fn child_of(parent: &EventBus<K>) -> EventBus<K> {
+        let child = EventBus::default();
+        {
+            let parent_state = parent.state.borrow();
+            let mut state = child.state.borrow_mut();
+            state.clock = parent_state.clock.clone();
+            state.logger = parent_state.logger.clone();
+            state.parent = Some(parent.state.clone());
+        }
+        child
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use crate::{ErrorPolicy, Event, EventBus, Subscriber};
+
+    struct NamedSubscriber {
+        name: &'static str,
+        log: Rc<RefCell<Vec<&'static str>>>,
+        fail: bool,
+    }
+
+    impl Subscriber for NamedSubscriber {
+        fn on_event(&mut self, _event: &mut Event) -> Result<(), String> {
+            self.log.borrow_mut().push(self.name);
+            if self.fail {
+                return Err(format!("{} failed", self.name));
+            }
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            self.name
+        }
+    }
+
+    fn named(name: &'static str, log: &Rc<RefCell<Vec<&'static str>>>, fail: bool) -> NamedSubscriber {
+        NamedSubscriber { name, log: log.clone(), fail }
+    }
+
+    #[test]
+    fn test_child_falls_through_to_parent() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let app = EventBus::new();
+        app.subscribe_listener("close", named("app", &log, false));
+        let dialog = EventBus::child_of(&app);
+        dialog
+            .subscribe_listener("close", named("dialog", &log, false))
+            .register("close", 1u32);
+
+        assert_eq!(vec!["dialog", "app"], dialog.publish_dry_run().deliveries[0].subscribers);
+        assert_eq!(Ok(()), dialog.publish());
+        assert_eq!(vec!["dialog", "app"], *log.borrow());
+        assert_eq!(0, app.pending(&"close".to_string()));
+    }
+
+    #[test]
+    fn test_parent_does_not_reach_child() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let app = EventBus::new();
+        app.subscribe_listener("close", named("app", &log, false));
+        let dialog = EventBus::child_of(&app);
+        dialog.subscribe_listener("close", named("dialog", &log, false));
+
+        assert_eq!(Ok(()), app.register("close", 1u32).publish());
+        assert_eq!(vec!["app"], *log.borrow());
+    }
+
+    #[test]
+    fn test_parent_failure_follows_child_policy() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let app = EventBus::new();
+        app
+            .set_error_policy(ErrorPolicy::Continue)
+            .subscribe_listener("close", named("app", &log, true));
+        let dialog = EventBus::child_of(&app);
+        dialog.subscribe_listener("close", named("dialog", &log, false));
+
+        assert_eq!(Err("app failed".to_string()), dialog.register("close", 1u32).publish());
+
+        dialog.set_error_policy(ErrorPolicy::Continue);
+        assert_eq!(Ok(()), dialog.register("close", 2u32).publish());
+        assert_eq!(vec!["dialog", "app", "dialog", "app"], *log.borrow());
+    }
+}
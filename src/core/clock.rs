@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+/// # Instant
+///
+/// A point in time read from a `Clock`. Outside the browser this is `std::time::Instant`.
+#[cfg(not(target_arch = "wasm32"))]
+pub use std::time::Instant;
+
+/// # Instant
+///
+/// A point in time read from a `Clock`, with the methods of `std::time::Instant`.
+/// In the browser `std::time::Instant::now` panics, so the time is read from JavaScript instead:
+/// `performance.now()` when the global object has one, otherwise `Date.now()`.
+#[cfg(target_arch = "wasm32")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Instant(Duration);
+
+#[cfg(target_arch = "wasm32")]
+impl Instant {
+    /// Returns the current time.
+    pub fn now() -> Instant {
+        Instant(Duration::from_secs_f64(browser_millis().max(0.0) / 1000.0))
+    }
+
+    /// Returns the time passed since the earlier instant, zero if it is later.
+    pub fn duration_since(&self, earlier: Instant) -> Duration {
+        self.saturating_duration_since(earlier)
+    }
+
+    /// Returns the time passed since the earlier instant, `None` if it is later.
+    pub fn checked_duration_since(&self, earlier: Instant) -> Option<Duration> {
+        self.0.checked_sub(earlier.0)
+    }
+
+    /// Returns the time passed since the earlier instant, zero if it is later.
+    pub fn saturating_duration_since(&self, earlier: Instant) -> Duration {
+        self.0.saturating_sub(earlier.0)
+    }
+
+    /// Returns the time passed since this instant.
+    pub fn elapsed(&self) -> Duration {
+        Instant::now().saturating_duration_since(*self)
+    }
+
+    /// Returns the instant the duration later, `None` if it cannot be represented.
+    pub fn checked_add(&self, duration: Duration) -> Option<Instant> {
+        self.0.checked_add(duration).map(Instant)
+    }
+
+    /// Returns the instant the duration earlier, `None` if it is before the time origin.
+    pub fn checked_sub(&self, duration: Duration) -> Option<Instant> {
+        self.0.checked_sub(duration).map(Instant)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl std::ops::Add<Duration> for Instant {
+    type Output = Instant;
+
+    fn add(self, duration: Duration) -> Instant {
+        self.checked_add(duration).expect("overflow when adding duration to instant")
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl std::ops::AddAssign<Duration> for Instant {
+    fn add_assign(&mut self, duration: Duration) {
+        *self = *self + duration;
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl std::ops::Sub<Duration> for Instant {
+    type Output = Instant;
+
+    fn sub(self, duration: Duration) -> Instant {
+        self.checked_sub(duration).expect("overflow when subtracting duration from instant")
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl std::ops::SubAssign<Duration> for Instant {
+    fn sub_assign(&mut self, duration: Duration) {
+        *self = *self - duration;
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl std::ops::Sub<Instant> for Instant {
+    type Output = Duration;
+
+    fn sub(self, earlier: Instant) -> Duration {
+        self.duration_since(earlier)
+    }
+}
+
+/// Returns the milliseconds since the time origin of the page, or since the Unix epoch
+/// when the global object has no `performance`.
+#[cfg(target_arch = "wasm32")]
+fn browser_millis() -> f64 {
+    use js_sys::wasm_bindgen::{JsCast, JsValue};
+    use js_sys::{Function, Reflect};
+
+    let performance = Reflect::get(&js_sys::global(), &JsValue::from_str("performance")).ok()
+        .filter(|performance| performance.is_object());
+    performance
+        .and_then(|performance| {
+            let now = Reflect::get(&performance, &JsValue::from_str("now")).ok()?.dyn_into::<Function>().ok()?;
+            now.call0(&performance).ok()?.as_f64()
+        })
+        .unwrap_or_else(js_sys::Date::now)
+}
+
+/// # Clock
+///
+/// The source of time of an event bus.
+/// Every time-based feature of the event bus reads the time from its clock,
+/// so tests can replace it with a `testing::ManualClock`.
+pub trait Clock: Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> Instant;
+
+    /// Waits until the duration has passed on this clock.
+    /// The browser cannot block its thread, so run loops that sleep are not supported there.
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// # System Clock
+///
+/// The clock an event bus uses by default, reading the time from the system,
+/// or from JavaScript in the browser, see `Instant`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
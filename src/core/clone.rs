@@ -0,0 +1,141 @@
+use std::any::{Any, TypeId};
+use std::cell::Ref;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use super::{Event, EventBus, TopicKey};
+
+type CloneData = fn(&dyn Any) -> Box<dyn Any>;
+
+/// # Clone Registry
+///
+/// Knows how to clone the payloads of events, as a boxed payload cannot be cloned by itself.
+/// Events are cloned with `Event::try_clone`.
+///
+/// ## Methods
+///
+/// * `register` - Registers a payload type that can be cloned.
+///
+/// * `is_cloneable` - Returns whether a payload type can be cloned.
+#[derive(Default)]
+pub struct CloneRegistry {
+    clones: HashMap<TypeId, CloneData>,
+}
+
+impl CloneRegistry {
+    /// # New
+    ///
+    /// Creates an empty clone registry.
+    pub fn new() -> CloneRegistry {
+        CloneRegistry::default()
+    }
+
+    /// # Register
+    ///
+    /// Registers payloads of type `T` as cloneable.
+    pub fn register<T: Clone + 'static>(&mut self) -> &mut Self {
+        self.clones.insert(TypeId::of::<T>(), |data| Box::new(data.downcast_ref::<T>().unwrap().clone()));
+        self
+    }
+
+    /// # Is Cloneable
+    ///
+    /// Returns whether payloads of type `T` are registered as cloneable.
+    pub fn is_cloneable<T: 'static>(&self) -> bool {
+        self.clones.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Returns a clone of the payload, or `None` if its type is not registered.
+    pub(crate) fn clone_data(&self, data: &dyn Any) -> Option<Box<dyn Any>> {
+        self.clones.get(&data.type_id()).map(|clone| clone(data))
+    }
+
+    /// Returns whether the payload of the event can be cloned.
+    pub(crate) fn can_clone(&self, event: &Event) -> bool {
+        let data: &dyn Any = &*event.data;
+        self.clones.contains_key(&data.type_id())
+    }
+}
+
+/// # Not Cloneable
+///
+/// The error returned when an event is cloned whose payload type is not registered as cloneable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotCloneable {
+    /// The type name of the payload.
+    pub type_name: &'static str,
+}
+
+impl fmt::Display for NotCloneable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Payloads of type {} are not registered as cloneable", self.type_name)
+    }
+}
+
+impl Error for NotCloneable {}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Register Cloneable
+    ///
+    /// Registers payloads of type `T` as cloneable, so events holding them can be cloned
+    /// with `Event::try_clone` and the clone registry of the event bus.
+    pub fn register_cloneable<T: Clone + 'static>(&self) -> &Self {
+        self.state.borrow_mut().cloneables.register::<T>();
+        self
+    }
+
+    /// # Cloneables
+    ///
+    /// Returns the clone registry of the event bus.
+    pub fn cloneables(&self) -> Ref<'_, CloneRegistry> {
+        Ref::map(self.state.borrow(), |state| &state.cloneables)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+    use super::NotCloneable;
+    use crate::{Event, EventBus, Subscriber};
+
+    struct RenamingSubscriber {
+        received: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Subscriber for RenamingSubscriber {
+        fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+            let name = event.data.downcast_mut::<String>().ok_or("Expected a name")?;
+            name.push_str(" (renamed)");
+            self.received.borrow_mut().push(name.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_clone_is_independent_of_original() {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .register_cloneable::<String>()
+            .subscribe_listener("player", RenamingSubscriber { received: received.clone() });
+        let original = Event::new("John".to_string()).with_schema_version(2).with_ttl(Duration::from_secs(5));
+
+        let clone = original.try_clone(&event_bus.cloneables()).unwrap();
+        assert_ne!(original.id(), clone.id());
+        assert_eq!(Some(2), clone.schema_version());
+        assert_eq!(Some(Duration::from_secs(5)), clone.ttl());
+
+        assert_eq!(Ok(()), event_bus.register("player", clone).publish());
+        assert_eq!(vec!["John (renamed)".to_string()], *received.borrow());
+        assert_eq!(Some(&"John".to_string()), original.get_data::<String>());
+    }
+
+    #[test]
+    fn test_unregistered_payload_is_not_cloneable() {
+        let event_bus = EventBus::new();
+        let result = Event::new(42u32).try_clone(&event_bus.cloneables());
+        assert_eq!(Some(NotCloneable { type_name: "u32" }), result.err());
+    }
+}
@@ -0,0 +1,124 @@
+use std::any::{type_name, Any};
+use super::{AlreadyPublishing, EventBus, EventContext, IntoEvent, Phase, PublishReport, TopicKey};
+use super::cancel::Halt;
+use super::event_bus::PublishingGuard;
+use super::state::BusState;
+
+/// A response of a subscriber: its name, the type name of the response and the response.
+pub(crate) type Response = (String, &'static str, Box<dyn Any>);
+
+impl<K: TopicKey> BusState<K> {
+    /// Keeps the responses the subscriber gave through the context, while `publish_collect` delivers its event.
+    pub(crate) fn collect_responses(&mut self, subscriber: &str, context: &mut EventContext) {
+        let responses = std::mem::take(&mut context.responses);
+        if let Some(collected) = &mut self.responses {
+            collected.extend(responses.into_iter().map(|(response_type, response)| (subscriber.to_string(), response_type, response)));
+        }
+    }
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Publish Collect
+    ///
+    /// Publishes a single event right away to the subscribers of the event name, like `publish` would,
+    /// and returns the responses of type `R` they gave with `EventContext::respond`, in the order the subscribers ran.
+    /// Subscribers that did not respond are left out, so the result can be empty.
+    /// A response of another type is a failure of its subscriber, logged and routed like any other failure,
+    /// and left out as well. The other queued events are not published.
+    ///
+    /// Returns the error of a subscriber that aborts the publish, or `AlreadyPublishing`
+    /// when called from within a subscriber.
+    pub fn publish_collect<R: 'static>(&self, event_name: impl Into<K>, message: impl IntoEvent) -> Result<Vec<R>, String> {
+        if self.publishing.replace(true) {
+            return Err(AlreadyPublishing.into());
+        }
+        let guard = PublishingGuard(&self.publishing);
+        let event_name = self.topic_key(event_name);
+        let message = message.into_event();
+        let event_id = message.id();
+        self.state.borrow_mut().responses = Some(Vec::new());
+        let result = self.publish_events([(event_name.clone(), vec![message])], &mut PublishReport::default(), &Halt::default());
+        drop(guard);
+        let mut state = self.state.borrow_mut();
+        let responses = state.responses.take().unwrap_or_default();
+        result?;
+        let mut collected = Vec::new();
+        for (subscriber, response_type, response) in responses {
+            match response.downcast::<R>() {
+                Ok(response) => collected.push(*response),
+                Err(_) => {
+                    let message = format!("Responded with {} rather than {}", response_type, type_name::<R>());
+                    state.logger.subscriber_error(&event_name, &subscriber, Phase::Event, &message);
+                    state.route_error(&event_name, &subscriber, Phase::Event, &message, event_id);
+                }
+            }
+        }
+        Ok(collected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Event, EventBus, EventContext, Outcome, Subscriber, SubscriberFailure};
+
+    struct Validator {
+        name: &'static str,
+        objections: Option<Vec<&'static str>>,
+    }
+
+    impl Subscriber for Validator {
+        fn on_event_with_context(&mut self, _event: &mut Event, context: &mut EventContext) -> Outcome {
+            if let Some(objections) = &self.objections {
+                context.respond(objections.clone());
+            }
+            Outcome::Ack
+        }
+
+        fn name(&self) -> &str {
+            self.name
+        }
+    }
+
+    struct LegacyValidator;
+
+    impl Subscriber for LegacyValidator {
+        fn on_event_with_context(&mut self, _event: &mut Event, context: &mut EventContext) -> Outcome {
+            context.respond("objection");
+            Outcome::Ack
+        }
+
+        fn name(&self) -> &str {
+            "legacy"
+        }
+    }
+
+    #[test]
+    fn test_responses_are_collected_in_dispatch_order() {
+        let event_bus = EventBus::new();
+        event_bus
+            .subscribe_listener("validate_action", Validator { name: "budget", objections: Some(vec!["over budget"]) })
+            .subscribe_listener("validate_action", Validator { name: "audit", objections: None })
+            .subscribe_listener("validate_action", Validator { name: "policy", objections: Some(vec!["needs approval", "weekend"]) });
+
+        let objections = event_bus.publish_collect::<Vec<&str>>("validate_action", "delete account".to_string()).unwrap();
+        assert_eq!(vec![vec!["over budget"], vec!["needs approval", "weekend"]], objections);
+    }
+
+    #[test]
+    fn test_wrong_typed_response_is_a_failure() {
+        let event_bus = EventBus::new();
+        let (collector, failures) = crate::subscribers::CollectingSubscriber::<SubscriberFailure>::new();
+        event_bus
+            .route_errors_to("errors")
+            .subscribe_listener("errors", collector)
+            .subscribe_listener("validate_action", LegacyValidator)
+            .subscribe_listener("validate_action", Validator { name: "budget", objections: Some(vec!["over budget"]) });
+
+        let objections = event_bus.publish_collect::<Vec<&str>>("validate_action", 1u32).unwrap();
+        assert_eq!(vec![vec!["over budget"]], objections);
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(1, failures.borrow().len());
+        assert_eq!("legacy", failures.borrow()[0].subscriber);
+        assert!(failures.borrow()[0].message.starts_with("Responded with &str rather than"));
+    }
+}
@@ -0,0 +1,166 @@
+use std::any::{type_name, Any};
+use std::rc::Rc;
+use super::{Event, Instant, IntoEvent, TopicKey};
+use super::state::BusState;
+use super::topic::topic_from_str;
+
+/// # Event Context
+///
+/// Gives subscribers access to the application context of the event bus, set with `set_context`,
+/// like a database pool or an asset cache. Every subscriber receives the same context.
+/// It is handed to `Subscriber::on_event_with_context`.
+///
+/// ## Methods
+///
+/// * `context` - Returns the application context, when it has the requested type.
+///
+/// * `topic` - Returns the event name of the event being delivered.
+///
+/// * `now` - Returns the time the delivery of the event name started.
+///
+/// * `emit` - Registers an event once the subscriber returns.
+///
+/// * `respond` - Answers the event delivered by `publish_collect`.
+pub struct EventContext {
+    context: Option<Rc<dyn Any>>,
+    topic: String,
+    now: Instant,
+    /// The events emitted by the subscriber, registered once it returns.
+    emitted: Vec<(String, Event)>,
+    /// The responses of the subscriber, with their type name.
+    pub(crate) responses: Vec<(&'static str, Box<dyn Any>)>,
+}
+
+impl EventContext {
+    pub(crate) fn new(context: Option<Rc<dyn Any>>, topic: String, now: Instant) -> EventContext {
+        EventContext { context, topic, now, emitted: Vec::new(), responses: Vec::new() }
+    }
+
+    /// # Respond
+    ///
+    /// Answers the event being delivered, for the caller of `publish_collect`.
+    /// A subscriber can respond more than once. Responses to events delivered by a regular publish,
+    /// and of a subscriber on a worker thread, are dropped.
+    pub fn respond<T: 'static>(&mut self, value: T) {
+        self.responses.push((type_name::<T>(), Box::new(value)));
+    }
+
+    /// # Emit
+    ///
+    /// Registers an event on the event bus once the subscribers of the current event returned,
+    /// so a subscriber can produce events without holding on to the event bus.
+    /// The events are published by the next publish. On an event bus whose event names are not strings,
+    /// and for a subscriber on a worker thread, the emitted events are dropped.
+    pub fn emit(&mut self, topic: impl Into<String>, event: impl IntoEvent) {
+        self.emitted.push((topic.into(), event.into_event()));
+    }
+
+    /// # Context
+    ///
+    /// Returns the application context of the event bus,
+    /// or `None` when no context is set or it has another type.
+    /// Use a `RefCell` or `Cell` inside the context for the parts subscribers change.
+    pub fn context<T: 'static>(&self) -> Option<&T> {
+        self.context.as_deref().and_then(|context| context.downcast_ref::<T>())
+    }
+
+    /// # Topic
+    ///
+    /// Returns the event name of the event being delivered, as text.
+    /// Event names that are not strings are rendered with their `Debug` format.
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    /// # Now
+    ///
+    /// Returns the time the delivery of the event name started, on the clock of the event bus,
+    /// or on the system clock for a subscriber on a worker thread.
+    /// Compare it with `Event::registered_at` for the age of the event.
+    pub fn now(&self) -> Instant {
+        self.now
+    }
+}
+
+impl<K: TopicKey> BusState<K> {
+    /// Registers the events emitted through the context.
+    pub(crate) fn register_emitted(&mut self, context: &mut EventContext) {
+        context.responses.clear();
+        for (topic, message) in std::mem::take(&mut context.emitted) {
+            if let Some(topic) = topic_from_str::<K>(&topic) {
+                let topic = self.normalize(topic);
+                self.register(topic, message);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use crate::{ErrorPolicy, Event, EventBus, EventContext, Outcome, Subscriber};
+
+    struct AssetCache {
+        lookups: RefCell<u32>,
+    }
+
+    struct LookupSubscriber;
+
+    impl Subscriber for LookupSubscriber {
+        fn on_event_with_context(&mut self, _event: &mut Event, context: &mut EventContext) -> Outcome {
+            match context.context::<AssetCache>() {
+                Some(cache) => {
+                    *cache.lookups.borrow_mut() += 1;
+                    Outcome::Ack
+                }
+                None => Outcome::Error("No asset cache in the context".to_string()),
+            }
+        }
+    }
+
+    struct PreloadSubscriber;
+
+    impl Subscriber for PreloadSubscriber {
+        fn on_event_with_context(&mut self, _event: &mut Event, context: &mut EventContext) -> Outcome {
+            let cache = context.context::<AssetCache>().unwrap();
+            *cache.lookups.borrow_mut() += 10;
+            Outcome::Ack
+        }
+    }
+
+    struct NumberContextSubscriber;
+
+    impl Subscriber for NumberContextSubscriber {
+        fn on_event_with_context(&mut self, _event: &mut Event, context: &mut EventContext) -> Outcome {
+            Outcome::Error(format!("{:?}", context.context::<u32>()))
+        }
+    }
+
+    #[test]
+    fn test_subscribers_share_the_context() {
+        let event_bus = EventBus::new();
+        event_bus
+            .set_context(Box::new(AssetCache { lookups: RefCell::new(0) }))
+            .subscribe_listener("load", LookupSubscriber)
+            .subscribe_listener("preload", PreloadSubscriber)
+            .register("load", 1u32)
+            .register("preload", 2u32)
+            .register("load", 3u32);
+
+        assert_eq!(Ok(()), event_bus.publish());
+        let lookups = *event_bus.state.borrow().context.as_ref().unwrap().downcast_ref::<AssetCache>().unwrap().lookups.borrow();
+        assert_eq!(12, lookups);
+    }
+
+    #[test]
+    fn test_context_of_another_type() {
+        let event_bus = EventBus::new();
+        event_bus
+            .set_error_policy(ErrorPolicy::Abort)
+            .set_context(Box::new("not a number"))
+            .subscribe_listener("load", NumberContextSubscriber)
+            .register("load", 1u32);
+
+        assert_eq!(Err("None".to_string()), event_bus.publish());
+    }
+}
@@ -0,0 +1,117 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::rc::Rc;
+use super::{EventBus, TopicKey};
+
+type Convert = Rc<dyn Fn(&dyn Any) -> Result<Box<dyn Any>, String>>;
+
+/// # Converter Registry
+///
+/// Holds the converters between payload types, so a subscriber expecting one type
+/// can receive events holding another. It is shared with the events registered on the event bus,
+/// and copied when a converter is registered while events still hold it.
+#[derive(Default, Clone)]
+pub(crate) struct ConverterRegistry {
+    converters: HashMap<(TypeId, TypeId), Convert>,
+}
+
+impl ConverterRegistry {
+    pub(crate) fn register<T: 'static, U: 'static>(&mut self, convert: impl Fn(&T) -> Result<U, String> + 'static) {
+        let convert: Convert = Rc::new(move |data| Ok(Box::new(convert(data.downcast_ref::<T>().unwrap())?)));
+        self.converters.insert((TypeId::of::<T>(), TypeId::of::<U>()), convert);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.converters.is_empty()
+    }
+
+    /// Converts the data to `U`, or returns `None` when no converter is registered from its type.
+    pub(crate) fn convert<U: 'static>(&self, data: &dyn Any) -> Option<Result<Box<dyn Any>, String>> {
+        self.converters.get(&(data.type_id(), TypeId::of::<U>())).map(|convert| convert(data))
+    }
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Register Converter
+    ///
+    /// Registers a converter from payloads of type `T` to `U`. When `Event::try_get_data` or the subscriber
+    /// of `subscribe_event` asks for a `U` while the event holds a `T`, the payload is converted once
+    /// and the result is kept on the event. A converter that fails makes the payload unavailable as a `U`,
+    /// with an error naming both types. It applies to the events registered after it.
+    pub fn register_converter<T: 'static, U: 'static>(&self, convert: impl Fn(&T) -> Result<U, String> + 'static) -> &Self {
+        Rc::make_mut(&mut self.state.borrow_mut().converters).register(convert);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::type_name;
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+    use crate::{define_event, ErrorPolicy, EventBus, Subscriber};
+
+    define_event!(Command { name: String, args: Vec<String> } => "commands");
+
+    fn parse(line: &str) -> Result<Command, String> {
+        let mut words = line.split_whitespace().map(str::to_string);
+        let name = words.next().ok_or("Empty command")?;
+        Ok(Command { name, args: words.collect() })
+    }
+
+    #[test]
+    fn test_string_is_converted_for_typed_subscriber() {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let store = received.clone();
+        let event_bus = EventBus::new();
+        event_bus
+            .register_converter(|line: &String| parse(line))
+            .subscribe_event(move |command: &mut Command| {
+                store.borrow_mut().push((command.name.clone(), command.args.len()));
+                Ok(())
+            });
+
+        assert_eq!(Ok(()), event_bus.register("commands", "move north 2".to_string()).publish());
+        assert_eq!(vec![("move".to_string(), 2)], *received.borrow());
+    }
+
+    #[test]
+    fn test_converted_payload_is_kept_on_the_event() {
+        let calls = Rc::new(Cell::new(0));
+        let counted = calls.clone();
+        let event_bus = EventBus::new();
+        event_bus
+            .register_converter(move |value: &u32| {
+                counted.set(counted.get() + 1);
+                Ok(value.to_string())
+            })
+            .subscribe_listener("tick", <dyn Subscriber>::builder().on_event(|event| {
+                assert_eq!(Ok(&"7".to_string()), event.try_get_data::<String>());
+                assert_eq!(Ok(&"7".to_string()), event.try_get_data::<String>());
+                assert_eq!(Ok(&7u32), event.try_get_data::<u32>());
+                assert!(event.try_get_data::<u64>().is_err());
+                Ok(())
+            }).build());
+
+        assert_eq!(Ok(()), event_bus.register("tick", 7u32).publish());
+        assert_eq!(1, calls.get());
+    }
+
+    #[test]
+    fn test_failed_conversion_follows_error_policy() {
+        let event_bus = EventBus::new();
+        event_bus
+            .set_error_policy(ErrorPolicy::Continue)
+            .register_converter(|line: &String| parse(line))
+            .subscribe_event(|_: &mut Command| Ok(()));
+
+        assert_eq!(Ok(()), event_bus.register("commands", "   ".to_string()).publish());
+        assert_eq!(1, event_bus.metrics().subscriber_errors(&"commands".to_string(), type_name::<Command>()));
+
+        event_bus.set_error_policy(ErrorPolicy::Abort).register("commands", "   ".to_string());
+        assert_eq!(
+            Err(format!("Could not convert event data of type alloc::string::String to {}: Empty command", type_name::<Command>())),
+            event_bus.publish()
+        );
+    }
+}
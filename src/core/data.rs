@@ -0,0 +1,227 @@
+use std::any::{Any, TypeId};
+use std::fmt;
+use std::mem::{align_of, size_of, ManuallyDrop, MaybeUninit};
+use std::ops::{Deref, DerefMut};
+use std::ptr;
+
+/// The number of words of data that are stored in the event itself instead of on the heap.
+const INLINE_WORDS: usize = 3;
+
+/// # Event Data
+///
+/// The data held by an event. Data of at most three words, aligned to at most a word,
+/// like numbers, small enums and `String`, is stored in the event itself, larger data is boxed.
+/// It dereferences to `dyn Any`, so it is read like a `Box<dyn Any>`.
+///
+/// ## Methods
+///
+/// * `new` - Stores the data, inline when it fits.
+///
+/// * `is_inline` - Returns whether the data is stored in the event itself.
+///
+/// * `is`, `downcast_ref`, `downcast_mut` - Like the methods of `dyn Any`, without an indirect call for inline data.
+///
+/// * `downcast` - Returns the data by value, when it is of type `T`.
+pub struct EventData {
+    storage: Storage,
+}
+
+enum Storage {
+    /// The data, its type id so it is downcast without an indirect call,
+    /// and the function that turns a pointer to it into a pointer to `dyn Any`.
+    Inline {
+        buffer: MaybeUninit<[usize; INLINE_WORDS]>,
+        type_id: TypeId,
+        as_any: fn(*const u8) -> *const dyn Any,
+    },
+    Boxed(Box<dyn Any>),
+}
+
+fn as_any<T: Any>(data: *const u8) -> *const dyn Any {
+    data as *const T as *const dyn Any
+}
+
+impl EventData {
+    /// # New
+    ///
+    /// Stores the data, inline when it fits.
+    pub fn new<T: Any>(data: T) -> EventData {
+        if size_of::<T>() > size_of::<[usize; INLINE_WORDS]>() || align_of::<T>() > align_of::<usize>() {
+            return EventData { storage: Storage::Boxed(Box::new(data)) };
+        }
+        let mut buffer = MaybeUninit::<[usize; INLINE_WORDS]>::uninit();
+        // SAFETY: The buffer is large enough and aligned enough for `T`, checked above.
+        unsafe { ptr::write(buffer.as_mut_ptr() as *mut T, data) };
+        EventData { storage: Storage::Inline { buffer, type_id: TypeId::of::<T>(), as_any: as_any::<T> } }
+    }
+
+    /// # Is
+    ///
+    /// Returns whether the data is of type `T`.
+    pub fn is<T: Any>(&self) -> bool {
+        match &self.storage {
+            Storage::Inline { type_id, .. } => *type_id == TypeId::of::<T>(),
+            Storage::Boxed(boxed) => boxed.is::<T>(),
+        }
+    }
+
+    /// # Downcast Ref
+    ///
+    /// Returns the data, when it is of type `T`.
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        match &self.storage {
+            // SAFETY: The buffer holds a `T`, as its type id is the type id of `T`.
+            Storage::Inline { buffer, type_id, .. } if *type_id == TypeId::of::<T>() => Some(unsafe { &*(buffer.as_ptr() as *const T) }),
+            Storage::Inline { .. } => None,
+            Storage::Boxed(boxed) => boxed.downcast_ref::<T>(),
+        }
+    }
+
+    /// # Downcast Mut
+    ///
+    /// Returns the data mutably, when it is of type `T`.
+    pub fn downcast_mut<T: Any>(&mut self) -> Option<&mut T> {
+        match &mut self.storage {
+            // SAFETY: The buffer holds a `T`, as its type id is the type id of `T`.
+            Storage::Inline { buffer, type_id, .. } if *type_id == TypeId::of::<T>() => Some(unsafe { &mut *(buffer.as_mut_ptr() as *mut T) }),
+            Storage::Inline { .. } => None,
+            Storage::Boxed(boxed) => boxed.downcast_mut::<T>(),
+        }
+    }
+
+    /// # Is Inline
+    ///
+    /// Returns whether the data is stored in the event itself, instead of on the heap.
+    pub fn is_inline(&self) -> bool {
+        matches!(self.storage, Storage::Inline { .. })
+    }
+
+    /// # Downcast
+    ///
+    /// Returns the data by value, or the data itself when it is not of type `T`.
+    pub fn downcast<T: Any>(self) -> Result<T, EventData> {
+        if !self.is::<T>() {
+            return Err(self);
+        }
+        let mut data = ManuallyDrop::new(self);
+        match &mut data.storage {
+            // SAFETY: The buffer holds a `T`, checked above. It is moved out once,
+            // as the data is not dropped after.
+            Storage::Inline { buffer, .. } => Ok(unsafe { ptr::read(buffer.as_ptr() as *const T) }),
+            Storage::Boxed(boxed) => {
+                // SAFETY: The box is moved out once, as the data is not dropped after.
+                let boxed = unsafe { ptr::read(boxed) };
+                Ok(*boxed.downcast::<T>().unwrap())
+            }
+        }
+    }
+}
+
+impl Deref for EventData {
+    type Target = dyn Any;
+
+    fn deref(&self) -> &dyn Any {
+        match &self.storage {
+            // SAFETY: The buffer holds the data `as_any` was created for.
+            Storage::Inline { buffer, as_any, .. } => unsafe { &*as_any(buffer.as_ptr() as *const u8) },
+            Storage::Boxed(boxed) => &**boxed,
+        }
+    }
+}
+
+impl DerefMut for EventData {
+    fn deref_mut(&mut self) -> &mut dyn Any {
+        match &mut self.storage {
+            // SAFETY: The buffer holds the data `as_any` was created for, and the pointer is derived
+            // from the mutable borrow of the buffer.
+            Storage::Inline { buffer, as_any, .. } => unsafe { &mut *(as_any(buffer.as_mut_ptr() as *const u8) as *mut dyn Any) },
+            Storage::Boxed(boxed) => &mut **boxed,
+        }
+    }
+}
+
+impl Drop for EventData {
+    fn drop(&mut self) {
+        if let Storage::Inline { buffer, as_any, .. } = &mut self.storage {
+            // SAFETY: The buffer holds the data `as_any` was created for, and it is dropped once.
+            unsafe { ptr::drop_in_place(as_any(buffer.as_mut_ptr() as *const u8) as *mut dyn Any) };
+        }
+    }
+}
+
+impl From<Box<dyn Any>> for EventData {
+    fn from(data: Box<dyn Any>) -> Self {
+        EventData { storage: Storage::Boxed(data) }
+    }
+}
+
+impl fmt::Debug for EventData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventData").field("inline", &self.is_inline()).finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::Any;
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use super::EventData;
+    use crate::Event;
+
+    struct DropCounter(Rc<Cell<u32>>);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn test_small_data_is_inline() {
+        assert!(EventData::new(42u32).is_inline());
+        assert!(EventData::new(()).is_inline());
+        assert!(EventData::new("text".to_string()).is_inline());
+        assert!(!EventData::new([0u64; 4]).is_inline());
+        assert!(!EventData::new(1u128).is_inline());
+    }
+
+    #[test]
+    fn test_inline_data_reads_and_writes() {
+        let mut data = EventData::new(41u32);
+        *data.downcast_mut::<u32>().unwrap() += 1;
+        assert_eq!(Some(&42u32), data.downcast_ref::<u32>());
+        assert_eq!(None, data.downcast_ref::<u64>());
+        let any: &mut dyn Any = &mut *data;
+        *any.downcast_mut::<u32>().unwrap() += 1;
+        assert_eq!(Some(&43u32), (*data).downcast_ref::<u32>());
+        let data = data.downcast::<u64>().unwrap_err();
+        assert_eq!(Some(43u32), data.downcast::<u32>().ok());
+    }
+
+    #[test]
+    fn test_inline_and_boxed_data_drop_once() {
+        let drops = Rc::new(Cell::new(0));
+        drop(EventData::new(DropCounter(drops.clone())));
+        drop(EventData::new((DropCounter(drops.clone()), [0u64; 4])));
+        assert_eq!(2, drops.get());
+
+        let taken = EventData::new(DropCounter(drops.clone())).downcast::<DropCounter>().unwrap();
+        assert_eq!(2, drops.get());
+        drop(taken);
+        assert_eq!(3, drops.get());
+    }
+
+    #[test]
+    fn test_set_data_switches_between_inline_and_boxed() {
+        let mut event = Event::new(1u8);
+        assert!(event.data.is_inline());
+        event.set_data([1u32; 100]);
+        assert!(!event.data.is_inline());
+        assert_eq!(Some(&[1u32; 100]), event.get_data::<[u32; 100]>());
+        event.set_data("inline again".to_string());
+        assert!(event.data.is_inline());
+        assert_eq!(Ok(()), event.map_data(|text: String| [text.len() as u64; 4]));
+        assert_eq!(Some(&[12u64; 4]), event.get_data::<[u64; 4]>());
+    }
+}
@@ -0,0 +1,76 @@
+use std::fmt;
+use super::{Event, PayloadError};
+use super::TopicKey;
+
+/// # Dead Letter
+///
+/// An event that could not be delivered to its subscribers.
+/// Dead letters are kept by the event bus until they are taken
+/// out with `take_dead_letters`.
+///
+/// ## Fields
+///
+/// * `topic` - The name of the event the message was registered under.
+///
+/// * `event` - The event that could not be delivered.
+///
+/// * `reason` - Why the event could not be delivered.
+#[derive(Debug)]
+pub struct DeadLetter<K: TopicKey = String> {
+    /// The name of the event the message was registered under.
+    pub topic: K,
+    /// The event that could not be delivered.
+    pub event: Event,
+    /// Why the event could not be delivered.
+    pub reason: DeadLetterReason,
+}
+
+/// # Dead Letter Reason
+///
+/// Describes why an event ended up in the dead-letter queue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeadLetterReason {
+    /// No upgrade is registered for the schema version of the event.
+    MissingUpgrade { version: u32 },
+    /// An upgrade failed to transform the event.
+    UpgradeFailed { from_version: u32, message: String },
+    /// The payload of an event received from outside the process could not be decoded.
+    Payload(PayloadError),
+    /// A subscriber rejected the event without requeueing it.
+    Nacked,
+    /// The event was rejected again after reaching its maximum number of redeliveries.
+    MaxRedeliveries { redeliveries: u32 },
+    /// The event was deferred again after reaching the maximum number of deferrals.
+    MaxDeferrals { deferrals: u32 },
+    /// All subscribers ignored the event.
+    Unhandled,
+    /// The ttl of the event passed before it was published.
+    Expired,
+    /// The validator of the event name rejected the event.
+    Invalid { message: String },
+    /// The subscriber was skipped because its circuit breaker was open.
+    CircuitOpen { subscriber: String },
+}
+
+impl fmt::Display for DeadLetterReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeadLetterReason::MissingUpgrade { version } => {
+                write!(f, "No upgrade registered for schema version {}", version)
+            }
+            DeadLetterReason::UpgradeFailed { from_version, message } => {
+                write!(f, "Upgrade from schema version {} failed: {}", from_version, message)
+            }
+            DeadLetterReason::Payload(e) => e.fmt(f),
+            DeadLetterReason::Nacked => write!(f, "Rejected by a subscriber"),
+            DeadLetterReason::MaxRedeliveries { redeliveries } => {
+                write!(f, "Rejected after {} redeliveries", redeliveries)
+            }
+            DeadLetterReason::MaxDeferrals { deferrals } => write!(f, "Deferred after {} deferrals", deferrals),
+            DeadLetterReason::Unhandled => write!(f, "Ignored by all subscribers"),
+            DeadLetterReason::Expired => write!(f, "Expired before it was published"),
+            DeadLetterReason::Invalid { message } => write!(f, "Rejected by the validator: {}", message),
+            DeadLetterReason::CircuitOpen { subscriber } => write!(f, "Skipped by {} while its circuit was open", subscriber),
+        }
+    }
+}
@@ -0,0 +1,267 @@
+use super::{AlreadyPublishing, Event, EventBus, Instant, PublishReport, TopicKey};
+use super::cancel::Halt;
+use super::dispatch::{StepInfo, TopicDispatch};
+use super::event_bus::PublishingGuard;
+
+/// # Debug Session
+///
+/// A publish cycle that is stepped one subscriber call at a time, created with `EventBus::debug_session`,
+/// to follow how the events are dispatched. The session takes the queued events out when it starts,
+/// so events registered in between are left for the next publish, like they are during a publish.
+/// Between steps the event being delivered can be inspected, and changed, with `current_event`.
+/// Dropping the session before it finished queues the events that were not delivered yet again,
+/// including the event being delivered, which is delivered anew by the next publish.
+///
+/// ## Methods
+///
+/// * `step` - Makes the next subscriber call.
+///
+/// * `run_to_next_topic` - Makes the remaining subscriber calls of the event name being published.
+///
+/// * `current_event` - Returns the event being delivered.
+///
+/// * `finish` - Makes the remaining subscriber calls and completes the publish cycle.
+pub struct DebugSession<'a, K: TopicKey = String> {
+    event_bus: &'a EventBus<K>,
+    _guard: PublishingGuard<'a>,
+    started: Instant,
+    halt: Halt<'static>,
+    events: std::vec::IntoIter<(K, Vec<Event>)>,
+    dispatch: Option<TopicDispatch<K>>,
+    report: PublishReport<K>,
+    unpublished: Vec<(K, Vec<Event>)>,
+    /// The error of a subscriber that aborted the publish cycle.
+    error: Option<String>,
+}
+
+impl<'a, K: TopicKey> DebugSession<'a, K> {
+    /// # Step
+    ///
+    /// Calls the next subscriber method, and returns what it was called for and what it returned.
+    /// Returns `None` once every event was delivered, and the error of a subscriber that aborts the publish.
+    pub fn step(&mut self) -> Result<Option<StepInfo<K>>, String> {
+        self.advance(true)
+    }
+
+    /// # Run To Next Topic
+    ///
+    /// Makes the remaining subscriber calls of the event name being published, or of the next event name
+    /// when none is, and returns them. The next step starts on the following event name.
+    pub fn run_to_next_topic(&mut self) -> Result<Vec<StepInfo<K>>, String> {
+        let mut steps = Vec::new();
+        let mut start_next = self.dispatch.is_none();
+        while let Some(step) = self.advance(start_next)? {
+            steps.push(step);
+            start_next = false;
+        }
+        Ok(steps)
+    }
+
+    /// # Current Event
+    ///
+    /// Returns the event being delivered, or `None` before the first step of an event name
+    /// and once the session finished. Changes are seen by the subscriber calls that follow.
+    pub fn current_event(&mut self) -> Option<&mut Event> {
+        self.dispatch.as_mut()?.current_event()
+    }
+
+    /// # Finish
+    ///
+    /// Makes the remaining subscriber calls and completes the publish cycle as `publish_report` would,
+    /// delivering the debounced events and the meta events. Returns the report of the whole cycle,
+    /// or the error of a subscriber that aborted it.
+    pub fn finish(mut self) -> Result<PublishReport<K>, String> {
+        while self.advance(true)?.is_some() {}
+        let result = match self.error.take() {
+            Some(error) => Err(error),
+            None => self.event_bus.complete_cycle(std::mem::take(&mut self.report), &self.halt),
+        };
+        let mut state = self.event_bus.state.borrow_mut();
+        let elapsed = state.clock.now().saturating_duration_since(self.started);
+        state.metrics.record_publish(elapsed);
+        state.check_idle();
+        result
+    }
+
+    /// Makes the next subscriber call, starting on the next event name when the current one is done
+    /// and `start_next` is set.
+    fn advance(&mut self, start_next: bool) -> Result<Option<StepInfo<K>>, String> {
+        if self.error.is_some() {
+            return Ok(None);
+        }
+        loop {
+            if self.dispatch.is_none() {
+                if !start_next {
+                    return Ok(None);
+                }
+                let Some((event_name, messages)) = self.events.next() else {
+                    return Ok(None);
+                };
+                self.dispatch = Some(TopicDispatch::start(self.event_bus, event_name, messages, true));
+            }
+            let dispatch = self.dispatch.as_mut().unwrap();
+            match dispatch.step(self.event_bus, &self.halt, &mut self.unpublished) {
+                Ok(true) => return Ok(dispatch.last_step.take()),
+                Ok(false) => {
+                    self.dispatch.take().unwrap().finish(self.event_bus, &mut self.report, true);
+                    if !start_next {
+                        return Ok(None);
+                    }
+                }
+                Err(error) => {
+                    let dispatch = self.dispatch.take().unwrap();
+                    let event_name = dispatch.event.clone();
+                    let undelivered = (event_name, dispatch.finish(self.event_bus, &mut self.report, false));
+                    let unpublished: Vec<(K, Vec<Event>)> = std::iter::once(undelivered)
+                        .chain(self.unpublished.drain(..))
+                        .chain(self.events.by_ref())
+                        .filter(|(_, messages)| !messages.is_empty())
+                        .collect();
+                    self.event_bus.requeue_unpublished(unpublished, &self.halt);
+                    self.error = Some(error.clone());
+                    return Err(error);
+                }
+            }
+        }
+    }
+}
+
+impl<K: TopicKey> Drop for DebugSession<'_, K> {
+    /// Queues the events that were not delivered again, when the session did not finish.
+    fn drop(&mut self) {
+        let mut undelivered = Vec::new();
+        if let Some(dispatch) = self.dispatch.take() {
+            let event_name = dispatch.event.clone();
+            undelivered.push((event_name, dispatch.finish(self.event_bus, &mut self.report, false)));
+        }
+        let unpublished: Vec<(K, Vec<Event>)> = undelivered.into_iter()
+            .chain(self.unpublished.drain(..))
+            .chain(self.events.by_ref())
+            .filter(|(_, messages)| !messages.is_empty())
+            .collect();
+        if !unpublished.is_empty() {
+            self.event_bus.requeue_unpublished(unpublished, &self.halt);
+        }
+    }
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Debug Session
+    ///
+    /// Starts a publish cycle that is stepped one subscriber call at a time, see `DebugSession`.
+    /// The subscribers are called exactly as `publish` would call them. The event bus is publishing
+    /// until the session is finished or dropped, so `publish` returns `AlreadyPublishing` meanwhile.
+    pub fn debug_session(&self) -> Result<DebugSession<'_, K>, String> {
+        if self.publishing.replace(true) {
+            return Err(AlreadyPublishing.into());
+        }
+        let guard = PublishingGuard(&self.publishing);
+        let halt = Halt::default();
+        let started = self.state.borrow().clock.now();
+        let events = self.take_queued(&halt);
+        Ok(DebugSession {
+            event_bus: self,
+            _guard: guard,
+            started,
+            halt,
+            events: events.into_iter(),
+            dispatch: None,
+            report: PublishReport::default(),
+            unpublished: Vec::new(),
+            error: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{EventBus, Outcome, Phase, StepInfo, Subscriber};
+    use crate::subscribers::CollectingSubscriber;
+
+    fn doubling(name: &str) -> Box<dyn Subscriber> {
+        <dyn Subscriber>::builder().name(name).on_event(|event| {
+            let value = event.get_data::<u32>().copied().unwrap_or_default();
+            event.set_data(value * 2);
+            Ok(())
+        }).build()
+    }
+
+    fn step(subscriber: &str, phase: Phase) -> StepInfo {
+        StepInfo { topic: "orders".to_string(), subscriber: subscriber.to_string(), phase, payload_type: "u32", outcome: Outcome::Ack }
+    }
+
+    #[test]
+    fn test_stepping_a_two_subscriber_topic() {
+        let event_bus = EventBus::new();
+        let (audit, audited) = CollectingSubscriber::<u32>::new();
+        event_bus
+            .subscribe_listener("orders", doubling("pricing"))
+            .subscribe_listener("orders", doubling("tax"))
+            .subscribe_listener("audit", audit)
+            .register("orders", 1u32)
+            .register("audit", 7u32);
+
+        let mut session = event_bus.debug_session().unwrap();
+        assert!(session.current_event().is_none());
+        assert_eq!(Some(step("pricing", Phase::Before)), session.step().unwrap());
+        assert_eq!(Some(step("tax", Phase::Before)), session.step().unwrap());
+        assert_eq!(Some(step("pricing", Phase::Event)), session.step().unwrap());
+        assert_eq!(Some(&2), session.current_event().unwrap().get_data::<u32>());
+        session.current_event().unwrap().set_data(10u32);
+        assert_eq!(Some(step("tax", Phase::Event)), session.step().unwrap());
+        assert_eq!(Some(&20), session.current_event().unwrap().get_data::<u32>());
+
+        let rest: Vec<(String, Phase)> = session.run_to_next_topic().unwrap().into_iter()
+            .map(|step| (step.subscriber, step.phase))
+            .collect();
+        assert_eq!(vec![("pricing".to_string(), Phase::After), ("tax".to_string(), Phase::After)], rest);
+        assert!(audited.borrow().is_empty());
+        assert!(event_bus.publish().is_err());
+
+        let report = session.finish().unwrap();
+        assert_eq!(vec![7], *audited.borrow());
+        assert_eq!(1, report.topic(&"orders".to_string()).handled);
+        assert_eq!(Ok(()), event_bus.publish());
+    }
+
+    #[test]
+    fn test_dropped_session_requeues_undelivered_events() {
+        let event_bus = EventBus::new();
+        let (collector, received) = CollectingSubscriber::<u32>::new();
+        event_bus
+            .subscribe_listener("orders", collector)
+            .register("orders", 1u32)
+            .register("orders", 2u32);
+
+        let mut session = event_bus.debug_session().unwrap();
+        assert_eq!(Phase::Before, session.step().unwrap().unwrap().phase);
+        assert_eq!(Phase::Event, session.step().unwrap().unwrap().phase);
+        drop(session);
+
+        assert_eq!(vec![1], *received.borrow());
+        assert_eq!(2, event_bus.pending(&"orders".to_string()));
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![1, 1, 2], *received.borrow());
+    }
+
+    #[test]
+    fn test_failing_step_requeues_the_remaining_events_of_its_topic() {
+        let event_bus = EventBus::new();
+        let failing = <dyn Subscriber>::builder().name("failing").on_event(|event| {
+            match event.get_data::<u32>() {
+                Some(1) => Err("1 rejected".to_string()),
+                _ => Ok(()),
+            }
+        }).build();
+        event_bus
+            .subscribe_listener("orders", failing)
+            .register("orders", 1u32)
+            .register("orders", 2u32);
+
+        let mut session = event_bus.debug_session().unwrap();
+        assert_eq!(Phase::Before, session.step().unwrap().unwrap().phase);
+        assert_eq!(Err("1 rejected".to_string()), session.step());
+        assert_eq!(Err("1 rejected".to_string()), session.finish().map(|_| ()));
+        assert_eq!(1, event_bus.pending(&"orders".to_string()));
+    }
+}
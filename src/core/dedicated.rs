@@ -0,0 +1,147 @@
+use std::sync::mpsc::{self, SendError};
+use super::{Event, EventBus, EventContext, EventData, Instant, Subscriber, TopicKey};
+use super::executor::MailSender;
+use super::state::meta_topic;
+use super::worker::{deliver_on_worker, Mail, Worker, WorkerFailure};
+
+/// Moves a registered event into the mailbox of the dedicated thread of its event name.
+/// Returns the event when its payload has another type, or the thread has stopped.
+pub(crate) type Forward = Box<dyn Fn(Event) -> Option<Event>>;
+
+fn forward<T: Send + 'static>(sender: MailSender<Mail<T>>) -> Forward {
+    Box::new(move |mut event| {
+        if !event.data.is::<T>() {
+            return Some(event);
+        }
+        let data = std::mem::replace(&mut event.data, EventData::new(())).downcast::<T>().unwrap();
+        match sender.send(Mail::Event(data, event.id())) {
+            Ok(()) => None,
+            Err(SendError(mail)) => {
+                if let Mail::Event(data, _) = mail {
+                    event.set_data(data);
+                }
+                Some(event)
+            }
+        }
+    })
+}
+
+/// Delivers a payload from the mailbox of a dedicated thread to its listeners, in order.
+fn run_dedicated<T: 'static>(
+    listeners: &mut Vec<Box<dyn Subscriber + Send>>,
+    topic: &str,
+    data: T,
+    event_id: u64,
+    failures: &mpsc::Sender<WorkerFailure>,
+) {
+    let mut event = Event::new(data);
+    let mut context = EventContext::new(None, topic.to_string(), Instant::now());
+    listeners.retain_mut(|listener| !deliver_on_worker(listener.as_mut(), &mut event, &mut context, event_id, failures));
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Dedicate Thread
+    ///
+    /// Moves the listeners of an event name onto a thread of its own, so a slow event name does not hold up
+    /// the others. From then on `register` moves the events of the event name with a payload of type `T`
+    /// into the mailbox of the thread, which holds up to `capacity` payloads, and only waits for the thread
+    /// when the mailbox is full; `publish` does not see them. The thread delivers each event to the listeners
+    /// in order, as a new event holding the payload. Events with another payload type are queued as usual,
+    /// for the subscribers on the event bus, which keep their subscriptions.
+    ///
+    /// The failures of the listeners are reported by the next publish, or by `join_workers`, like the failures
+    /// of `subscribe_on_thread`. `join_workers` stops the thread once it delivered the events in its mailbox,
+    /// after which the events of the event name are queued on the event bus again.
+    /// Replaces the dedicated thread the event name had, which keeps running until `join_workers`.
+    pub fn dedicate_thread<T: Send + 'static>(
+        &self,
+        event_name: impl Into<K>,
+        listeners: Vec<Box<dyn Subscriber + Send>>,
+        capacity: usize,
+    ) -> &Self {
+        let event_name = self.topic_key(event_name);
+        let topic = meta_topic(&event_name);
+        let executor = self.state.borrow().executor.clone();
+        let mut listeners = listeners;
+        let (worker, sender) = Worker::spawn(&executor, event_name.clone(), capacity, move |data: T, event_id, failures| {
+            run_dedicated(&mut listeners, &topic, data, event_id, failures);
+            true
+        });
+        {
+            let mut state = self.state.borrow_mut();
+            state.workers.push(worker);
+            state.dedicated.insert(event_name, forward(sender));
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::mpsc::{self, Receiver};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use crate::{Event, EventBus, Subscriber, SubscriberFailure};
+    use crate::subscribers::CollectingSubscriber;
+
+    struct DiskWriter {
+        gate: Receiver<()>,
+        written: Arc<Mutex<Vec<u32>>>,
+    }
+
+    impl Subscriber for DiskWriter {
+        fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+            self.gate.recv().map_err(|e| e.to_string())?;
+            let block = *event.get_data::<u32>().unwrap();
+            self.written.lock().unwrap().push(block);
+            if block == 2 {
+                return Err("Disk full".to_string());
+            }
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "disk writer"
+        }
+    }
+
+    #[test]
+    fn test_slow_dedicated_topic_does_not_hold_up_publish() {
+        let (gate, gate_receiver) = mpsc::channel();
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let (input, inputs) = CollectingSubscriber::<u32>::new();
+        let failures: Rc<RefCell<Vec<SubscriberFailure>>> = Rc::new(RefCell::new(Vec::new()));
+        let recorded = failures.clone();
+        let event_bus = EventBus::new();
+        event_bus
+            .route_errors_to("errors")
+            .subscribe_listener("errors", <dyn Subscriber>::builder().on_event(move |event| {
+                recorded.borrow_mut().push(event.get_data::<SubscriberFailure>().unwrap().clone());
+                Ok(())
+            }).build())
+            .subscribe_listener("input", input)
+            .dedicate_thread::<u32>("disk", vec![Box::new(DiskWriter { gate: gate_receiver, written: written.clone() })], 8);
+        for block in 1..=3u32 {
+            event_bus.register("disk", block).register("input", block);
+        }
+
+        assert_eq!(0, event_bus.pending(&"disk".to_string()));
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![1, 2, 3], *inputs.borrow());
+        assert!(written.lock().unwrap().is_empty());
+
+        for _ in 0..3 {
+            gate.send(()).unwrap();
+        }
+        assert!(event_bus.join_workers(Duration::from_secs(5)).is_ok());
+        assert_eq!(vec![1, 2, 3], *written.lock().unwrap());
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(1, failures.borrow().len());
+        assert_eq!(("disk writer", "Disk full"), (failures.borrow()[0].subscriber.as_str(), failures.borrow()[0].message.as_str()));
+
+        event_bus.register("disk", 4u32);
+        assert_eq!(1, event_bus.pending(&"disk".to_string()));
+    }
+}
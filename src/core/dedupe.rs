@@ -0,0 +1,24 @@
+use super::Event;
+
+type Comparator = Box<dyn Fn(&Event, &Event) -> bool>;
+
+/// How the queued events of an event name are deduplicated.
+pub(crate) enum Dedupe {
+    /// Events are equal when the comparator says so.
+    Comparator(Comparator),
+    /// Events are equal when their payloads have the same type.
+    ByType,
+}
+
+impl Dedupe {
+    /// Returns whether an event equal to the incoming event is already queued.
+    pub(crate) fn is_duplicate(&self, queued: &[Event], incoming: &Event) -> bool {
+        match self {
+            Dedupe::Comparator(equals) => queued.iter().any(|event| equals(event, incoming)),
+            Dedupe::ByType => {
+                let type_id = (*incoming.data).type_id();
+                queued.iter().any(|event| (*event.data).type_id() == type_id)
+            }
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use super::{EventBus, TopicKey};
+use super::state::BusState;
+
+/// # Queue Depth Alert
+///
+/// Tells the callback of `on_queue_depth` that the number of queued events crossed its threshold.
+///
+/// ## Fields
+///
+/// * `above` - Whether the number of queued events rose above the threshold, or fell back below it.
+///
+/// * `depth` - The number of queued events, over all event names.
+///
+/// * `threshold` - The threshold that was crossed.
+///
+/// * `topics` - The number of queued events per event name, the event names without queued events left out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueueDepthAlert<K: TopicKey = String> {
+    /// Whether the number of queued events rose above the threshold, or fell back below it.
+    pub above: bool,
+    /// The number of queued events, over all event names.
+    pub depth: usize,
+    /// The threshold that was crossed.
+    pub threshold: usize,
+    /// The number of queued events per event name.
+    pub topics: Vec<(K, usize)>,
+}
+
+/// The threshold of the number of queued events, with the callback alerted when it is crossed.
+pub(crate) struct QueueDepthWatch<K: TopicKey> {
+    threshold: usize,
+    /// Whether the number of queued events is above the threshold, as the callback was last told.
+    above: bool,
+    callback: Box<dyn FnMut(QueueDepthAlert<K>)>,
+}
+
+impl<K: TopicKey> BusState<K> {
+    /// Updates the number of queued events, tells the idle watch when work arrived,
+    /// and alerts the queue depth watch when it crossed its threshold.
+    pub(crate) fn set_queued(&mut self, queued: usize) {
+        self.queued = queued;
+        if queued > 0 {
+            self.work_arrived();
+        }
+        let Some(watch) = &mut self.queue_depth_watch else {
+            return;
+        };
+        let crossed = if watch.above { queued < watch.threshold } else { queued > watch.threshold };
+        if !crossed {
+            return;
+        }
+        watch.above = !watch.above;
+        let topics = self.events.iter()
+            .filter(|(_, messages)| !messages.is_empty())
+            .map(|(event_name, messages)| (event_name.clone(), messages.len()))
+            .collect();
+        (watch.callback)(QueueDepthAlert { above: watch.above, depth: queued, threshold: watch.threshold, topics });
+    }
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # On Queue Depth
+    ///
+    /// Calls the callback when the number of queued events, over all event names, rises above the threshold,
+    /// and again when it falls back below the threshold, so it is called once per crossing.
+    /// Queued events are the events waiting for a publish, a publish takes them out of the queue.
+    /// The callback is called while the events are queued or taken out, so it cannot use the event bus.
+    /// Replaces the callback that was set before.
+    pub fn on_queue_depth(&self, threshold: usize, callback: impl FnMut(QueueDepthAlert<K>) + 'static) -> &Self {
+        let mut state = self.state.borrow_mut();
+        let above = state.queued > threshold;
+        state.queue_depth_watch = Some(QueueDepthWatch { threshold, above, callback: Box::new(callback) });
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use super::QueueDepthAlert;
+    use crate::EventBus;
+
+    #[test]
+    fn test_alerts_once_per_crossing() {
+        let alerts: Rc<RefCell<Vec<QueueDepthAlert>>> = Rc::new(RefCell::new(Vec::new()));
+        let recorded = alerts.clone();
+        let event_bus = EventBus::new();
+        event_bus.on_queue_depth(3, move |alert| recorded.borrow_mut().push(alert));
+
+        for frame in 0..3u32 {
+            event_bus.register("frames", frame);
+        }
+        assert!(alerts.borrow().is_empty());
+        event_bus.register("input", 0u32).register("frames", 3u32);
+        assert_eq!(1, alerts.borrow().len());
+
+        assert_eq!(Ok(()), event_bus.publish());
+        event_bus.register("frames", 4u32);
+
+        let alerts = alerts.borrow();
+        assert_eq!(2, alerts.len());
+        let mut topics = alerts[0].topics.clone();
+        topics.sort();
+        assert!(alerts[0].above);
+        assert_eq!(4, alerts[0].depth);
+        assert_eq!(vec![("frames".to_string(), 3), ("input".to_string(), 1)], topics);
+        assert!(!alerts[1].above);
+        assert_eq!(0, alerts[1].depth);
+        assert!(alerts[1].topics.is_empty());
+    }
+}
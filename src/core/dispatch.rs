@@ -0,0 +1,530 @@
+use std::rc::Rc;
+use super::{BeforeFailure, BreakerState, BusLogger, DeadLetterReason, DeferMode, ErrorPolicy, Event, EventBus, EventContext, Instant, Outcome, Phase, PublishReport, TopicKey, TopicReport};
+use super::breaker::{Admission, Breaker};
+use super::cancel::Halt;
+use super::child::{restore_inherited, Inherited};
+use super::pattern::PatternSubscription;
+use super::state::{meta_topic, BusState};
+use super::subscription::{release, Subscription};
+use super::topic::topic_str;
+use super::trace::Tracer;
+
+/// # Step Info
+///
+/// A single call of a subscriber method, returned by `DebugSession::step`.
+///
+/// ## Fields
+///
+/// * `topic` - The event name the event is published on.
+///
+/// * `subscriber` - The name of the subscriber.
+///
+/// * `phase` - The method of the subscriber that was called.
+///
+/// * `payload_type` - The type name of the payload of the event.
+///
+/// * `outcome` - What the method returned, an `Err` of `on_before` or `on_after` as `Outcome::Error`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepInfo<K: TopicKey = String> {
+    /// The event name the event is published on.
+    pub topic: K,
+    /// The name of the subscriber.
+    pub subscriber: String,
+    /// The method of the subscriber that was called.
+    pub phase: Phase,
+    /// The type name of the payload of the event.
+    pub payload_type: &'static str,
+    /// What the method returned.
+    pub outcome: Outcome,
+}
+
+/// A subscription an event is delivered to: of the event bus itself or of an ancestor, by the position of the
+/// event bus in the lineage, and of the event name or a pattern, by its position.
+#[derive(Clone, Copy)]
+struct Target {
+    bus: usize,
+    pattern: bool,
+    index: usize,
+}
+
+/// Returns the subscriptions of one event bus an event is delivered to, in order:
+/// the subscriptions of the event name followed by the pattern subscriptions matching it.
+fn dispatch_targets(bus: usize, topic: Option<&str>, subscriptions: &[Subscription], patterns: &[PatternSubscription]) -> Vec<Target> {
+    let mut targets: Vec<(Target, &Subscription)> = subscriptions.iter().enumerate()
+        .map(|(index, subscription)| (Target { bus, pattern: false, index }, subscription))
+        .chain(patterns.iter().enumerate()
+            .filter(|(_, pattern)| pattern.matches(topic))
+            .map(|(index, pattern)| (Target { bus, pattern: true, index }, &pattern.subscription)))
+        .collect();
+    targets.sort_by_key(|(_, subscription)| subscription.read_only);
+    targets.into_iter().map(|(target, _)| target).collect()
+}
+
+/// A subscription a dry run plans to deliver to, with a copy of its circuit breaker.
+pub(crate) struct PlannedTarget {
+    pub(crate) name: String,
+    breaker: Option<Breaker>,
+}
+
+impl PlannedTarget {
+    /// Returns whether a planned event reaches the subscription, admitted by the copy of its circuit breaker
+    /// like a published event is. The outcome of a trial is not known, so the events after it are skipped.
+    pub(crate) fn admits(&mut self, now: Instant) -> bool {
+        self.breaker.as_mut().is_none_or(|breaker| !matches!(breaker.admit(now), Admission::Skip))
+    }
+}
+
+/// Returns the subscriptions of the event bus and its ancestors an event of the event name would be delivered to,
+/// in the order `TopicDispatch` delivers to them, leaving out the ones that do not receive the events as they are published.
+pub(crate) fn planned_targets<K: TopicKey>(state: &BusState<K>, event: &K) -> Vec<PlannedTarget> {
+    let mut targets = bus_targets(state, event);
+    let mut parent = state.parent.clone();
+    while let Some(ancestor) = parent {
+        let ancestor = ancestor.borrow();
+        targets.extend(bus_targets(&ancestor, event));
+        parent = ancestor.parent.clone();
+    }
+    targets
+}
+
+fn bus_targets<K: TopicKey>(state: &BusState<K>, event: &K) -> Vec<PlannedTarget> {
+    let subscriptions = state.subscribers.get(event).map_or(&[][..], Vec::as_slice);
+    let patterns = &state.pattern_subscriptions;
+    dispatch_targets(0, topic_str(event), subscriptions, patterns).into_iter()
+        .map(|target| match target.pattern {
+            false => &subscriptions[target.index],
+            true => &patterns[target.index].subscription,
+        })
+        .filter(|subscription| subscription.receives_published())
+        .map(|subscription| PlannedTarget { name: subscription.listener.name().to_string(), breaker: subscription.breaker.clone() })
+        .collect()
+}
+
+/// The message being delivered, with the phase and the subscriber it is at.
+struct Delivery {
+    message: Event,
+    event_id: u64,
+    phase: Phase,
+    next: usize,
+    skipped: Vec<bool>,
+    /// The subscribers with a half-open circuit breaker the message is the trial of.
+    trials: Vec<usize>,
+    nack: Option<bool>,
+    deferred: bool,
+    ignored: bool,
+    handled: bool,
+    failed: bool,
+}
+
+/// Publishes the messages of one event name to its subscriptions, one subscriber call per step,
+/// followed by the pattern subscriptions matching the event name,
+/// and then by the subscriptions of the ancestors of a child event bus.
+/// The subscriptions are taken out of the event bus, and its ancestors, until the dispatch is finished.
+pub(crate) struct TopicDispatch<K: TopicKey> {
+    pub(crate) event: K,
+    subscriptions: Vec<Subscription>,
+    patterns: Vec<PatternSubscription>,
+    inherited: Vec<Inherited<K>>,
+    targets: Vec<Target>,
+    messages: std::vec::IntoIter<Event>,
+    delivery: Option<Delivery>,
+    logger: Rc<dyn BusLogger>,
+    before_failure: BeforeFailure,
+    error_policy: ErrorPolicy,
+    defer_mode: DeferMode,
+    now: Instant,
+    context: EventContext,
+    tracer: Tracer,
+    #[cfg(feature = "net")]
+    exports: Vec<(usize, String)>,
+    counts: TopicReport,
+    latest: Option<Event>,
+    watched: bool,
+    /// Whether the steps are recorded, for a debug session.
+    recording: bool,
+    pub(crate) last_step: Option<StepInfo<K>>,
+}
+
+impl<K: TopicKey> TopicDispatch<K> {
+    /// Takes the subscriptions of the event name out of the event bus and its ancestors, to publish the messages.
+    /// When nothing subscribed, the messages are exported and reported as unsubscribed right away.
+    pub(crate) fn start(bus: &EventBus<K>, event: K, mut messages: Vec<Event>, recording: bool) -> TopicDispatch<K> {
+        let (mut subscriptions, mut patterns, mut inherited) = {
+            let mut state = bus.state.borrow_mut();
+            state.apply_limit(&event, &mut messages);
+            let inherited = state.take_inherited(&event);
+            (state.subscribers.remove(&event).unwrap_or_default(), std::mem::take(&mut state.pattern_subscriptions), inherited)
+        };
+        let topic = topic_str(&event);
+        let mut targets = dispatch_targets(0, topic, &subscriptions, &patterns);
+        for (position, ancestor) in inherited.iter().enumerate() {
+            targets.extend(dispatch_targets(position + 1, topic, &ancestor.subscriptions, &ancestor.patterns));
+        }
+        if !messages.is_empty() {
+            for target in &targets {
+                subscription(&mut subscriptions, &mut patterns, &mut inherited, *target).construct();
+            }
+        }
+
+        let state = bus.state.borrow();
+        #[cfg(feature = "net")]
+        let exports = state.remote_exports_for(&event);
+        let now = state.clock.now();
+        let mut dispatch = TopicDispatch {
+            before_failure: *state.topic_before_failures.get(&event).unwrap_or(&state.before_failure),
+            error_policy: state.error_policy_for(&event),
+            defer_mode: state.defer_mode,
+            context: EventContext::new(state.context.clone(), meta_topic(&event), now),
+            now,
+            logger: state.logger.clone(),
+            tracer: bus.tracer(),
+            #[cfg(feature = "net")]
+            exports,
+            event,
+            subscriptions,
+            patterns,
+            inherited,
+            targets,
+            messages: messages.into_iter(),
+            delivery: None,
+            counts: TopicReport::default(),
+            latest: None,
+            watched: false,
+            recording,
+            last_step: None,
+        };
+        drop(state);
+        if dispatch.targets.is_empty() {
+            let messages: Vec<Event> = dispatch.messages.by_ref().collect();
+            #[cfg(feature = "net")]
+            for message in &messages {
+                bus.state.borrow_mut().export_remote(&dispatch.exports, message);
+            }
+            dispatch.logger.no_subscribers(&dispatch.event);
+            if !messages.is_empty() {
+                bus.state.borrow_mut().watch_unsubscribed(&dispatch.event);
+            }
+        }
+        dispatch
+    }
+
+    /// Returns the message being delivered, between two steps.
+    pub(crate) fn current_event(&mut self) -> Option<&mut Event> {
+        self.delivery.as_mut().map(|delivery| &mut delivery.message)
+    }
+
+    /// Calls one subscriber method, returns false once every message was delivered.
+    /// Once the publish is halted, the messages that were not published yet are added to the unpublished events.
+    pub(crate) fn step(&mut self, bus: &EventBus<K>, halt: &Halt<'_>, unpublished: &mut Vec<(K, Vec<Event>)>) -> Result<bool, String> {
+        if self.targets.is_empty() {
+            return Ok(false);
+        }
+        loop {
+            if self.delivery.is_none() && !self.next_message(bus, halt, unpublished) {
+                return Ok(false);
+            }
+            let delivery = self.delivery.as_mut().unwrap();
+            let next = (delivery.next..self.targets.len()).find(|index| !delivery.skipped[*index]);
+            match (next, delivery.phase) {
+                (Some(index), phase) => {
+                    delivery.next = index + 1;
+                    self.call(bus, index, phase)?;
+                    return Ok(true);
+                }
+                (None, Phase::Before) => {
+                    delivery.phase = Phase::Event;
+                    delivery.next = 0;
+                }
+                (None, Phase::Event) => {
+                    delivery.phase = Phase::After;
+                    delivery.next = 0;
+                }
+                (None, Phase::After) => self.complete(bus),
+            }
+        }
+    }
+
+    /// Takes the next message to deliver, dead-lettering the expired messages and the messages that
+    /// cannot be upgraded. Returns false once there are no messages left, or the publish is halted.
+    fn next_message(&mut self, bus: &EventBus<K>, halt: &Halt<'_>, unpublished: &mut Vec<(K, Vec<Event>)>) -> bool {
+        while let Some(mut message) = self.messages.next() {
+            if halt.before_message() {
+                unpublished.push((self.event.clone(), std::iter::once(message).chain(self.messages.by_ref()).collect()));
+                return false;
+            }
+
+            if message.is_expired(self.now) {
+                bus.state.borrow_mut().dead_letter(&self.event, message, DeadLetterReason::Expired);
+                continue;
+            }
+
+            let upgraded = bus.state.borrow().upgrades.upgrade(&mut message);
+            if let Err(reason) = upgraded {
+                self.logger.upgrade_failed(&self.event, &reason);
+                bus.state.borrow_mut().dead_letter(&self.event, message, reason);
+                continue;
+            }
+
+            #[cfg(feature = "net")]
+            bus.state.borrow_mut().export_remote(&self.exports, &message);
+            if let Some(registered_at) = message.registered_at() {
+                self.counts.latency.record(self.now.saturating_duration_since(registered_at), message.redeliveries());
+            }
+
+            if !self.watched {
+                self.watched = true;
+                let received: Vec<u64> = (0..self.targets.len())
+                    .filter_map(|index| {
+                        let subscription = self.target(index);
+                        subscription.receives_published().then_some(subscription.id)
+                    })
+                    .collect();
+                bus.state.borrow_mut().watch_received(received);
+            }
+
+            let mut trials = Vec::new();
+            let skipped = (0..self.targets.len()).map(|index| self.skips(bus, index, &message, &mut trials)).collect();
+            self.delivery = Some(Delivery {
+                event_id: message.id(),
+                message,
+                phase: Phase::Before,
+                next: 0,
+                skipped,
+                trials,
+                nack: None,
+                deferred: false,
+                ignored: false,
+                handled: false,
+                failed: false,
+            });
+            return true;
+        }
+        false
+    }
+
+    /// Returns whether the message skips a subscriber: when it does not receive the events as they are published,
+    /// or its circuit breaker is open, which dead-letters a clone of the message when the breaker is set to.
+    /// The subscribers the message is the trial of are added to the trials.
+    fn skips(&mut self, bus: &EventBus<K>, index: usize, message: &Event, trials: &mut Vec<usize>) -> bool {
+        let (now, event) = (self.now, self.event.clone());
+        let subscription = self.target(index);
+        if !subscription.receives_published() {
+            return true;
+        }
+        let Some(breaker) = &mut subscription.breaker else {
+            return false;
+        };
+        let admission = breaker.admit(now);
+        let dead_letter = breaker.config.dead_letter;
+        let name = subscription.listener.name().to_string();
+        let mut state = bus.state.borrow_mut();
+        match admission {
+            Admission::Deliver => false,
+            Admission::Trial { reopened } => {
+                if reopened {
+                    state.breaker_changed(&event, &name, BreakerState::HalfOpen);
+                }
+                trials.push(index);
+                false
+            }
+            Admission::Skip => {
+                if let Some(copy) = dead_letter.then(|| message.try_clone(&state.cloneables).ok()).flatten() {
+                    state.dead_letter(&event, copy, DeadLetterReason::CircuitOpen { subscriber: name });
+                }
+                true
+            }
+        }
+    }
+
+    fn target(&mut self, index: usize) -> &mut Subscription {
+        subscription(&mut self.subscriptions, &mut self.patterns, &mut self.inherited, self.targets[index])
+    }
+
+    /// Admits new trials with the next message, for the trials the settled message did not reach.
+    fn rearm(&mut self, trials: &[usize]) {
+        for &index in trials {
+            if let Some(breaker) = &mut self.target(index).breaker {
+                breaker.rearm();
+            }
+        }
+    }
+
+    /// Calls the method of the phase of a subscriber, returns the error of a subscriber that aborts the publish.
+    fn call(&mut self, bus: &EventBus<K>, index: usize, phase: Phase) -> Result<(), String> {
+        let target = self.targets[index];
+        let mut delivery = self.delivery.take().unwrap();
+        let subscription = subscription(&mut self.subscriptions, &mut self.patterns, &mut self.inherited, target);
+        let listener = &mut subscription.listener;
+        let started = self.tracer.start();
+        let outcome = match phase {
+            Phase::Before => {
+                let before = listener.on_before(&mut delivery.message);
+                self.tracer.record(&mut delivery.message, started, listener.name(), phase, || before.clone().into());
+                Outcome::from(before)
+            }
+            Phase::Event => {
+                let outcome = listener.on_event_with_context(&mut delivery.message, &mut self.context);
+                self.tracer.record(&mut delivery.message, started, listener.name(), phase, || outcome.clone());
+                if !self.context.responses.is_empty() {
+                    bus.state.borrow_mut().collect_responses(listener.name(), &mut self.context);
+                }
+                outcome
+            }
+            Phase::After => {
+                let after = listener.on_after(&delivery.message);
+                self.tracer.record(&mut delivery.message, started, listener.name(), phase, || after.clone().into());
+                Outcome::from(after)
+            }
+        };
+        if self.recording {
+            self.last_step = Some(StepInfo {
+                topic: self.event.clone(),
+                subscriber: listener.name().to_string(),
+                phase,
+                payload_type: delivery.message.payload_type_name(),
+                outcome: outcome.clone(),
+            });
+        }
+        if let (Phase::Event, Some(breaker)) = (phase, &mut subscription.breaker) {
+            if let Some(changed) = breaker.record(matches!(outcome, Outcome::Error(_)), self.now) {
+                bus.state.borrow_mut().breaker_changed(&self.event, subscription.listener.name(), changed);
+            }
+        }
+        let aborts = subscription.error_policy.unwrap_or(self.error_policy) == ErrorPolicy::Abort;
+        let name = subscription.listener.name();
+        match (phase, outcome) {
+            (Phase::Before, Outcome::Error(message)) => {
+                if self.before_failure == BeforeFailure::SkipThisSubscriber {
+                    self.logger.subscriber_skipped(&self.event, name, &message);
+                    delivery.skipped[index] = true;
+                    self.delivery = Some(delivery);
+                    return Ok(());
+                }
+                self.logger.subscriber_error(&self.event, name, phase, &message);
+                bus.state.borrow_mut().route_error(&self.event, name, phase, &message, delivery.event_id);
+                if aborts {
+                    self.rearm(&delivery.trials);
+                    return Err(message);
+                }
+                // The message is not delivered to any subscriber.
+                self.rearm(&delivery.trials);
+                return Ok(());
+            }
+            (Phase::Event, Outcome::Ack) => delivery.handled = true,
+            (Phase::Event, Outcome::AckAndUnsubscribe) => {
+                delivery.handled = true;
+                subscription.unsubscribed = true;
+            }
+            (Phase::Event, Outcome::Stop) => {
+                delivery.handled = true;
+                delivery.skipped[index + 1..].fill(true);
+            }
+            (Phase::Event, Outcome::Ignored) => delivery.ignored = true,
+            (Phase::Event, Outcome::Nack { requeue }) => delivery.nack = Some(requeue && delivery.nack != Some(false)),
+            (Phase::Event, Outcome::Defer) => {
+                delivery.deferred = true;
+                if self.defer_mode == DeferMode::SkipRemaining {
+                    delivery.skipped[index + 1..].fill(true);
+                }
+            }
+            (Phase::Event, Outcome::Error(message)) => {
+                delivery.failed = true;
+                self.logger.subscriber_error(&self.event, name, phase, &message);
+                bus.state.borrow_mut().route_error(&self.event, name, phase, &message, delivery.event_id);
+                if aborts {
+                    self.rearm(&delivery.trials);
+                    return Err(message);
+                }
+            }
+            (Phase::After, Outcome::Error(message)) => {
+                self.logger.subscriber_error(&self.event, name, phase, &message);
+                bus.state.borrow_mut().route_error(&self.event, name, phase, &message, delivery.event_id);
+                if aborts {
+                    self.rearm(&delivery.trials);
+                    return Err(message);
+                }
+            }
+            _ => {}
+        }
+        self.delivery = Some(delivery);
+        Ok(())
+    }
+
+    /// Settles the message that was delivered to every subscriber: requeued, dead-lettered, deferred, failed or handled.
+    fn complete(&mut self, bus: &EventBus<K>) {
+        let Delivery { message, trials, nack, deferred, ignored, handled, failed, .. } = self.delivery.take().unwrap();
+        self.rearm(&trials);
+        let mut state = bus.state.borrow_mut();
+        state.register_emitted(&mut self.context);
+        match nack {
+            Some(true) => state.requeue(&self.event, message),
+            Some(false) => {
+                state.dead_letter(&self.event, message, DeadLetterReason::Nacked);
+            }
+            None if deferred => state.defer(&self.event, message),
+            None if failed && !handled => self.counts.failed += 1,
+            None if ignored && !handled => {
+                self.counts.ignored += 1;
+                state.dead_letter(&self.event, message, DeadLetterReason::Unhandled);
+            }
+            None => {
+                self.counts.handled += 1;
+                self.counts.last_sequence = message.sequence();
+                self.latest = Some(message);
+            }
+        }
+    }
+
+    /// Returns the subscriptions to the event bus and its ancestors. The counts of the messages that were settled
+    /// are added to the metrics and the report, also when the dispatch stopped at an error, and when it completed,
+    /// the latest handled message is kept for the debounced subscriptions. Returns the messages that were not delivered yet, the one being delivered first.
+    pub(crate) fn finish(mut self, bus: &EventBus<K>, report: &mut PublishReport<K>, completed: bool) -> Vec<Event> {
+        if let Some(trials) = self.delivery.as_ref().map(|delivery| delivery.trials.clone()) {
+            self.rearm(&trials);
+        }
+        let undelivered: Vec<Event> = self.delivery.take().map(|delivery| delivery.message).into_iter()
+            .chain(self.messages.by_ref())
+            .collect();
+        if !self.targets.is_empty() {
+            if self.counts.latency.count > 0 {
+                let mut state = bus.state.borrow_mut();
+                let metrics = state.metrics.topic_mut(&self.event);
+                metrics.delivered += self.counts.handled;
+                metrics.failed += self.counts.failed;
+                metrics.latency.merge(&self.counts.latency);
+            }
+            report.add(&self.event, std::mem::take(&mut self.counts));
+        }
+        if completed && !self.targets.is_empty() {
+            if let (Some(debounced), Some(latest)) = (bus.state.borrow_mut().debounced.get_mut(&self.event), self.latest.take()) {
+                debounced.latest = Some(latest);
+            }
+        }
+        restore_inherited(&self.event, std::mem::take(&mut self.inherited));
+        let mut state = bus.state.borrow_mut();
+        let removed = state.remove_unsubscribed(&self.event, &mut self.subscriptions, &mut self.patterns);
+        state.restore_subscriptions(self.event.clone(), std::mem::take(&mut self.subscriptions), std::mem::take(&mut self.patterns));
+        drop(state);
+        release(removed);
+        undelivered
+    }
+}
+
+fn subscription<'a, K: TopicKey>(
+    subscriptions: &'a mut [Subscription],
+    patterns: &'a mut [PatternSubscription],
+    inherited: &'a mut [Inherited<K>],
+    target: Target,
+) -> &'a mut Subscription {
+    let (subscriptions, patterns) = match target.bus {
+        0 => (subscriptions, patterns),
+        bus => {
+            let ancestor = &mut inherited[bus - 1];
+            (&mut ancestor.subscriptions[..], &mut ancestor.patterns[..])
+        }
+    };
+    match target.pattern {
+        false => &mut subscriptions[target.index],
+        true => &mut patterns[target.index].subscription,
+    }
+}
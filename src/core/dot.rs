@@ -0,0 +1,185 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use super::{EventBus, RouteMode, TopicKey};
+use super::pattern::TopicPattern;
+use super::state::{meta_topic, BusState};
+use super::subscription::Subscription;
+use super::topic::topic_from_str;
+
+/// Quotes a DOT id: backslashes, double quotes and line feeds are escaped with a backslash.
+fn quoted(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"))
+}
+
+fn topic_id(topic: &str) -> String {
+    quoted(&format!("topic:{}", topic))
+}
+
+fn subscriber_id(subscriber: &str) -> String {
+    quoted(&format!("subscriber:{}", subscriber))
+}
+
+/// A node of an event name or pattern, with its priority level.
+struct TopicNode {
+    pattern: bool,
+    priority: u8,
+}
+
+/// Returns the annotations of the edge of a subscription.
+fn annotations(subscription: &Subscription) -> Vec<String> {
+    let mut annotations = Vec::new();
+    if subscription.read_only {
+        annotations.push("read-only".to_string());
+    }
+    if !subscription.after.is_empty() {
+        annotations.push(format!("after {}", subscription.after.join(", ")));
+    }
+    if let Some(error_policy) = subscription.error_policy {
+        annotations.push(format!("{:?} on error", error_policy));
+    }
+    if let Some(debounce) = &subscription.debounce {
+        annotations.push(format!("debounced {:?}", debounce.quiet_period));
+    }
+    annotations
+}
+
+fn edge(from: &str, to: &str, label: &str, style: Option<&str>) -> String {
+    let mut attributes = Vec::new();
+    if !label.is_empty() {
+        attributes.push(format!("label={}", quoted(label)));
+    }
+    if let Some(style) = style {
+        attributes.push(format!("style={}", style));
+    }
+    match attributes.is_empty() {
+        true => format!("    {} -> {};", from, to),
+        false => format!("    {} -> {} [{}];", from, to, attributes.join(", ")),
+    }
+}
+
+impl<K: TopicKey> BusState<K> {
+    fn topic_node(&self, nodes: &mut BTreeMap<String, TopicNode>, topic: &str) {
+        let pattern = TopicPattern::is_pattern(topic);
+        let priority = match pattern {
+            true => 0,
+            false => topic_from_str::<K>(topic).map_or(0, |event_name| self.topic_priority(&event_name)),
+        };
+        nodes.entry(topic.to_string()).or_insert(TopicNode { pattern, priority });
+    }
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # To Dot
+    ///
+    /// Returns the topology of the event bus as a Graphviz DOT graph: event names and patterns as boxes,
+    /// with their priority level when it is not 0, subscribers as ellipses by their name,
+    /// an edge from an event name to each of its subscribers, annotated with how they subscribed,
+    /// and dashed edges for the routes. Remote publishers and sources are drawn as external nodes.
+    /// Only the subscriptions and settings are read, never the queued events, so it can be called at any time.
+    /// The nodes and edges are sorted by name, the subscribers of an event name in the order they run.
+    pub fn to_dot(&self) -> String {
+        let state = self.state.borrow();
+        let mut topics = BTreeMap::new();
+        let mut subscribers = BTreeSet::new();
+        let mut subscriptions = BTreeMap::new();
+        for (event_name, listeners) in &state.subscribers {
+            let topic = meta_topic(event_name);
+            let edges: Vec<(String, Vec<String>)> = listeners.iter()
+                .filter(|subscription| !subscription.unsubscribed)
+                .map(|subscription| (subscription.listener.name().to_string(), annotations(subscription)))
+                .collect();
+            if edges.is_empty() {
+                continue;
+            }
+            topics.insert(topic.clone(), TopicNode { pattern: false, priority: state.topic_priority(event_name) });
+            subscribers.extend(edges.iter().map(|(name, _)| name.clone()));
+            subscriptions.insert(topic, edges);
+        }
+        for pattern in state.pattern_subscriptions.iter().filter(|pattern| !pattern.subscription.unsubscribed) {
+            let topic = pattern.pattern.to_string();
+            state.topic_node(&mut topics, &topic);
+            let name = pattern.subscription.listener.name().to_string();
+            subscribers.insert(name.clone());
+            subscriptions.entry(topic).or_insert_with(Vec::new).push((name, annotations(&pattern.subscription)));
+        }
+        let routes: Vec<_> = state.routes.iter().map(|route| route.route.clone()).collect();
+        for route in &routes {
+            state.topic_node(&mut topics, &route.from);
+            state.topic_node(&mut topics, &route.to);
+        }
+
+        let mut dot = String::from("digraph event_bus {\n    rankdir=LR;\n");
+        for (topic, node) in &topics {
+            let label = match node.priority {
+                0 => topic.clone(),
+                priority => format!("{} (priority {})", topic, priority),
+            };
+            let style = if node.pattern { ", style=dashed" } else { "" };
+            let _ = writeln!(dot, "    {} [label={}, shape=box{}];", topic_id(topic), quoted(&label), style);
+        }
+        for subscriber in &subscribers {
+            let _ = writeln!(dot, "    {} [label={}, shape=ellipse];", subscriber_id(subscriber), quoted(subscriber));
+        }
+        #[cfg(feature = "net")]
+        {
+            for index in 0..state.remote_exports.len() {
+                let _ = writeln!(dot, "    \"remote publisher {}\" [shape=component];", index + 1);
+            }
+            for index in 0..state.remote_sources.len() {
+                let _ = writeln!(dot, "    \"remote source {}\" [shape=component];", index + 1);
+            }
+        }
+        for (topic, edges) in &subscriptions {
+            for (subscriber, annotations) in edges {
+                let _ = writeln!(dot, "{}", edge(&topic_id(topic), &subscriber_id(subscriber), &annotations.join(", "), None));
+            }
+        }
+        for route in &routes {
+            let mode = match route.mode {
+                RouteMode::Copy => "copy",
+                RouteMode::Move => "move",
+            };
+            let _ = writeln!(dot, "{}", edge(&topic_id(&route.from), &topic_id(&route.to), mode, Some("dashed")));
+        }
+        #[cfg(feature = "net")]
+        for (index, (matcher, _)) in state.remote_exports.iter().enumerate() {
+            let mut exported: Vec<String> = state.subscribers.keys()
+                .filter(|event_name| matcher(event_name).is_some())
+                .map(meta_topic)
+                .filter(|topic| topics.contains_key(topic))
+                .collect();
+            exported.sort();
+            let publisher = format!("\"remote publisher {}\"", index + 1);
+            for topic in exported {
+                let _ = writeln!(dot, "{}", edge(&topic_id(&topic), &publisher, "export", None));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorPolicy, EventBus, RouteMode, Subscriber};
+
+    fn named(name: &str) -> Box<dyn Subscriber> {
+        <dyn Subscriber>::builder().name(name).on_event(|_| Ok(())).build()
+    }
+
+    #[test]
+    fn test_dot_export_matches_golden_file() {
+        let event_bus = EventBus::new();
+        event_bus
+            .set_topic_priority("shop/orders", 2)
+            .subscribe_listener("shop/orders", named("billing"))
+            .subscribe_listener("say \"hi\"", named("C:\\logs"))
+            .subscribe_listener_after("shop/orders", named("invoices"), &["billing"]).unwrap()
+            .add_route("shop/+", "audit", RouteMode::Copy).unwrap()
+            .subscribe_pattern("shop/+", named("C:\\logs")).unwrap()
+            .subscribe_listener_with_policy("audit", named("archive"), ErrorPolicy::Continue)
+            .register("shop/orders", 1u32);
+
+        assert_eq!(include_str!("../../tests/golden/topology.dot"), event_bus.to_dot());
+    }
+}
@@ -1,4 +1,15 @@
-use std::any::Any;
+use std::any::{type_name, Any};
+use std::cell::OnceCell;
+use std::error::Error;
+use std::fmt;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use super::{CloneRegistry, EventData, Instant, NotCloneable, TraceEntry};
+use super::convert::ConverterRegistry;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
 /// # Event
 ///
 /// An event is a struct that can
@@ -10,19 +21,68 @@ use std::any::Any;
 ///
 /// ## Fields
 ///
-/// * `data` - The data that is held by the event.
+/// * `data` - The data that is held by the event, stored in the event itself when it is small.
+///
+/// * `type_name` - The type name of the data.
+///
+/// * `id` - A process-wide unique id of the event.
+///
+/// * `schema_version` - The optional schema version of the data.
+///
+/// * `sequence` - The bus-wide sequence number the event was registered with.
 ///
 /// ## Methods
 ///
 /// * `new` - Creates a new event.
 ///
+/// * `with_schema_version` - Sets the schema version of a new event.
+///
+/// * `with_ttl` - Sets how long a new event stays valid after it is registered.
+///
+/// * `with_max_redeliveries` - Sets how many times a new event can be redelivered after a nack.
+///
+/// * `data`, `data_mut` - Return the data held by the event as `dyn Any`.
+///
 /// * `get_data` - Returns the data held by the event.
-
-
-#[derive(Debug)]
+///
+/// * `try_get_data` - Returns the data held by the event, or an error naming its type.
+///
+/// * `map_data` - Replaces the data held by the event with the result of a function.
+///
+/// * `try_clone` - Clones the event, when the type of its data is registered as cloneable.
+///
+/// * `new2`, `new3` - Creates a new event holding two or three parts, read with `get_part` and `get_parts2`.
 pub struct Event {
     /// The data that is held by the event.
-    pub data: Box<dyn Any>,
+    pub(crate) data: EventData,
+    /// The type name of the data, as the data itself cannot tell.
+    type_name: &'static str,
+    /// A process-wide unique id of the event.
+    id: u64,
+    /// The schema version of the data, used to upgrade older payloads.
+    schema_version: Option<u32>,
+    /// The number of times the event was queued again after a nack.
+    redeliveries: u32,
+    /// The number of times the event was queued again after a subscriber deferred it.
+    deferrals: u32,
+    /// The number of publish cycles that stopped before delivering the event.
+    cycles_waited: u32,
+    /// The calls of the subscribers with the event, when delivery tracing is enabled.
+    pub(crate) trace: Vec<TraceEntry>,
+    /// Overrides the maximum number of redeliveries of the event bus.
+    max_redeliveries: Option<u32>,
+    /// How long the event stays valid after it is registered.
+    ttl: Option<Duration>,
+    /// The priority level of the event, overriding the level of its event name.
+    priority: Option<u8>,
+    /// When the event was registered, according to the clock of the event bus.
+    registered_at: Option<Instant>,
+    /// The order the event was registered in, among all events of the event bus.
+    sequence: Option<u64>,
+    /// The converters of the event bus the event is registered on.
+    converters: Option<Rc<ConverterRegistry>>,
+    /// The data converted to the type last asked for, so it is converted once.
+    converted: OnceCell<Box<dyn Any>>,
 }
 
 impl Event {
@@ -30,8 +90,231 @@ impl Event {
     ///
     /// Creates a new event.
     pub fn new<T: 'static>(data: T) -> Event {
-        let data = Box::new(data);
-        Event { data }
+        Event::from_data(EventData::new(data), type_name::<T>())
+    }
+
+    pub(crate) fn from_boxed(data: Box<dyn Any>, type_name: &'static str) -> Event {
+        Event::from_data(data.into(), type_name)
+    }
+
+    fn from_data(data: EventData, type_name: &'static str) -> Event {
+        Event {
+            data,
+            type_name,
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            schema_version: None,
+            redeliveries: 0,
+            deferrals: 0,
+            cycles_waited: 0,
+            trace: Vec::new(),
+            max_redeliveries: None,
+            ttl: None,
+            priority: None,
+            registered_at: None,
+            sequence: None,
+            converters: None,
+            converted: OnceCell::new(),
+        }
+    }
+
+    /// # Try Clone
+    ///
+    /// Returns a clone of the event holding a clone of its data, or an error naming the type of the data
+    /// when it is not registered in the clone registry, see `EventBus::register_cloneable`.
+    /// The clone gets a new id, and keeps the other properties of the event like its schema version and ttl.
+    pub fn try_clone(&self, registry: &CloneRegistry) -> Result<Event, NotCloneable> {
+        let data = registry.clone_data(&*self.data).ok_or(NotCloneable { type_name: self.type_name })?;
+        Ok(Event {
+            data: data.into(),
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            converters: self.converters.clone(),
+            converted: OnceCell::new(),
+            trace: self.trace.clone(),
+            ..*self
+        })
+    }
+
+    /// Moves the data into a new event with the same properties and id, leaving `()` in this event.
+    pub(crate) fn take(&mut self) -> Event {
+        let data = std::mem::replace(&mut self.data, EventData::new(()));
+        let taken = Event {
+            data,
+            converters: self.converters.clone(),
+            converted: std::mem::take(&mut self.converted),
+            trace: std::mem::take(&mut self.trace),
+            ..*self
+        };
+        self.type_name = type_name::<()>();
+        taken
+    }
+
+    /// # Id
+    ///
+    /// Returns the process-wide unique id of the event, kept when the event is redelivered.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// # With Schema Version
+    ///
+    /// Sets the schema version of the data held by the event.
+    pub fn with_schema_version(mut self, version: u32) -> Event {
+        self.schema_version = Some(version);
+        self
+    }
+
+    /// # Schema Version
+    ///
+    /// Returns the schema version of the data held by the event.
+    pub fn schema_version(&self) -> Option<u32> {
+        self.schema_version
+    }
+
+    /// # Set Schema Version
+    ///
+    /// Changes the schema version of the data held by the event.
+    pub fn set_schema_version(&mut self, version: u32) {
+        self.schema_version = Some(version);
+    }
+
+    /// # With Max Redeliveries
+    ///
+    /// Sets how many times the event can be queued again after a nack,
+    /// overriding the maximum of the event bus.
+    pub fn with_max_redeliveries(mut self, max_redeliveries: u32) -> Event {
+        self.max_redeliveries = Some(max_redeliveries);
+        self
+    }
+
+    /// # Redeliveries
+    ///
+    /// Returns the number of times the event was queued again after a nack.
+    pub fn redeliveries(&self) -> u32 {
+        self.redeliveries
+    }
+
+    pub(crate) fn max_redeliveries(&self) -> Option<u32> {
+        self.max_redeliveries
+    }
+
+    pub(crate) fn redeliver(&mut self) {
+        self.redeliveries += 1;
+    }
+
+    /// # Deferral Count
+    ///
+    /// Returns the number of times the event was queued again after a subscriber deferred it with `Outcome::Defer`.
+    pub fn deferral_count(&self) -> u32 {
+        self.deferrals
+    }
+
+    pub(crate) fn defer(&mut self) {
+        self.deferrals += 1;
+    }
+
+    /// # Cycles Waited
+    ///
+    /// Returns the number of publish cycles that stopped before delivering the event,
+    /// which raise its priority with `EventBus::set_priority_aging`.
+    pub fn cycles_waited(&self) -> u32 {
+        self.cycles_waited
+    }
+
+    /// # Trace
+    ///
+    /// Returns the calls of the subscribers with the event, over all its delivery attempts,
+    /// when delivery tracing is enabled, see `EventBus::enable_delivery_tracing`.
+    pub fn trace(&self) -> &[TraceEntry] {
+        &self.trace
+    }
+
+    pub(crate) fn wait_cycle(&mut self) {
+        self.cycles_waited = self.cycles_waited.saturating_add(1);
+    }
+
+    /// # With Ttl
+    ///
+    /// Sets how long the event stays valid after it is registered.
+    /// An event that is published after its ttl passed is dead-lettered instead.
+    pub fn with_ttl(mut self, ttl: Duration) -> Event {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// # Ttl
+    ///
+    /// Returns how long the event stays valid after it is registered.
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+
+    /// # With Priority
+    ///
+    /// Sets the priority level of the event, which takes precedence over the level its payload type declares,
+    /// see `EventBus::register_priority_source`, and over the level of its event name.
+    pub fn with_priority(mut self, level: u8) -> Event {
+        self.priority = Some(level);
+        self
+    }
+
+    /// # Priority
+    ///
+    /// Returns the priority level of the event, when it has one of its own.
+    pub fn priority(&self) -> Option<u8> {
+        self.priority
+    }
+
+    pub(crate) fn set_priority(&mut self, level: u8) {
+        self.priority = Some(level);
+    }
+
+    /// # Registered At
+    ///
+    /// Returns when the event was registered, according to the clock of the event bus.
+    pub fn registered_at(&self) -> Option<Instant> {
+        self.registered_at
+    }
+
+    pub(crate) fn set_registered_at(&mut self, now: Instant) {
+        self.registered_at = Some(now);
+    }
+
+    /// # Sequence
+    ///
+    /// Returns the sequence number the event was registered with. The event bus numbers
+    /// its events in the order they are registered, across all event names, starting at 1.
+    /// The sequence number is kept when the event is redelivered.
+    pub fn sequence(&self) -> Option<u64> {
+        self.sequence
+    }
+
+    pub(crate) fn set_sequence(&mut self, sequence: u64) {
+        self.sequence = Some(sequence);
+    }
+
+    pub(crate) fn set_converters(&mut self, converters: Rc<ConverterRegistry>) {
+        self.converters = Some(converters);
+    }
+
+    pub(crate) fn is_expired(&self, now: Instant) -> bool {
+        match (self.ttl, self.registered_at) {
+            (Some(ttl), Some(registered_at)) => now.duration_since(registered_at) >= ttl,
+            _ => false,
+        }
+    }
+
+    /// # Data
+    ///
+    /// Returns the data held by the event.
+    pub fn data(&self) -> &dyn Any {
+        &*self.data
+    }
+
+    /// # Data Mut
+    ///
+    /// Returns the data held by the event mutably.
+    pub fn data_mut(&mut self) -> &mut dyn Any {
+        &mut *self.data
     }
 
     /// # Get Data
@@ -41,16 +324,176 @@ impl Event {
         self.data.downcast_ref::<T>()
     }
 
+    /// # Try Get Data
+    ///
+    /// Returns the data held by the event, or an error naming the type of the data
+    /// when it is not of type `T`. Data of another type is converted to `T` when the event bus
+    /// has a converter for it, see `EventBus::register_converter`.
+    pub fn try_get_data<T: 'static>(&self) -> Result<&T, PayloadTypeError> {
+        self.convert_data::<T>().map_err(|_| self.type_error::<T>())
+    }
+
+    /// Returns the data, converted to `T` when the event holds another type and a converter is registered for it.
+    /// The converted data is kept, so the event is converted once; a conversion to yet another type fails.
+    pub(crate) fn convert_data<T: 'static>(&self) -> Result<&T, String> {
+        if let Some(data) = self.data.downcast_ref::<T>() {
+            return Ok(data);
+        }
+        if let Some(converted) = self.converted.get() {
+            return converted.downcast_ref::<T>().ok_or_else(|| self.type_error::<T>().to_string());
+        }
+        let converted = self.converters.as_ref()
+            .and_then(|converters| converters.convert::<T>(&*self.data))
+            .ok_or_else(|| self.type_error::<T>().to_string())?
+            .map_err(|message| format!("Could not convert event data of type {} to {}: {}", self.type_name, type_name::<T>(), message))?;
+        Ok(self.converted.get_or_init(|| converted).downcast_ref::<T>().unwrap())
+    }
+
+    /// Returns the data mutably, converted like `convert_data`.
+    pub(crate) fn convert_data_mut<T: 'static>(&mut self) -> Result<&mut T, String> {
+        self.convert_data::<T>()?;
+        match self.data.is::<T>() {
+            true => Ok(self.data.downcast_mut::<T>().unwrap()),
+            false => Ok(self.converted.get_mut().unwrap().downcast_mut::<T>().unwrap()),
+        }
+    }
+
+    /// # Map Data
+    ///
+    /// Takes the data held by the event out by value, and replaces it with the result of the function,
+    /// which may be of another type. The data is left intact when it is not of type `T`.
+    pub fn map_data<T: 'static, U: 'static>(&mut self, f: impl FnOnce(T) -> U) -> Result<(), PayloadTypeError> {
+        if !self.data.is::<T>() {
+            return Err(self.type_error::<T>());
+        }
+        let data = std::mem::replace(&mut self.data, EventData::new(()));
+        self.set_data(f(data.downcast::<T>().unwrap()));
+        Ok(())
+    }
+
+    fn type_error<T: 'static>(&self) -> PayloadTypeError {
+        PayloadTypeError { expected: type_name::<T>(), found: self.type_name }
+    }
+
+    /// # Payload Type Name
+    ///
+    /// Returns the type name of the data held by the event.
+    pub fn payload_type_name(&self) -> &'static str {
+        self.type_name
+    }
+
     /// # Set Data
     ///
     /// Changes the data held by the event.
     pub fn set_data<T: 'static>(&mut self, data: T) {
-        self.data = Box::new(data);
+        self.data = EventData::new(data);
+        self.type_name = type_name::<T>();
+        self.converted = OnceCell::new();
+    }
+}
+
+impl fmt::Debug for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Event")
+            .field("type", &self.type_name)
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// # Payload Type Error
+///
+/// The error returned when the data of an event is not of the expected type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PayloadTypeError {
+    /// The type name of the expected data.
+    pub expected: &'static str,
+    /// The type name of the data held by the event.
+    pub found: &'static str,
+}
+
+impl fmt::Display for PayloadTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Expected event data of type {}, but the event holds {}", self.expected, self.found)
     }
 }
 
-// impl<T> From<T> for Event<T> {
-//     fn from(data: T) -> Self {
-//         Event::new(data)
-//     }
-// }
\ No newline at end of file
+impl Error for PayloadTypeError {}
+
+impl From<PayloadTypeError> for String {
+    fn from(e: PayloadTypeError) -> Self {
+        e.to_string()
+    }
+}
+
+/// # Into Event
+///
+/// Converts any data into an event, so it can be registered without `Event::new`.
+/// An `Event` is passed through as it is, instead of being wrapped in another event.
+pub trait IntoEvent {
+    /// Converts the data into an event.
+    fn into_event(self) -> Event;
+}
+
+impl<T: 'static> IntoEvent for T {
+    fn into_event(self) -> Event {
+        let mut data = Some(self);
+        match (&mut data as &mut dyn Any).downcast_mut::<Option<Event>>() {
+            Some(event) => event.take().unwrap(),
+            None => Event::new(data.unwrap()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Event, PayloadTypeError};
+
+    #[test]
+    fn test_payload_type_name() {
+        let mut event = Event::new("hello".to_string());
+        assert_eq!("alloc::string::String", event.payload_type_name());
+        assert_eq!(format!("Event {{ type: \"alloc::string::String\", id: {}, .. }}", event.id()), format!("{:?}", event));
+
+        event.set_data(42u32);
+        assert_eq!("u32", event.payload_type_name());
+        assert_eq!(Ok(&42u32), event.try_get_data::<u32>());
+        assert_eq!(
+            Err("Expected event data of type alloc::string::String, but the event holds u32".to_string()),
+            event.try_get_data::<String>().map_err(String::from)
+        );
+    }
+
+    #[test]
+    fn test_map_data() {
+        let mut event = Event::new(41u32);
+        assert_eq!(Ok(()), event.map_data(|n: u32| n + 1));
+        assert_eq!(Some(&42u32), event.get_data::<u32>());
+    }
+
+    #[test]
+    fn test_map_data_mismatch_keeps_payload() {
+        let mut event = Event::new(42u32);
+        let error = PayloadTypeError { expected: "alloc::string::String", found: "u32" };
+        assert_eq!(Err(error), event.map_data(|text: String| text.len()));
+        assert_eq!(Some(&42u32), event.get_data::<u32>());
+        assert_eq!("u32", event.payload_type_name());
+    }
+
+    #[test]
+    fn test_map_data_changes_type() {
+        let mut event = Event::new("hello".to_string());
+        assert_eq!(Ok(()), event.map_data(|text: String| text.len()));
+        assert_eq!(Some(&5usize), event.get_data::<usize>());
+        assert_eq!("usize", event.payload_type_name());
+        assert_eq!(None, event.get_data::<String>());
+    }
+
+    #[test]
+    fn test_data_is_read_as_any() {
+        let mut event = Event::new("hello".to_string());
+        event.data_mut().downcast_mut::<String>().unwrap().push_str(" world");
+        assert_eq!(Some(&"hello world".to_string()), event.data().downcast_ref::<String>());
+        assert!(!event.data().is::<u32>());
+    }
+}
@@ -1,10 +1,26 @@
 #![allow(dead_code)]
 
-use std::any::{Any, TypeId};
+use std::any::{type_name, Any, TypeId};
+use std::cell::{Cell, Ref, RefCell};
+use std::rc::Rc;
 use std::collections::HashMap;
-use super::Event;
-use super::Subscriber;
-use log::{info, error, warn};
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use super::{BeforeFailure, BusLogger, BusMetrics, Clock, DeadLetter, DeferMode, DispatchOrder, ErrorPolicy, Event, EventContext, IntoEvent, PayloadRegistry, PublishReport};
+use super::{DuplicateSubscriber, PublishCompleted, SubscriptionHandle, UnknownHandle};
+use super::access::TopicAccess;
+use super::cancel::Halt;
+use super::dispatch::TopicDispatch;
+use super::dedupe::Dedupe;
+use super::{CapacityOverflow, EventSink, OverflowAction, ReadOnlySubscriber, RegisterError, Subscriber, TopicKey, TopicMode, UnknownTopic, ValidationMode};
+use super::ordering::dependency_order;
+use super::pattern::{InvalidPattern, TopicPattern};
+use super::state::{meta_topic, BusState, Replacement, SubscriptionTarget};
+use super::subscriber::ReadOnly;
+use super::topic::{topic_str, TopicLimit};
+use super::subscription::{release, Debounce, Debounced, Subscription};
 
 /// # Event Bus
 ///
@@ -12,11 +28,19 @@ use log::{info, error, warn};
 /// It is responsible for managing all subscribers and publishing events
 /// related to the event bus.
 ///
+/// Every method takes a shared reference, so the event bus can be shared with an `Rc`
+/// by components that both subscribe and register events, also from within a subscriber.
+///
 /// ## Fields
 ///
-/// * `events` - A vec of events grouped by their name that have been published to the event bus.
+/// * `state` - The events, subscribers and settings of the event bus.
+///
+/// * `publishing` - Whether the event bus is publishing.
 ///
-/// * `subscribers` - A vec of subscribers grouped by an event name.
+/// ## Type Parameters
+///
+/// * `K` - The type of the event names, `String` by default.
+///   Any `TopicKey`, like a plain enum, can be used instead.
 ///
 /// ## Methods
 ///
@@ -26,174 +50,1056 @@ use log::{info, error, warn};
 ///
 /// * `run` - Runs through each event, and calls each listener's on_event method.
 ///
+/// * `register_upgrade` - Registers a schema upgrade for a payload type.
+///
+/// * `register_converter` - Registers a converter between payload types.
+///
 /// * `clear` - Clears all events from the event bus.
+pub struct EventBus<K: TopicKey = String> {
+    /// The events, subscribers and settings of the event bus, shared with its sinks.
+    /// It is never borrowed while a subscriber is called.
+    pub(crate) state: Rc<RefCell<BusState<K>>>,
+
+    /// Whether the event bus is publishing, to reject a publish from within a subscriber.
+    pub(crate) publishing: Cell<bool>,
+}
+
+impl<K: TopicKey> Default for EventBus<K> {
+    /// Creates a new event bus, use this to create an event bus with a custom key type:
+    /// `let bus: EventBus<Topic> = EventBus::default();`
+    fn default() -> Self {
+        EventBus { state: Rc::new(RefCell::new(BusState::default())), publishing: Cell::new(false) }
+    }
+}
+
+/// # Already Publishing
+///
+/// The error returned when the event bus is published from within one of its subscribers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlreadyPublishing;
+
+impl fmt::Display for AlreadyPublishing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The event bus is already publishing")
+    }
+}
+
+impl Error for AlreadyPublishing {}
 
-pub struct EventBus {
-    /// A vec of events grouped by an event name that have been published to the event bus.
-    events: HashMap<String, Vec<Box<Event>>>,
-    /// A vec of all subscribers that are linked to the event bus.
-    subscribers: HashMap<String, Vec<Box<dyn Subscriber>>>,
+impl From<AlreadyPublishing> for String {
+    fn from(e: AlreadyPublishing) -> Self {
+        e.to_string()
+    }
+}
 
-    suppress_subscribers: Option<Vec<TypeId>>,
+/// Marks the event bus as publishing, until it is dropped.
+pub(crate) struct PublishingGuard<'a>(pub(crate) &'a Cell<bool>);
 
-    fail_on_error: bool
+impl Drop for PublishingGuard<'_> {
+    fn drop(&mut self) {
+        self.0.set(false);
+    }
 }
 
 impl EventBus {
     /// # New
     ///
-    /// Creates a new event bus.
+    /// Creates a new event bus with `String` event names.
     pub fn new() -> EventBus {
-        EventBus {
-            events: HashMap::new(),
-            subscribers: HashMap::new(),
-            suppress_subscribers: None,
-            fail_on_error: true,
-        }
+        EventBus::default()
+    }
+
+    /// # Subscribe Pattern
+    ///
+    /// Subscribes a listener to every event name matching an MQTT-style wildcard pattern,
+    /// or returns an error when the pattern is malformed. See `subscribe_listener`.
+    pub fn subscribe_pattern<R: Subscriber + 'static>(&self, pattern: &str, listener: R) -> Result<&Self, InvalidPattern> {
+        let pattern = match &self.state.borrow().normalizer {
+            Some(normalizer) => normalizer(pattern),
+            None => pattern.to_string(),
+        };
+        let pattern = TopicPattern::parse(&pattern)?;
+        let mut subscription = Subscription::new(listener);
+        subscription.attach(pattern.to_string());
+        self.state.borrow_mut().add_subscription(SubscriptionTarget::Pattern(pattern), subscription);
+        Ok(self)
+    }
+
+    /// # Enable Meta Events
+    ///
+    /// Makes the event bus register events about itself on reserved event names,
+    /// so it can be observed by ordinary subscribers:
+    ///
+    /// * `SubscriberAdded::TOPIC` - A listener was subscribed.
+    ///
+    /// * `SubscriberRemoved::TOPIC` - A listener unsubscribed itself.
+    ///
+    /// * `TopicFirstEvent::TOPIC` - The first event of an event name was registered.
+    ///
+    /// * `DeadLettered::TOPIC` - An event was moved to the dead-letter queue.
+    ///
+    /// * `PublishCompleted::TOPIC` - A publish cycle completed.
+    ///
+    /// Meta events are published at the end of the publish cycle that produced them,
+    /// and never produce meta events themselves.
+    pub fn enable_meta_events(&self) -> &Self {
+        self.state.borrow_mut().meta_events.get_or_insert_with(Vec::new);
+        self
     }
+}
 
+impl<K: TopicKey> EventBus<K> {
     /// # Register
     ///
     /// Registers an event with the event bus.
-    pub fn register(&mut self, event_name: &str, message: Event) -> &mut Self {
-        info!("EVENT: Register '{}' event with message: {:?}", event_name, &message);
+    /// Any data can be registered, it is wrapped in an event unless it is an `Event` already.
+    /// On a deduplicated event name, the event is dropped if an equal event is already queued.
+    /// On an event name in `TopicMode::CoalesceLatest`, the event replaces the queued event.
+    ///
+    /// Events registered by a subscriber while the event bus publishes are published by the next publish.
+    pub fn register(&self, event_name: impl Into<K>, message: impl IntoEvent) -> &Self {
+        let event_name = self.topic_key(event_name);
+        self.state.borrow_mut().register(event_name, message.into_event());
+        self
+    }
+
+    /// # Register All
+    ///
+    /// Registers each event, like `register`.
+    /// Consecutive events of the same event name are queued together.
+    pub fn register_all<E: IntoEvent>(&self, events: impl IntoIterator<Item = (K, E)>) -> &Self {
+        self.state.borrow_mut().register_all(events);
+        self
+    }
+
+    /// # Sink
+    ///
+    /// Returns a handle that can only register events, feeding the queue of this event bus.
+    /// Hand it to producers that should neither manage subscribers nor publish.
+    pub fn sink(&self) -> EventSink<K> {
+        EventSink { state: self.state.clone() }
+    }
 
-        if self.events.contains_key(event_name) {
-            self.events.get_mut(event_name).unwrap()
-                .push(Box::new(message));
-        } else {
-            self.events.insert(event_name.to_string(), vec![Box::new(message)]);
+    /// # Try Register
+    ///
+    /// Registers an event, or returns an error when the queue of the event name is at its capacity,
+    /// the validator of the event name rejects the event in `ValidationMode::Reject`,
+    /// or the topic policy denies the event name. See `set_topic_capacity`, `set_validator` and `set_topic_policy`.
+    pub fn try_register(&self, event_name: impl Into<K>, message: impl IntoEvent) -> Result<&Self, RegisterError<K>> {
+        let event_name = self.topic_key(event_name);
+        let message = message.into_event();
+        let mut state = self.state.borrow_mut();
+        if state.topic_access(&event_name) == TopicAccess::Denied {
+            return Err(RegisterError::Denied { topic: event_name });
+        }
+        if state.validation_mode == ValidationMode::Reject {
+            if let Some(reason) = state.validation_error(&event_name, &message) {
+                state.logger.event_invalid(&event_name, &reason);
+                return Err(RegisterError::Invalid { topic: event_name, reason });
+            }
+        }
+        if let Some(capacity) = state.full_capacity(&event_name, &message) {
+            return Err(RegisterError::TopicFull { topic: event_name, capacity });
         }
+        state.register(event_name, message);
+        Ok(self)
+    }
+
+    /// # Register After
+    ///
+    /// Registers an event once the delay has passed, according to the clock of the event bus.
+    /// The event is registered by the first publish after the delay, and published by it.
+    /// Use `schedule` to get an id to cancel or reschedule the event with.
+    pub fn register_after(&self, event_name: impl Into<K>, message: impl IntoEvent, delay: Duration) -> &Self {
+        self.schedule(event_name, message, delay);
         self
     }
 
+    /// # Pending
+    ///
+    /// Returns the number of queued events of an event name.
+    pub fn pending(&self, event_name: &K) -> usize {
+        let state = self.state.borrow();
+        state.events.get(&state.normalize(event_name.clone())).map_or(0, Vec::len)
+    }
+
     /// # Subscribe Listener
     ///
     /// Subscribes a listener to the event bus.
-    pub fn subscribe_listener<R: Subscriber + 'static>(&mut self, event_name:&str, listener: R) -> &mut Self {
-        if self.subscribers.contains_key(event_name) {
-            self.subscribers.get_mut(event_name).unwrap()
-                .push(Box::new(listener));
-        } else {
-            self.subscribers.insert(event_name.to_string(), vec![Box::new(listener)]);
+    ///
+    /// With `String` event names, the event name can be an MQTT-style wildcard pattern
+    /// with `/` separating the levels: `+` matches exactly one level and a final `#` matches
+    /// any number of remaining levels, like `sensors/+/temperature` or `sensors/#`.
+    /// Only an event name with a level that is exactly `+` or `#` is a pattern, so `c++` is an event name.
+    /// A malformed pattern is logged and not subscribed, use `subscribe_pattern` to get the error.
+    pub fn subscribe_listener<R: Subscriber + 'static>(&self, event_name: impl Into<K>, listener: R) -> &Self {
+        self.subscribe(self.topic_key(event_name), Subscription::new(listener))
+    }
+
+    /// # Subscribe Readonly
+    ///
+    /// Subscribes a listener that cannot change the events it receives.
+    /// It receives each event after the subscribers that can change it, so it observes the final payload.
+    pub fn subscribe_readonly<R: ReadOnlySubscriber + 'static>(&self, event_name: impl Into<K>, listener: R) -> &Self {
+        let mut subscription = Subscription::new(ReadOnly(listener));
+        subscription.read_only = true;
+        self.subscribe(self.topic_key(event_name), subscription)
+    }
+
+    /// # Subscribe Unique
+    ///
+    /// Subscribes a listener, unless a listener of the same type is already subscribed to the event name.
+    /// Returns a handle to unsubscribe the listener with, or the error when it is a duplicate.
+    /// `subscribe_listener` keeps allowing duplicate subscribers.
+    pub fn subscribe_unique<R: Subscriber + 'static>(
+        &self,
+        event_name: impl Into<K>,
+        listener: R,
+    ) -> Result<SubscriptionHandle<K>, DuplicateSubscriber<K>> {
+        let event_name = self.topic_key(event_name);
+        if self.state.borrow().has_subscriber(&event_name, TypeId::of::<R>()) {
+            return Err(DuplicateSubscriber { topic: event_name, subscriber: type_name::<R>() });
         }
-        self
+        let subscription = Subscription::new(listener);
+        let handle = SubscriptionHandle { topic: event_name.clone(), id: subscription.id };
+        self.subscribe(event_name, subscription);
+        Ok(handle)
     }
 
-    /* Upon run, messages will be cleared! */
+    /// # Has Subscriber
+    ///
+    /// Returns whether a listener of type `R` is subscribed to the event name.
+    pub fn has_subscriber<R: Subscriber + 'static>(&self, event_name: &K) -> bool {
+        let state = self.state.borrow();
+        state.has_subscriber(&state.normalize(event_name.clone()), TypeId::of::<R>())
+    }
 
-    /// # Publish
+    /// # Unsubscribe
     ///
-    /// Publishes each event, and calls each listener's methods.
-    /// The on_before of all listeners is called first, then the on_event and finally the on_after
-    pub fn publish(&mut self) -> Result<(), String> {
-        for (event, mut messages) in self.events.drain() {
-            if self.subscribers.contains_key(&event) {
-               'message_loop: for message in &mut messages {
-
-                    // on before
-                    for listener in self.subscribers.get_mut(&event).unwrap().iter_mut() {
-                        match listener.on_before(message) {
-                            Err(message) => {
-                                error!("Subscriber error: {}", message);
-                                if self.fail_on_error { return Err(message)}
-                                break 'message_loop;
-                            }
-                            _ => {}
-                        }
-                    }
+    /// Removes the subscription of the handle, returns whether it was still subscribed.
+    /// A subscriber cannot be unsubscribed this way while its event name is being published,
+    /// it can unsubscribe itself with `Outcome::AckAndUnsubscribe` instead.
+    pub fn unsubscribe(&self, handle: &SubscriptionHandle<K>) -> bool {
+        let removed = self.state.borrow_mut().unsubscribe(&handle.topic, handle.id);
+        let found = !removed.is_empty();
+        release(removed);
+        found
+    }
 
-                    // on event
-                    for listener in self.subscribers.get_mut(&event).unwrap().iter_mut() {
-                        match listener.on_event(message) {
-                            Err(message) => {
-                                error!("Subscriber error: {}", message);
-                                if self.fail_on_error { return Err(message)}
-                                break 'message_loop;
-                            }
-                            _ => {}
-                        }
-                    }
+    /// # Shutdown
+    ///
+    /// Unsubscribes every subscriber, in reverse subscription order per event name, so each can release
+    /// its resources in `on_unsubscribe`. The queued events stay queued. Subscribers still subscribed
+    /// when the last handle of the event bus is dropped are told as well.
+    /// The subscribers of an event name that is being published are not unsubscribed.
+    pub fn shutdown(&self) -> &Self {
+        let removed = {
+            let mut state = self.state.borrow_mut();
+            let removed = state.take_all_subscriptions();
+            state.announce_removed(removed.iter().map(|(topic, _)| topic.clone()).collect());
+            removed
+        };
+        release(removed);
+        self
+    }
 
-                    // on after
-                    for listener in self.subscribers.get_mut(&event).unwrap().iter_mut() {
-                        match listener.on_after(message) {
-                            Err(message) => {
-                                error!("Subscriber error: {}", message);
-                                if self.fail_on_error { return Err(message)}
-                                break 'message_loop;
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-            } else {
-                warn!("No event subscribers for '{}'", event);
+    /// # Replace Listener
+    ///
+    /// Replaces the listener of the subscription of the handle, and returns the listener it replaced
+    /// so its state can be moved to the new listener. The subscription keeps its place among
+    /// the subscribers of the event name, and its settings like its error policy and its dependencies.
+    /// While its event name is being published, the listener is replaced once the event name is published,
+    /// so a publish delivers each event to either the old or the new listener. Returns `None` then,
+    /// and the replaced listener is dropped. Returns an error when the subscription is not subscribed.
+    pub fn replace_listener<R: Subscriber + 'static>(
+        &self,
+        handle: &SubscriptionHandle<K>,
+        listener: R,
+    ) -> Result<Option<Box<dyn Subscriber>>, UnknownHandle<K>> {
+        let replacement = Replacement { topic: handle.topic.clone(), id: handle.id, type_id: TypeId::of::<R>(), listener: Box::new(listener) };
+        let mut state = self.state.borrow_mut();
+        match state.replace_listener(replacement) {
+            Ok(replaced) => Ok(Some(replaced)),
+            Err(replacement) if self.publishing.get() => {
+                state.replacements.push(replacement);
+                Ok(None)
             }
+            Err(_) => Err(UnknownHandle { topic: handle.topic.clone() }),
         }
-        Ok(())
     }
 
-    pub fn suppress_subscriber<R: Subscriber + 'static>(&mut self, listener: R ) {
-        let type_id = listener.type_id();
-        match &mut self.suppress_subscribers {
-            Some(subscribers) => {
-                if subscribers.contains(&type_id) == false {
-                    subscribers.push(type_id);
-                }
-            }
-            None => {
-                self.suppress_subscribers = Some(
-                    vec![type_id]
-                )
+    /// # Subscribe Listener After
+    ///
+    /// Subscribes a listener that runs after the subscribers of the same event name with one of the names,
+    /// also when those subscribe later. Subscribers are named by `Subscriber::name`.
+    /// The subscribers are sorted when they subscribe, not when they are published.
+    /// Returns an error, without subscribing, when the dependencies would form a cycle.
+    /// Pattern subscriptions always run after the subscriptions of the event name, regardless of dependencies.
+    pub fn subscribe_listener_after<R: Subscriber + 'static>(
+        &self,
+        event_name: impl Into<K>,
+        listener: R,
+        after: &[&str],
+    ) -> Result<&Self, String> {
+        let event_name = self.topic_key(event_name);
+        let mut subscription = Subscription::new(listener);
+        subscription.after = after.iter().map(|name| name.to_string()).collect();
+        {
+            let state = self.state.borrow();
+            let mut nodes: Vec<(&str, &[String])> = state.subscribers.get(&event_name).into_iter().flatten()
+                .map(|existing| (existing.listener.name(), existing.after.as_slice()))
+                .collect();
+            nodes.push((subscription.listener.name(), subscription.after.as_slice()));
+            if let Err(cycle) = dependency_order(&nodes) {
+                return Err(format!(
+                    "Subscribing {} to {:?} creates a dependency cycle among {}",
+                    subscription.listener.name(), event_name, cycle.join(", ")
+                ));
             }
         }
+        Ok(self.subscribe(event_name, subscription))
     }
 
+    /// # Subscribe Listener With Policy
+    ///
+    /// Subscribes a listener to the event bus, with an error policy
+    /// that overrides the error policy of the event bus for this subscription only.
+    pub fn subscribe_listener_with_policy<R: Subscriber + 'static>(
+        &self,
+        event_name: impl Into<K>,
+        listener: R,
+        error_policy: ErrorPolicy,
+    ) -> &Self {
+        let mut subscription = Subscription::new(listener);
+        subscription.error_policy = Some(error_policy);
+        self.subscribe(self.topic_key(event_name), subscription)
+    }
 
-    /// # Clear
+    /// # Subscribe Debounced
     ///
-    /// Clears all events from the event bus.
-    pub fn clear(&mut self) {
-        self.events.clear();
+    /// Subscribes a listener that only receives the latest event of the event name,
+    /// once no event was registered for the quiet period.
+    /// The event is delivered by the first publish after the quiet period has passed.
+    pub fn subscribe_debounced<R: Subscriber + 'static>(
+        &self,
+        event_name: impl Into<K>,
+        listener: R,
+        quiet_period: Duration,
+    ) -> &Self {
+        let event_name = self.topic_key(event_name);
+        {
+            let mut state = self.state.borrow_mut();
+            let now = state.clock.now();
+            state.debounced.entry(event_name.clone())
+                .or_insert_with(|| Debounced { latest: None, last_registered: now });
+        }
+        let mut subscription = Subscription::new(listener);
+        subscription.debounce = Some(Debounce { quiet_period, pending: false });
+        self.subscribe(event_name, subscription)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use log::{debug};
-    use crate::{Event, EventBus, Subscriber};
+    /// # Try Subscribe Listener
+    ///
+    /// Subscribes a listener to the event bus, or returns an error
+    /// when the event bus is restricted to topics that do not include the event name,
+    /// or the topic policy denies the event name.
+    pub fn try_subscribe_listener<R: Subscriber + 'static>(
+        &self,
+        event_name: impl Into<K>,
+        listener: R,
+    ) -> Result<&Self, UnknownTopic<K>> {
+        let event_name = self.topic_key(event_name);
+        let allowed = {
+            let state = self.state.borrow();
+            state.is_allowed_topic(&event_name) && state.topic_access(&event_name) != TopicAccess::Denied
+        };
+        if !allowed {
+            return Err(UnknownTopic { topic: event_name });
+        }
+        Ok(self.subscribe(event_name, Subscription::new(listener)))
+    }
 
-    struct ExampleSubscriber {
+    /// # Restrict Topics
+    ///
+    /// Restricts the event names that can be subscribed to, usually to the `ALL` of a `topics!` declaration.
+    /// `try_subscribe_listener` returns an error for other event names,
+    /// and in debug builds the other subscribe methods warn about them.
+    pub fn restrict_topics<T: Into<K> + Clone>(&self, topics: &[T]) -> &Self {
+        let topics = topics.iter().cloned().map(|topic| self.topic_key(topic)).collect();
+        self.state.borrow_mut().restricted_topics = Some(topics);
+        self
     }
 
-    impl ExampleSubscriber {
-        const NAME: &'static str = "ExampleSubscriber";
+    /// Tells the listener what it is subscribed to before the subscription is added,
+    /// while the event bus is not borrowed, so `on_subscribe` can use the event bus.
+    pub(crate) fn subscribe(&self, event_name: K, mut subscription: Subscription) -> &Self {
+        let target = self.state.borrow().subscription_target(event_name);
+        if let Some(target) = target {
+            subscription.attach(target.topic());
+            self.state.borrow_mut().add_subscription(target, subscription);
+        }
+        self
+    }
 
-        pub fn new() -> ExampleSubscriber {
-            ExampleSubscriber { }
+    /// # Dedupe Topic
+    ///
+    /// Deduplicates the queued events of an event name: a registered event is dropped
+    /// when the comparator finds it equal to an event that is already queued.
+    pub fn dedupe_topic(
+        &self,
+        event_name: impl Into<K>,
+        equals: impl Fn(&Event, &Event) -> bool + 'static,
+    ) -> &Self {
+        let event_name = self.topic_key(event_name);
+        self.state.borrow_mut().dedupes.insert(event_name, Dedupe::Comparator(Box::new(equals)));
+        self
+    }
+
+    /// # Dedupe Topic By Type
+    ///
+    /// Deduplicates the queued events of an event name to at most one event per payload type.
+    pub fn dedupe_topic_by_type(&self, event_name: impl Into<K>) -> &Self {
+        let event_name = self.topic_key(event_name);
+        self.state.borrow_mut().dedupes.insert(event_name, Dedupe::ByType);
+        self
+    }
+
+    /// # Set Max Redeliveries
+    ///
+    /// Sets how many times an event can be queued again after a subscriber nacks it
+    /// with `requeue`, before it is dead-lettered. Events can override this maximum.
+    pub fn set_max_redeliveries(&self, max_redeliveries: u32) -> &Self {
+        self.state.borrow_mut().max_redeliveries = max_redeliveries;
+        self
+    }
+
+    /// # Set Defer Mode
+    ///
+    /// Sets which subscribers receive an event after one of them deferred it with `Outcome::Defer`.
+    pub fn set_defer_mode(&self, defer_mode: DeferMode) -> &Self {
+        self.state.borrow_mut().defer_mode = defer_mode;
+        self
+    }
+
+    /// # Set Max Deferrals
+    ///
+    /// Sets how many times an event can be queued again after a subscriber defers it, before it is dead-lettered.
+    /// The default is 10.
+    pub fn set_max_deferrals(&self, max_deferrals: u32) -> &Self {
+        self.state.borrow_mut().max_deferrals = max_deferrals;
+        self
+    }
+
+    /// # Limit Topic
+    ///
+    /// Limits the number of events of an event name that are published per cycle.
+    /// The overflow action decides whether the events over the limit are deferred
+    /// to the next publish, keeping their order, or dropped.
+    pub fn limit_topic(&self, event_name: impl Into<K>, max_per_publish: usize, overflow: OverflowAction) -> &Self {
+        let event_name = self.topic_key(event_name);
+        self.state.borrow_mut().topic_limits.insert(event_name, TopicLimit { max_per_publish, overflow });
+        self
+    }
+
+    /// # Set Topic Capacity
+    ///
+    /// Limits the number of queued events of an event name. `try_register` returns an error for an event name
+    /// at its capacity, `register` handles the event by its `CapacityOverflow`, dropping it by default.
+    /// An event replacing a queued event, by coalescing or deduplication, does not count against the capacity.
+    pub fn set_topic_capacity(&self, event_name: impl Into<K>, capacity: usize) -> &Self {
+        let event_name = self.topic_key(event_name);
+        self.state.borrow_mut().topic_capacities.insert(event_name, capacity);
+        self
+    }
+
+    /// # Set Capacity Overflow
+    ///
+    /// Sets what `register` does with an event for an event name at its capacity.
+    pub fn set_capacity_overflow(&self, event_name: impl Into<K>, overflow: CapacityOverflow) -> &Self {
+        let event_name = self.topic_key(event_name);
+        self.state.borrow_mut().capacity_overflows.insert(event_name, overflow);
+        self
+    }
+
+    /// # Set Context
+    ///
+    /// Sets the application context every subscriber can use, through the `EventContext`
+    /// handed to `Subscriber::on_event_with_context`. It lives as long as the event bus,
+    /// and replaces the context that was set before.
+    pub fn set_context(&self, context: Box<dyn Any>) -> &Self {
+        self.state.borrow_mut().context = Some(Rc::from(context));
+        self
+    }
+
+    /// # Set Logger
+    ///
+    /// Replaces the logger receiving the notifications of the event bus,
+    /// like `NullLogger` to silence the event bus.
+    pub fn set_logger(&self, logger: impl BusLogger + 'static) -> &Self {
+        self.state.borrow_mut().logger = Rc::new(logger);
+        self
+    }
+
+    /// # With Clock
+    ///
+    /// Creates a new event bus with a source of time, like a `ManualClock` in tests.
+    /// Every time-based feature of the event bus reads the time from this clock.
+    pub fn with_clock(clock: impl Clock + 'static) -> Self {
+        let event_bus = EventBus::default();
+        event_bus.set_clock(clock);
+        event_bus
+    }
+
+    /// # Set Clock
+    ///
+    /// Replaces the source of time of the event bus.
+    pub fn set_clock(&self, clock: impl Clock + 'static) -> &Self {
+        self.state.borrow_mut().clock = Arc::new(clock);
+        self
+    }
+
+    /// # Set Topic Mode
+    ///
+    /// Sets how registered events are queued for an event name.
+    pub fn set_topic_mode(&self, event_name: impl Into<K>, mode: TopicMode) -> &Self {
+        let event_name = self.topic_key(event_name);
+        self.state.borrow_mut().topic_modes.insert(event_name, mode);
+        self
+    }
+
+    /// # Metrics
+    ///
+    /// Returns the counters of the event bus.
+    pub fn metrics(&self) -> Ref<'_, BusMetrics<K>> {
+        Ref::map(self.state.borrow(), |state| &state.metrics)
+    }
+
+    /// # Register Upgrade
+    ///
+    /// Registers a schema upgrade for events carrying a payload of type `T`
+    /// with the given schema version. The upgrade transforms the event into the next
+    /// schema version, and may replace the payload with a different type.
+    /// Upgrades are chained until the event reaches the current version of its payload type.
+    pub fn register_upgrade<T: 'static>(
+        &self,
+        from_version: u32,
+        upgrade: impl Fn(&mut Event) -> Result<(), String> + 'static,
+    ) -> &Self {
+        self.state.borrow_mut().upgrades.register::<T>(from_version, Box::new(upgrade));
+        self
+    }
+
+    /// # Set Fail On Error
+    ///
+    /// Sets whether publishing stops at the first subscriber error.
+    /// Shorthand for `set_error_policy` with `ErrorPolicy::Abort` or `ErrorPolicy::Continue`.
+    pub fn set_fail_on_error(&self, fail_on_error: bool) -> &Self {
+        let policy = if fail_on_error { ErrorPolicy::Abort } else { ErrorPolicy::Continue };
+        self.set_error_policy(policy)
+    }
+
+    /// # Set Error Policy
+    ///
+    /// Sets what happens when a subscriber fails.
+    /// The error policy of a subscription takes precedence over the error policy of its event name,
+    /// which takes precedence over this policy.
+    pub fn set_error_policy(&self, error_policy: ErrorPolicy) -> &Self {
+        self.state.borrow_mut().error_policy = error_policy;
+        self
+    }
+
+    /// # Set Dispatch Order
+    ///
+    /// Sets the order a publish delivers the queued events in, event name by event name by default.
+    /// With `DispatchOrder::GlobalFifo` the events are delivered in the order they were registered,
+    /// also across event names, at the cost of sorting the queued events.
+    /// The priority levels of event names still go first, see `set_topic_priority`.
+    pub fn set_dispatch_order(&self, dispatch_order: DispatchOrder) -> &Self {
+        self.state.borrow_mut().dispatch_order = dispatch_order;
+        self
+    }
+
+    /// # Set Topic Priority
+    ///
+    /// Sets the priority level of an event name, or of every event name starting with a prefix
+    /// when it ends with `*`, like `system.*`. A publish delivers the events of event names with
+    /// a higher level first, so control events can be drained before the others.
+    /// Within a level, the event name whose first queued event was registered first goes first.
+    /// The level of an event name is 0 unless set, a level set for the event name itself
+    /// takes precedence over the level of the longest matching prefix.
+    pub fn set_topic_priority(&self, topic_or_prefix: impl Into<K>, level: u8) -> &Self {
+        let topic_or_prefix = self.topic_key(topic_or_prefix);
+        let mut state = self.state.borrow_mut();
+        state.topic_priorities.retain(|(topic, _)| *topic != topic_or_prefix);
+        state.topic_priorities.push((topic_or_prefix, level));
+        self
+    }
+
+    /// # Topic Priority
+    ///
+    /// Returns the priority level the events of an event name are delivered by, see `set_topic_priority`.
+    pub fn topic_priority(&self, event_name: &K) -> u8 {
+        let state = self.state.borrow();
+        state.topic_priority(&state.normalize(event_name.clone()))
+    }
+
+    /// # Set Priority Aging
+    ///
+    /// Raises the priority level of a queued event by the boost for every publish cycle that stopped before
+    /// delivering it, like a `pump` running out of budget, up to the highest level. So the events of a low level
+    /// are delivered eventually, while events of a higher level keep arriving. The level of the event name
+    /// is left as it is, see `topic_priority` and `effective_priority`. A boost of 0, the default, turns aging off.
+    pub fn set_priority_aging(&self, boost_per_cycle: u8) -> &Self {
+        self.state.borrow_mut().priority_aging = boost_per_cycle;
+        self
+    }
+
+    /// # Effective Priority
+    ///
+    /// Returns the priority level the oldest queued event of an event name is delivered by,
+    /// its own level or else its `topic_priority`, raised by the priority aging. See `set_priority_aging`.
+    pub fn effective_priority(&self, event_name: &K) -> u8 {
+        let state = self.state.borrow();
+        let event_name = state.normalize(event_name.clone());
+        state.effective_priority(&event_name, state.events.get(&event_name).and_then(|messages| messages.first()))
+    }
+
+    /// # Set Topic Order
+    ///
+    /// Sets the order a publish dispatches the event names in, like the phases of a game loop:
+    /// `["input", "simulation", "audio", "render"]`. The event names that are not listed go after them,
+    /// the event name whose first queued event was registered first going first. Priority levels still go first,
+    /// see `set_topic_priority`, and `DispatchOrder::GlobalFifo` dispatches the events in registration order instead.
+    /// Replaces the order set before, from the next publish; an empty list restores the registration order.
+    pub fn set_topic_order<T: Into<K> + Clone>(&self, event_names: &[T]) -> &Self {
+        let topic_order: Vec<K> = event_names.iter().map(|event_name| self.topic_key(event_name.clone())).collect();
+        self.state.borrow_mut().topic_order = topic_order;
+        self
+    }
+
+    /// # Topic Order
+    ///
+    /// Returns the order a publish dispatches the event names in, see `set_topic_order`.
+    pub fn topic_order(&self) -> Vec<K> {
+        self.state.borrow().topic_order.clone()
+    }
+
+    /// # Set Topic Error Policy
+    ///
+    /// Overrides what happens when a subscriber of one event name fails.
+    /// The error policy of a subscription still takes precedence over this policy.
+    pub fn set_topic_error_policy(&self, event_name: impl Into<K>, error_policy: ErrorPolicy) -> &Self {
+        let event_name = self.topic_key(event_name);
+        self.state.borrow_mut().topic_error_policies.insert(event_name, error_policy);
+        self
+    }
+
+    /// # Topic Error Policy
+    ///
+    /// Returns the error policy of an event name, as it applies to subscriptions without their own policy.
+    pub fn topic_error_policy(&self, event_name: &K) -> ErrorPolicy {
+        let state = self.state.borrow();
+        state.error_policy_for(&state.normalize(event_name.clone()))
+    }
+
+    /// # Effective Error Policies
+    ///
+    /// Returns the name and effective error policy of each subscriber of an event name, in order.
+    pub fn effective_error_policies(&self, event_name: &K) -> Vec<(String, ErrorPolicy)> {
+        let state = self.state.borrow();
+        let event_name = state.normalize(event_name.clone());
+        let topic_policy = state.error_policy_for(&event_name);
+        state.subscribers.get(&event_name).into_iter().flatten()
+            .map(|subscription| {
+                (subscription.listener.name().to_string(), subscription.error_policy.unwrap_or(topic_policy))
+            })
+            .collect()
+    }
+
+    /// # Route Errors To
+    ///
+    /// Registers a `SubscriberFailure` event on the event name for every failure of a subscriber,
+    /// to be handled by the subscribers of that event name on the next publish.
+    /// The error policy still decides whether publishing stops.
+    /// Failures of the subscribers of the error topic itself are only logged.
+    pub fn route_errors_to(&self, event_name: impl Into<K>) -> &Self {
+        let event_name = self.topic_key(event_name);
+        self.state.borrow_mut().error_topic = Some(event_name);
+        self
+    }
+
+    /// # Set Before Failure
+    ///
+    /// Sets what happens when the on_before of a subscriber fails.
+    pub fn set_before_failure(&self, before_failure: BeforeFailure) -> &Self {
+        self.state.borrow_mut().before_failure = before_failure;
+        self
+    }
+
+    /// # Set Topic Before Failure
+    ///
+    /// Overrides what happens when the on_before of a subscriber fails for one event name.
+    pub fn set_topic_before_failure(&self, event_name: impl Into<K>, before_failure: BeforeFailure) -> &Self {
+        let event_name = self.topic_key(event_name);
+        self.state.borrow_mut().topic_before_failures.insert(event_name, before_failure);
+        self
+    }
+
+    /// # Register Payload
+    ///
+    /// Registers the encoding and decoding of payloads of type `T` under a name,
+    /// see `PayloadRegistry::register`.
+    pub fn register_payload<T: 'static>(
+        &self,
+        name: &str,
+        encode: impl Fn(&T) -> Vec<u8> + 'static,
+        decode: impl Fn(&[u8]) -> Result<T, String> + 'static,
+    ) -> &Self {
+        self.state.borrow_mut().payloads.register::<T>(name, encode, decode);
+        self
+    }
+
+    /// # Payloads
+    ///
+    /// Returns the payload registry of the event bus.
+    pub fn payloads(&self) -> Ref<'_, PayloadRegistry> {
+        Ref::map(self.state.borrow(), |state| &state.payloads)
+    }
+
+    /* Upon run, messages will be cleared! */
+
+    /// # Publish
+    ///
+    /// Publishes each event, and calls each listener's methods.
+    /// The on_before of all listeners is called first, then the on_event and finally the on_after
+    ///
+    /// Events whose ttl passed are dead-lettered, delayed events that are due are published.
+    /// Events with an outdated schema version are upgraded first,
+    /// events that cannot be upgraded are moved to the dead-letter queue.
+    ///
+    /// A subscriber that is skipped by its on_before (see `BeforeFailure::SkipThisSubscriber`)
+    /// does not receive the on_event and on_after of that message.
+    ///
+    /// When a subscriber fails, the error policy of its subscription decides
+    /// whether publishing stops, falling back to the error policy of the event bus.
+    ///
+    /// A subscriber can nack an event with `on_event_outcome`. After all subscribers received it,
+    /// a nacked event is either queued again for the next publish, when all nacks requeue it,
+    /// or dead-lettered. Requeued events are delivered to every subscriber again.
+    ///
+    /// A subscriber can also ignore an event with `on_event_outcome`,
+    /// an event that is ignored by all of its subscribers is dead-lettered as unhandled.
+    /// With `Outcome::AckAndUnsubscribe` a subscriber handles the event and unsubscribes itself,
+    /// it still receives the on_after of that event but no later events.
+    /// With `Outcome::Stop` a subscriber consumes the event, the subscribers after it
+    /// receive neither the on_event nor the on_after of that event.
+    ///
+    /// The subscribers of an event name receive its events first,
+    /// followed by the subscribers of matching wildcard patterns.
+    ///
+    /// Subscribers can register events and subscribe listeners while the event bus publishes,
+    /// publishing from within a subscriber returns an `AlreadyPublishing` error.
+    pub fn publish(&self) -> Result<(), String> {
+        self.publish_report().map(|_| ())
+    }
+
+    /// # Publish Report
+    ///
+    /// Publishes each event like `publish`, and returns how many events were handled
+    /// or ignored per event name.
+    pub fn publish_report(&self) -> Result<PublishReport<K>, String> {
+        self.publish_cycle(&Halt::default())
+    }
+
+    /// Publishes each queued event, until the publish is halted.
+    pub(crate) fn publish_cycle(&self, halt: &Halt<'_>) -> Result<PublishReport<K>, String> {
+        if self.publishing.replace(true) {
+            return Err(AlreadyPublishing.into());
         }
+        let _guard = PublishingGuard(&self.publishing);
+        let started = self.state.borrow().clock.now();
+        let result = self.publish_queued(halt);
+        let mut state = self.state.borrow_mut();
+        // Every subscription is put back, the replacements left belong to subscriptions that unsubscribed.
+        state.replacements.clear();
+        let elapsed = state.clock.now().saturating_duration_since(started);
+        state.metrics.record_publish(elapsed);
+        state.check_idle();
+        result
     }
 
-    impl Subscriber for ExampleSubscriber {
+    /// Publishes the queued events, the delayed events that are due and the debounced events,
+    /// followed by the meta events they produced.
+    fn publish_queued(&self, halt: &Halt<'_>) -> Result<PublishReport<K>, String> {
+        let mut report = PublishReport::default();
+        let events = self.take_queued(halt);
+        self.publish_events(events, &mut report, halt)?;
+        self.complete_cycle(report, halt)
+    }
 
-        fn on_event(&mut self, event: &mut Event) -> Result<(), String>{
-            match event.get_data::<String>() {
-                Some(value) => {
-                    debug!("{} received STRING message: {}", ExampleSubscriber::NAME, value);
-                    Ok(())
-                }
-                None => {
-                    let message = format!("{} received UNKNOWN message", ExampleSubscriber::NAME);
-                    Err(message)
+    /// Takes the queued events out for a publish cycle, after registering the events of the remote sources,
+    /// the thread sinks and the delayed events that are due, in the order they are published.
+    pub(crate) fn take_queued(&self, halt: &Halt<'_>) -> Vec<(K, Vec<Event>)> {
+        #[cfg(feature = "net")]
+        self.state.borrow_mut().poll_remote_sources();
+
+        #[cfg(feature = "threaded")]
+        self.state.borrow_mut().drain_ingested();
+        self.state.borrow_mut().collect_failures_of_workers();
+        self.state.borrow_mut().register_due();
+        let dispatch_order = self.state.borrow().dispatch_order;
+        match dispatch_order {
+            DispatchOrder::PerTopic => {
+                let events = self.state.borrow_mut().take_by_priority();
+                let mut events = self.state.borrow_mut().apply_routes(events);
+                if let Some(resume) = self.state.borrow_mut().resume_from.take().filter(|_| halt.is_budgeted()) {
+                    let position = events.iter().position(|(event_name, _)| *event_name == resume).unwrap_or(0);
+                    events.rotate_left(position);
                 }
+                events
+            }
+            DispatchOrder::GlobalFifo => {
+                let events = self.state.borrow_mut().take_in_registration_order();
+                self.state.borrow_mut().apply_routes(events)
             }
         }
     }
 
-    #[test]
-    fn test_publisher() {
-        let mut event_bus = EventBus::new();
+    /// Completes a publish cycle once its events are published: delivers the debounced events
+    /// and publishes the meta events the cycle produced.
+    pub(crate) fn complete_cycle(&self, mut report: PublishReport<K>, halt: &Halt<'_>) -> Result<PublishReport<K>, String> {
+        if !halt.is_halted() {
+            self.deliver_debounced()?;
+        }
+
+        let (handled, ignored) = report.topics()
+            .fold((0, 0), |(handled, ignored), (_, topic)| (handled + topic.handled, ignored + topic.ignored));
+        self.state.borrow_mut().emit_meta(PublishCompleted::TOPIC, PublishCompleted { handled, ignored });
+        self.publish_meta_events(&mut report)?;
+        let mut state = self.state.borrow_mut();
+        state.check_topic_watchdog();
+        if state.auto_prune {
+            state.prune(true);
+            state.shrink_to_fit();
+        }
+        Ok(report)
+    }
+
+    /// Publishes the meta events of the publish cycle, without producing new meta events.
+    fn publish_meta_events(&self, report: &mut PublishReport<K>) -> Result<(), String> {
+        let mut events: HashMap<K, Vec<Event>> = HashMap::new();
+        {
+            let mut state = self.state.borrow_mut();
+            let meta_events = match state.meta_events.take() {
+                Some(meta_events) => meta_events,
+                None => return Ok(()),
+            };
+            for (event, message) in meta_events {
+                let topic = topic_str(&event);
+                let subscribed = state.subscribers.contains_key(&event) || state.pattern_subscriptions.iter()
+                    .any(|pattern| pattern.matches(topic));
+                if subscribed {
+                    events.entry(event).or_default().push(message);
+                }
+            }
+        }
+        let result = self.publish_events(events, report, &Halt::default());
+        self.state.borrow_mut().meta_events = Some(Vec::new());
+        result
+    }
+
+    /// Publishes the events of each event name to their subscriptions, run by run.
+    /// When publishing stops at an error, the events that were not delivered yet stay queued,
+    /// those of the event name that failed in front of its queue.
+    /// Once the publish is halted, the events that were not published yet stay queued as well,
+    /// and a budgeted publish remembers the event name it stopped at for the next budgeted publish.
+    pub(crate) fn publish_events(
+        &self,
+        events: impl IntoIterator<Item = (K, Vec<Event>)>,
+        report: &mut PublishReport<K>,
+        halt: &Halt<'_>,
+    ) -> Result<(), String> {
+        let mut events = events.into_iter();
+        let mut unpublished = Vec::new();
+        while let Some((event, messages)) = events.next() {
+            let mut dispatch = TopicDispatch::start(self, event, messages, false);
+            let result = loop {
+                match dispatch.step(self, halt, &mut unpublished) {
+                    Ok(true) => continue,
+                    Ok(false) => break Ok(()),
+                    Err(e) => break Err(e),
+                }
+            };
+            let event_name = dispatch.event.clone();
+            let undelivered = dispatch.finish(self, report, result.is_ok());
+            if !undelivered.is_empty() {
+                unpublished.insert(0, (event_name, undelivered));
+            }
+            if result.is_err() || halt.is_halted() {
+                self.requeue_unpublished(unpublished.into_iter().chain(events), halt);
+                return result;
+            }
+        }
+        Ok(())
+    }
+
+    /// Queues the events that were not published again, remembering the event name a budgeted publish stopped at.
+    pub(crate) fn requeue_unpublished(&self, unpublished: impl IntoIterator<Item = (K, Vec<Event>)>, halt: &Halt<'_>) {
+        let mut unpublished = unpublished.into_iter().peekable();
+        let mut state = self.state.borrow_mut();
+        if halt.is_budgeted() {
+            state.resume_from = unpublished.peek().map(|(event_name, _)| event_name.clone());
+        }
+        state.requeue_unpublished(unpublished);
+    }
+
+    /// Delivers the latest event of each event name to the debounced subscriptions
+    /// whose quiet period has passed since the last registration.
+    fn deliver_debounced(&self) -> Result<(), String> {
+        let (now, logger, topics, app_context) = {
+            let state = self.state.borrow();
+            let topics: Vec<K> = state.debounced.iter()
+                .filter(|(_, debounced)| debounced.latest.is_some())
+                .map(|(event, _)| event.clone())
+                .collect();
+            (state.clock.now(), state.logger.clone(), topics, state.context.clone())
+        };
+        for event in topics {
+            let mut context = EventContext::new(app_context.clone(), meta_topic(&event), now);
+            let (mut message, quiet, error_policy, mut subscriptions) = {
+                let mut state = self.state.borrow_mut();
+                let error_policy = state.error_policy_for(&event);
+                let Some(debounced) = state.debounced.get_mut(&event) else {
+                    continue;
+                };
+                let Some(message) = debounced.latest.take() else {
+                    continue;
+                };
+                let quiet = now.duration_since(debounced.last_registered);
+                (message, quiet, error_policy, state.subscribers.remove(&event).unwrap_or_default())
+            };
+            let mut waiting = false;
+            let mut failures = Vec::new();
+            let mut delivered = Vec::new();
+            let mut result = Ok(());
+            for subscription in subscriptions.iter_mut() {
+                match &mut subscription.debounce {
+                    Some(debounce) if debounce.pending && quiet < debounce.quiet_period => {
+                        waiting = true;
+                        continue;
+                    }
+                    Some(debounce) if debounce.pending => debounce.pending = false,
+                    _ => continue,
+                }
+                delivered.push(subscription.id);
+                if let Err((phase, e)) = subscription.deliver(&mut message, &mut context) {
+                    logger.subscriber_error(&event, subscription.listener.name(), phase, &e);
+                    failures.push((subscription.listener.name().to_string(), phase, e.clone()));
+                    if subscription.error_policy.unwrap_or(error_policy) == ErrorPolicy::Abort {
+                        result = Err(e);
+                        break;
+                    }
+                }
+            }
+            let event_id = message.id();
+            let mut state = self.state.borrow_mut();
+            state.register_emitted(&mut context);
+            state.watch_received(delivered);
+            if let Some(debounced) = state.debounced.get_mut(&event).filter(|_| waiting) {
+                debounced.latest.get_or_insert(message);
+            }
+            for (subscriber, phase, e) in failures {
+                state.route_error(&event, &subscriber, phase, &e, event_id);
+            }
+            let removed = state.remove_unsubscribed(&event, &mut subscriptions, &mut Vec::new());
+            state.restore_subscriptions(event, subscriptions, Vec::new());
+            drop(state);
+            release(removed);
+            result?;
+        }
+        Ok(())
+    }
+
+    /// # Dead Letters
+    ///
+    /// Returns the events that could not be delivered.
+    pub fn dead_letters(&self) -> Ref<'_, [DeadLetter<K>]> {
+        Ref::map(self.state.borrow(), |state| state.dead_letters.as_slice())
+    }
+
+    /// # Take Dead Letters
+    ///
+    /// Removes and returns the events that could not be delivered.
+    pub fn take_dead_letters(&self) -> Vec<DeadLetter<K>> {
+        std::mem::take(&mut self.state.borrow_mut().dead_letters)
+    }
+
+    /// # Clear
+    ///
+    /// Clears all events from the event bus.
+    pub fn clear(&self) {
+        let mut state = self.state.borrow_mut();
+        state.events.clear();
+        state.set_queued(0);
+    }
+}
+
+impl<K: TopicKey, E: IntoEvent> Extend<(K, E)> for EventBus<K> {
+    /// Registers each event, like `register`.
+    /// Consecutive events of the same event name are queued together.
+    fn extend<I: IntoIterator<Item = (K, E)>>(&mut self, iter: I) {
+        self.register_all(iter);
+    }
+}
+
+impl<K: TopicKey, E: IntoEvent> FromIterator<(K, E)> for EventBus<K> {
+    /// Creates an event bus with each event registered.
+    fn from_iter<I: IntoIterator<Item = (K, E)>>(iter: I) -> Self {
+        let event_bus = EventBus::default();
+        event_bus.register_all(iter);
+        event_bus
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use log::{debug};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+    use crate::testing::ManualClock;
+    use crate::{AlreadyPublishing, BeforeFailure, CapacityOverflow, DeadLetterReason, DeadLettered, DeferMode, DispatchOrder, ErrorPolicy, Event, EventBus, EventSink, Outcome, OverflowAction, Phase, ReadOnlySubscriber, RegisterError, Subscriber, SubscriberAdded, SubscriberFailure, SubscriptionHandle, TopicMode, UnknownTopic};
+
+    struct ExampleSubscriber {
+    }
+
+    impl ExampleSubscriber {
+        const NAME: &'static str = "ExampleSubscriber";
+
+        pub fn new() -> ExampleSubscriber {
+            ExampleSubscriber { }
+        }
+    }
+
+    impl Subscriber for ExampleSubscriber {
+
+        fn on_event(&mut self, event: &mut Event) -> Result<(), String>{
+            match event.get_data::<String>() {
+                Some(value) => {
+                    debug!("{} received STRING message: {}", ExampleSubscriber::NAME, value);
+                    Ok(())
+                }
+                None => {
+                    let message = format!("{} received UNKNOWN message", ExampleSubscriber::NAME);
+                    Err(message)
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_publisher() {
+        let event_bus = EventBus::new();
         event_bus.subscribe_listener("bar", ExampleSubscriber::new());
         let result =
             event_bus
@@ -204,7 +1110,7 @@ mod tests {
 
     #[test]
     fn test_publisher_with_invalid_payload() {
-        let mut event_bus = EventBus::new();
+        let event_bus = EventBus::new();
         event_bus.subscribe_listener("bar", ExampleSubscriber::new());
         let result =
             event_bus
@@ -214,4 +1120,1500 @@ mod tests {
         let expected = Err(message.clone());
         assert_eq!(expected, result, "Expected error message: '{}'", message);
     }
-}
\ No newline at end of file
+
+    struct PlayerV1 {
+        name: String,
+    }
+
+    struct PlayerV2 {
+        first_name: String,
+        last_name: String,
+    }
+
+    struct PlayerSubscriber {
+        received: Rc<RefCell<Vec<(String, String)>>>,
+    }
+
+    impl Subscriber for PlayerSubscriber {
+        fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+            match event.get_data::<PlayerV2>() {
+                Some(player) => {
+                    self.received.borrow_mut().push((player.first_name.clone(), player.last_name.clone()));
+                    Ok(())
+                }
+                None => Err("PlayerSubscriber received UNKNOWN message".to_string()),
+            }
+        }
+    }
+
+    fn upgrade_player(event: &mut Event) -> Result<(), String> {
+        let player = event.get_data::<PlayerV1>().ok_or("Not a PlayerV1")?;
+        let (first_name, last_name) = player.name.split_once(' ').ok_or("Missing last name")?;
+        let upgraded = PlayerV2 { first_name: first_name.to_string(), last_name: last_name.to_string() };
+        event.set_data(upgraded);
+        Ok(())
+    }
+
+    #[test]
+    fn test_publisher_upgrades_schema_version() {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .register_upgrade::<PlayerV1>(1, upgrade_player)
+            .subscribe_listener("player", PlayerSubscriber { received: received.clone() });
+        let result =
+            event_bus
+                .register("player", Event::new(PlayerV1 { name: "John Doe".to_string() }).with_schema_version(1))
+                .publish();
+        assert_eq!(Ok(()), result);
+        assert_eq!(vec![("John".to_string(), "Doe".to_string())], *received.borrow());
+        assert!(event_bus.dead_letters().is_empty());
+    }
+
+    #[test]
+    fn test_publisher_dead_letters_failed_upgrade() {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .register_upgrade::<PlayerV1>(1, upgrade_player)
+            .subscribe_listener("player", PlayerSubscriber { received: received.clone() });
+        let result =
+            event_bus
+                .register("player", Event::new(PlayerV1 { name: "John".to_string() }).with_schema_version(1))
+                .register("player", Event::new(PlayerV1 { name: "Jane Doe".to_string() }).with_schema_version(0))
+                .publish();
+        assert_eq!(Ok(()), result);
+        assert!(received.borrow().is_empty());
+
+        let dead_letters = event_bus.take_dead_letters();
+        assert_eq!(2, dead_letters.len());
+        assert_eq!(DeadLetterReason::UpgradeFailed { from_version: 1, message: "Missing last name".to_string() }, dead_letters[0].reason);
+        assert_eq!(DeadLetterReason::MissingUpgrade { version: 0 }, dead_letters[1].reason);
+        assert!(event_bus.dead_letters().is_empty());
+    }
+
+    struct RecordingSubscriber {
+        name: &'static str,
+        fail_before: bool,
+        log: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl RecordingSubscriber {
+        fn new(name: &'static str, fail_before: bool, log: &Rc<RefCell<Vec<String>>>) -> RecordingSubscriber {
+            RecordingSubscriber { name, fail_before, log: log.clone() }
+        }
+    }
+
+    impl Subscriber for RecordingSubscriber {
+        fn on_before(&mut self, _event: &mut Event) -> Result<(), String> {
+            self.log.borrow_mut().push(format!("{}:before", self.name));
+            if self.fail_before {
+                return Err(format!("{} rejected message", self.name));
+            }
+            Ok(())
+        }
+
+        fn on_event(&mut self, _event: &mut Event) -> Result<(), String> {
+            self.log.borrow_mut().push(format!("{}:event", self.name));
+            Ok(())
+        }
+
+        fn on_after(&self, _event: &Event) -> Result<(), String> {
+            self.log.borrow_mut().push(format!("{}:after", self.name));
+            Ok(())
+        }
+    }
+
+    fn subscribe_three(event_bus: &mut EventBus, log: &Rc<RefCell<Vec<String>>>) {
+        event_bus
+            .subscribe_listener("bar", RecordingSubscriber::new("first", false, log))
+            .subscribe_listener("bar", RecordingSubscriber::new("middle", true, log))
+            .subscribe_listener("bar", RecordingSubscriber::new("last", false, log));
+    }
+
+    #[test]
+    fn test_publisher_before_failure_aborts_message() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut event_bus = EventBus::new();
+        subscribe_three(&mut event_bus, &log);
+        let result =
+            event_bus
+                .set_fail_on_error(false)
+                .register("bar", Event::new("hello".to_string()))
+                .publish();
+        assert_eq!(Ok(()), result);
+        assert_eq!(vec!["first:before", "middle:before"], *log.borrow());
+    }
+
+    #[test]
+    fn test_publisher_before_failure_skips_subscriber() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut event_bus = EventBus::new();
+        subscribe_three(&mut event_bus, &log);
+        let result =
+            event_bus
+                .set_topic_before_failure("bar", BeforeFailure::SkipThisSubscriber)
+                .register("bar", Event::new("hello".to_string()))
+                .publish();
+        assert_eq!(Ok(()), result);
+        assert_eq!(
+            vec!["first:before", "middle:before", "last:before", "first:event", "last:event", "first:after", "last:after"],
+            *log.borrow()
+        );
+    }
+
+    struct PickySubscriber {
+        log: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Subscriber for PickySubscriber {
+        fn on_before(&mut self, event: &mut Event) -> Result<(), String> {
+            let number = *event.try_get_data::<u32>()?;
+            self.log.borrow_mut().push(format!("{}:before", number));
+            if number == 2 {
+                return Err("2 rejected".to_string());
+            }
+            Ok(())
+        }
+
+        fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+            self.log.borrow_mut().push(format!("{}:event", event.try_get_data::<u32>()?));
+            Ok(())
+        }
+
+        fn on_after(&self, event: &Event) -> Result<(), String> {
+            self.log.borrow_mut().push(format!("{}:after", event.try_get_data::<u32>()?));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_before_failure_only_aborts_its_own_message() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .set_fail_on_error(false)
+            .subscribe_listener("bar", PickySubscriber { log: log.clone() })
+            .register("bar", 1u32)
+            .register("bar", 2u32)
+            .register("bar", 3u32);
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec!["1:before", "1:event", "1:after", "2:before", "3:before", "3:event", "3:after"], *log.borrow());
+    }
+
+    struct FailingSubscriber {
+        log: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Subscriber for FailingSubscriber {
+        fn on_event(&mut self, _event: &mut Event) -> Result<(), String> {
+            self.log.borrow_mut().push("audit:event".to_string());
+            Err("audit failed".to_string())
+        }
+    }
+
+    #[test]
+    fn test_publisher_subscription_policy_continues() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        let result =
+            event_bus
+                .set_error_policy(ErrorPolicy::Abort)
+                .subscribe_listener_with_policy("payment", FailingSubscriber { log: log.clone() }, ErrorPolicy::Continue)
+                .subscribe_listener("payment", RecordingSubscriber::new("payment", false, &log))
+                .register("payment", Event::new(100u32))
+                .publish();
+        assert_eq!(Ok(()), result);
+        assert_eq!(vec!["payment:before", "audit:event", "payment:event", "payment:after"], *log.borrow());
+    }
+
+    #[test]
+    fn test_subscription_policy_overrides_topic_policy_overrides_bus_policy() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        let result = event_bus
+            .set_error_policy(ErrorPolicy::Abort)
+            .set_topic_error_policy("payment", ErrorPolicy::Continue)
+            .subscribe_listener("payment", FailingSubscriber { log: log.clone() })
+            .subscribe_listener("payment", RecordingSubscriber::new("payment", false, &log))
+            .register("payment", Event::new(100u32))
+            .publish();
+        assert_eq!(Ok(()), result);
+        assert_eq!(vec!["payment:before", "audit:event", "payment:event", "payment:after"], *log.borrow());
+
+        log.borrow_mut().clear();
+        let event_bus = EventBus::new();
+        let result = event_bus
+            .set_error_policy(ErrorPolicy::Continue)
+            .set_topic_error_policy("payment", ErrorPolicy::Continue)
+            .subscribe_listener_with_policy("payment", FailingSubscriber { log: log.clone() }, ErrorPolicy::Abort)
+            .subscribe_listener("payment", RecordingSubscriber::new("payment", false, &log))
+            .register("payment", Event::new(100u32))
+            .publish();
+        assert!(result.is_err());
+        assert!(!log.borrow().contains(&"payment:event".to_string()));
+    }
+
+    #[test]
+    fn test_subscribe_unique_refuses_duplicate() {
+        let count = Rc::new(RefCell::new(0));
+        let event_bus = EventBus::new();
+        let handle = event_bus.subscribe_unique("reload", CountingSubscriber { count: count.clone() });
+        assert!(handle.is_ok());
+        assert!(event_bus.has_subscriber::<CountingSubscriber>(&"reload".to_string()));
+
+        let duplicate = event_bus.subscribe_unique("reload", CountingSubscriber { count: count.clone() });
+        assert_eq!("reload", duplicate.unwrap_err().topic);
+        event_bus.register("reload", 1u32).publish().unwrap();
+        assert_eq!(1, *count.borrow());
+
+        assert!(event_bus.unsubscribe(&handle.unwrap()));
+        assert!(!event_bus.has_subscriber::<CountingSubscriber>(&"reload".to_string()));
+    }
+
+    #[test]
+    fn test_replace_listener_splits_counts() {
+        let old_count = Rc::new(RefCell::new(0));
+        let new_count = Rc::new(RefCell::new(0));
+        let event_bus = EventBus::new();
+        let handle = event_bus.subscribe_unique("reload", CountingSubscriber { count: old_count.clone() }).unwrap();
+        event_bus.register("reload", 1u32).register("reload", 2u32).publish().unwrap();
+
+        let old = event_bus.replace_listener(&handle, CountingSubscriber { count: new_count.clone() });
+        assert!(old.unwrap().unwrap().name().ends_with("CountingSubscriber"));
+        event_bus.register("reload", 3u32).publish().unwrap();
+        assert_eq!((2, 1), (*old_count.borrow(), *new_count.borrow()));
+
+        event_bus.unsubscribe(&handle);
+        let unknown = event_bus.replace_listener(&handle, CountingSubscriber { count: new_count.clone() });
+        assert_eq!("reload", unknown.err().unwrap().topic);
+    }
+
+    /// The event bus and the handle a `ReloadingSubscriber` replaces its listener with, once.
+    type Reload = Option<(std::rc::Weak<EventBus>, SubscriptionHandle)>;
+
+    struct ReloadingSubscriber {
+        reload: Rc<RefCell<Reload>>,
+        count: Rc<RefCell<usize>>,
+        reloaded: Rc<RefCell<usize>>,
+    }
+
+    impl Subscriber for ReloadingSubscriber {
+        fn on_event(&mut self, _event: &mut Event) -> Result<(), String> {
+            *self.count.borrow_mut() += 1;
+            if let Some((event_bus, handle)) = self.reload.borrow_mut().take() {
+                let replaced = event_bus.upgrade().unwrap().replace_listener(&handle, CountingSubscriber { count: self.reloaded.clone() });
+                assert!(replaced.unwrap().is_none());
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_replace_listener_while_publishing_applies_after_the_event_name() {
+        let (reload, old_count, new_count) = (Rc::new(RefCell::new(None)), Rc::new(RefCell::new(0)), Rc::new(RefCell::new(0)));
+        let event_bus = Rc::new(EventBus::new());
+        let handle = event_bus
+            .subscribe_unique("reload", ReloadingSubscriber { reload: reload.clone(), count: old_count.clone(), reloaded: new_count.clone() })
+            .unwrap();
+        *reload.borrow_mut() = Some((Rc::downgrade(&event_bus), handle));
+        event_bus.register("reload", 1u32).register("reload", 2u32).publish().unwrap();
+        assert_eq!((2, 0), (*old_count.borrow(), *new_count.borrow()));
+
+        event_bus.register("reload", 3u32).publish().unwrap();
+        assert_eq!((2, 1), (*old_count.borrow(), *new_count.borrow()));
+        assert!(event_bus.state.borrow().replacements.is_empty());
+    }
+
+    #[test]
+    fn test_try_register_on_full_topic() {
+        let count = Rc::new(RefCell::new(0));
+        let event_bus = EventBus::new();
+        event_bus
+            .set_topic_capacity("commands", 2)
+            .subscribe_listener("commands", CountingSubscriber { count: count.clone() });
+
+        assert!(event_bus.try_register("commands", 1u32).is_ok());
+        assert!(event_bus.try_register("commands", 2u32).is_ok());
+        assert_eq!(
+            Some(RegisterError::TopicFull { topic: "commands".to_string(), capacity: 2 }),
+            event_bus.try_register("commands", 3u32).err()
+        );
+        event_bus.publish().unwrap();
+        assert_eq!(2, *count.borrow());
+        assert!(event_bus.try_register("commands", 4u32).is_ok());
+    }
+
+    #[test]
+    fn test_register_on_full_topic_follows_overflow() {
+        let event_bus = EventBus::new();
+        event_bus
+            .set_topic_capacity("commands", 1)
+            .set_capacity_overflow("commands", CapacityOverflow::DropOldest)
+            .set_topic_capacity("render", 1)
+            .set_topic_mode("render", TopicMode::CoalesceLatest)
+            .register("commands", 1u32)
+            .register("commands", 2u32)
+            .register("render", 1u32);
+
+        let commands = "commands".to_string();
+        assert_eq!(1, event_bus.pending(&commands));
+        assert_eq!(1, event_bus.metrics().topic(&commands).dropped);
+        assert!(event_bus.try_register("render", 2u32).is_ok());
+        assert_eq!(1, event_bus.pending(&"render".to_string()));
+    }
+
+    struct DoublingSubscriber;
+
+    impl Subscriber for DoublingSubscriber {
+        fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+            event.map_data(|n: u32| n * 2).map_err(String::from)
+        }
+    }
+
+    struct ObservingSubscriber {
+        observed: Rc<RefCell<Vec<u32>>>,
+    }
+
+    impl ReadOnlySubscriber for ObservingSubscriber {
+        fn on_event(&mut self, event: &Event) -> Result<(), String> {
+            self.observed.borrow_mut().push(*event.try_get_data::<u32>()?);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_readonly_subscriber_observes_final_payload() {
+        let observed = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .subscribe_readonly("score", ObservingSubscriber { observed: observed.clone() })
+            .subscribe_listener("score", DoublingSubscriber)
+            .register("score", 21u32);
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![42], *observed.borrow());
+    }
+
+    #[test]
+    fn test_topic_error_policies_in_one_cycle() {
+        let payments = Rc::new(RefCell::new(Vec::new()));
+        let telemetry = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .set_error_policy(ErrorPolicy::Continue)
+            .set_topic_error_policy("payments", ErrorPolicy::Abort)
+            .set_topic_error_policy("telemetry", ErrorPolicy::Continue)
+            .subscribe_listener("payments", FailingSubscriber { log: payments.clone() })
+            .subscribe_listener("telemetry", FailingSubscriber { log: telemetry.clone() })
+            .register("payments", 1u32)
+            .register("payments", 2u32)
+            .register("telemetry", 1u32)
+            .register("telemetry", 2u32);
+
+        assert_eq!(Err("audit failed".to_string()), event_bus.publish());
+        // The second payments event and the telemetry events stay queued when the payments topic aborted before them.
+        assert_eq!(Err("audit failed".to_string()), event_bus.publish());
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(2, payments.borrow().len());
+        assert_eq!(2, telemetry.borrow().len());
+    }
+
+    #[test]
+    fn test_effective_error_policies() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .set_error_policy(ErrorPolicy::Abort)
+            .set_topic_error_policy("telemetry", ErrorPolicy::Continue)
+            .subscribe_listener("telemetry", RecordingSubscriber::new("sampler", false, &log))
+            .subscribe_listener_with_policy("telemetry", RecordingSubscriber::new("uploader", false, &log), ErrorPolicy::Abort);
+
+        assert_eq!(ErrorPolicy::Continue, event_bus.topic_error_policy(&"telemetry".to_string()));
+        assert_eq!(ErrorPolicy::Abort, event_bus.topic_error_policy(&"payments".to_string()));
+        let policies: Vec<ErrorPolicy> = event_bus.effective_error_policies(&"telemetry".to_string())
+            .into_iter().map(|(_, policy)| policy).collect();
+        assert_eq!(vec![ErrorPolicy::Continue, ErrorPolicy::Abort], policies);
+    }
+
+    #[test]
+    fn test_publisher_subscription_policy_aborts() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        let result =
+            event_bus
+                .set_error_policy(ErrorPolicy::Continue)
+                .subscribe_listener_with_policy("payment", FailingSubscriber { log: log.clone() }, ErrorPolicy::Abort)
+                .subscribe_listener("payment", RecordingSubscriber::new("payment", false, &log))
+                .register("payment", Event::new(100u32))
+                .publish();
+        assert_eq!(Err("audit failed".to_string()), result);
+        assert_eq!(vec!["payment:before", "audit:event"], *log.borrow());
+    }
+
+    #[test]
+    fn test_aborted_topic_counts_the_events_handled_before_the_error() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .subscribe_listener("bar", PickySubscriber { log: log.clone() })
+            .register("bar", 1u32)
+            .register("bar", 2u32)
+            .register("bar", 3u32);
+
+        assert_eq!(Err("2 rejected".to_string()), event_bus.publish());
+        assert_eq!(1, event_bus.metrics().topic(&"bar".to_string()).delivered);
+    }
+
+    #[test]
+    fn test_aborted_topic_keeps_its_remaining_events_queued() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .subscribe_listener("bar", PickySubscriber { log: log.clone() })
+            .register("bar", 1u32)
+            .register("bar", 2u32)
+            .register("bar", 3u32);
+
+        assert_eq!(Err("2 rejected".to_string()), event_bus.publish());
+        assert_eq!(1, event_bus.pending(&"bar".to_string()));
+        event_bus.register("bar", 4u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(
+            vec!["1:before", "1:event", "1:after", "2:before", "3:before", "3:event", "3:after", "4:before", "4:event", "4:after"],
+            *log.borrow()
+        );
+    }
+
+    #[test]
+    fn test_partially_failed_topic_is_reported() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        let report =
+            event_bus
+                .subscribe_listener_with_policy("payment", FailingSubscriber { log: log.clone() }, ErrorPolicy::Continue)
+                .register("payment", 100u32)
+                .register("payment", 200u32)
+                .publish_report()
+                .unwrap();
+        let payment = "payment".to_string();
+        assert_eq!((0, 2), (report.topic(&payment).handled, report.topic(&payment).failed));
+        assert_eq!(2, report.topic(&payment).latency.count);
+        assert_eq!((0, 2), (event_bus.metrics().topic(&payment).delivered, event_bus.metrics().topic(&payment).failed));
+    }
+
+    #[test]
+    fn test_failed_event_handled_by_another_subscriber_is_handled() {
+        let event_bus = EventBus::new();
+        let report = event_bus
+            .set_error_policy(ErrorPolicy::Continue)
+            .subscribe_listener("payment", <dyn Subscriber>::builder().on_event(|_| Err("declined".to_string())).build())
+            .subscribe_listener("payment", <dyn Subscriber>::builder().on_event(|_| Ok(())).build())
+            .register("payment", 100u32)
+            .publish_report()
+            .unwrap();
+        let payment = "payment".to_string();
+        assert_eq!((1, 0), (report.topic(&payment).handled, report.topic(&payment).failed));
+        assert_eq!(1, event_bus.metrics().topic(&payment).delivered);
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    enum Topic {
+        PlayerMoved,
+        PlayerDied,
+    }
+
+    #[test]
+    fn test_publisher_with_enum_topics() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let event_bus: EventBus<Topic> = EventBus::default();
+        event_bus
+            .subscribe_listener(Topic::PlayerMoved, RecordingSubscriber::new("moved", false, &log))
+            .subscribe_listener(Topic::PlayerDied, FailingSubscriber { log: log.clone() });
+        let result =
+            event_bus
+                .register(Topic::PlayerMoved, Event::new((1u32, 2u32)))
+                .publish();
+        assert_eq!(Ok(()), result);
+        assert_eq!(vec!["moved:before", "moved:event", "moved:after"], *log.borrow());
+
+        let result = event_bus.register(Topic::PlayerDied, Event::new(1u32)).publish();
+        assert_eq!(Err("audit failed".to_string()), result);
+    }
+
+    mod declared {
+        crate::topics! {
+            PLAYER_MOVED = "player.moved",
+            PLAYER_DIED = "player.died",
+        }
+    }
+
+    #[test]
+    fn test_restricted_topics_reject_unknown_subscription() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus.restrict_topics(declared::ALL);
+
+        let result = event_bus.try_subscribe_listener("player.dead", RecordingSubscriber::new("dead", false, &log));
+        assert_eq!(Some(UnknownTopic { topic: "player.dead".to_string() }), result.err());
+
+        let result =
+            event_bus
+                .try_subscribe_listener(declared::PLAYER_DIED, RecordingSubscriber::new("died", false, &log))
+                .unwrap()
+                .register(declared::PLAYER_DIED, Event::new(1u32))
+                .register("player.dead", Event::new(1u32))
+                .publish();
+        assert_eq!(Ok(()), result);
+        assert_eq!(vec!["died:before", "died:event", "died:after"], *log.borrow());
+    }
+
+    struct CountingSubscriber {
+        count: Rc<RefCell<usize>>,
+    }
+
+    impl Subscriber for CountingSubscriber {
+        fn on_event(&mut self, _event: &mut Event) -> Result<(), String> {
+            *self.count.borrow_mut() += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_dedupe_topic_with_comparator() {
+        let count = Rc::new(RefCell::new(0));
+        let event_bus = EventBus::new();
+        event_bus
+            .dedupe_topic("layout_dirty", |a, b| a.get_data::<u32>() == b.get_data::<u32>())
+            .subscribe_listener("layout_dirty", CountingSubscriber { count: count.clone() });
+        let result =
+            event_bus
+                .register("layout_dirty", Event::new(1u32))
+                .register("layout_dirty", Event::new(1u32))
+                .register("layout_dirty", Event::new(2u32))
+                .register("layout_dirty", Event::new(1u32))
+                .publish();
+        assert_eq!(Ok(()), result);
+        assert_eq!(2, *count.borrow());
+
+        let metrics = event_bus.metrics().topic(&"layout_dirty".to_string());
+        assert_eq!(4, metrics.registered);
+        assert_eq!(2, metrics.deduplicated);
+    }
+
+    #[test]
+    fn test_dedupe_topic_by_type() {
+        let count = Rc::new(RefCell::new(0));
+        let event_bus = EventBus::new();
+        event_bus
+            .dedupe_topic_by_type("layout_dirty")
+            .subscribe_listener("layout_dirty", CountingSubscriber { count: count.clone() });
+        let result =
+            event_bus
+                .register("layout_dirty", Event::new(1u32))
+                .register("layout_dirty", Event::new(2u32))
+                .register("layout_dirty", Event::new("hello".to_string()))
+                .register("layout_dirty", Event::new(3u32))
+                .publish();
+        assert_eq!(Ok(()), result);
+        assert_eq!(2, *count.borrow());
+        assert_eq!(2, event_bus.metrics().topic(&"layout_dirty".to_string()).deduplicated);
+    }
+
+    struct SizeSubscriber {
+        received: Rc<RefCell<Vec<(u32, u32)>>>,
+    }
+
+    impl Subscriber for SizeSubscriber {
+        fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+            let size = event.get_data::<(u32, u32)>().ok_or("Expected a size")?;
+            self.received.borrow_mut().push(*size);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_coalesce_latest_topic() {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .set_topic_mode("window_resized", TopicMode::CoalesceLatest)
+            .subscribe_listener("window_resized", SizeSubscriber { received: received.clone() });
+        for width in 1..=5u32 {
+            event_bus.register("window_resized", Event::new((width * 100, 600u32)));
+        }
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![(500, 600)], *received.borrow());
+        assert_eq!(4, event_bus.metrics().topic(&"window_resized".to_string()).coalesced);
+    }
+
+    struct TextSubscriber {
+        received: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Subscriber for TextSubscriber {
+        fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+            let text = event.get_data::<String>().ok_or("Expected a String")?;
+            self.received.borrow_mut().push(text.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_debounced_subscription_delivers_once_after_gap() {
+        let clock = ManualClock::new();
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let immediate = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .set_clock(clock.clone())
+            .subscribe_debounced("search_text_changed", TextSubscriber { received: received.clone() }, Duration::from_millis(300))
+            .subscribe_listener("search_text_changed", TextSubscriber { received: immediate.clone() });
+
+        for text in ["r", "ru", "rus"] {
+            event_bus.register("search_text_changed", Event::new(text.to_string())).publish().unwrap();
+            clock.advance(Duration::from_millis(100));
+        }
+        assert!(received.borrow().is_empty());
+        assert_eq!(3, immediate.borrow().len());
+
+        clock.advance(Duration::from_millis(200));
+        event_bus.publish().unwrap();
+        event_bus.publish().unwrap();
+        assert_eq!(vec!["rus".to_string()], *received.borrow());
+    }
+
+    #[test]
+    fn test_debounced_subscription_delivers_lone_event_after_period() {
+        let clock = ManualClock::new();
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .set_clock(clock.clone())
+            .subscribe_debounced("search_text_changed", TextSubscriber { received: received.clone() }, Duration::from_millis(300));
+
+        event_bus.register("search_text_changed", Event::new("rust".to_string())).publish().unwrap();
+        assert!(received.borrow().is_empty());
+
+        clock.advance(Duration::from_millis(300));
+        event_bus.publish().unwrap();
+        assert_eq!(vec!["rust".to_string()], *received.borrow());
+    }
+
+    #[test]
+    fn test_event_ttl_expires_with_manual_clock() {
+        let clock = ManualClock::new();
+        let count = Rc::new(RefCell::new(0));
+        let event_bus: EventBus = EventBus::with_clock(clock.clone());
+        event_bus
+            .subscribe_listener("session_token", CountingSubscriber { count: count.clone() })
+            .register("session_token", Event::new(1u32).with_ttl(Duration::from_secs(30)))
+            .register("session_token", Event::new(2u32));
+
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(1, *count.borrow());
+        let dead_letters = event_bus.take_dead_letters();
+        assert_eq!(1, dead_letters.len());
+        assert_eq!(DeadLetterReason::Expired, dead_letters[0].reason);
+        assert_eq!(Some(&1u32), dead_letters[0].event.get_data::<u32>());
+    }
+
+    #[test]
+    fn test_delayed_event_is_published_once_due() {
+        let clock = ManualClock::new();
+        let count = Rc::new(RefCell::new(0));
+        let event_bus: EventBus = EventBus::with_clock(clock.clone());
+        event_bus
+            .subscribe_listener("reminder", CountingSubscriber { count: count.clone() })
+            .register_after("reminder", 1u32, Duration::from_secs(60));
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(0, *count.borrow());
+
+        clock.advance(Duration::from_secs(59));
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(0, *count.borrow());
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(1, *count.borrow());
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(1, *count.borrow());
+    }
+
+    fn publish_counts(event_bus: &mut EventBus, count: &Rc<RefCell<usize>>, cycles: usize) -> Vec<usize> {
+        (0..cycles)
+            .map(|_| {
+                *count.borrow_mut() = 0;
+                event_bus.publish().unwrap();
+                *count.borrow()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_limit_topic_defers_overflow() {
+        let count = Rc::new(RefCell::new(0));
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let mut event_bus = EventBus::new();
+        event_bus
+            .limit_topic("mouse.moved", 3, OverflowAction::Defer)
+            .subscribe_listener("mouse.moved", CountingSubscriber { count: count.clone() })
+            .subscribe_listener("mouse.moved", SizeSubscriber { received: received.clone() });
+        for x in 0..10u32 {
+            event_bus.register("mouse.moved", Event::new((x, 0u32)));
+        }
+        assert_eq!(vec![3, 3, 3, 1], publish_counts(&mut event_bus, &count, 4));
+        let expected: Vec<(u32, u32)> = (0..10).map(|x| (x, 0)).collect();
+        assert_eq!(expected, *received.borrow());
+    }
+
+    #[test]
+    fn test_limit_topic_drops_overflow() {
+        let count = Rc::new(RefCell::new(0));
+        let mut event_bus = EventBus::new();
+        event_bus
+            .limit_topic("mouse.moved", 3, OverflowAction::Drop)
+            .subscribe_listener("mouse.moved", CountingSubscriber { count: count.clone() });
+        for x in 0..10u32 {
+            event_bus.register("mouse.moved", Event::new((x, 0u32)));
+        }
+        assert_eq!(vec![3, 0], publish_counts(&mut event_bus, &count, 2));
+        assert_eq!(7, event_bus.metrics().topic(&"mouse.moved".to_string()).dropped);
+    }
+
+    #[test]
+    fn test_wildcard_subscriptions_deliver_once_per_match() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .subscribe_listener("sensors/kitchen/temperature", RecordingSubscriber::new("exact", false, &log))
+            .subscribe_listener("sensors/+/temperature", RecordingSubscriber::new("single", false, &log))
+            .subscribe_listener("sensors/#", RecordingSubscriber::new("multi", false, &log))
+            .subscribe_listener("sensors/#/temperature", RecordingSubscriber::new("invalid", false, &log));
+        assert!(event_bus.subscribe_pattern("sensors/#/temperature", CountingSubscriber { count: Rc::new(RefCell::new(0)) }).is_err());
+
+        let result =
+            event_bus
+                .register("sensors/kitchen/temperature", Event::new(21u32))
+                .publish();
+        assert_eq!(Ok(()), result);
+        let events: Vec<String> = log.borrow().iter().filter(|entry| entry.ends_with(":event")).cloned().collect();
+        assert_eq!(vec!["exact:event", "single:event", "multi:event"], events);
+
+        log.borrow_mut().clear();
+        event_bus.register("sensors/kitchen/humidity", Event::new(40u32)).publish().unwrap();
+        assert_eq!(vec!["multi:before", "multi:event", "multi:after"], *log.borrow());
+    }
+
+    struct NackingSubscriber {
+        requeue: bool,
+        nacks: usize,
+        received: Rc<RefCell<Vec<u32>>>,
+    }
+
+    impl Subscriber for NackingSubscriber {
+        fn on_event_outcome(&mut self, event: &mut Event) -> Outcome {
+            self.received.borrow_mut().push(event.redeliveries());
+            if self.nacks == 0 {
+                return Outcome::Ack;
+            }
+            self.nacks -= 1;
+            Outcome::Nack { requeue: self.requeue }
+        }
+    }
+
+    #[test]
+    fn test_nack_requeues_event() {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .subscribe_listener("startup", NackingSubscriber { requeue: true, nacks: 1, received: received.clone() })
+            .register("startup", Event::new(1u32));
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![0], *received.borrow());
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![0, 1], *received.borrow());
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![0, 1], *received.borrow());
+        assert!(event_bus.dead_letters().is_empty());
+    }
+
+    #[test]
+    fn test_nack_without_requeue_dead_letters_event() {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .subscribe_listener("startup", NackingSubscriber { requeue: false, nacks: 1, received: received.clone() })
+            .register("startup", Event::new(1u32));
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![0], *received.borrow());
+        assert_eq!(DeadLetterReason::Nacked, event_bus.dead_letters()[0].reason);
+    }
+
+    #[test]
+    fn test_nack_redeliveries_are_capped() {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .set_max_redeliveries(5)
+            .subscribe_listener("startup", NackingSubscriber { requeue: true, nacks: usize::MAX, received: received.clone() })
+            .register("startup", Event::new(1u32).with_max_redeliveries(2));
+        for _ in 0..5 {
+            assert_eq!(Ok(()), event_bus.publish());
+        }
+        assert_eq!(vec![0, 1, 2], *received.borrow());
+        assert_eq!(DeadLetterReason::MaxRedeliveries { redeliveries: 2 }, event_bus.dead_letters()[0].reason);
+    }
+
+    struct DeferringSubscriber {
+        defers: u32,
+        received: Rc<RefCell<Vec<u32>>>,
+    }
+
+    impl Subscriber for DeferringSubscriber {
+        fn on_event_outcome(&mut self, event: &mut Event) -> Outcome {
+            self.received.borrow_mut().push(event.deferral_count());
+            if event.deferral_count() < self.defers {
+                return Outcome::Defer;
+            }
+            Outcome::Ack
+        }
+    }
+
+    #[test]
+    fn test_deferred_event_is_redelivered_on_later_cycles() {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let (collector, collected) = crate::subscribers::CollectingSubscriber::<u32>::new();
+        let event_bus = EventBus::new();
+        event_bus
+            .set_max_redeliveries(0)
+            .set_defer_mode(DeferMode::SkipRemaining)
+            .subscribe_listener("assets", DeferringSubscriber { defers: 2, received: received.clone() })
+            .subscribe_listener("assets", collector)
+            .register("assets", 7u32);
+
+        for cycle in 1..=3 {
+            assert_eq!(Ok(()), event_bus.publish());
+            assert_eq!(cycle, received.borrow().len());
+        }
+        assert_eq!(vec![0, 1, 2], *received.borrow());
+        assert_eq!(vec![7], *collected.borrow());
+        assert!(event_bus.dead_letters().is_empty());
+        assert_eq!(0, event_bus.pending(&"assets".to_string()));
+    }
+
+    #[test]
+    fn test_deferrals_are_capped() {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .set_max_deferrals(1)
+            .subscribe_listener("assets", DeferringSubscriber { defers: u32::MAX, received: received.clone() })
+            .register("assets", 7u32);
+        for _ in 0..3 {
+            assert_eq!(Ok(()), event_bus.publish());
+        }
+        assert_eq!(vec![0, 1], *received.borrow());
+        assert_eq!(DeadLetterReason::MaxDeferrals { deferrals: 1 }, event_bus.dead_letters()[0].reason);
+    }
+
+    struct IgnoringSubscriber;
+
+    impl Subscriber for IgnoringSubscriber {
+        fn on_event_outcome(&mut self, _event: &mut Event) -> Outcome {
+            Outcome::Ignored
+        }
+    }
+
+    #[test]
+    fn test_event_ignored_by_all_subscribers_is_unhandled() {
+        let event_bus = EventBus::new();
+        event_bus
+            .subscribe_listener("startup", IgnoringSubscriber)
+            .subscribe_listener("startup", IgnoringSubscriber)
+            .register("startup", Event::new(1u32));
+        let report = event_bus.publish_report().unwrap();
+        assert_eq!(1, report.topic(&"startup".to_string()).ignored);
+        assert_eq!(0, report.topic(&"startup".to_string()).handled);
+        assert_eq!(DeadLetterReason::Unhandled, event_bus.dead_letters()[0].reason);
+    }
+
+    #[test]
+    fn test_event_handled_by_one_subscriber_is_handled() {
+        let count = Rc::new(RefCell::new(0));
+        let event_bus = EventBus::new();
+        event_bus
+            .subscribe_listener("startup", IgnoringSubscriber)
+            .subscribe_listener("startup", CountingSubscriber { count: count.clone() })
+            .register("startup", Event::new(1u32));
+        let report = event_bus.publish_report().unwrap();
+        assert_eq!(0, report.topic(&"startup".to_string()).ignored);
+        assert_eq!(1, report.topic(&"startup".to_string()).handled);
+        assert!(event_bus.dead_letters().is_empty());
+    }
+
+    struct SequenceSubscriber {
+        sequences: Rc<RefCell<Vec<(u32, u64)>>>,
+        nack_once: bool,
+    }
+
+    impl Subscriber for SequenceSubscriber {
+        fn on_event_outcome(&mut self, event: &mut Event) -> Outcome {
+            self.sequences.borrow_mut().push((*event.get_data::<u32>().unwrap(), event.sequence().unwrap()));
+            if std::mem::take(&mut self.nack_once) {
+                return Outcome::Nack { requeue: true };
+            }
+            Outcome::Ack
+        }
+    }
+
+    #[test]
+    fn test_sequence_follows_registration_across_topics() {
+        let sequences = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .subscribe_listener("clicks", SequenceSubscriber { sequences: sequences.clone(), nack_once: true })
+            .subscribe_listener("keys", SequenceSubscriber { sequences: sequences.clone(), nack_once: false })
+            .register("clicks", 1u32)
+            .register("keys", 2u32)
+            .register("clicks", 3u32)
+            .register("keys", 4u32);
+
+        let report = event_bus.publish_report().unwrap();
+        event_bus.publish().unwrap();
+        let mut received = sequences.borrow().clone();
+        received.sort();
+        assert_eq!(vec![(1, 1), (1, 1), (2, 2), (3, 3), (4, 4)], received);
+        assert_eq!(Some(3), report.topic(&"clicks".to_string()).last_sequence);
+        assert_eq!(Some(4), report.topic(&"keys".to_string()).last_sequence);
+    }
+
+    #[test]
+    fn test_dropped_events_take_no_sequence_number() {
+        let sequences = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .set_topic_capacity("clicks", 1)
+            .dedupe_topic_by_type("keys")
+            .subscribe_listener("clicks", SequenceSubscriber { sequences: sequences.clone(), nack_once: false })
+            .subscribe_listener("keys", SequenceSubscriber { sequences: sequences.clone(), nack_once: false })
+            .register("clicks", 1u32)
+            .register("clicks", 2u32)
+            .register("keys", 3u32)
+            .register("keys", 4u32);
+
+        event_bus.publish().unwrap();
+        let mut received = sequences.borrow().clone();
+        received.sort();
+        assert_eq!(vec![(1, 1), (3, 2)], received);
+    }
+
+    #[test]
+    fn test_global_fifo_interleaves_topics() {
+        let sequences = Rc::new(RefCell::new(Vec::new()));
+        let recording = || SequenceSubscriber { sequences: sequences.clone(), nack_once: false };
+        let event_bus = EventBus::new();
+        event_bus
+            .set_dispatch_order(DispatchOrder::GlobalFifo)
+            .subscribe_listener("config_changed", recording())
+            .subscribe_listener("request_received", recording())
+            .register("config_changed", 1u32)
+            .register("request_received", 2u32)
+            .register("request_received", 3u32)
+            .register("config_changed", 4u32)
+            .register("request_received", 5u32);
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![(1, 1), (2, 2), (3, 3), (4, 4), (5, 5)], *sequences.borrow());
+    }
+
+    #[test]
+    fn test_priority_topic_is_published_first() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .set_topic_priority("system.*", 10)
+            .subscribe_listener("gameplay.move", RecordingSubscriber::new("move", false, &log))
+            .subscribe_listener("system.pause", RecordingSubscriber::new("pause", false, &log))
+            .register("gameplay.move", 1u32)
+            .register("system.pause", 2u32);
+
+        assert_eq!(10, event_bus.topic_priority(&"system.pause".to_string()));
+        assert_eq!(0, event_bus.topic_priority(&"gameplay.move".to_string()));
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(
+            vec!["pause:before", "pause:event", "pause:after", "move:before", "move:event", "move:after"],
+            *log.borrow()
+        );
+    }
+
+    #[test]
+    fn test_topics_are_published_in_topic_order() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus.set_topic_order(&["input", "simulation", "audio", "render"]);
+        for topic in ["render", "ui", "audio", "input", "net", "simulation"] {
+            let log = log.clone();
+            event_bus
+                .subscribe_listener(topic, <dyn Subscriber>::builder().on_event(move |_| {
+                    log.borrow_mut().push(topic);
+                    Ok(())
+                }).build())
+                .register(topic, 1u32);
+        }
+
+        assert_eq!(vec!["input", "simulation", "audio", "render"], event_bus.topic_order());
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec!["input", "simulation", "audio", "render", "ui", "net"], *log.borrow());
+
+        log.borrow_mut().clear();
+        event_bus.set_topic_order(&["net", "render"]);
+        for topic in ["audio", "render", "net"] {
+            event_bus.register(topic, 2u32);
+        }
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec!["net", "render", "audio"], *log.borrow());
+    }
+
+    struct StoppingSubscriber {
+        log: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Subscriber for StoppingSubscriber {
+        fn on_event_outcome(&mut self, _event: &mut Event) -> Outcome {
+            self.log.borrow_mut().push("stop:event".to_string());
+            Outcome::Stop
+        }
+    }
+
+    #[test]
+    fn test_stop_outcome_skips_remaining_subscribers() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .subscribe_listener("startup", RecordingSubscriber::new("first", false, &log))
+            .subscribe_listener("startup", StoppingSubscriber { log: log.clone() })
+            .subscribe_listener("startup", RecordingSubscriber::new("third", false, &log))
+            .register("startup", Event::new(1u32));
+        let report = event_bus.publish_report().unwrap();
+        assert_eq!(
+            vec!["first:before", "third:before", "first:event", "stop:event", "first:after"],
+            *log.borrow()
+        );
+        assert_eq!(1, report.topic(&"startup".to_string()).handled);
+    }
+
+    struct MetaSubscriber {
+        topics: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Subscriber for MetaSubscriber {
+        fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+            if let Some(added) = event.get_data::<SubscriberAdded>() {
+                self.topics.borrow_mut().push(added.topic.clone());
+            }
+            Ok(())
+        }
+    }
+
+    struct AttachedSubscriber {
+        topics: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Subscriber for AttachedSubscriber {
+        fn on_subscribe(&mut self, topic: &str) {
+            self.topics.borrow_mut().push(topic.to_string());
+        }
+    }
+
+    #[test]
+    fn test_subscriber_is_told_the_topics_it_is_subscribed_to() {
+        let topics = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .subscribe_listener("orders", AttachedSubscriber { topics: topics.clone() })
+            .subscribe_listener("sensors/+/temperature", AttachedSubscriber { topics: topics.clone() });
+        assert_eq!(vec!["orders", "sensors/+/temperature"], *topics.borrow());
+
+        let lazy_topics = topics.clone();
+        event_bus.subscribe_lazy("invoices", move || Box::new(AttachedSubscriber { topics: lazy_topics }));
+        assert_eq!(2, topics.borrow().len());
+        event_bus.register("invoices", 1u32).publish().unwrap();
+        assert_eq!(vec!["orders", "sensors/+/temperature", "invoices"], *topics.borrow());
+    }
+
+    struct ReplayingSubscriber {
+        sink: EventSink,
+        received: Rc<RefCell<Vec<u32>>>,
+    }
+
+    impl Subscriber for ReplayingSubscriber {
+        fn on_subscribe(&mut self, topic: &str) {
+            self.sink.register(topic, 7u32);
+        }
+
+        fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+            self.received.borrow_mut().push(*event.try_get_data::<u32>()?);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_subscriber_can_use_the_event_bus_when_subscribed() {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus.subscribe_listener("orders", ReplayingSubscriber { sink: event_bus.sink(), received: received.clone() });
+        assert_eq!(1, event_bus.pending(&"orders".to_string()));
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![7], *received.borrow());
+    }
+
+    struct ReleasingSubscriber {
+        name: &'static str,
+        released: Rc<RefCell<Vec<(&'static str, String)>>>,
+    }
+
+    impl Subscriber for ReleasingSubscriber {
+        fn on_unsubscribe(&mut self, topic: &str) {
+            self.released.borrow_mut().push((self.name, topic.to_string()));
+        }
+    }
+
+    struct FlushingSubscriber {
+        sink: EventSink,
+    }
+
+    impl Subscriber for FlushingSubscriber {
+        fn on_event_outcome(&mut self, _event: &mut Event) -> Outcome {
+            Outcome::AckAndUnsubscribe
+        }
+
+        fn on_unsubscribe(&mut self, topic: &str) {
+            self.sink.register("flushed", topic.to_string());
+        }
+    }
+
+    #[test]
+    fn test_subscriber_can_use_the_event_bus_when_unsubscribed() {
+        let event_bus = EventBus::new();
+        let (collector, flushed) = crate::subscribers::CollectingSubscriber::<String>::new();
+        let handle = event_bus.subscribe_unique("orders", FlushingSubscriber { sink: event_bus.sink() }).unwrap();
+        assert!(event_bus.unsubscribe(&handle));
+        event_bus
+            .subscribe_listener("flushed", collector)
+            .subscribe_listener("invoices", FlushingSubscriber { sink: event_bus.sink() })
+            .register("invoices", 1u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec!["orders".to_string(), "invoices".to_string()], *flushed.borrow());
+
+        event_bus.subscribe_listener("payments", FlushingSubscriber { sink: event_bus.sink() }).shutdown();
+        assert_eq!(1, event_bus.pending(&"flushed".to_string()));
+    }
+
+    #[test]
+    fn test_subscriber_is_released_once() {
+        let released = Rc::new(RefCell::new(Vec::new()));
+        let subscriber = |name| ReleasingSubscriber { name, released: released.clone() };
+        let event_bus = EventBus::new();
+        let file = event_bus.subscribe_unique("logs", subscriber("file")).unwrap();
+        event_bus
+            .subscribe_listener("logs", subscriber("socket"))
+            .subscribe_listener("logs", subscriber("console"));
+
+        assert!(event_bus.unsubscribe(&file));
+        assert_eq!(vec![("file", "logs".to_string())], *released.borrow());
+        event_bus.shutdown();
+        drop(event_bus);
+        let expected: Vec<(&str, String)> = ["file", "console", "socket"].iter().map(|name| (*name, "logs".to_string())).collect();
+        assert_eq!(expected, *released.borrow());
+
+        released.borrow_mut().clear();
+        let event_bus = EventBus::new();
+        event_bus.subscribe_listener("logs", subscriber("file"));
+        drop(event_bus);
+        assert_eq!(vec![("file", "logs".to_string())], *released.borrow());
+    }
+
+    #[test]
+    fn test_meta_event_on_subscriber_added() {
+        let topics = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .enable_meta_events()
+            .subscribe_listener(SubscriberAdded::TOPIC, MetaSubscriber { topics: topics.clone() })
+            .subscribe_listener("startup", ExampleSubscriber::new());
+        assert!(topics.borrow().is_empty());
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![SubscriberAdded::TOPIC.to_string(), "startup".to_string()], *topics.borrow());
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(2, topics.borrow().len());
+    }
+
+    #[test]
+    fn test_meta_events_are_reported() {
+        let topics = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .enable_meta_events()
+            .subscribe_listener(SubscriberAdded::TOPIC, MetaSubscriber { topics: topics.clone() })
+            .subscribe_listener("startup", ExampleSubscriber::new());
+
+        let report = event_bus.publish_report().unwrap();
+        let meta_topic = SubscriberAdded::TOPIC.to_string();
+        assert_eq!(2, topics.borrow().len());
+        assert_eq!(2, report.topic(&meta_topic).handled);
+        assert_eq!(2, report.topic(&meta_topic).latency.count);
+        assert_eq!(2, event_bus.metrics().topic(&meta_topic).delivered);
+    }
+
+    #[test]
+    fn test_meta_events_do_not_produce_meta_events() {
+        let event_bus = EventBus::new();
+        event_bus
+            .enable_meta_events()
+            .subscribe_listener(DeadLettered::TOPIC, NackingSubscriber { requeue: false, nacks: usize::MAX, received: Rc::default() })
+            .subscribe_listener("startup", NackingSubscriber { requeue: false, nacks: 1, received: Rc::default() })
+            .register("startup", Event::new(1u32));
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(2, event_bus.dead_letters().len());
+        assert_eq!(DeadLettered::TOPIC, event_bus.dead_letters()[1].topic);
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(2, event_bus.dead_letters().len());
+    }
+
+    struct UnsubscribingSubscriber {
+        remaining: usize,
+        received: Rc<RefCell<Vec<u32>>>,
+    }
+
+    impl Subscriber for UnsubscribingSubscriber {
+        fn on_event_outcome(&mut self, event: &mut Event) -> Outcome {
+            self.received.borrow_mut().push(*event.get_data::<u32>().unwrap());
+            self.remaining -= 1;
+            if self.remaining == 0 {
+                return Outcome::AckAndUnsubscribe;
+            }
+            Outcome::Ack
+        }
+    }
+
+    #[test]
+    fn test_subscriber_unsubscribes_itself() {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let count = Rc::new(RefCell::new(0));
+        let event_bus = EventBus::new();
+        event_bus
+            .subscribe_listener("startup", UnsubscribingSubscriber { remaining: 2, received: received.clone() })
+            .subscribe_listener("startup", CountingSubscriber { count: count.clone() });
+        for index in 1..=5u32 {
+            event_bus.register("startup", Event::new(index));
+        }
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![1, 2], *received.borrow());
+        assert_eq!(5, *count.borrow());
+
+        event_bus.register("startup", Event::new(6u32));
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![1, 2], *received.borrow());
+        assert_eq!(6, *count.borrow());
+    }
+
+    struct FailureSubscriber {
+        failures: Rc<RefCell<Vec<SubscriberFailure>>>,
+    }
+
+    impl Subscriber for FailureSubscriber {
+        fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+            self.failures.borrow_mut().push(event.get_data::<SubscriberFailure>().unwrap().clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_route_errors_to_error_topic() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let failures = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .set_error_policy(ErrorPolicy::Continue)
+            .route_errors_to("__errors")
+            .subscribe_listener("__errors", FailureSubscriber { failures: failures.clone() })
+            .subscribe_listener("__errors", FailingSubscriber { log: log.clone() })
+            .subscribe_listener("startup", FailingSubscriber { log: log.clone() });
+        let event = Event::new(1u32);
+        let event_id = event.id();
+        event_bus.register("startup", event);
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert!(failures.borrow().is_empty());
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(1, failures.borrow().len());
+        let failure = &failures.borrow()[0];
+        assert_eq!("startup", failure.topic);
+        assert!(failure.subscriber.ends_with("FailingSubscriber"));
+        assert_eq!(Phase::Event, failure.phase);
+        assert_eq!("audit failed", failure.message);
+        assert_eq!(event_id, failure.event_id);
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(1, failures.borrow().len());
+        assert_eq!(2, log.borrow().len());
+    }
+
+    #[test]
+    fn test_register_data_without_event() {
+        let sizes = Rc::new(RefCell::new(Vec::new()));
+        let texts = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .subscribe_listener("size", SizeSubscriber { received: sizes.clone() })
+            .subscribe_listener("text", TextSubscriber { received: texts.clone() })
+            .register("size", (4u32, 2u32))
+            .register("size", Event::new((4u32, 3u32)))
+            .register("text", "hello".to_string());
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![(4, 2), (4, 3)], *sizes.borrow());
+        assert_eq!(vec!["hello".to_string()], *texts.borrow());
+    }
+
+    #[test]
+    fn test_collect_events_into_event_bus() {
+        let pairs = (0..1000u32).map(|index| (format!("topic/{}", index / 100), Event::new(index)));
+        let mut event_bus: EventBus = pairs.collect();
+        for topic in 0..10 {
+            assert_eq!(100, event_bus.pending(&format!("topic/{}", topic)));
+            assert_eq!(100, event_bus.metrics().topic(&format!("topic/{}", topic)).registered);
+        }
+
+        event_bus.extend(vec![("a".to_string(), Event::new(1u32)), ("b".to_string(), Event::new(2u32))]);
+        event_bus.extend(vec![("a".to_string(), 3u32)]);
+        assert_eq!(2, event_bus.pending(&"a".to_string()));
+        assert_eq!(1, event_bus.pending(&"b".to_string()));
+    }
+
+    struct ReentrantSubscriber {
+        event_bus: Rc<EventBus>,
+        results: Rc<RefCell<Vec<Result<(), String>>>>,
+    }
+
+    impl Subscriber for ReentrantSubscriber {
+        fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+            self.results.borrow_mut().push(self.event_bus.publish());
+            self.event_bus.register("echo", *event.get_data::<u32>().unwrap());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_reentrant_publish_is_rejected() {
+        let event_bus = Rc::new(EventBus::new());
+        let results = Rc::new(RefCell::new(Vec::new()));
+        let count = Rc::new(RefCell::new(0));
+        event_bus
+            .subscribe_listener("startup", ReentrantSubscriber { event_bus: event_bus.clone(), results: results.clone() })
+            .subscribe_listener("echo", CountingSubscriber { count: count.clone() })
+            .register("startup", 1u32);
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![Err(AlreadyPublishing.to_string())], *results.borrow());
+        assert_eq!(0, *count.borrow());
+        assert_eq!(1, event_bus.pending(&"echo".to_string()));
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(1, *count.borrow());
+    }
+
+    struct SubscribingSubscriber {
+        event_bus: Rc<EventBus>,
+        count: Rc<RefCell<usize>>,
+    }
+
+    impl Subscriber for SubscribingSubscriber {
+        fn on_event(&mut self, _event: &mut Event) -> Result<(), String> {
+            self.event_bus.subscribe_listener("startup", CountingSubscriber { count: self.count.clone() });
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_subscribe_while_publishing() {
+        let event_bus = Rc::new(EventBus::new());
+        let count = Rc::new(RefCell::new(0));
+        event_bus
+            .subscribe_listener("startup", SubscribingSubscriber { event_bus: event_bus.clone(), count: count.clone() })
+            .register("startup", 1u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(0, *count.borrow());
+
+        event_bus.register("startup", 2u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(1, *count.borrow());
+    }
+
+    struct HandleUnsubscriber {
+        event_bus: Rc<EventBus>,
+        handle: SubscriptionHandle,
+        results: Rc<RefCell<Vec<bool>>>,
+    }
+
+    impl Subscriber for HandleUnsubscriber {
+        fn on_event(&mut self, _event: &mut Event) -> Result<(), String> {
+            self.results.borrow_mut().push(self.event_bus.unsubscribe(&self.handle));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_unsubscribe_other_topic_while_publishing() {
+        let event_bus = Rc::new(EventBus::new());
+        let count = Rc::new(RefCell::new(0));
+        let results = Rc::new(RefCell::new(Vec::new()));
+        let handle = event_bus.subscribe_unique("tick", CountingSubscriber { count: count.clone() }).unwrap();
+        event_bus
+            .subscribe_listener("shutdown", HandleUnsubscriber { event_bus: event_bus.clone(), handle, results: results.clone() })
+            .register("shutdown", 1u32)
+            .register("tick", 2u32)
+            .register("tick", 3u32);
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![true], *results.borrow());
+        assert_eq!(0, *count.borrow());
+        assert!(!event_bus.has_subscriber::<CountingSubscriber>(&"tick".to_string()));
+    }
+
+    struct OneShotTextSubscriber {
+        received: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Subscriber for OneShotTextSubscriber {
+        fn on_event_outcome(&mut self, event: &mut Event) -> Outcome {
+            self.received.borrow_mut().push(event.get_data::<String>().unwrap().clone());
+            Outcome::AckAndUnsubscribe
+        }
+    }
+
+    #[test]
+    fn test_debounced_subscriber_unsubscribes_itself() {
+        let clock = ManualClock::new();
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .set_clock(clock.clone())
+            .subscribe_debounced("search_text_changed", OneShotTextSubscriber { received: received.clone() }, Duration::from_millis(300))
+            .register("search_text_changed", Event::new("rust".to_string()));
+
+        for text in ["rust", "rust event bus"] {
+            event_bus.register("search_text_changed", Event::new(text.to_string()));
+            clock.advance(Duration::from_millis(300));
+            assert_eq!(Ok(()), event_bus.publish());
+            clock.advance(Duration::from_millis(300));
+            assert_eq!(Ok(()), event_bus.publish());
+        }
+        assert_eq!(vec!["rust".to_string()], *received.borrow());
+    }
+}
@@ -0,0 +1,408 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::mpsc::{Receiver, SendError, SyncSender, TryRecvError, TrySendError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use crate::testing::ManualClock;
+use super::{Clock, EventBus, TopicKey};
+
+/// What a step of a task did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Step {
+    Progressed,
+    Idle,
+    Finished,
+}
+
+/// A task of the event bus that runs off its thread, one step at a time.
+pub(crate) trait Task: Send {
+    /// Runs one step. Waits for work when `block` is set, rather than returning `Step::Idle`.
+    fn step(&mut self, block: bool) -> Step;
+}
+
+/// A task handling the mail of a mailbox one at a time, until the handler returns false or the mailbox is closed.
+pub(crate) struct MailboxTask<M, F> {
+    pub(crate) mailbox: Receiver<M>,
+    pub(crate) handle: F,
+}
+
+impl<M: Send, F: FnMut(M) -> bool + Send> Task for MailboxTask<M, F> {
+    fn step(&mut self, block: bool) -> Step {
+        let mail = match block {
+            true => self.mailbox.recv().ok(),
+            false => match self.mailbox.try_recv() {
+                Ok(mail) => Some(mail),
+                Err(TryRecvError::Empty) => return Step::Idle,
+                Err(TryRecvError::Disconnected) => None,
+            },
+        };
+        match mail.map(&mut self.handle) {
+            Some(true) => Step::Progressed,
+            _ => Step::Finished,
+        }
+    }
+}
+
+/// A task of a test, running one step per call of its function until it returns false.
+struct Steps<F>(F);
+
+impl<F: FnMut() -> bool + Send> Task for Steps<F> {
+    fn step(&mut self, _block: bool) -> Step {
+        match (self.0)() {
+            true => Step::Progressed,
+            false => Step::Finished,
+        }
+    }
+}
+
+/// A task spawned on a simulator, dropped once it finished.
+pub(crate) struct SimulatedTask {
+    task: Option<Box<dyn Task>>,
+}
+
+type TaskRef = Rc<RefCell<SimulatedTask>>;
+
+/// Runs one step of a simulated task, returns whether it progressed or finished.
+fn step_simulated(task: &TaskRef) -> bool {
+    let mut task = task.borrow_mut();
+    let Some(running) = &mut task.task else {
+        return false;
+    };
+    match running.step(false) {
+        Step::Progressed => true,
+        Step::Idle => false,
+        Step::Finished => {
+            task.task = None;
+            true
+        }
+    }
+}
+
+/// Runs the tasks of an event bus: each on a thread of its own, or on a simulator stepped by a test.
+#[derive(Clone, Default)]
+pub(crate) enum Executor {
+    #[default]
+    Threads,
+    Simulated(SimulatedExecutor),
+}
+
+impl Executor {
+    pub(crate) fn spawn(&self, mut task: impl Task + 'static) -> TaskHandle {
+        match self {
+            Executor::Threads => TaskHandle::Thread(thread::spawn(move || while task.step(true) != Step::Finished {})),
+            Executor::Simulated(simulator) => TaskHandle::Simulated(simulator.add(Box::new(task))),
+        }
+    }
+}
+
+/// The handle of a task, to wait for it to finish.
+pub(crate) enum TaskHandle {
+    Thread(JoinHandle<()>),
+    Simulated(TaskRef),
+}
+
+impl TaskHandle {
+    pub(crate) fn is_finished(&self) -> bool {
+        match self {
+            TaskHandle::Thread(handle) => handle.is_finished(),
+            TaskHandle::Simulated(task) => task.borrow().task.is_none(),
+        }
+    }
+
+    /// Gives the task a moment to progress: a thread is waited for, a simulated task is stepped.
+    pub(crate) fn yield_to(&self, clock: &dyn Clock) {
+        if let TaskHandle::Simulated(task) = self {
+            if step_simulated(task) {
+                return;
+            }
+        }
+        clock.sleep(Duration::from_millis(1));
+    }
+
+    /// Waits for the task to finish, returns false when it panicked.
+    pub(crate) fn join(self) -> bool {
+        match self {
+            TaskHandle::Thread(handle) => handle.join().is_ok(),
+            TaskHandle::Simulated(_) => true,
+        }
+    }
+
+    /// Returns a sender to the mailbox of the task, which steps a simulated task while the mailbox is full.
+    pub(crate) fn sender<M>(&self, sender: SyncSender<M>) -> MailSender<M> {
+        let task = match self {
+            TaskHandle::Thread(_) => None,
+            TaskHandle::Simulated(task) => Some(task.clone()),
+        };
+        MailSender { sender, task }
+    }
+}
+
+/// The sender of the mailbox of a task.
+pub(crate) struct MailSender<M> {
+    sender: SyncSender<M>,
+    task: Option<TaskRef>,
+}
+
+impl<M> MailSender<M> {
+    /// Sends the mail, waiting while the mailbox is full. A simulated task is stepped instead,
+    /// as it only runs when it is stepped.
+    pub(crate) fn send(&self, mail: M) -> Result<(), SendError<M>> {
+        let Some(task) = &self.task else {
+            return self.sender.send(mail);
+        };
+        let mut mail = mail;
+        loop {
+            match self.sender.try_send(mail) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Disconnected(unsent)) => return Err(SendError(unsent)),
+                Err(TrySendError::Full(unsent)) if !step_simulated(task) => return Err(SendError(unsent)),
+                Err(TrySendError::Full(unsent)) => mail = unsent,
+            }
+        }
+    }
+}
+
+/// # Task Id
+///
+/// Identifies a task of a simulated executor, in the order the tasks were spawned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TaskId(usize);
+
+#[derive(Default)]
+struct Simulation {
+    tasks: Vec<TaskRef>,
+    cursor: usize,
+}
+
+/// # Simulated Executor
+///
+/// Runs the worker threads of an event bus as cooperative tasks on the thread of the test, see `EventBus::simulate`,
+/// so concurrent deliveries interleave the same way every run. A task only runs when it is stepped:
+/// a worker handles one mail per step. The test can spawn tasks of its own, such as producers
+/// registering events through a thread sink, and force an interleaving by stepping tasks one by one.
+/// Clones share the same tasks and clock.
+///
+/// A publish that finds the mailbox of a worker full steps that worker until there is room,
+/// and `join_workers` steps the workers until they finish, waiting on the manual clock.
+///
+/// ## Methods
+///
+/// * `new` - Creates a simulator on a manual clock.
+///
+/// * `clock` - Returns the manual clock of the simulator.
+///
+/// * `spawn` - Spawns a task of the test.
+///
+/// * `step` - Runs one step of the next task that can progress.
+///
+/// * `step_task` - Runs one step of a task.
+///
+/// * `run_until_idle` - Steps the tasks until none can progress.
+///
+/// * `is_finished` - Returns whether a task finished.
+#[derive(Clone)]
+pub struct SimulatedExecutor {
+    simulation: Rc<RefCell<Simulation>>,
+    clock: ManualClock,
+}
+
+impl SimulatedExecutor {
+    /// # New
+    ///
+    /// Creates a simulator without tasks, on the manual clock.
+    pub fn new(clock: ManualClock) -> SimulatedExecutor {
+        SimulatedExecutor { simulation: Rc::new(RefCell::new(Simulation::default())), clock }
+    }
+
+    /// # Clock
+    ///
+    /// Returns the manual clock of the simulator, which `EventBus::simulate` hands to the event bus.
+    pub fn clock(&self) -> ManualClock {
+        self.clock.clone()
+    }
+
+    /// # Spawn
+    ///
+    /// Spawns a task that runs the function once per step, until it returns false.
+    pub fn spawn(&self, steps: impl FnMut() -> bool + Send + 'static) -> TaskId {
+        self.add(Box::new(Steps(steps)));
+        TaskId(self.simulation.borrow().tasks.len() - 1)
+    }
+
+    fn add(&self, task: Box<dyn Task>) -> TaskRef {
+        let task = Rc::new(RefCell::new(SimulatedTask { task: Some(task) }));
+        self.simulation.borrow_mut().tasks.push(task.clone());
+        task
+    }
+
+    /// # Step
+    ///
+    /// Runs one step of the next task, in round-robin order, that can progress.
+    /// Returns false when every task is idle or finished.
+    pub fn step(&self) -> bool {
+        let (tasks, cursor) = {
+            let simulation = self.simulation.borrow();
+            (simulation.tasks.clone(), simulation.cursor)
+        };
+        for offset in 0..tasks.len() {
+            let index = (cursor + offset) % tasks.len();
+            if step_simulated(&tasks[index]) {
+                self.simulation.borrow_mut().cursor = index + 1;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// # Step Task
+    ///
+    /// Runs one step of the task, to force an interleaving.
+    /// Returns false when the task is idle or finished.
+    pub fn step_task(&self, id: TaskId) -> bool {
+        let task = self.task(id);
+        step_simulated(&task)
+    }
+
+    /// # Run Until Idle
+    ///
+    /// Steps the tasks in round-robin order until none can progress, returns the number of steps.
+    pub fn run_until_idle(&self) -> usize {
+        let mut steps = 0;
+        while self.step() {
+            steps += 1;
+        }
+        steps
+    }
+
+    /// # Is Finished
+    ///
+    /// Returns whether the task finished.
+    pub fn is_finished(&self, id: TaskId) -> bool {
+        self.task(id).borrow().task.is_none()
+    }
+
+    /// Returns the task, panics for an id of another simulator.
+    fn task(&self, id: TaskId) -> TaskRef {
+        self.simulation.borrow().tasks.get(id.0).cloned().expect("No task with this id on the simulator")
+    }
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Simulate
+    ///
+    /// Runs the worker threads the event bus spawns from now on, of `subscribe_on_thread` and `dedicate_thread`,
+    /// as tasks of the simulator, and sets the clock of the event bus to the manual clock of the simulator.
+    /// See `testing::SimulatedExecutor`.
+    pub fn simulate(&self, simulator: &SimulatedExecutor) -> &Self {
+        self.set_clock(simulator.clock());
+        self.state.borrow_mut().executor = Executor::Simulated(simulator.clone());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use crate::{Event, EventBus, Outcome, Subscriber};
+    use crate::subscribers::CollectingSubscriber;
+    use crate::testing::{ManualClock, SimulatedExecutor};
+
+    struct Recorder {
+        received: Arc<Mutex<Vec<u32>>>,
+        unsubscribe_at: u32,
+    }
+
+    impl Subscriber for Recorder {
+        fn on_event_outcome(&mut self, event: &mut Event) -> Outcome {
+            let value = *event.get_data::<u32>().unwrap();
+            self.received.lock().unwrap().push(value);
+            match value == self.unsubscribe_at {
+                true => Outcome::AckAndUnsubscribe,
+                false => Outcome::Ack,
+            }
+        }
+    }
+
+    fn simulated() -> (SimulatedExecutor, EventBus) {
+        let simulator = SimulatedExecutor::new(ManualClock::new());
+        let event_bus = EventBus::new();
+        event_bus.simulate(&simulator);
+        (simulator, event_bus)
+    }
+
+    #[test]
+    fn test_worker_runs_only_when_stepped() {
+        let (simulator, event_bus) = simulated();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        event_bus
+            .subscribe_on_thread::<u32, _>("frames", Recorder { received: received.clone(), unsubscribe_at: 0 }, 2)
+            .register("frames", 1u32)
+            .register("frames", 2u32)
+            .register("frames", 3u32);
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![1], *received.lock().unwrap());
+        assert!(simulator.step());
+        assert_eq!(vec![1, 2], *received.lock().unwrap());
+        assert_eq!(1, simulator.run_until_idle());
+        assert!(!simulator.step());
+        assert_eq!(vec![1, 2, 3], *received.lock().unwrap());
+        assert!(event_bus.join_workers(Duration::from_secs(1)).is_ok());
+    }
+
+    #[test]
+    fn test_worker_unsubscribing_mid_mailbox_drops_the_rest() {
+        let (simulator, event_bus) = simulated();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let (main, main_received) = CollectingSubscriber::<u32>::new();
+        event_bus
+            .subscribe_on_thread::<u32, _>("frames", Recorder { received: received.clone(), unsubscribe_at: 2 }, 8)
+            .subscribe_listener("frames", main);
+        for frame in 1..=3u32 {
+            event_bus.register("frames", frame);
+        }
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(2, simulator.run_until_idle());
+        assert_eq!(vec![1, 2], *received.lock().unwrap());
+
+        event_bus.register("frames", 4u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        event_bus.register("frames", 5u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![1, 2, 3, 4, 5], *main_received.borrow());
+        assert_eq!(1, event_bus.subscriber_states(&"frames".to_string()).len());
+        assert!(event_bus.join_workers(Duration::from_secs(1)).is_ok());
+    }
+
+    #[cfg(feature = "threaded")]
+    #[test]
+    fn test_forced_producer_interleaving_is_kept() {
+        let (simulator, event_bus) = simulated();
+        let (collector, received) = CollectingSubscriber::<(char, u32)>::new();
+        event_bus.subscribe_listener("ingest", collector);
+        let producers: Vec<_> = ['a', 'b']
+            .into_iter()
+            .map(|producer| {
+                let sink = event_bus.thread_sink();
+                let mut sequence = 0u32;
+                simulator.spawn(move || {
+                    sequence += 1;
+                    sink.register("ingest", (producer, sequence));
+                    sequence < 3
+                })
+            })
+            .collect();
+
+        for producer in [1, 0, 0, 1] {
+            assert!(simulator.step_task(producers[producer]));
+        }
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![('b', 1), ('a', 1), ('a', 2), ('b', 2)], *received.borrow());
+        assert_eq!(2, simulator.run_until_idle());
+        assert!(simulator.is_finished(producers[0]) && simulator.is_finished(producers[1]));
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![('b', 1), ('a', 1), ('a', 2), ('b', 2), ('a', 3), ('b', 3)], *received.borrow());
+    }
+}
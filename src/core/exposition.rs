@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use super::{EventBus, TopicKey, TopicMetrics};
+use super::state::meta_topic;
+
+/// A counter of `TopicMetrics`, with the name and the help text it is exported with.
+type TopicCounter = (&'static str, &'static str, fn(&TopicMetrics) -> u64);
+
+/// The counters of `TopicMetrics` that are exported.
+const TOPIC_COUNTERS: [TopicCounter; 6] = [
+    ("event_bus_events_registered_total", "The number of events registered.", |metrics| metrics.registered),
+    ("event_bus_events_delivered_total", "The number of events handled by at least one subscriber.", |metrics| metrics.delivered),
+    ("event_bus_events_failed_total", "The number of events a subscriber failed on and no other subscriber handled.", |metrics| metrics.failed),
+    ("event_bus_events_deduplicated_total", "The number of events dropped as a duplicate of a queued event.", |metrics| metrics.deduplicated),
+    ("event_bus_events_coalesced_total", "The number of queued events replaced by a later event.", |metrics| metrics.coalesced),
+    ("event_bus_events_dropped_total", "The number of events dropped by a limit or a capacity.", |metrics| metrics.dropped),
+];
+
+/// Escapes a label value: backslashes, double quotes and line feeds are escaped with a backslash.
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Writes the help and type lines of a metric.
+fn header(text: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(text, "# HELP {} {}", name, help);
+    let _ = writeln!(text, "# TYPE {} {}", name, kind);
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Metrics Text
+    ///
+    /// Returns the counters of the event bus in the Prometheus text exposition format,
+    /// with the event names and subscriber names as labels. The samples are sorted by their labels.
+    ///
+    /// * `event_bus_events_registered_total`, `event_bus_events_delivered_total`, `event_bus_events_failed_total`,
+    ///   `event_bus_events_deduplicated_total`, `event_bus_events_coalesced_total` and `event_bus_events_dropped_total` - The counters of each event name.
+    ///
+    /// * `event_bus_subscriber_errors_total` - The failures of each subscriber of each event name.
+    ///
+    /// * `event_bus_pending_events` - The number of queued events of each event name.
+    ///
+    /// * `event_bus_publish_duration_seconds` - A summary of the time spent publishing, on the clock of the event bus.
+    pub fn metrics_text(&self) -> String {
+        let state = self.state.borrow();
+        let metrics = &state.metrics;
+        let mut text = String::new();
+
+        let topics: BTreeMap<String, &TopicMetrics> = metrics.topics()
+            .map(|(topic, counters)| (escape_label(&meta_topic(topic)), counters))
+            .collect();
+        for (name, help, counter) in TOPIC_COUNTERS {
+            header(&mut text, name, help, "counter");
+            for (topic, counters) in &topics {
+                let _ = writeln!(text, "{}{{topic=\"{}\"}} {}", name, topic, counter(counters));
+            }
+        }
+
+        header(&mut text, "event_bus_subscriber_errors_total", "The number of failures of a subscriber.", "counter");
+        let errors: BTreeMap<(String, String), u64> = metrics.all_subscriber_errors()
+            .map(|(topic, subscriber, errors)| ((escape_label(&meta_topic(topic)), escape_label(subscriber)), errors))
+            .collect();
+        for ((topic, subscriber), errors) in errors {
+            let _ = writeln!(text, "event_bus_subscriber_errors_total{{topic=\"{}\",subscriber=\"{}\"}} {}", topic, subscriber, errors);
+        }
+
+        header(&mut text, "event_bus_pending_events", "The number of queued events.", "gauge");
+        let pending: BTreeMap<String, usize> = state.events.iter()
+            .map(|(topic, messages)| (escape_label(&meta_topic(topic)), messages.len()))
+            .collect();
+        for (topic, pending) in pending {
+            let _ = writeln!(text, "event_bus_pending_events{{topic=\"{}\"}} {}", topic, pending);
+        }
+
+        header(&mut text, "event_bus_publish_duration_seconds", "The time spent publishing.", "summary");
+        let _ = writeln!(text, "event_bus_publish_duration_seconds_sum {}", metrics.publish_time().as_secs_f64());
+        let _ = writeln!(text, "event_bus_publish_duration_seconds_count {}", metrics.publishes());
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::testing::ManualClock;
+    use crate::{ErrorPolicy, Event, EventBus, Subscriber};
+
+    struct SlowSubscriber {
+        clock: ManualClock,
+    }
+
+    impl Subscriber for SlowSubscriber {
+        fn on_event(&mut self, _event: &mut Event) -> Result<(), String> {
+            self.clock.advance(Duration::from_millis(250));
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "slow"
+        }
+    }
+
+    struct RejectingSubscriber;
+
+    impl Subscriber for RejectingSubscriber {
+        fn on_event(&mut self, _event: &mut Event) -> Result<(), String> {
+            Err("Rejected".to_string())
+        }
+
+        fn name(&self) -> &str {
+            "the \"rejecting\" one"
+        }
+    }
+
+    #[test]
+    fn test_metrics_text_of_known_workload() {
+        let clock = ManualClock::new();
+        let event_bus: EventBus = EventBus::with_clock(clock.clone());
+        event_bus
+            .set_error_policy(ErrorPolicy::Continue)
+            .subscribe_listener("orders", SlowSubscriber { clock: clock.clone() })
+            .subscribe_listener("payments", RejectingSubscriber)
+            .register("orders", 1u32)
+            .register("orders", 2u32)
+            .register("payments", 3u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        event_bus.register("orders", 4u32);
+
+        let text = event_bus.metrics_text();
+        let lines: Vec<&str> = text.lines().collect();
+        for expected in [
+            "# TYPE event_bus_events_delivered_total counter",
+            "event_bus_events_registered_total{topic=\"orders\"} 3",
+            "event_bus_events_delivered_total{topic=\"orders\"} 2",
+            "event_bus_events_delivered_total{topic=\"payments\"} 0",
+            "event_bus_events_failed_total{topic=\"payments\"} 1",
+            "event_bus_subscriber_errors_total{topic=\"payments\",subscriber=\"the \\\"rejecting\\\" one\"} 1",
+            "event_bus_pending_events{topic=\"orders\"} 1",
+            "event_bus_publish_duration_seconds_sum 0.5",
+            "event_bus_publish_duration_seconds_count 1",
+        ] {
+            assert!(lines.contains(&expected), "Missing {} in\n{}", expected, text);
+        }
+    }
+}
@@ -0,0 +1,42 @@
+/// # Phase
+///
+/// The method of a subscriber that was called when it failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// The on_before of the subscriber.
+    Before,
+    /// The on_event of the subscriber.
+    Event,
+    /// The on_after of the subscriber.
+    After,
+}
+
+/// # Subscriber Failure
+///
+/// The payload of the events registered on the error topic of an event bus,
+/// when errors are routed with `route_errors_to`.
+///
+/// ## Fields
+///
+/// * `topic` - The event name of the event the subscriber failed on.
+///
+/// * `subscriber` - The name of the subscriber that failed.
+///
+/// * `phase` - The method of the subscriber that failed.
+///
+/// * `message` - The error returned by the subscriber.
+///
+/// * `event_id` - The id of the event the subscriber failed on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubscriberFailure {
+    /// The event name of the event the subscriber failed on.
+    pub topic: String,
+    /// The name of the subscriber that failed.
+    pub subscriber: String,
+    /// The method of the subscriber that failed.
+    pub phase: Phase,
+    /// The error returned by the subscriber.
+    pub message: String,
+    /// The id of the event the subscriber failed on.
+    pub event_id: u64,
+}
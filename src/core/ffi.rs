@@ -0,0 +1,221 @@
+//! A C interface to an event bus, for hosts written in other languages.
+//!
+//! An event bus is created with `event_bus_new` and freed with `event_bus_free`.
+//! Payloads cross the boundary as bytes: the bytes registered with `event_bus_register_bytes`
+//! are handed to the callbacks as they are, other payloads are encoded with the payload registry.
+//! Every function returns `EVENT_BUS_OK` or one of the negative error codes,
+//! a handle that was not returned by `event_bus_new` or is already freed is rejected.
+//! The event bus can only be used on the thread that created it.
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::ffi::{c_char, c_void, CStr};
+use std::rc::{Rc, Weak};
+use super::{Event, EventBus, Outcome, RawPayload, Subscriber};
+use super::state::BusState;
+
+/// The function succeeded.
+pub const EVENT_BUS_OK: i32 = 0;
+/// The handle is null, freed, or not an event bus.
+pub const EVENT_BUS_INVALID_HANDLE: i32 = -1;
+/// A pointer argument is null, or the topic is not UTF-8.
+pub const EVENT_BUS_INVALID_ARGUMENT: i32 = -2;
+/// A subscriber failed, or the event bus is already publishing.
+pub const EVENT_BUS_PUBLISH_FAILED: i32 = -3;
+
+/// The function a host subscribes, called with its context and the bytes of the payload.
+pub type EventCallback = extern "C" fn(ctx: *mut c_void, payload: *const u8, len: usize);
+
+thread_local! {
+    /// The event buses created with `event_bus_new` and not freed yet.
+    static LIVE: RefCell<HashSet<usize>> = RefCell::new(HashSet::new());
+}
+
+/// Calls the function with the event bus, or returns `EVENT_BUS_INVALID_HANDLE` when it is not a live event bus.
+fn with_bus(bus: *mut EventBus, f: impl FnOnce(&EventBus) -> i32) -> i32 {
+    if !LIVE.with(|live| live.borrow().contains(&(bus as usize))) {
+        return EVENT_BUS_INVALID_HANDLE;
+    }
+    // SAFETY: the pointer was returned by `event_bus_new` on this thread and is not freed yet.
+    f(unsafe { &*bus })
+}
+
+/// Hands the bytes of the payloads to a callback of the host.
+struct CallbackSubscriber {
+    callback: EventCallback,
+    ctx: *mut c_void,
+    /// The state of the event bus, for its payload registry.
+    /// It is not borrowed mutably while a subscriber is called.
+    state: Weak<RefCell<BusState<String>>>,
+}
+
+impl Subscriber for CallbackSubscriber {
+    fn on_event_outcome(&mut self, event: &mut Event) -> Outcome {
+        let encoded;
+        let bytes = match (event.get_data::<Vec<u8>>(), event.get_data::<RawPayload>()) {
+            (Some(bytes), _) => bytes.as_slice(),
+            (_, Some(raw)) => raw.bytes.as_slice(),
+            _ => {
+                let Some(state) = self.state.upgrade() else {
+                    return Outcome::AckAndUnsubscribe;
+                };
+                encoded = state.borrow().payloads.encode(event).map(|(_, bytes)| bytes);
+                match &encoded {
+                    Some(bytes) => bytes.as_slice(),
+                    None => return Outcome::Error(format!("Payloads of type {} are not registered", event.payload_type_name())),
+                }
+            }
+        };
+        (self.callback)(self.ctx, bytes.as_ptr(), bytes.len());
+        Outcome::Ack
+    }
+
+    fn name(&self) -> &str {
+        "C callback"
+    }
+}
+
+/// Reads the topic, or returns `None` when it is null or not UTF-8.
+///
+/// # Safety
+///
+/// A non-null topic must point to a nul-terminated string.
+unsafe fn topic(topic: *const c_char) -> Option<String> {
+    if topic.is_null() {
+        return None;
+    }
+    CStr::from_ptr(topic).to_str().ok().map(str::to_string)
+}
+
+/// # Event Bus New
+///
+/// Creates an event bus, to be freed with `event_bus_free`.
+#[no_mangle]
+pub extern "C" fn event_bus_new() -> *mut EventBus {
+    let bus = Box::into_raw(Box::new(EventBus::new()));
+    LIVE.with(|live| live.borrow_mut().insert(bus as usize));
+    bus
+}
+
+/// # Event Bus Subscribe Callback
+///
+/// Subscribes the callback to the topic. The context is passed to the callback untouched.
+///
+/// # Safety
+///
+/// The topic must be null or point to a nul-terminated string.
+/// The callback must be safe to call with the context for as long as the event bus lives.
+#[no_mangle]
+pub unsafe extern "C" fn event_bus_subscribe_callback(
+    bus: *mut EventBus,
+    topic: *const c_char,
+    callback: Option<EventCallback>,
+    ctx: *mut c_void,
+) -> i32 {
+    with_bus(bus, |event_bus| {
+        let (Some(topic), Some(callback)) = (self::topic(topic), callback) else {
+            return EVENT_BUS_INVALID_ARGUMENT;
+        };
+        event_bus.subscribe_listener(topic, CallbackSubscriber { callback, ctx, state: Rc::downgrade(&event_bus.state) });
+        EVENT_BUS_OK
+    })
+}
+
+/// # Event Bus Register Bytes
+///
+/// Registers an event on the topic holding a copy of the bytes.
+///
+/// # Safety
+///
+/// The topic must be null or point to a nul-terminated string.
+/// The bytes must be null or point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn event_bus_register_bytes(bus: *mut EventBus, topic: *const c_char, ptr: *const u8, len: usize) -> i32 {
+    with_bus(bus, |event_bus| {
+        let Some(topic) = self::topic(topic) else {
+            return EVENT_BUS_INVALID_ARGUMENT;
+        };
+        let bytes = match (ptr.is_null(), len) {
+            (_, 0) => Vec::new(),
+            (true, _) => return EVENT_BUS_INVALID_ARGUMENT,
+            (false, _) => std::slice::from_raw_parts(ptr, len).to_vec(),
+        };
+        event_bus.register(topic, bytes);
+        EVENT_BUS_OK
+    })
+}
+
+/// # Event Bus Publish
+///
+/// Publishes the registered events.
+#[no_mangle]
+pub extern "C" fn event_bus_publish(bus: *mut EventBus) -> i32 {
+    with_bus(bus, |event_bus| match event_bus.publish() {
+        Ok(()) => EVENT_BUS_OK,
+        Err(_) => EVENT_BUS_PUBLISH_FAILED,
+    })
+}
+
+/// # Event Bus Free
+///
+/// Frees the event bus. Freeing a freed or unknown handle returns `EVENT_BUS_INVALID_HANDLE`.
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn event_bus_free(bus: *mut EventBus) -> i32 {
+    if !LIVE.with(|live| live.borrow_mut().remove(&(bus as usize))) {
+        return EVENT_BUS_INVALID_HANDLE;
+    }
+    // SAFETY: the pointer was returned by `event_bus_new` on this thread and is freed only once.
+    drop(unsafe { Box::from_raw(bus) });
+    EVENT_BUS_OK
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::c_void;
+    use std::ptr;
+    use super::*;
+
+    extern "C" fn record(ctx: *mut c_void, payload: *const u8, len: usize) {
+        let received = unsafe { &mut *(ctx as *mut Vec<Vec<u8>>) };
+        received.push(unsafe { std::slice::from_raw_parts(payload, len) }.to_vec());
+    }
+
+    #[test]
+    fn test_foreign_caller_round_trip() {
+        let mut received: Vec<Vec<u8>> = Vec::new();
+        let ctx = &mut received as *mut Vec<Vec<u8>> as *mut c_void;
+        let bus = event_bus_new();
+        unsafe {
+            assert_eq!(EVENT_BUS_OK, event_bus_subscribe_callback(bus, c"chat".as_ptr(), Some(record), ctx));
+            assert_eq!(EVENT_BUS_OK, event_bus_register_bytes(bus, c"chat".as_ptr(), b"hello".as_ptr(), 5));
+            assert_eq!(EVENT_BUS_OK, event_bus_register_bytes(bus, c"chat".as_ptr(), ptr::null(), 0));
+        }
+        let event_bus = unsafe { &*bus };
+        event_bus
+            .register_payload::<u32>("u32", |value| value.to_be_bytes().to_vec(), |_| Err("Not decoded".to_string()))
+            .register("chat", 7u32);
+        assert_eq!(EVENT_BUS_OK, event_bus_publish(bus));
+        assert_eq!(EVENT_BUS_OK, event_bus_free(bus));
+
+        assert_eq!(vec![b"hello".to_vec(), Vec::new(), vec![0, 0, 0, 7]], received);
+    }
+
+    #[test]
+    fn test_invalid_handles_and_arguments() {
+        let mut unknown = EventBus::new();
+        unsafe {
+            assert_eq!(EVENT_BUS_INVALID_HANDLE, event_bus_register_bytes(ptr::null_mut(), c"chat".as_ptr(), ptr::null(), 0));
+            assert_eq!(EVENT_BUS_INVALID_HANDLE, event_bus_register_bytes(&mut unknown, c"chat".as_ptr(), ptr::null(), 0));
+        }
+        let bus = event_bus_new();
+        unsafe {
+            assert_eq!(EVENT_BUS_INVALID_ARGUMENT, event_bus_subscribe_callback(bus, ptr::null(), Some(record), ptr::null_mut()));
+            assert_eq!(EVENT_BUS_INVALID_ARGUMENT, event_bus_subscribe_callback(bus, c"chat".as_ptr(), None, ptr::null_mut()));
+            assert_eq!(EVENT_BUS_INVALID_ARGUMENT, event_bus_register_bytes(bus, c"chat".as_ptr(), ptr::null(), 3));
+        }
+        assert_eq!(EVENT_BUS_OK, event_bus_free(bus));
+        assert_eq!(EVENT_BUS_INVALID_HANDLE, event_bus_publish(bus));
+        assert_eq!(EVENT_BUS_INVALID_HANDLE, event_bus_free(bus));
+    }
+}
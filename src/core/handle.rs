@@ -0,0 +1,58 @@
+use std::error::Error;
+use std::fmt;
+use super::TopicKey;
+
+/// # Subscription Handle
+///
+/// Identifies a single subscription, so it can be unsubscribed later.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SubscriptionHandle<K: TopicKey = String> {
+    pub(crate) topic: K,
+    pub(crate) id: u64,
+}
+
+impl<K: TopicKey> SubscriptionHandle<K> {
+    /// # Topic
+    ///
+    /// Returns the event name, or pattern, of the subscription.
+    pub fn topic(&self) -> &K {
+        &self.topic
+    }
+}
+
+/// # Duplicate Subscriber
+///
+/// The error returned by `subscribe_unique` when a subscriber of the same type
+/// is already subscribed to the event name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateSubscriber<K: TopicKey = String> {
+    /// The event name the subscriber is already subscribed to.
+    pub topic: K,
+    /// The type name of the subscriber.
+    pub subscriber: &'static str,
+}
+
+impl<K: TopicKey> fmt::Display for DuplicateSubscriber<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "A {} is already subscribed to {:?}", self.subscriber, self.topic)
+    }
+}
+
+impl<K: TopicKey> Error for DuplicateSubscriber<K> {}
+
+/// # Unknown Handle
+///
+/// The error returned by `replace_listener` when the subscription of the handle is not subscribed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownHandle<K: TopicKey = String> {
+    /// The event name of the handle.
+    pub topic: K,
+}
+
+impl<K: TopicKey> fmt::Display for UnknownHandle<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The subscription of the handle is not subscribed to {:?}", self.topic)
+    }
+}
+
+impl<K: TopicKey> Error for UnknownHandle<K> {}
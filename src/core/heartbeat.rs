@@ -0,0 +1,112 @@
+use std::time::Duration;
+use super::{Event, EventBus, Instant, TopicKey};
+use super::state::BusState;
+
+/// # Heartbeat
+///
+/// The payload of the events registered by a heartbeat, see `EventBus::add_heartbeat`.
+///
+/// ## Fields
+///
+/// * `seq` - The number of the tick, starting at 1.
+///
+/// * `at` - The time the tick was due, on the clock of the event bus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Heartbeat {
+    /// The number of the tick, starting at 1.
+    pub seq: u64,
+    /// The time the tick was due, on the clock of the event bus.
+    pub at: Instant,
+}
+
+/// A heartbeat of an event name, and its next tick.
+pub(crate) struct HeartbeatSource<K: TopicKey> {
+    topic: K,
+    interval: Duration,
+    next: Instant,
+    seq: u64,
+}
+
+impl<K: TopicKey> BusState<K> {
+    /// Registers the ticks of the heartbeats that are due, every tick that passed since the last publish.
+    pub(crate) fn register_heartbeats(&mut self) {
+        let now = self.clock.now();
+        let mut ticks = Vec::new();
+        for heartbeat in self.heartbeats.iter_mut() {
+            while heartbeat.next <= now {
+                heartbeat.seq += 1;
+                ticks.push((heartbeat.topic.clone(), Heartbeat { seq: heartbeat.seq, at: heartbeat.next }));
+                heartbeat.next += heartbeat.interval;
+            }
+        }
+        for (topic, heartbeat) in ticks {
+            self.register(topic, Event::new(heartbeat));
+        }
+    }
+
+    /// Returns when the next tick of a heartbeat is due.
+    pub(crate) fn next_heartbeat(&self) -> Option<Instant> {
+        self.heartbeats.iter().map(|heartbeat| heartbeat.next).min()
+    }
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Add Heartbeat
+    ///
+    /// Registers an event holding a `Heartbeat` on the event name every interval, on the clock of the event bus,
+    /// replacing the heartbeat the event name had. The first tick is due one interval from now.
+    /// Each publish registers the ticks that became due since the previous publish and publishes them,
+    /// and `run_loop` wakes up for them. Panics when the interval is zero.
+    pub fn add_heartbeat(&self, event_name: impl Into<K>, interval: Duration) -> &Self {
+        assert!(!interval.is_zero(), "The interval of a heartbeat cannot be zero");
+        let topic = self.topic_key(event_name);
+        let mut state = self.state.borrow_mut();
+        let next = state.clock.now() + interval;
+        state.heartbeats.retain(|heartbeat| heartbeat.topic != topic);
+        state.heartbeats.push(HeartbeatSource { topic, interval, next, seq: 0 });
+        self
+    }
+
+    /// # Remove Heartbeat
+    ///
+    /// Stops the heartbeat of the event name, returns whether it had one.
+    /// Ticks that are already registered are still published.
+    pub fn remove_heartbeat(&self, event_name: impl Into<K>) -> bool {
+        let topic = self.topic_key(event_name);
+        let mut state = self.state.borrow_mut();
+        let before = state.heartbeats.len();
+        state.heartbeats.retain(|heartbeat| heartbeat.topic != topic);
+        state.heartbeats.len() < before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use super::Heartbeat;
+    use crate::{Clock, EventBus};
+    use crate::subscribers::CollectingSubscriber;
+    use crate::testing::ManualClock;
+
+    #[test]
+    fn test_heartbeat_ticks_that_became_due() {
+        let clock = ManualClock::new();
+        let start = clock.now();
+        let event_bus: EventBus = EventBus::with_clock(clock.clone());
+        let (collector, ticks) = CollectingSubscriber::<Heartbeat>::new();
+        event_bus
+            .subscribe_listener("tick", collector)
+            .add_heartbeat("tick", Duration::from_secs(2));
+
+        clock.advance(Duration::from_secs(7));
+        assert_eq!(Ok(()), event_bus.publish());
+        let expected: Vec<Heartbeat> = (1..=3).map(|seq| Heartbeat { seq, at: start + Duration::from_secs(2 * seq) }).collect();
+        assert_eq!(expected, *ticks.borrow());
+
+        clock.advance(Duration::from_secs(1));
+        assert!(event_bus.remove_heartbeat("tick"));
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(3, ticks.borrow().len());
+        assert!(!event_bus.remove_heartbeat("tick"));
+    }
+}
@@ -0,0 +1,126 @@
+use super::{EventBus, TopicKey};
+use super::state::BusState;
+
+/// The callbacks told when the event bus runs out of work, and when work arrives again.
+pub(crate) struct IdleWatch {
+    /// Whether the event bus has no work, as the callbacks were last told.
+    idle: bool,
+    on_idle: Option<Box<dyn FnMut()>>,
+    on_work_available: Option<Box<dyn FnMut()>>,
+}
+
+impl Default for IdleWatch {
+    /// A new event bus has no work yet, so its first registered event makes work available.
+    fn default() -> Self {
+        IdleWatch { idle: true, on_idle: None, on_work_available: None }
+    }
+}
+
+impl<K: TopicKey> BusState<K> {
+    /// Tells the callback of `on_work_available` that events are queued, when the event bus was idle.
+    pub(crate) fn work_arrived(&mut self) {
+        let watch = &mut self.idle_watch;
+        if watch.idle {
+            watch.idle = false;
+            if let Some(callback) = &mut watch.on_work_available {
+                callback();
+            }
+        }
+    }
+
+    /// Returns whether events are queued, or delayed or recurring events or heartbeat ticks are due.
+    pub(crate) fn has_work(&self) -> bool {
+        let now = self.clock.now();
+        self.queued > self.held_count(now)
+            || self.next_window().is_some_and(|closes| closes <= now)
+            || self.next_scheduled().is_some_and(|due| due <= now)
+            || self.next_heartbeat().is_some_and(|next| next <= now)
+    }
+
+    /// Tells the callback of `on_idle` that a publish left no work, when the event bus was not idle yet.
+    pub(crate) fn check_idle(&mut self) {
+        if self.idle_watch.idle || self.has_work() {
+            return;
+        }
+        self.idle_watch.idle = true;
+        if let Some(callback) = &mut self.idle_watch.on_idle {
+            callback();
+        }
+    }
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # On Idle
+    ///
+    /// Calls the callback at the end of a publish that leaves no queued events and no delayed events that are due.
+    /// It is called once when the event bus runs out of work, not again until work arrived in between,
+    /// see `on_work_available`. The callback is called while the event bus finishes the publish,
+    /// so it cannot use the event bus. Replaces the callback that was set before.
+    pub fn on_idle(&self, callback: impl FnMut() + 'static) -> &Self {
+        self.state.borrow_mut().idle_watch.on_idle = Some(Box::new(callback));
+        self
+    }
+
+    /// # On Work Available
+    ///
+    /// Calls the callback when an event is queued while the event bus is idle, like the first event registered
+    /// after a publish that called the callback of `on_idle`. The callback is called while the event is queued,
+    /// so it cannot use the event bus. Replaces the callback that was set before.
+    pub fn on_work_available(&self, callback: impl FnMut() + 'static) -> &Self {
+        self.state.borrow_mut().idle_watch.on_work_available = Some(Box::new(callback));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+    use crate::{EventBus, Subscriber};
+    use crate::testing::ManualClock;
+
+    #[test]
+    fn test_idle_and_work_available_fire_on_transitions() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let (idle, work) = (calls.clone(), calls.clone());
+        let event_bus = EventBus::new();
+        event_bus
+            .on_idle(move || idle.borrow_mut().push("idle"))
+            .on_work_available(move || work.borrow_mut().push("work"));
+
+        event_bus.register("tick", 1u32).register("tick", 2u32);
+        assert_eq!(vec!["work"], *calls.borrow());
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec!["work", "idle"], *calls.borrow());
+
+        event_bus.register("tick", 3u32).register("tock", 4u32);
+        assert_eq!(vec!["work", "idle", "work"], *calls.borrow());
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec!["work", "idle", "work", "idle"], *calls.borrow());
+    }
+
+    #[test]
+    fn test_due_delayed_event_is_not_idle() {
+        let clock = ManualClock::new();
+        let idles = Rc::new(RefCell::new(0));
+        let counted = idles.clone();
+        let advanced = clock.clone();
+        let event_bus = EventBus::new();
+        event_bus
+            .set_clock(clock.clone())
+            .on_idle(move || *counted.borrow_mut() += 1)
+            .subscribe_listener("tick", <dyn Subscriber>::builder().on_event(move |_| {
+                advanced.advance(Duration::from_secs(1));
+                Ok(())
+            }).build())
+            .register("tick", 1u32)
+            .register_after("tock", 2u32, Duration::from_secs(1));
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(0, *idles.borrow());
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(1, *idles.borrow());
+    }
+}
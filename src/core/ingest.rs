@@ -0,0 +1,118 @@
+use std::sync::Arc;
+use crossbeam_deque::{Injector, Steal};
+use super::{Event, EventBus, IntoEvent, TopicKey};
+use super::state::BusState;
+
+/// An event registered on another thread, created on the thread of the event bus when it is drained.
+pub(crate) type Ingested<K> = (K, Box<dyn FnOnce() -> Event + Send>);
+
+/// The queue the thread sinks of an event bus push their events onto.
+pub(crate) struct Ingest<K: TopicKey> {
+    queue: Arc<Injector<Ingested<K>>>,
+}
+
+/// # Thread Sink
+///
+/// A handle that registers events with an event bus from any thread, created with `EventBus::thread_sink`.
+/// Registering pushes the event onto a lock-free queue, without touching the event bus, and the next publish
+/// drains the queue into the queues of the event names. The events of one thread keep their order;
+/// the events of different threads are merged in the order they were pushed.
+/// Each event is boxed for the trip to the thread of the event bus, so whether the queue is faster than
+/// registering behind a mutex depends on how many producers run in parallel, see the `ingestion` benchmark.
+/// Cloning a sink is cheap, every clone feeds the same event bus.
+///
+/// ## Methods
+///
+/// * `register` - Registers an event with the event bus.
+pub struct ThreadSink<K: TopicKey = String> {
+    queue: Arc<Injector<Ingested<K>>>,
+}
+
+impl<K: TopicKey> Clone for ThreadSink<K> {
+    fn clone(&self) -> Self {
+        ThreadSink { queue: self.queue.clone() }
+    }
+}
+
+impl<K: TopicKey + Send> ThreadSink<K> {
+    /// # Register
+    ///
+    /// Registers an event with the event bus, see `EventBus::register`. The event is created,
+    /// normalized, validated and counted when the event bus drains the queue, on the thread of the event bus.
+    /// Events registered after the event bus was dropped are never registered, and are dropped with the last sink.
+    pub fn register<E: IntoEvent + Send + 'static>(&self, event_name: impl Into<K>, message: E) -> &Self {
+        self.queue.push((event_name.into(), Box::new(move || message.into_event())));
+        self
+    }
+}
+
+impl<K: TopicKey> BusState<K> {
+    /// Registers the events the thread sinks pushed since the last drain, in the order they were pushed.
+    pub(crate) fn drain_ingested(&mut self) {
+        let Some(ingest) = &self.ingest else {
+            return;
+        };
+        let mut events: Vec<(K, Event)> = Vec::with_capacity(ingest.queue.len());
+        loop {
+            match ingest.queue.steal() {
+                Steal::Success((event_name, message)) => events.push((event_name, message())),
+                Steal::Empty => break,
+                Steal::Retry => {}
+            }
+        }
+        if !events.is_empty() {
+            self.register_all(events);
+        }
+    }
+}
+
+impl<K: TopicKey + Send> EventBus<K> {
+    /// # Thread Sink
+    ///
+    /// Returns a handle that registers events with this event bus from any thread, see `ThreadSink`.
+    /// Its events are registered by the next publish, or the next cycle of `run_loop`,
+    /// which may park for its tick before it sees them.
+    pub fn thread_sink(&self) -> ThreadSink<K> {
+        let mut state = self.state.borrow_mut();
+        let ingest = state.ingest.get_or_insert_with(|| Ingest { queue: Arc::new(Injector::new()) });
+        ThreadSink { queue: ingest.queue.clone() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use crate::EventBus;
+    use crate::subscribers::CollectingSubscriber;
+
+    #[test]
+    fn test_concurrent_producers_lose_nothing_and_keep_their_order() {
+        const PRODUCERS: u32 = 8;
+        const EVENTS: u32 = 10_000;
+        let event_bus = EventBus::new();
+        let (collector, received) = CollectingSubscriber::<(u32, u32)>::new();
+        event_bus.subscribe_listener("ingest", collector);
+
+        let producers: Vec<_> = (0..PRODUCERS)
+            .map(|producer| {
+                let sink = event_bus.thread_sink();
+                thread::spawn(move || {
+                    for sequence in 0..EVENTS {
+                        sink.register("ingest", (producer, sequence));
+                    }
+                })
+            })
+            .collect();
+        producers.into_iter().for_each(|producer| producer.join().unwrap());
+        assert_eq!(Ok(()), event_bus.publish());
+
+        let received = received.borrow();
+        assert_eq!((PRODUCERS * EVENTS) as usize, received.len());
+        let mut next = vec![0; PRODUCERS as usize];
+        for (producer, sequence) in received.iter() {
+            assert_eq!(next[*producer as usize], *sequence);
+            next[*producer as usize] += 1;
+        }
+        assert_eq!((PRODUCERS * EVENTS) as u64, event_bus.metrics().topic(&"ingest".to_string()).registered);
+    }
+}
@@ -0,0 +1,436 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::time::Duration;
+use super::{Event, EventBus, Instant, InvalidPattern, TopicKey};
+use super::pattern::TopicPattern;
+use super::state::BusState;
+use super::topic::topic_str;
+
+/// Appends the registered events to a file, as they are registered.
+pub(crate) struct Journal {
+    file: File,
+    started: Instant,
+}
+
+/// # Replay Options
+///
+/// How `replay_journal` replays the events of a journal.
+///
+/// ## Fields
+///
+/// * `speed` - How much faster than the original the events are replayed, `0.0` replays them as fast as possible.
+///
+/// * `topics` - The only event names that are replayed, or `None` to replay every event name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayOptions {
+    /// How much faster than the original the events are replayed, `0.0` replays them as fast as possible.
+    pub speed: f32,
+    /// The only event names that are replayed, or `None` to replay every event name.
+    pub topics: Option<Vec<String>>,
+}
+
+impl Default for ReplayOptions {
+    fn default() -> Self {
+        ReplayOptions { speed: 1.0, topics: None }
+    }
+}
+
+type Predicate = Box<dyn Fn(&Event) -> bool>;
+
+/// # Replay Filter
+///
+/// Which events of a journal `replay_journal_filtered` replays, and where to. An event is replayed when its event name
+/// matches one of the topics, its time lies in the time range, and its decoded payload satisfies the predicate;
+/// a filter without topics, time range or predicate does not filter on it.
+///
+/// ## Methods
+///
+/// * `new` - Creates a filter that replays every event, at its original speed.
+///
+/// * `speed` - Sets how much faster than the original the events are replayed.
+///
+/// * `topics` - Replays only the event names matching one of the event names or patterns.
+///
+/// * `between` - Replays only the events written in a time range.
+///
+/// * `matching` - Replays only the events satisfying a predicate.
+///
+/// * `retarget` - Replays the events of an event name on another event name.
+pub struct ReplayFilter {
+    speed: f32,
+    topics: Option<Vec<TopicPattern>>,
+    between: Option<Range<Duration>>,
+    predicate: Option<Predicate>,
+    retargets: HashMap<String, String>,
+}
+
+impl ReplayFilter {
+    /// # New
+    ///
+    /// Creates a filter that replays every event on its own event name, at its original speed.
+    pub fn new() -> ReplayFilter {
+        ReplayFilter { speed: 1.0, topics: None, between: None, predicate: None, retargets: HashMap::new() }
+    }
+
+    /// # Speed
+    ///
+    /// Sets how much faster than the original the events are replayed, `0.0` replays them as fast as possible.
+    pub fn speed(mut self, speed: f32) -> ReplayFilter {
+        self.speed = speed;
+        self
+    }
+
+    /// # Topics
+    ///
+    /// Replays only the events whose event name matches one of the event names or patterns.
+    /// Returns an error when one of the patterns is invalid.
+    pub fn topics(mut self, topics: &[&str]) -> Result<ReplayFilter, InvalidPattern> {
+        let patterns = topics.iter().map(|topic| TopicPattern::parse(topic)).collect::<Result<Vec<_>, _>>()?;
+        self.topics.get_or_insert_with(Vec::new).extend(patterns);
+        Ok(self)
+    }
+
+    /// # Between
+    ///
+    /// Replays only the events written from the start up to the end of the range,
+    /// as times since the journal was started.
+    pub fn between(mut self, range: Range<Duration>) -> ReplayFilter {
+        self.between = Some(range);
+        self
+    }
+
+    /// # Matching
+    ///
+    /// Replays only the events satisfying the predicate, which receives the event with its decoded payload.
+    pub fn matching(mut self, predicate: impl Fn(&Event) -> bool + 'static) -> ReplayFilter {
+        self.predicate = Some(Box::new(predicate));
+        self
+    }
+
+    /// # Retarget
+    ///
+    /// Replays the events of the event name on the target event name,
+    /// so the subscribers of the original event name do not receive them.
+    pub fn retarget(mut self, event_name: &str, target: &str) -> ReplayFilter {
+        self.retargets.insert(event_name.to_string(), target.to_string());
+        self
+    }
+
+    /// Returns whether the record is left out by its event name or time.
+    fn excludes(&self, record: &Record) -> bool {
+        self.topics.as_ref().is_some_and(|topics| !topics.iter().any(|topic| topic.matches(&record.topic)))
+            || self.between.as_ref().is_some_and(|between| !between.contains(&record.offset))
+    }
+}
+
+impl Default for ReplayFilter {
+    fn default() -> Self {
+        ReplayFilter::new()
+    }
+}
+
+impl From<ReplayOptions> for ReplayFilter {
+    fn from(options: ReplayOptions) -> Self {
+        let topics = options.topics.map(|topics| topics.iter()
+            .map(|topic| TopicPattern::exact(topic))
+            .collect());
+        ReplayFilter { speed: options.speed, topics, ..ReplayFilter::new() }
+    }
+}
+
+/// # Replay Report
+///
+/// What happened while replaying a journal.
+///
+/// ## Fields
+///
+/// * `replayed` - The number of events that were registered and published.
+///
+/// * `skipped` - The number of events whose payload could not be decoded, for example because its type is unknown.
+///
+/// * `filtered` - The number of events left out by the topics, time range or predicate of the replay.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplayReport {
+    /// The number of events that were registered and published.
+    pub replayed: usize,
+    /// The number of events whose payload could not be decoded, for example because its type is unknown.
+    pub skipped: usize,
+    /// The number of events left out by the topics, time range or predicate of the replay.
+    pub filtered: usize,
+}
+
+/// An event as it is written to a journal.
+struct Record {
+    offset: Duration,
+    topic: String,
+    name: String,
+    schema_version: Option<u32>,
+    bytes: Vec<u8>,
+}
+
+impl Record {
+    fn encode(&self) -> Vec<u8> {
+        let length = 8 + 2 + self.topic.len() + 2 + self.name.len() + 5 + self.bytes.len();
+        let mut record = Vec::with_capacity(4 + length);
+        record.extend_from_slice(&(length as u32).to_be_bytes());
+        record.extend_from_slice(&(self.offset.as_micros() as u64).to_be_bytes());
+        record.extend_from_slice(&(self.topic.len() as u16).to_be_bytes());
+        record.extend_from_slice(self.topic.as_bytes());
+        record.extend_from_slice(&(self.name.len() as u16).to_be_bytes());
+        record.extend_from_slice(self.name.as_bytes());
+        record.push(self.schema_version.is_some() as u8);
+        record.extend_from_slice(&self.schema_version.unwrap_or(0).to_be_bytes());
+        record.extend_from_slice(&self.bytes);
+        record
+    }
+
+    /// Decodes all records of a journal.
+    fn decode_all(mut journal: &[u8]) -> Result<Vec<Record>, String> {
+        fn take<'a>(body: &mut &'a [u8], length: usize) -> Result<&'a [u8], String> {
+            if body.len() < length {
+                return Err("Truncated journal record".to_string());
+            }
+            let (taken, rest) = body.split_at(length);
+            *body = rest;
+            Ok(taken)
+        }
+        fn take_string(body: &mut &[u8]) -> Result<String, String> {
+            let length = u16::from_be_bytes(take(body, 2)?.try_into().unwrap()) as usize;
+            String::from_utf8(take(body, length)?.to_vec()).map_err(|e| e.to_string())
+        }
+
+        let mut records = Vec::new();
+        while !journal.is_empty() {
+            let length = u32::from_be_bytes(take(&mut journal, 4)?.try_into().unwrap()) as usize;
+            let mut body = take(&mut journal, length)?;
+            let offset = Duration::from_micros(u64::from_be_bytes(take(&mut body, 8)?.try_into().unwrap()));
+            let topic = take_string(&mut body)?;
+            let name = take_string(&mut body)?;
+            let has_version = take(&mut body, 1)?[0] == 1;
+            let version = u32::from_be_bytes(take(&mut body, 4)?.try_into().unwrap());
+            records.push(Record { offset, topic, name, schema_version: has_version.then_some(version), bytes: body.to_vec() });
+        }
+        Ok(records)
+    }
+}
+
+impl EventBus {
+    /// # Journal To
+    ///
+    /// Appends every registered event to the file at the path, so it can be replayed with `replay_journal`.
+    /// Only events with a payload type in the payload registry can be written to the journal.
+    pub fn journal_to(&self, path: impl AsRef<Path>) -> Result<&Self, String> {
+        let path = path.as_ref();
+        let file = OpenOptions::new().create(true).append(true).open(path)
+            .map_err(|e| format!("Cannot open journal {}: {}", path.display(), e))?;
+        let mut state = self.state.borrow_mut();
+        let started = state.clock.now();
+        state.journal = Some(Journal { file, started });
+        Ok(self)
+    }
+
+    /// # Replay Journal
+    ///
+    /// Registers the events of a journal again, and publishes after each one so subscribers receive them in their original order.
+    /// The time between the events is kept, divided by the speed of the options, and waited out on the clock of the event bus.
+    /// Events whose payload cannot be decoded are skipped and counted in the report.
+    pub fn replay_journal(&self, path: impl AsRef<Path>, options: ReplayOptions) -> Result<ReplayReport, String> {
+        self.replay_journal_filtered(path, ReplayFilter::from(options))
+    }
+
+    /// # Replay Journal Filtered
+    ///
+    /// Replays the events of a journal like `replay_journal`, but only the events the filter lets through,
+    /// on the event names the filter retargets them to. The time between the replayed events is kept.
+    /// The filter on event names and time is applied before decoding, the predicate after decoding.
+    pub fn replay_journal_filtered(&self, path: impl AsRef<Path>, filter: ReplayFilter) -> Result<ReplayReport, String> {
+        let path = path.as_ref();
+        let mut journal = Vec::new();
+        File::open(path).and_then(|mut file| file.read_to_end(&mut journal))
+            .map_err(|e| format!("Cannot read journal {}: {}", path.display(), e))?;
+
+        let mut report = ReplayReport::default();
+        let mut previous = None;
+        for record in Record::decode_all(&journal)? {
+            if filter.excludes(&record) {
+                report.filtered += 1;
+                continue;
+            }
+            let event = match self.payloads().decode_versioned(&record.name, record.schema_version, &record.bytes) {
+                Ok(event) => event,
+                Err(_) => {
+                    report.skipped += 1;
+                    continue;
+                }
+            };
+            if filter.predicate.as_ref().is_some_and(|predicate| !predicate(&event)) {
+                report.filtered += 1;
+                continue;
+            }
+            if let Some(previous) = previous.replace(record.offset) {
+                if filter.speed > 0.0 {
+                    let delay = record.offset.saturating_sub(previous).div_f32(filter.speed);
+                    let clock = self.state.borrow().clock.clone();
+                    clock.sleep(delay);
+                }
+            }
+            let topic = filter.retargets.get(&record.topic).cloned().unwrap_or(record.topic);
+            self.register(topic, event).publish()?;
+            report.replayed += 1;
+        }
+        Ok(report)
+    }
+}
+
+impl<K: TopicKey> BusState<K> {
+    /// Appends a registered event to the journal, when the event bus keeps one.
+    pub(crate) fn write_journal(&mut self, event_name: &K, message: &Event) {
+        let Some(journal) = &mut self.journal else {
+            return;
+        };
+        let (Some(topic), Some((name, bytes))) = (topic_str(event_name), self.payloads.encode(message)) else {
+            self.logger.journal_error(&format!("Cannot journal {:?}, its payload type is not registered", message));
+            return;
+        };
+        let record = Record {
+            offset: self.clock.now().duration_since(journal.started),
+            topic: topic.to_string(),
+            name: name.to_string(),
+            schema_version: message.schema_version(),
+            bytes,
+        };
+        if let Err(e) = journal.file.write_all(&record.encode()) {
+            self.logger.journal_error(&format!("Writing '{}' to the journal failed: {}", topic, e));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+    use super::{ReplayFilter, ReplayOptions, ReplayReport};
+    use crate::testing::ManualClock;
+    use crate::{Event, EventBus, Subscriber};
+
+    struct OrderSubscriber {
+        received: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Subscriber for OrderSubscriber {
+        fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+            self.received.borrow_mut().push(format!("{:?}", event.get_data::<u32>()));
+            Ok(())
+        }
+    }
+
+    fn register_u32(event_bus: &EventBus) {
+        event_bus.register_payload::<u32>(
+            "u32",
+            |value| value.to_be_bytes().to_vec(),
+            |bytes| bytes.try_into().map(u32::from_be_bytes).map_err(|_| "Expected 4 bytes".to_string()),
+        );
+    }
+
+    #[test]
+    fn test_replay_journal_in_order() {
+        let path = std::env::temp_dir().join(format!("simple_event_bus_journal_{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let clock = ManualClock::new();
+
+        let recording = EventBus::new();
+        register_u32(&recording);
+        recording.register_payload::<String>("string", |value| value.as_bytes().to_vec(), |bytes| {
+            String::from_utf8(bytes.to_vec()).map_err(|e| e.to_string())
+        });
+        recording.set_clock(clock.clone()).journal_to(&path).unwrap();
+        recording.register("clicks", 1u32).register("keys", 2u32);
+        clock.advance(Duration::from_secs(5));
+        recording.register("clicks", "unknown".to_string()).register("clicks", 3u32);
+        drop(recording);
+
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let replaying = EventBus::new();
+        register_u32(&replaying);
+        replaying
+            .subscribe_listener("clicks", OrderSubscriber { received: received.clone() })
+            .subscribe_listener("keys", OrderSubscriber { received: received.clone() });
+        let report = replaying.replay_journal(&path, ReplayOptions { speed: 0.0, topics: None }).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(ReplayReport { replayed: 3, skipped: 1, filtered: 0 }, report);
+        assert_eq!(vec!["Some(1)", "Some(2)", "Some(3)"], *received.borrow());
+    }
+
+    #[test]
+    fn test_dropped_events_are_not_journaled() {
+        let path = std::env::temp_dir().join(format!("simple_event_bus_dropped_journal_{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let recording = EventBus::new();
+        register_u32(&recording);
+        recording.journal_to(&path).unwrap();
+        recording
+            .set_topic_capacity("clicks", 1)
+            .dedupe_topic_by_type("keys")
+            .register("clicks", 1u32)
+            .register("clicks", 2u32)
+            .register("keys", 3u32)
+            .register("keys", 4u32);
+        drop(recording);
+
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let replaying = EventBus::new();
+        register_u32(&replaying);
+        replaying
+            .subscribe_listener("clicks", OrderSubscriber { received: received.clone() })
+            .subscribe_listener("keys", OrderSubscriber { received: received.clone() });
+        let report = replaying.replay_journal(&path, ReplayOptions { speed: 0.0, topics: None }).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(ReplayReport { replayed: 2, skipped: 0, filtered: 0 }, report);
+        assert_eq!(vec!["Some(1)", "Some(3)"], *received.borrow());
+    }
+
+    #[test]
+    fn test_replay_journal_filtered_by_topic_time_and_payload() {
+        let path = std::env::temp_dir().join(format!("simple_event_bus_filtered_journal_{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let clock = ManualClock::new();
+
+        let recording = EventBus::new();
+        register_u32(&recording);
+        recording.set_clock(clock.clone()).journal_to(&path).unwrap();
+        for second in 0..6u32 {
+            recording.register("orders", second).register("payments", 10 + second).register("audit", 20 + second);
+            clock.advance(Duration::from_secs(1));
+        }
+        drop(recording);
+
+        let replaying = EventBus::new();
+        register_u32(&replaying);
+        let live = Rc::new(RefCell::new(Vec::new()));
+        let replayed = Rc::new(RefCell::new(Vec::new()));
+        replaying
+            .subscribe_listener("orders", OrderSubscriber { received: live.clone() })
+            .subscribe_listener("orders.replay", OrderSubscriber { received: replayed.clone() })
+            .subscribe_listener("payments", OrderSubscriber { received: replayed.clone() })
+            .subscribe_listener("audit", OrderSubscriber { received: live.clone() });
+        let filter = ReplayFilter::new()
+            .speed(0.0)
+            .topics(&["orders", "payments"]).unwrap()
+            .between(Duration::from_secs(2)..Duration::from_secs(5))
+            .matching(|event| event.get_data::<u32>().is_some_and(|value| *value != 13))
+            .retarget("orders", "orders.replay");
+        let report = replaying.replay_journal_filtered(&path, filter).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(ReplayReport { replayed: 5, skipped: 0, filtered: 13 }, report);
+        assert_eq!(vec!["Some(2)", "Some(12)", "Some(3)", "Some(4)", "Some(14)"], *replayed.borrow());
+        assert!(live.borrow().is_empty());
+    }
+}
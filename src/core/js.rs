@@ -0,0 +1,47 @@
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use js_sys::{Function, Uint8Array};
+use wasm_bindgen::JsValue;
+use super::{Event, EventBus, EventContext, Outcome, Subscriber, TopicKey};
+use super::state::BusState;
+
+/// Hands the events to a JavaScript callback, encoded with the payload registry of the event bus.
+struct JsSubscriber<K: TopicKey> {
+    callback: Function,
+    /// The state of the event bus, for its payload registry.
+    /// It is not borrowed mutably while a subscriber is called.
+    state: Weak<RefCell<BusState<K>>>,
+}
+
+impl<K: TopicKey> Subscriber for JsSubscriber<K> {
+    fn on_event_with_context(&mut self, event: &mut Event, context: &mut EventContext) -> Outcome {
+        let Some(state) = self.state.upgrade() else {
+            return Outcome::AckAndUnsubscribe;
+        };
+        let encoded = state.borrow().payloads.encode(event).map(|(name, bytes)| (name.to_string(), bytes));
+        let Some((name, bytes)) = encoded else {
+            return Outcome::Error(format!("Payloads of type {} are not registered", event.payload_type_name()));
+        };
+        let payload = Uint8Array::from(bytes.as_slice());
+        match self.callback.call3(&JsValue::NULL, &payload, &JsValue::from_str(&name), &JsValue::from_str(context.topic())) {
+            Ok(_) => Outcome::Ack,
+            Err(thrown) => Outcome::Error(thrown.as_string().unwrap_or_else(|| format!("{:?}", thrown))),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "JavaScript callback"
+    }
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Subscribe JS
+    ///
+    /// Subscribes a JavaScript function to the event name. It is called with the payload encoded
+    /// by the payload registry as a `Uint8Array`, the name the payload type is registered under,
+    /// and the event name. Events whose payload type is not registered with `register_payload` fail the subscriber,
+    /// as does an exception thrown by the function.
+    pub fn subscribe_js(&self, event_name: impl Into<K>, callback: Function) -> &Self {
+        self.subscribe_listener(event_name, JsSubscriber { callback, state: Rc::downgrade(&self.state) })
+    }
+}
@@ -0,0 +1,287 @@
+use std::any::{type_name, Any, TypeId};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use super::{Event, PayloadError, PayloadRegistry};
+
+pub(crate) type JsonEncode = Box<dyn Fn(&dyn Any) -> Result<Value, String>>;
+pub(crate) type JsonDecode = Box<dyn Fn(Value) -> Result<Event, String>>;
+pub(crate) type MigrateDecode = Box<dyn Fn(Value) -> Result<Box<dyn Any>, String>>;
+pub(crate) type MigrateUpgrade = Box<dyn Fn(Box<dyn Any>) -> Result<Box<dyn Any>, String>>;
+
+/// A migration of a historical representation of a payload into the next one.
+pub(crate) struct Migration {
+    /// Decodes the JSON of the historical representation, and migrates it.
+    decode: MigrateDecode,
+    /// Migrates the historical representation made by the previous migration.
+    upgrade: MigrateUpgrade,
+    /// The type name of the next representation.
+    into: &'static str,
+}
+
+impl PayloadRegistry {
+    /// # Register Json
+    ///
+    /// Registers payloads of type `T` under a name, encoded as JSON. The payloads are encoded to the JSON bytes
+    /// by `encode`, and to a JSON value by `Event::to_json`.
+    pub fn register_json<T: Serialize + DeserializeOwned + 'static>(&mut self, name: &str) -> &mut Self {
+        self.register::<T>(
+            name,
+            |data| serde_json::to_vec(data).unwrap_or_default(),
+            |bytes| serde_json::from_slice(bytes).map_err(|e| e.to_string()),
+        );
+        let encode: JsonEncode = Box::new(|data| serde_json::to_value(data.downcast_ref::<T>().unwrap()).map_err(|e| e.to_string()));
+        let decode: JsonDecode = Box::new(|value| serde_json::from_value::<T>(value).map(Event::new).map_err(|e| e.to_string()));
+        self.json_encoders.insert(TypeId::of::<T>(), encode);
+        self.json_decoders.insert(name.to_string(), decode);
+        self
+    }
+
+    /// # Register Versioned
+    ///
+    /// Registers `Old` as the representation of the payloads under the name at a schema version, migrated
+    /// into the next representation `New` by the upgrade. Payloads of an older version are decoded as JSON
+    /// and migrated along the chain of versions, until they reach the current version: one past the last
+    /// registered version, decoded as the type registered with `register_json`.
+    /// Payloads without a version are decoded as the current version.
+    ///
+    /// The migrations apply wherever the registry decodes a payload together with its version:
+    /// `Event::from_json`, `decode_versioned`, journal replays and remote sources.
+    pub fn register_versioned<Old, New>(&mut self, name: &str, version: u32, upgrade: impl Fn(Old) -> New + 'static) -> &mut Self
+    where
+        Old: DeserializeOwned + 'static,
+        New: 'static,
+    {
+        let upgrade = std::rc::Rc::new(upgrade);
+        let decode_upgrade = upgrade.clone();
+        let migration = Migration {
+            decode: Box::new(move |value| serde_json::from_value::<Old>(value)
+                .map(|old| Box::new(decode_upgrade(old)) as Box<dyn Any>)
+                .map_err(|e| e.to_string())),
+            upgrade: Box::new(move |data| data.downcast::<Old>()
+                .map(|old| Box::new(upgrade(*old)) as Box<dyn Any>)
+                .map_err(|_| format!("Version {} expects {}", version, type_name::<Old>()))),
+            into: type_name::<New>(),
+        };
+        self.migrations.entry(name.to_string()).or_default().insert(version, migration);
+        self
+    }
+
+    /// Returns the versions of the payloads under the name the registry can decode, when it has migrations for them.
+    fn supported_versions(&self, name: &str) -> Option<Vec<u32>> {
+        let migrations = self.migrations.get(name)?;
+        let current = migrations.keys().next_back().map_or(0, |last| last + 1);
+        Some(migrations.keys().copied().chain(std::iter::once(current)).collect())
+    }
+
+    /// Migrates the JSON of a payload under the name at the version into an event of the current version.
+    /// Returns `None` when the payload is at the current version, so it is decoded as usual.
+    pub(crate) fn migrate(&self, name: &str, version: u32, data: impl FnOnce() -> Result<Value, String>) -> Option<Result<Event, PayloadError>> {
+        let supported = self.supported_versions(name)?;
+        let current = *supported.last().unwrap();
+        if version == current {
+            return None;
+        }
+        let migrations = &self.migrations[name];
+        let unsupported = || PayloadError::UnsupportedVersion { name: name.to_string(), found: version, supported: supported.clone() };
+        let invalid = |message| PayloadError::Invalid { name: name.to_string(), message };
+        let Some(first) = migrations.get(&version) else {
+            return Some(Err(unsupported()));
+        };
+        let mut migrated = match data().and_then(|value| (first.decode)(value)) {
+            Ok(migrated) => migrated,
+            Err(message) => return Some(Err(invalid(message))),
+        };
+        let mut into = first.into;
+        for next in version + 1..current {
+            let Some(migration) = migrations.get(&next) else {
+                return Some(Err(unsupported()));
+            };
+            migrated = match (migration.upgrade)(migrated) {
+                Ok(migrated) => migrated,
+                Err(message) => return Some(Err(invalid(message))),
+            };
+            into = migration.into;
+        }
+        let mut event = Event::from_boxed(migrated, into);
+        event.set_schema_version(current);
+        Some(Ok(event))
+    }
+
+    /// Returns the registered name and the JSON of the payload of the event. Payloads that are not registered
+    /// with `register_json` are encoded as an array of their bytes.
+    fn encode_json(&self, event: &Event) -> Result<(&str, Value), PayloadError> {
+        let unknown = || PayloadError::Unknown { name: event.payload_type_name().to_string() };
+        let name = self.name_of(event).ok_or_else(unknown)?;
+        let data = match self.json_encoders.get(&(*event.data).type_id()) {
+            Some(encode) => encode(&*event.data).map_err(|message| PayloadError::Invalid { name: name.to_string(), message })?,
+            None => Value::from(self.encode(event).ok_or_else(unknown)?.1),
+        };
+        Ok((name, data))
+    }
+
+    /// Decodes the JSON of a payload registered under the name into an event.
+    fn decode_json(&self, name: &str, data: Value) -> Result<Event, PayloadError> {
+        if let Some(decode) = self.json_decoders.get(name) {
+            return decode(data).map_err(|message| PayloadError::Invalid { name: name.to_string(), message });
+        }
+        let bytes: Vec<u8> = serde_json::from_value(data)
+            .map_err(|_| PayloadError::Invalid { name: name.to_string(), message: "Expected an array of bytes".to_string() })?;
+        self.decode(name, &bytes)
+    }
+}
+
+impl Event {
+    /// # To Json
+    ///
+    /// Returns the event as a JSON object, with the registered name of its payload type as `type`,
+    /// its payload as `data`, its `id`, and its `schema_version` when it has one.
+    /// Returns an error when the payload type is not registered.
+    pub fn to_json(&self, registry: &PayloadRegistry) -> Result<Value, PayloadError> {
+        let (name, data) = registry.encode_json(self)?;
+        let mut json = json!({ "type": name, "data": data, "id": self.id() });
+        if let Some(version) = self.schema_version() {
+            json["schema_version"] = Value::from(version);
+        }
+        Ok(json)
+    }
+
+    /// # From Json
+    ///
+    /// Creates an event from a JSON object made by `to_json`, keeping its schema version.
+    /// The event gets a new id, as the id in the JSON may be in use by another event of this process.
+    pub fn from_json(registry: &PayloadRegistry, json: Value) -> Result<Event, PayloadError> {
+        let Value::Object(mut json) = json else {
+            return Err(PayloadError::Malformed { message: "Expected a JSON object".to_string() });
+        };
+        let name = match json.remove("type") {
+            Some(Value::String(name)) => name,
+            _ => return Err(PayloadError::Malformed { message: "Expected a string 'type'".to_string() }),
+        };
+        let data = json.remove("data")
+            .ok_or_else(|| PayloadError::Malformed { message: "Expected 'data'".to_string() })?;
+        let version = match json.get("schema_version") {
+            None | Some(Value::Null) => None,
+            Some(version) => Some(version.as_u64().and_then(|version| u32::try_from(version).ok())
+                .ok_or_else(|| PayloadError::Malformed { message: "Expected a number 'schema_version'".to_string() })?),
+        };
+        let Some(version) = version else {
+            return registry.decode_json(&name, data);
+        };
+        if let Some(migrated) = registry.migrate(&name, version, || Ok(data.clone())) {
+            return migrated;
+        }
+        let mut event = registry.decode_json(&name, data)?;
+        event.set_schema_version(version);
+        Ok(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use crate::{Event, PayloadError, PayloadRegistry};
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Order {
+        id: u32,
+        items: Vec<String>,
+    }
+
+    fn registry() -> PayloadRegistry {
+        let mut registry = PayloadRegistry::new();
+        registry
+            .register_json::<Order>("order")
+            .register::<u32>(
+                "u32",
+                |value| value.to_be_bytes().to_vec(),
+                |bytes| bytes.try_into().map(u32::from_be_bytes).map_err(|_| "Expected 4 bytes".to_string()),
+            );
+        registry
+    }
+
+    #[test]
+    fn test_struct_payload_round_trips() {
+        let registry = registry();
+        let order = Order { id: 7, items: vec!["tea".to_string()] };
+        let event = Event::new(order.clone()).with_schema_version(2);
+
+        let json = event.to_json(&registry).unwrap();
+        assert_eq!(json!({ "type": "order", "data": { "id": 7, "items": ["tea"] }, "id": event.id(), "schema_version": 2 }), json);
+
+        let imported = Event::from_json(&registry, json).unwrap();
+        assert_eq!(Some(&order), imported.get_data::<Order>());
+        assert_eq!(Some(2), imported.schema_version());
+        assert_ne!(event.id(), imported.id());
+    }
+
+    #[test]
+    fn test_byte_payload_round_trips() {
+        let registry = registry();
+        let json = Event::new(42u32).to_json(&registry).unwrap();
+        assert_eq!(json!([0, 0, 0, 42]), json["data"]);
+        assert_eq!(Some(&42u32), Event::from_json(&registry, json).unwrap().get_data::<u32>());
+    }
+
+    #[test]
+    fn test_json_errors() {
+        let registry = registry();
+        assert_eq!(Some(PayloadError::Unknown { name: "f64".to_string() }), Event::new(1.5f64).to_json(&registry).err());
+        assert_eq!(
+            Some(PayloadError::Malformed { message: "Expected a string 'type'".to_string() }),
+            Event::from_json(&registry, json!({ "data": 1 })).err()
+        );
+        assert!(matches!(
+            Event::from_json(&registry, json!({ "type": "order", "data": { "id": "seven" } })),
+            Err(PayloadError::Invalid { .. })
+        ));
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct OrderV1 {
+        id: u32,
+        item: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct OrderV2 {
+        id: u32,
+        lines: Vec<String>,
+    }
+
+    fn versioned_registry() -> PayloadRegistry {
+        let mut registry = registry();
+        registry
+            .register_versioned::<OrderV1, OrderV2>("order", 1, |old| OrderV2 { id: old.id, lines: vec![old.item] })
+            .register_versioned::<OrderV2, Order>("order", 2, |old| Order { id: old.id, items: old.lines });
+        registry
+    }
+
+    #[test]
+    fn test_old_payload_is_migrated_through_every_version() {
+        let registry = versioned_registry();
+        let json = json!({ "type": "order", "data": { "id": 7, "item": "tea" }, "schema_version": 1 });
+
+        let imported = Event::from_json(&registry, json).unwrap();
+        assert_eq!(Some(&Order { id: 7, items: vec!["tea".to_string()] }), imported.get_data::<Order>());
+        assert_eq!(Some(3), imported.schema_version());
+
+        let bytes = serde_json::to_vec(&json!({ "id": 8, "lines": ["milk"] })).unwrap();
+        let decoded = registry.decode_versioned("order", Some(2), &bytes).unwrap();
+        assert_eq!(Some(&Order { id: 8, items: vec!["milk".to_string()] }), decoded.get_data::<Order>());
+        let bytes = serde_json::to_vec(&json!({ "id": 9, "items": [] })).unwrap();
+        let current = registry.decode_versioned("order", Some(3), &bytes).unwrap();
+        assert_eq!(Some(3), current.schema_version());
+    }
+
+    #[test]
+    fn test_unsupported_version_is_an_error() {
+        let registry = versioned_registry();
+        let json = json!({ "type": "order", "data": { "id": 7 }, "schema_version": 0 });
+
+        let error = Event::from_json(&registry, json).unwrap_err();
+        assert_eq!(PayloadError::UnsupportedVersion { name: "order".to_string(), found: 0, supported: vec![1, 2, 3] }, error);
+        assert_eq!("No migration of 'order' payload from version 0, supported versions: 1, 2, 3", error.to_string());
+    }
+}
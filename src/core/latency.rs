@@ -0,0 +1,173 @@
+use std::time::Duration;
+
+/// The upper bounds of the latency buckets, the last bucket holds the latencies above the last bound.
+const BUCKET_BOUNDS: [Duration; 6] = [
+    Duration::from_micros(100),
+    Duration::from_millis(1),
+    Duration::from_millis(10),
+    Duration::from_millis(100),
+    Duration::from_secs(1),
+    Duration::from_secs(10),
+];
+
+/// # Latency Stats
+///
+/// How long the events of an event name were queued, from their registration until a publish started
+/// dispatching them, on the clock of the event bus. A redelivered event counts from its first registration.
+///
+/// ## Fields
+///
+/// * `count` - The number of dispatched events.
+///
+/// * `redelivered` - The number of dispatched events that were redelivered after a nack.
+///
+/// * `min`, `max` - The shortest and longest latency.
+///
+/// * `total` - The sum of the latencies.
+///
+/// * `buckets` - The number of latencies per bucket, see `bucket_bounds`.
+///
+/// ## Methods
+///
+/// * `mean` - Returns the mean latency.
+///
+/// * `percentile` - Returns the upper bound of the bucket that holds a percentile.
+///
+/// * `bucket_bounds` - Returns the upper bounds of the buckets.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LatencyStats {
+    /// The number of dispatched events.
+    pub count: u64,
+    /// The number of dispatched events that were redelivered after a nack.
+    pub redelivered: u64,
+    /// The shortest latency.
+    pub min: Duration,
+    /// The longest latency.
+    pub max: Duration,
+    /// The sum of the latencies.
+    pub total: Duration,
+    /// The number of latencies up to each bound of `bucket_bounds`, and above the last bound.
+    pub buckets: [u64; 7],
+}
+
+impl LatencyStats {
+    /// # Mean
+    ///
+    /// Returns the mean latency, or `None` when no event was dispatched.
+    pub fn mean(&self) -> Option<Duration> {
+        u32::try_from(self.count).ok().filter(|count| *count > 0).map(|count| self.total / count)
+    }
+
+    /// # Percentile
+    ///
+    /// Returns the upper bound of the bucket that holds the percentile, between 0 and 100,
+    /// or the longest latency when that is lower. Returns `None` when no event was dispatched.
+    pub fn percentile(&self, percentile: f64) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = ((percentile.clamp(0.0, 100.0) / 100.0 * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (index, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Some(BUCKET_BOUNDS.get(index).map_or(self.max, |bound| (*bound).min(self.max)));
+            }
+        }
+        Some(self.max)
+    }
+
+    /// # Bucket Bounds
+    ///
+    /// Returns the upper bounds of the buckets: 100 µs, 1 ms, 10 ms, 100 ms, 1 s and 10 s.
+    pub fn bucket_bounds() -> &'static [Duration] {
+        &BUCKET_BOUNDS
+    }
+
+    pub(crate) fn record(&mut self, latency: Duration, redeliveries: u32) {
+        self.min = if self.count == 0 { latency } else { self.min.min(latency) };
+        self.max = self.max.max(latency);
+        self.count += 1;
+        self.total += latency;
+        if redeliveries > 0 {
+            self.redelivered += 1;
+        }
+        self.buckets[BUCKET_BOUNDS.partition_point(|bound| *bound < latency)] += 1;
+    }
+
+    pub(crate) fn merge(&mut self, other: &LatencyStats) {
+        if other.count == 0 {
+            return;
+        }
+        self.min = if self.count == 0 { other.min } else { self.min.min(other.min) };
+        self.max = self.max.max(other.max);
+        self.count += other.count;
+        self.redelivered += other.redelivered;
+        self.total += other.total;
+        for (bucket, count) in self.buckets.iter_mut().zip(other.buckets) {
+            *bucket += count;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use super::LatencyStats;
+    use crate::{Event, EventBus, Outcome, Subscriber};
+    use crate::testing::ManualClock;
+
+    struct NackOnce {
+        nacked: bool,
+    }
+
+    impl Subscriber for NackOnce {
+        fn on_event_outcome(&mut self, _event: &mut Event) -> Outcome {
+            if self.nacked {
+                return Outcome::Ack;
+            }
+            self.nacked = true;
+            Outcome::Nack { requeue: true }
+        }
+    }
+
+    #[test]
+    fn test_latency_is_the_time_between_register_and_publish() {
+        let clock = ManualClock::new();
+        let event_bus = EventBus::with_clock(clock.clone());
+        event_bus
+            .subscribe_listener("order", <dyn Subscriber>::builder().on_event(|_| Ok(())).build())
+            .register("order", 1u32);
+        clock.advance(Duration::from_millis(40));
+        event_bus.register("order", 2u32);
+        clock.advance(Duration::from_millis(10));
+
+        let report = event_bus.publish_report().unwrap();
+        let latency = report.topic(&"order".to_string()).latency;
+        assert_eq!((2, Duration::from_millis(10), Duration::from_millis(50)), (latency.count, latency.min, latency.max));
+        assert_eq!(Some(Duration::from_millis(30)), latency.mean());
+        assert_eq!(Some(Duration::from_millis(10)), latency.percentile(50.0));
+        assert_eq!(Some(Duration::from_millis(50)), latency.percentile(99.0));
+        assert_eq!(latency, event_bus.metrics().topic(&"order".to_string()).latency);
+    }
+
+    #[test]
+    fn test_redelivered_event_counts_from_its_registration() {
+        let clock = ManualClock::new();
+        let event_bus = EventBus::with_clock(clock.clone());
+        event_bus
+            .subscribe_listener("order", NackOnce { nacked: false })
+            .register("order", 1u32);
+        clock.advance(Duration::from_millis(5));
+        assert_eq!(Ok(()), event_bus.publish());
+        clock.advance(Duration::from_millis(20));
+
+        let latency = event_bus.publish_report().unwrap().topic(&"order".to_string()).latency;
+        assert_eq!((1, 1, Duration::from_millis(25)), (latency.count, latency.redelivered, latency.max));
+        let mut expected = LatencyStats::default();
+        expected.record(Duration::from_millis(5), 0);
+        expected.record(Duration::from_millis(25), 1);
+        assert_eq!(expected, event_bus.metrics().topic(&"order".to_string()).latency);
+    }
+}
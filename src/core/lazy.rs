@@ -0,0 +1,119 @@
+use super::{EventBus, Subscriber, TopicKey};
+use super::subscription::Subscription;
+
+/// # Subscriber State
+///
+/// Whether the listener of a subscription is constructed, as returned by `subscriber_states`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscriberState {
+    /// A lazy subscription whose listener is constructed once its first event is about to be delivered.
+    Registered,
+    /// The listener is constructed and receives the events.
+    Active,
+}
+
+/// Stands in for the listener of a lazy subscription until it is constructed.
+struct Unconstructed;
+
+impl Subscriber for Unconstructed {
+    fn name(&self) -> &str {
+        "lazy subscriber"
+    }
+}
+
+impl Subscription {
+    pub(crate) fn lazy(factory: impl FnOnce() -> Box<dyn Subscriber> + 'static) -> Subscription {
+        let mut subscription = Subscription::new(Unconstructed);
+        subscription.factory = Some(Box::new(factory));
+        subscription
+    }
+
+    /// Constructs the listener of a lazy subscription, when it is not constructed yet.
+    pub(crate) fn construct(&mut self) {
+        if let Some(factory) = self.factory.take() {
+            self.listener = factory();
+        }
+    }
+
+    pub(crate) fn state(&self) -> SubscriberState {
+        match self.factory {
+            Some(_) => SubscriberState::Registered,
+            None => SubscriberState::Active,
+        }
+    }
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Subscribe Lazy
+    ///
+    /// Subscribes a listener that is only constructed by the factory once the first event
+    /// of the event name is about to be delivered, for listeners that are expensive to construct.
+    /// From then on it receives the events like any other subscriber.
+    /// A panicking factory panics the publish, like a panicking subscriber does.
+    ///
+    /// Until it is constructed, the listener is named `lazy subscriber`,
+    /// so other subscribers cannot run after it by its name.
+    pub fn subscribe_lazy(&self, event_name: impl Into<K>, factory: impl FnOnce() -> Box<dyn Subscriber> + 'static) -> &Self {
+        self.subscribe(self.topic_key(event_name), Subscription::lazy(factory))
+    }
+
+    /// # Subscriber States
+    ///
+    /// Returns the names of the subscribers of the event name, in subscription order,
+    /// with whether their listener is constructed.
+    /// The subscribers are not known while the event name is being published.
+    pub fn subscriber_states(&self, event_name: &K) -> Vec<(String, SubscriberState)> {
+        let state = self.state.borrow();
+        state.subscribers.get(&state.normalize(event_name.clone())).into_iter().flatten()
+            .map(|subscription| (subscription.listener.name().to_string(), subscription.state()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+    use super::SubscriberState;
+    use crate::{Event, EventBus, Subscriber};
+
+    struct IndexSubscriber {
+        received: Rc<RefCell<Vec<u32>>>,
+    }
+
+    impl Subscriber for IndexSubscriber {
+        fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+            self.received.borrow_mut().push(*event.try_get_data::<u32>()?);
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "index"
+        }
+    }
+
+    #[test]
+    fn test_lazy_subscriber_constructed_on_first_event() {
+        let constructed = Rc::new(Cell::new(0));
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        {
+            let (constructed, received) = (constructed.clone(), received.clone());
+            event_bus.subscribe_lazy("documents", move || {
+                constructed.set(constructed.get() + 1);
+                Box::new(IndexSubscriber { received })
+            });
+        }
+        let documents = "documents".to_string();
+
+        event_bus.register("other", 1u32).publish().unwrap();
+        assert_eq!(0, constructed.get());
+        assert_eq!(vec![("lazy subscriber".to_string(), SubscriberState::Registered)], event_bus.subscriber_states(&documents));
+
+        event_bus.register("documents", 2u32).publish().unwrap();
+        event_bus.register("documents", 3u32).publish().unwrap();
+        assert_eq!(1, constructed.get());
+        assert_eq!(vec![2, 3], *received.borrow());
+        assert_eq!(vec![("index".to_string(), SubscriberState::Active)], event_bus.subscriber_states(&documents));
+    }
+}
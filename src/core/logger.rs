@@ -0,0 +1,229 @@
+#![allow(unused_variables)]
+use std::fmt::Debug;
+use super::{DeadLetterReason, Event, InvalidPattern, Phase};
+
+/// # Bus Logger
+///
+/// Receives the notifications of an event bus, set with `EventBus::set_logger`.
+/// Every method does nothing by default, so a logger only implements the notifications it needs.
+///
+/// ## Methods
+///
+/// * `event_registered` - An event was registered.
+///
+/// * `events_registered` - A run of events of the same event name was registered at once.
+///
+/// * `no_subscribers` - An event was published to an event name without subscribers.
+///
+/// * `subscriber_error` - A subscriber failed.
+///
+/// * `subscriber_skipped` - The on_before of a subscriber failed, and the subscriber skipped the event.
+///
+/// * `subscriber_removed` - A subscriber unsubscribed itself.
+///
+/// * `invalid_pattern` - A listener was not subscribed, because its wildcard pattern is malformed.
+///
+/// * `undeclared_topic` - A listener was subscribed to an event name the event bus is not restricted to.
+///
+/// * `topic_denied` - An event or listener was dropped, because the topic policy denies its event name.
+///
+/// * `unlisted_topic` - An event name was used that the entries of `TopicPolicy::WarnUnknown` do not match.
+///
+/// * `upgrade_failed` - An event could not be upgraded to the current schema version.
+///
+/// * `remote_error` - Exporting or receiving remote events failed.
+///
+/// * `event_invalid` - The validator of an event name rejected an event.
+///
+/// * `route_not_copied` - A copy route skipped an event, because its payload type is not cloneable.
+pub trait BusLogger {
+    /// An event was registered.
+    fn event_registered(&self, topic: &dyn Debug, event: &Event) {}
+
+    /// A run of events of the same event name was registered at once.
+    fn events_registered(&self, topic: &dyn Debug, count: usize) {}
+
+    /// An event was published to an event name without subscribers.
+    fn no_subscribers(&self, topic: &dyn Debug) {}
+
+    /// A subscriber failed.
+    fn subscriber_error(&self, topic: &dyn Debug, subscriber: &str, phase: Phase, message: &str) {}
+
+    /// The on_before of a subscriber failed, and the subscriber skipped the event.
+    fn subscriber_skipped(&self, topic: &dyn Debug, subscriber: &str, message: &str) {}
+
+    /// A subscriber unsubscribed itself.
+    fn subscriber_removed(&self, topic: &str) {}
+
+    /// A listener was not subscribed, because its wildcard pattern is malformed.
+    fn invalid_pattern(&self, error: &InvalidPattern) {}
+
+    /// A listener was subscribed to an event name the event bus is not restricted to.
+    fn undeclared_topic(&self, topic: &dyn Debug) {}
+
+    /// An event or listener was dropped, because the topic policy denies its event name.
+    fn topic_denied(&self, topic: &dyn Debug) {}
+
+    /// An event name was used that the entries of `TopicPolicy::WarnUnknown` do not match.
+    fn unlisted_topic(&self, topic: &dyn Debug) {}
+
+    /// An event could not be upgraded to the current schema version.
+    fn upgrade_failed(&self, topic: &dyn Debug, reason: &DeadLetterReason) {}
+
+    /// Exporting or receiving remote events failed.
+    fn remote_error(&self, message: &str) {}
+
+    /// Writing an event to the journal failed.
+    fn journal_error(&self, message: &str) {}
+
+    /// The subscribers of an event name depend on each other in a cycle, they keep their subscription order.
+    fn dependency_cycle(&self, topic: &dyn Debug, subscribers: &[String]) {}
+
+    /// The validator of an event name rejected an event.
+    fn event_invalid(&self, topic: &dyn Debug, message: &str) {}
+
+    /// A copy route skipped an event, because its payload type is not registered as cloneable.
+    fn route_not_copied(&self, from: &str, to: &str, payload_type: &str) {}
+}
+
+/// # Null Logger
+///
+/// A logger that ignores every notification.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NullLogger;
+
+impl BusLogger for NullLogger {}
+
+/// # Log Logger
+///
+/// The default logger, forwarding every notification to the `log` crate.
+#[cfg(feature = "log")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogLogger;
+
+#[cfg(feature = "log")]
+impl BusLogger for LogLogger {
+    fn event_registered(&self, topic: &dyn Debug, event: &Event) {
+        log::info!("EVENT: Register {:?} event with message: {:?}", topic, event);
+    }
+
+    fn events_registered(&self, topic: &dyn Debug, count: usize) {
+        log::info!("EVENT: Register {} {:?} events", count, topic);
+    }
+
+    fn no_subscribers(&self, topic: &dyn Debug) {
+        log::warn!("No event subscribers for {:?}", topic);
+    }
+
+    fn subscriber_error(&self, topic: &dyn Debug, subscriber: &str, phase: Phase, message: &str) {
+        log::error!("Subscriber error: {}", message);
+    }
+
+    fn subscriber_skipped(&self, topic: &dyn Debug, subscriber: &str, message: &str) {
+        log::info!("Subscriber skipped message: {}", message);
+    }
+
+    fn subscriber_removed(&self, topic: &str) {
+        log::info!("EVENT: Subscriber unsubscribed from {:?}", topic);
+    }
+
+    fn invalid_pattern(&self, error: &InvalidPattern) {
+        log::error!("Subscriber not subscribed: {}", error);
+    }
+
+    fn undeclared_topic(&self, topic: &dyn Debug) {
+        log::warn!("Subscribing to undeclared topic {:?}", topic);
+    }
+
+    fn topic_denied(&self, topic: &dyn Debug) {
+        log::error!("Topic {:?} is denied by the topic policy", topic);
+    }
+
+    fn unlisted_topic(&self, topic: &dyn Debug) {
+        log::warn!("Topic {:?} is not listed by the topic policy", topic);
+    }
+
+    fn upgrade_failed(&self, topic: &dyn Debug, reason: &DeadLetterReason) {
+        log::error!("Upgrade error: {}", reason);
+    }
+
+    fn remote_error(&self, message: &str) {
+        log::error!("{}", message);
+    }
+
+    fn journal_error(&self, message: &str) {
+        log::error!("{}", message);
+    }
+
+    fn dependency_cycle(&self, topic: &dyn Debug, subscribers: &[String]) {
+        log::warn!("Subscribers of {:?} depend on each other in a cycle: {}", topic, subscribers.join(", "));
+    }
+
+    fn event_invalid(&self, topic: &dyn Debug, message: &str) {
+        log::warn!("Invalid {:?} event: {}", topic, message);
+    }
+
+    fn route_not_copied(&self, from: &str, to: &str, payload_type: &str) {
+        log::warn!("Route from {:?} to {:?} did not copy a payload of type {}, which is not cloneable", from, to, payload_type);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::fmt::Debug;
+    use std::rc::Rc;
+    use crate::{BusLogger, Event, EventBus, Phase, Subscriber};
+
+    #[derive(Default)]
+    struct CapturingLogger {
+        notifications: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl BusLogger for CapturingLogger {
+        fn event_registered(&self, topic: &dyn Debug, _event: &Event) {
+            self.notifications.borrow_mut().push(format!("registered {:?}", topic));
+        }
+
+        fn no_subscribers(&self, topic: &dyn Debug) {
+            self.notifications.borrow_mut().push(format!("no subscribers {:?}", topic));
+        }
+
+        fn subscriber_error(&self, topic: &dyn Debug, _subscriber: &str, phase: Phase, message: &str) {
+            self.notifications.borrow_mut().push(format!("error {:?} {:?} {}", topic, phase, message));
+        }
+    }
+
+    struct FailingSubscriber;
+
+    impl Subscriber for FailingSubscriber {
+        fn on_event(&mut self, _event: &mut Event) -> Result<(), String> {
+            Err("failed".to_string())
+        }
+    }
+
+    #[test]
+    fn test_capturing_logger() {
+        let notifications = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .set_logger(CapturingLogger { notifications: notifications.clone() })
+            .set_fail_on_error(false)
+            .subscribe_listener("startup", FailingSubscriber)
+            .register("startup", 1u32)
+            .register("shutdown", 2u32);
+        event_bus.publish().unwrap();
+
+        let mut notifications = notifications.borrow().clone();
+        notifications[2..].sort();
+        assert_eq!(
+            vec![
+                "registered \"startup\"",
+                "registered \"shutdown\"",
+                "error \"startup\" Event failed",
+                "no subscribers \"shutdown\"",
+            ],
+            notifications
+        );
+    }
+}
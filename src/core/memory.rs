@@ -0,0 +1,154 @@
+use super::{EventBus, TopicKey};
+use super::state::BusState;
+
+/// # Memory Footprint
+///
+/// The number of entries and the capacities of the collections of an event bus,
+/// as returned by `approximate_memory_footprint`.
+///
+/// ## Fields
+///
+/// * `event_topics` - The number of event names with a queue.
+///
+/// * `event_topic_capacity` - The number of event names the queues can hold without growing.
+///
+/// * `queued_events` - The number of queued events.
+///
+/// * `queued_event_capacity` - The number of events the queues can hold without growing.
+///
+/// * `subscriber_topics` - The number of event names with subscriptions.
+///
+/// * `subscriber_topic_capacity` - The number of event names the subscriptions can hold without growing.
+///
+/// * `subscriptions` - The number of subscriptions, including pattern subscriptions.
+///
+/// * `subscription_capacity` - The number of subscriptions that can be held without growing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryFootprint {
+    /// The number of event names with a queue.
+    pub event_topics: usize,
+    /// The number of event names the queues can hold without growing.
+    pub event_topic_capacity: usize,
+    /// The number of queued events.
+    pub queued_events: usize,
+    /// The number of events the queues can hold without growing.
+    pub queued_event_capacity: usize,
+    /// The number of event names with subscriptions.
+    pub subscriber_topics: usize,
+    /// The number of event names the subscriptions can hold without growing.
+    pub subscriber_topic_capacity: usize,
+    /// The number of subscriptions, including pattern subscriptions.
+    pub subscriptions: usize,
+    /// The number of subscriptions that can be held without growing.
+    pub subscription_capacity: usize,
+}
+
+impl<K: TopicKey> BusState<K> {
+    /// Removes the queues without events, and the subscription lists without subscriptions when asked to.
+    pub(crate) fn prune(&mut self, subscribers: bool) {
+        self.events.retain(|_, messages| !messages.is_empty());
+        if subscribers {
+            self.subscribers.retain(|_, subscriptions| !subscriptions.is_empty());
+        }
+    }
+
+    /// Shrinks the capacity of the collections of the event bus as much as possible.
+    pub(crate) fn shrink_to_fit(&mut self) {
+        self.events.values_mut().for_each(Vec::shrink_to_fit);
+        self.events.shrink_to_fit();
+        self.subscribers.values_mut().for_each(Vec::shrink_to_fit);
+        self.subscribers.shrink_to_fit();
+        self.pattern_subscriptions.shrink_to_fit();
+        self.scheduled.shrink_to_fit();
+        self.dead_letters.shrink_to_fit();
+    }
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Prune
+    ///
+    /// Removes the entries of event names without queued events, and of event names
+    /// without subscriptions when `subscribers` is set, so transient event names like one per connection
+    /// do not keep their entries. Use `shrink_to_fit` to release the capacity that is left.
+    pub fn prune(&self, subscribers: bool) -> &Self {
+        self.state.borrow_mut().prune(subscribers);
+        self
+    }
+
+    /// # Shrink To Fit
+    ///
+    /// Shrinks the capacity of the queues, the subscriptions, the delayed events and the dead letters
+    /// as much as possible.
+    pub fn shrink_to_fit(&self) -> &Self {
+        self.state.borrow_mut().shrink_to_fit();
+        self
+    }
+
+    /// # Set Auto Prune
+    ///
+    /// Sets whether each publish ends by pruning the event names without queued events or subscriptions
+    /// and shrinking the collections, see `prune` and `shrink_to_fit`.
+    pub fn set_auto_prune(&self, auto_prune: bool) -> &Self {
+        self.state.borrow_mut().auto_prune = auto_prune;
+        self
+    }
+
+    /// # Approximate Memory Footprint
+    ///
+    /// Returns the number of entries and the capacities of the queues and the subscriptions,
+    /// to observe the effect of `prune` and `shrink_to_fit`.
+    pub fn approximate_memory_footprint(&self) -> MemoryFootprint {
+        let state = self.state.borrow();
+        MemoryFootprint {
+            event_topics: state.events.len(),
+            event_topic_capacity: state.events.capacity(),
+            queued_events: state.events.values().map(Vec::len).sum(),
+            queued_event_capacity: state.events.values().map(Vec::capacity).sum(),
+            subscriber_topics: state.subscribers.len(),
+            subscriber_topic_capacity: state.subscribers.capacity(),
+            subscriptions: state.subscribers.values().map(Vec::len).sum::<usize>() + state.pattern_subscriptions.len(),
+            subscription_capacity: state.subscribers.values().map(Vec::capacity).sum::<usize>()
+                + state.pattern_subscriptions.capacity(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Event, EventBus, Outcome, Subscriber};
+
+    struct OneShotSubscriber;
+
+    impl Subscriber for OneShotSubscriber {
+        fn on_event_outcome(&mut self, _event: &mut Event) -> Outcome {
+            Outcome::AckAndUnsubscribe
+        }
+    }
+
+    #[test]
+    fn test_prune_releases_transient_topics() {
+        let event_bus = EventBus::new();
+        for connection in 0..10_000 {
+            let topic = format!("connection/{}", connection);
+            event_bus.subscribe_listener(topic.clone(), OneShotSubscriber);
+            event_bus.register(topic, connection);
+        }
+        assert_eq!(Ok(()), event_bus.publish());
+        for connection in 0..10_000 {
+            event_bus.register(format!("closed/{}", connection), connection);
+        }
+        event_bus.clear();
+
+        let footprint = event_bus.approximate_memory_footprint();
+        assert_eq!(0, footprint.subscriptions);
+        assert!(footprint.event_topic_capacity >= 10_000);
+        assert!(footprint.subscriber_topic_capacity >= 10_000);
+
+        event_bus.prune(true).shrink_to_fit();
+        let footprint = event_bus.approximate_memory_footprint();
+        assert_eq!(0, footprint.event_topics);
+        assert_eq!(0, footprint.subscriber_topics);
+        assert!(footprint.event_topic_capacity < 16);
+        assert!(footprint.subscriber_topic_capacity < 16);
+    }
+}
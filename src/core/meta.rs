@@ -0,0 +1,99 @@
+use super::{BreakerState, DeadLetterReason};
+
+/// # Subscriber Added
+///
+/// The payload of the meta event registered on `SubscriberAdded::TOPIC`
+/// when a listener is subscribed to the event bus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubscriberAdded {
+    /// The event name or pattern the listener is subscribed to.
+    pub topic: String,
+}
+
+impl SubscriberAdded {
+    /// The reserved event name of the meta event.
+    pub const TOPIC: &'static str = "__bus.subscriber_added";
+}
+
+/// # Subscriber Removed
+///
+/// The payload of the meta event registered on `SubscriberRemoved::TOPIC`
+/// when a listener unsubscribed itself from the event bus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubscriberRemoved {
+    /// The event name or pattern the listener was subscribed to.
+    pub topic: String,
+}
+
+impl SubscriberRemoved {
+    /// The reserved event name of the meta event.
+    pub const TOPIC: &'static str = "__bus.subscriber_removed";
+}
+
+/// # Topic First Event
+///
+/// The payload of the meta event registered on `TopicFirstEvent::TOPIC`
+/// when the first event of an event name is registered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopicFirstEvent {
+    /// The event name that received its first event.
+    pub topic: String,
+}
+
+impl TopicFirstEvent {
+    /// The reserved event name of the meta event.
+    pub const TOPIC: &'static str = "__bus.topic_first_event";
+}
+
+/// # Dead Lettered
+///
+/// The payload of the meta event registered on `DeadLettered::TOPIC`
+/// when an event is moved to the dead-letter queue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeadLettered {
+    /// The event name of the dead-lettered event.
+    pub topic: String,
+    /// Why the event could not be delivered.
+    pub reason: DeadLetterReason,
+}
+
+impl DeadLettered {
+    /// The reserved event name of the meta event.
+    pub const TOPIC: &'static str = "__bus.dead_lettered";
+}
+
+/// # Breaker State Changed
+///
+/// The payload of the meta event registered on `BreakerStateChanged::TOPIC`
+/// when the circuit breaker of a subscription changes its state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BreakerStateChanged {
+    /// The event name or pattern the subscriber is subscribed to.
+    pub topic: String,
+    /// The name of the subscriber.
+    pub subscriber: String,
+    /// The new state of the breaker.
+    pub state: BreakerState,
+}
+
+impl BreakerStateChanged {
+    /// The reserved event name of the meta event.
+    pub const TOPIC: &'static str = "__bus.breaker_state_changed";
+}
+
+/// # Publish Completed
+///
+/// The payload of the meta event registered on `PublishCompleted::TOPIC`
+/// when a publish cycle completes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublishCompleted {
+    /// The number of events handled by at least one subscriber.
+    pub handled: u64,
+    /// The number of events ignored by all of their subscribers.
+    pub ignored: u64,
+}
+
+impl PublishCompleted {
+    /// The reserved event name of the meta event.
+    pub const TOPIC: &'static str = "__bus.publish_completed";
+}
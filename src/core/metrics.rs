@@ -0,0 +1,162 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use super::{LatencyStats, TopicKey};
+
+/// # Bus Metrics
+///
+/// Counters kept by the event bus, grouped by event name.
+#[derive(Debug, Clone)]
+pub struct BusMetrics<K: TopicKey = String> {
+    pub(crate) topics: HashMap<K, TopicMetrics>,
+    /// The number of failures per event name and subscriber name.
+    pub(crate) subscriber_errors: HashMap<(K, String), u64>,
+    /// The number of completed publish cycles.
+    pub(crate) publishes: u64,
+    /// The time spent in the publish cycles, on the clock of the event bus.
+    pub(crate) publish_time: Duration,
+    /// The number of events forwarded per route, by the pattern and target of the route.
+    pub(crate) route_hits: HashMap<(String, String), u64>,
+    /// The event names that had counters when they were reset, so they are not seen as new event names.
+    reset_topics: HashSet<K>,
+}
+
+impl<K: TopicKey> Default for BusMetrics<K> {
+    fn default() -> Self {
+        BusMetrics {
+            topics: HashMap::new(),
+            subscriber_errors: HashMap::new(),
+            publishes: 0,
+            publish_time: Duration::ZERO,
+            route_hits: HashMap::new(),
+            reset_topics: HashSet::new(),
+        }
+    }
+}
+
+impl<K: TopicKey> BusMetrics<K> {
+    /// # Topic
+    ///
+    /// Returns the counters of an event name, all zero if nothing happened for it yet.
+    pub fn topic(&self, event_name: &K) -> TopicMetrics {
+        self.topics.get(event_name).copied().unwrap_or_default()
+    }
+
+    /// # Topics
+    ///
+    /// Returns the counters of every event name something happened for.
+    pub fn topics(&self) -> impl Iterator<Item = (&K, &TopicMetrics)> {
+        self.topics.iter()
+    }
+
+    /// # Subscriber Errors
+    ///
+    /// Returns the number of failures of the subscribers with the name, of an event name.
+    pub fn subscriber_errors(&self, event_name: &K, subscriber: &str) -> u64 {
+        self.subscriber_errors.iter()
+            .find(|((topic, name), _)| topic == event_name && name == subscriber)
+            .map_or(0, |(_, errors)| *errors)
+    }
+
+    /// # All Subscriber Errors
+    ///
+    /// Returns the number of failures of every event name and subscriber name that failed.
+    pub fn all_subscriber_errors(&self) -> impl Iterator<Item = (&K, &str, u64)> {
+        self.subscriber_errors.iter().map(|((topic, subscriber), errors)| (topic, subscriber.as_str(), *errors))
+    }
+
+    /// # Publishes
+    ///
+    /// Returns the number of publish cycles, including the ones that stopped at an error.
+    pub fn publishes(&self) -> u64 {
+        self.publishes
+    }
+
+    /// # Publish Time
+    ///
+    /// Returns the total time spent publishing, on the clock of the event bus.
+    pub fn publish_time(&self) -> Duration {
+        self.publish_time
+    }
+
+    /// # Route Hits
+    ///
+    /// Returns the number of events the route from the event name or pattern to the event name forwarded.
+    pub fn route_hits(&self, from: &str, to: &str) -> u64 {
+        self.route_hits.get(&(from.to_string(), to.to_string())).copied().unwrap_or(0)
+    }
+
+    pub(crate) fn record_subscriber_error(&mut self, event_name: &K, subscriber: &str) {
+        *self.subscriber_errors.entry((event_name.clone(), subscriber.to_string())).or_default() += 1;
+    }
+
+    pub(crate) fn record_route_hits(&mut self, from: &str, to: &str, hits: usize) {
+        *self.route_hits.entry((from.to_string(), to.to_string())).or_default() += hits as u64;
+    }
+
+    pub(crate) fn record_publish(&mut self, elapsed: Duration) {
+        self.publishes += 1;
+        self.publish_time += elapsed;
+    }
+
+    /// Returns whether the event name had events registered before the counters were reset.
+    pub(crate) fn registered_before_reset(&self, event_name: &K) -> bool {
+        self.reset_topics.contains(event_name)
+    }
+
+    /// Sets every counter to zero.
+    pub(crate) fn reset(&mut self) {
+        let topics = std::mem::take(&mut self.topics);
+        self.reset_topics.extend(topics.into_iter()
+            .filter(|(_, metrics)| metrics.registered > 0)
+            .map(|(event_name, _)| event_name));
+        self.subscriber_errors.clear();
+        self.route_hits.clear();
+        self.publishes = 0;
+        self.publish_time = Duration::ZERO;
+    }
+
+    pub(crate) fn topic_mut(&mut self, event_name: &K) -> &mut TopicMetrics {
+        if !self.topics.contains_key(event_name) {
+            self.topics.insert(event_name.clone(), TopicMetrics::default());
+        }
+        self.topics.get_mut(event_name).unwrap()
+    }
+}
+
+/// # Topic Metrics
+///
+/// Counters of a single event name.
+///
+/// ## Fields
+///
+/// * `registered` - The number of events registered.
+///
+/// * `delivered` - The number of events handled by at least one subscriber.
+///
+/// * `failed` - The number of events a subscriber returned an error for, and no other subscriber handled.
+///
+/// * `deduplicated` - The number of events dropped because an equal event was already queued.
+///
+/// * `coalesced` - The number of queued events replaced by a later event.
+///
+/// * `dropped` - The number of events dropped because they exceeded the rate limit.
+///
+/// * `latency` - How long the dispatched events were queued.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TopicMetrics {
+    /// The number of events registered.
+    pub registered: u64,
+    /// The number of events handled by at least one subscriber.
+    pub delivered: u64,
+    /// The number of events a subscriber returned an error for, and no other subscriber handled.
+    pub failed: u64,
+    /// The number of events dropped because an equal event was already queued.
+    pub deduplicated: u64,
+    /// The number of queued events replaced by a later event.
+    pub coalesced: u64,
+    /// The number of events dropped because they exceeded the rate limit.
+    pub dropped: u64,
+    /// How long the dispatched events were queued.
+    pub latency: LatencyStats,
+}
@@ -1,7 +1,144 @@
+mod access;
+mod breaker;
+mod builder;
+mod cancel;
+mod child;
+mod clock;
+mod clone;
+mod collect;
+mod context;
+mod convert;
+mod data;
+#[cfg(feature = "threaded")]
+mod dedicated;
+mod dead_letter;
+mod debug;
+mod dedupe;
+mod depth;
+mod dispatch;
+mod dot;
 mod event;
 mod event_bus;
+mod executor;
+#[cfg(feature = "metrics-export")]
+mod exposition;
+mod failure;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod handle;
+mod heartbeat;
+mod idle;
+#[cfg(feature = "threaded")]
+mod ingest;
+#[cfg(feature = "wasm")]
+mod js;
+#[cfg(feature = "serde")]
+mod json;
+mod journal;
+mod latency;
+mod lazy;
+mod memory;
+mod meta;
+mod logger;
+mod metrics;
+#[cfg(feature = "net")]
+mod net;
+mod normalize;
+mod ordering;
+mod outcome;
+#[cfg(feature = "threaded")]
+mod partition;
+mod parts;
+mod pattern;
+mod payload;
+mod plan;
+#[cfg(feature = "plugins")]
+mod plugin;
+mod policy;
+mod priority;
+mod publisher;
+mod pump;
+mod report;
+mod route;
+mod router;
+mod run;
+mod schedule;
+#[cfg(feature = "signals")]
+mod signal;
+mod sink;
+mod state;
+mod stats;
+#[cfg(feature = "stream")]
+mod stream;
 mod subscriber;
+mod subscription;
+mod topic;
+mod trace;
+mod typed;
+#[cfg(feature = "uds")]
+mod uds;
+mod upgrade;
+mod validate;
+mod watchdog;
+mod window;
+mod worker;
 
-pub use event::Event;
-pub use event_bus::EventBus;
-pub use subscriber::Subscriber;
+pub use access::TopicPolicy;
+pub use breaker::{BreakerState, CircuitBreaker};
+pub use builder::SubscriberBuilder;
+pub use cancel::{CancelToken, PublishStatus};
+pub use clock::{Clock, Instant, SystemClock};
+pub use clone::{CloneRegistry, NotCloneable};
+pub use context::EventContext;
+pub(crate) use data::EventData;
+pub use dead_letter::{DeadLetter, DeadLetterReason};
+pub use debug::DebugSession;
+pub use depth::QueueDepthAlert;
+pub use dispatch::StepInfo;
+pub use event::{Event, IntoEvent, PayloadTypeError};
+pub use event_bus::{AlreadyPublishing, EventBus};
+pub use executor::{SimulatedExecutor, TaskId};
+pub use failure::{Phase, SubscriberFailure};
+pub use handle::{DuplicateSubscriber, SubscriptionHandle, UnknownHandle};
+pub use heartbeat::Heartbeat;
+#[cfg(feature = "threaded")]
+pub use ingest::ThreadSink;
+pub use journal::{ReplayFilter, ReplayOptions, ReplayReport};
+pub use latency::LatencyStats;
+pub use lazy::SubscriberState;
+pub use memory::MemoryFootprint;
+#[cfg(feature = "log")]
+pub use logger::LogLogger;
+pub use logger::{BusLogger, NullLogger};
+pub use meta::{BreakerStateChanged, DeadLettered, PublishCompleted, SubscriberAdded, SubscriberRemoved, TopicFirstEvent};
+pub use metrics::{BusMetrics, TopicMetrics};
+#[cfg(feature = "net")]
+pub use net::{RemotePublisher, RemoteSource};
+#[cfg(feature = "uds")]
+pub use uds::UdsPublisher;
+pub use outcome::Outcome;
+pub use pattern::InvalidPattern;
+pub use payload::{PayloadError, PayloadRegistry, RawPayload};
+#[cfg(feature = "plugins")]
+pub use plugin::{PluginEntry, PluginError, PluginHandle, PluginVTable, PLUGIN_ABI_VERSION};
+pub use plan::{PlannedDelivery, RoutingPlan, SkipReason};
+pub use schedule::{ScheduleId, ScheduleInfo};
+pub use pump::{Budget, PublishOutcome, PumpResult, RemainingWork};
+pub use priority::Prioritized;
+pub use publisher::{PermissionDenied, Publisher, TopicInspector};
+pub use policy::{BeforeFailure, DeferMode, DispatchOrder, ErrorPolicy, ValidationMode};
+pub use report::{PublishReport, TopicReport};
+pub use route::{RouteError, RouteMode, TopicRoute};
+pub use router::{BusRouter, UnroutableEvent};
+pub use run::{RunOptions, RunSummary, ShutdownSignal};
+#[cfg(feature = "signals")]
+pub use signal::{Signal, SignalError};
+pub use sink::EventSink;
+pub use stats::BusStatsSnapshot;
+#[cfg(feature = "stream")]
+pub use stream::{Backpressure, EventStream, StreamedEvent};
+pub use subscriber::{ReadOnlySubscriber, Subscriber};
+pub use typed::BusEvent;
+pub use topic::{CapacityOverflow, OverflowAction, RegisterError, TopicKey, TopicMode, UnknownTopic};
+pub use trace::TraceEntry;
+pub use watchdog::{SilentSubscription, TopicWatchReport, WatchdogPeriod};
@@ -0,0 +1,377 @@
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use super::{BusLogger, DeadLetterReason, Event, EventBus, RawPayload, TopicKey};
+use super::state::BusState;
+
+type Reconnect = Box<dyn FnMut() -> io::Result<TcpStream>>;
+
+/// A remote publisher, with the matcher returning the name to export an event name under.
+pub(crate) type RemoteExport<K> = (Box<dyn Fn(&K) -> Option<String>>, RemotePublisher);
+
+/// A remote source, with the conversion of the received names into event names.
+pub(crate) type AttachedSource<K> = (RemoteSource, fn(String) -> K);
+
+/// # Remote Publisher
+///
+/// Writes the events of exported topics to a TCP stream,
+/// so a `RemoteSource` in another process can register them into its own event bus.
+/// Each event is written as a length-prefixed frame holding the event name,
+/// the payload name from the payload registry, the schema version and the encoded payload.
+/// Events whose frame would exceed 16 MiB, or whose names exceed 64 KiB, are not exported, which is logged.
+///
+/// ## Methods
+///
+/// * `new` - Creates a remote publisher writing to a stream.
+///
+/// * `on_reconnect` - Sets the hook that opens a new stream when writing fails.
+pub struct RemotePublisher {
+    stream: Option<TcpStream>,
+    reconnect: Option<Reconnect>,
+}
+
+impl RemotePublisher {
+    /// # New
+    ///
+    /// Creates a remote publisher writing to a stream.
+    pub fn new(stream: TcpStream) -> RemotePublisher {
+        RemotePublisher { stream: Some(stream), reconnect: None }
+    }
+
+    /// # On Reconnect
+    ///
+    /// Sets the hook that opens a new stream when writing to the current one fails.
+    pub fn on_reconnect(mut self, reconnect: impl FnMut() -> io::Result<TcpStream> + 'static) -> RemotePublisher {
+        self.reconnect = Some(Box::new(reconnect));
+        self
+    }
+
+    pub(crate) fn send(&mut self, frame: &[u8], logger: &dyn BusLogger) -> io::Result<()> {
+        if let Some(stream) = &mut self.stream {
+            match stream.write_all(frame) {
+                Ok(()) => return Ok(()),
+                Err(e) => logger.remote_error(&format!("Remote publisher write failed: {}", e)),
+            }
+        }
+        self.stream = None;
+        let reconnect = self.reconnect.as_mut()
+            .ok_or_else(|| io::Error::new(ErrorKind::NotConnected, "Remote publisher is disconnected"))?;
+        let mut stream = reconnect()?;
+        stream.write_all(frame)?;
+        self.stream = Some(stream);
+        Ok(())
+    }
+}
+
+impl From<TcpStream> for RemotePublisher {
+    fn from(stream: TcpStream) -> Self {
+        RemotePublisher::new(stream)
+    }
+}
+
+/// # Remote Source
+///
+/// Reads the frames written by a `RemotePublisher`, either from one stream
+/// or from every connection accepted by a listener.
+/// Reading never blocks: whatever has arrived is registered when the event bus publishes.
+/// A connection declaring a frame longer than 16 MiB is dropped, which is logged.
+pub struct RemoteSource {
+    listener: Option<Box<dyn Accept>>,
+    connections: Vec<(Box<dyn Read>, FrameReader)>,
+}
+
+/// A listener whose connections are read by a remote source.
+pub(crate) trait Accept {
+    /// Accepts a connection, set to not block on reads, or fails with `WouldBlock` when none is waiting.
+    fn accept_nonblocking(&self) -> io::Result<Box<dyn Read>>;
+}
+
+impl Accept for TcpListener {
+    fn accept_nonblocking(&self) -> io::Result<Box<dyn Read>> {
+        let (stream, _) = self.accept()?;
+        stream.set_nonblocking(true)?;
+        Ok(Box::new(stream))
+    }
+}
+
+impl RemoteSource {
+    pub(crate) fn listening(listener: impl Accept + 'static) -> RemoteSource {
+        RemoteSource { listener: Some(Box::new(listener)), connections: Vec::new() }
+    }
+
+    pub(crate) fn reading(stream: impl Read + 'static) -> RemoteSource {
+        RemoteSource { listener: None, connections: vec![(Box::new(stream), FrameReader::default())] }
+    }
+
+    /// Returns the frames that arrived, and the errors of the connections.
+    fn poll(&mut self) -> Vec<Result<Frame, String>> {
+        let mut frames = Vec::new();
+        if let Some(listener) = &self.listener {
+            loop {
+                match listener.accept_nonblocking() {
+                    Ok(stream) => self.connections.push((stream, FrameReader::default())),
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                    Err(e) => {
+                        frames.push(Err(format!("Remote source accept failed: {}", e)));
+                        break;
+                    }
+                }
+            }
+        }
+
+        let mut buffer = [0u8; 4096];
+        self.connections.retain_mut(|(stream, reader)| {
+            // The frames read before the connection closed are still registered.
+            // Complete frames are taken after every read, so at most one frame is buffered.
+            let open = loop {
+                match stream.read(&mut buffer) {
+                    Ok(0) => break false,
+                    Ok(read) => reader.push(&buffer[..read]),
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => break true,
+                    Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                    Err(e) => {
+                        frames.push(Err(format!("Remote source read failed: {}", e)));
+                        return false;
+                    }
+                }
+                while let Some(frame) = reader.next_frame() {
+                    frames.push(frame.map_err(|e| format!("Remote source received an invalid frame: {}", e)));
+                }
+                if reader.oversized {
+                    return false;
+                }
+            };
+            if !open && !reader.buffer.is_empty() {
+                frames.push(Err("Remote source connection closed in the middle of a frame".to_string()));
+            }
+            open
+        });
+        frames
+    }
+}
+
+impl TryFrom<TcpStream> for RemoteSource {
+    type Error = io::Error;
+
+    fn try_from(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        Ok(RemoteSource::reading(stream))
+    }
+}
+
+impl TryFrom<TcpListener> for RemoteSource {
+    type Error = io::Error;
+
+    fn try_from(listener: TcpListener) -> io::Result<Self> {
+        listener.set_nonblocking(true)?;
+        Ok(RemoteSource::listening(listener))
+    }
+}
+
+/// The longest frame body a remote source accepts, and a remote publisher writes: 16 MiB.
+pub(crate) const MAX_FRAME_LENGTH: usize = 16 * 1024 * 1024;
+
+pub(crate) struct Frame {
+    topic: String,
+    name: String,
+    schema_version: Option<u32>,
+    bytes: Vec<u8>,
+}
+
+impl Frame {
+    /// Returns the frame holding the event, or an error when the event name or payload name is longer
+    /// than its 16-bit length field, or the frame would exceed `MAX_FRAME_LENGTH`.
+    pub(crate) fn encode(topic: &str, name: &str, schema_version: Option<u32>, bytes: &[u8]) -> Result<Vec<u8>, String> {
+        let topic_length = u16::try_from(topic.len()).map_err(|_| format!("Topic of {} bytes is too long for a frame", topic.len()))?;
+        let name_length = u16::try_from(name.len()).map_err(|_| format!("Payload name of {} bytes is too long for a frame", name.len()))?;
+        let length = 2 + topic.len() + 2 + name.len() + 5 + bytes.len();
+        if length > MAX_FRAME_LENGTH {
+            return Err(format!("Frame of {} bytes exceeds the maximum of {} bytes", length, MAX_FRAME_LENGTH));
+        }
+        let mut frame = Vec::with_capacity(4 + length);
+        frame.extend_from_slice(&(length as u32).to_be_bytes());
+        frame.extend_from_slice(&topic_length.to_be_bytes());
+        frame.extend_from_slice(topic.as_bytes());
+        frame.extend_from_slice(&name_length.to_be_bytes());
+        frame.extend_from_slice(name.as_bytes());
+        frame.push(schema_version.is_some() as u8);
+        frame.extend_from_slice(&schema_version.unwrap_or(0).to_be_bytes());
+        frame.extend_from_slice(bytes);
+        Ok(frame)
+    }
+
+    fn decode(mut body: &[u8]) -> Result<Frame, String> {
+        fn take<'a>(body: &mut &'a [u8], length: usize) -> Result<&'a [u8], String> {
+            if body.len() < length {
+                return Err("Truncated frame".to_string());
+            }
+            let (taken, rest) = body.split_at(length);
+            *body = rest;
+            Ok(taken)
+        }
+        fn take_string(body: &mut &[u8]) -> Result<String, String> {
+            let length = u16::from_be_bytes(take(body, 2)?.try_into().unwrap()) as usize;
+            String::from_utf8(take(body, length)?.to_vec()).map_err(|e| e.to_string())
+        }
+
+        let topic = take_string(&mut body)?;
+        let name = take_string(&mut body)?;
+        let has_version = take(&mut body, 1)?[0] == 1;
+        let version = u32::from_be_bytes(take(&mut body, 4)?.try_into().unwrap());
+        Ok(Frame { topic, name, schema_version: has_version.then_some(version), bytes: body.to_vec() })
+    }
+}
+
+/// Collects partial reads until complete frames are available.
+#[derive(Default)]
+pub(crate) struct FrameReader {
+    buffer: Vec<u8>,
+    /// Set when the peer declared a frame longer than `MAX_FRAME_LENGTH`, after which the connection is dropped.
+    oversized: bool,
+}
+
+impl FrameReader {
+    fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    fn next_frame(&mut self) -> Option<Result<Frame, String>> {
+        if self.oversized {
+            return None;
+        }
+        let length = u32::from_be_bytes(self.buffer.get(..4)?.try_into().unwrap()) as usize;
+        if length > MAX_FRAME_LENGTH {
+            self.oversized = true;
+            self.buffer.clear();
+            return Some(Err(format!("Frame of {} bytes exceeds the maximum of {} bytes", length, MAX_FRAME_LENGTH)));
+        }
+        if self.buffer.len() < 4 + length {
+            return None;
+        }
+        let frame = Frame::decode(&self.buffer[4..4 + length]);
+        self.buffer.drain(..4 + length);
+        Some(frame)
+    }
+}
+
+pub(crate) fn matches_pattern(pattern: &str, topic: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => topic.starts_with(prefix),
+        None => pattern == topic,
+    }
+}
+
+impl EventBus {
+    /// # Export Topics
+    ///
+    /// Writes every published event whose name matches one of the patterns to a remote publisher.
+    /// A pattern either is an exact event name, or ends with `*` to match every event name with that prefix.
+    /// Only events with a payload type in the payload registry can be exported.
+    pub fn export_topics(&self, patterns: &[&str], publisher: impl Into<RemotePublisher>) -> &Self {
+        let patterns: Vec<String> = patterns.iter().map(|pattern| pattern.to_string()).collect();
+        let matcher = move |topic: &String| {
+            patterns.iter().any(|pattern| matches_pattern(pattern, topic)).then(|| topic.clone())
+        };
+        self.state.borrow_mut().remote_exports.push((Box::new(matcher), publisher.into()));
+        self
+    }
+
+    /// # Attach Remote Source
+    ///
+    /// Registers the events read from a remote source each time the event bus publishes.
+    /// Events with an unknown payload type are moved to the dead-letter queue, holding the `RawPayload`.
+    pub fn attach_remote_source(&self, source: RemoteSource) -> &Self {
+        self.state.borrow_mut().remote_sources.push((source, |topic| topic));
+        self
+    }
+}
+
+impl<K: TopicKey> BusState<K> {
+    /// Returns the remote publishers the events with the event name are exported to,
+    /// together with the event name as it is written to the frames.
+    pub(crate) fn remote_exports_for(&self, event_name: &K) -> Vec<(usize, String)> {
+        self.remote_exports.iter().enumerate()
+            .filter_map(|(index, (matcher, _))| matcher(event_name).map(|topic| (index, topic)))
+            .collect()
+    }
+
+    pub(crate) fn export_remote(&mut self, exports: &[(usize, String)], message: &Event) {
+        if exports.is_empty() {
+            return;
+        }
+        let (name, bytes) = match self.payloads.encode(message) {
+            Some(encoded) => encoded,
+            None => {
+                self.logger.remote_error(&format!("Cannot export {:?}, its payload type is not registered", message));
+                return;
+            }
+        };
+        for (index, topic) in exports {
+            let frame = match Frame::encode(topic, name, message.schema_version(), &bytes) {
+                Ok(frame) => frame,
+                Err(e) => {
+                    self.logger.remote_error(&format!("Cannot export '{}': {}", topic, e));
+                    continue;
+                }
+            };
+            if let Err(e) = self.remote_exports[*index].1.send(&frame, self.logger.as_ref()) {
+                self.logger.remote_error(&format!("Remote export of '{}' failed: {}", topic, e));
+            }
+        }
+    }
+
+    pub(crate) fn poll_remote_sources(&mut self) {
+        let mut frames = Vec::new();
+        for (source, into_topic) in self.remote_sources.iter_mut() {
+            frames.extend(source.poll().into_iter().map(|frame| (frame, *into_topic)));
+        }
+        for (frame, into_topic) in frames {
+            let frame = match frame {
+                Ok(frame) => frame,
+                Err(e) => {
+                    self.logger.remote_error(&e);
+                    continue;
+                }
+            };
+            let topic = self.normalize(into_topic(frame.topic.clone()));
+            match self.payloads.decode_versioned(&frame.name, frame.schema_version, &frame.bytes) {
+                Ok(event) => self.register(topic, event),
+                Err(e) => {
+                    self.logger.remote_error(&format!("Remote source cannot decode '{}': {}", frame.topic, e));
+                    let payload = RawPayload { name: frame.name, bytes: frame.bytes };
+                    self.dead_letter(&topic, Event::new(payload), DeadLetterReason::Payload(e));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use super::{Frame, FrameReader, RemoteSource, MAX_FRAME_LENGTH};
+
+    #[test]
+    fn test_encode_rejects_lengths_that_do_not_fit() {
+        let long_topic = "t".repeat(usize::from(u16::MAX) + 1);
+        assert!(Frame::encode(&long_topic, "u32", None, &[]).is_err());
+        assert!(Frame::encode("topic", &long_topic, None, &[]).is_err());
+        assert!(Frame::encode("topic", "bytes", None, &vec![0; MAX_FRAME_LENGTH]).is_err());
+        assert!(Frame::encode("topic", "u32", None, &[0; 4]).is_ok());
+    }
+
+    #[test]
+    fn test_oversized_frame_drops_the_connection() {
+        let mut reader = FrameReader::default();
+        reader.push(&((MAX_FRAME_LENGTH + 1) as u32).to_be_bytes());
+        assert!(matches!(reader.next_frame(), Some(Err(_))));
+        assert!(reader.next_frame().is_none());
+
+        let mut bytes = ((MAX_FRAME_LENGTH + 1) as u32).to_be_bytes().to_vec();
+        bytes.extend(Frame::encode("topic", "u32", None, &[0; 4]).unwrap());
+        let mut source = RemoteSource::reading(Cursor::new(bytes));
+        let frames = source.poll();
+        assert_eq!(1, frames.len());
+        assert!(frames[0].is_err());
+        assert!(source.connections.is_empty());
+    }
+}
@@ -0,0 +1,92 @@
+use std::rc::Rc;
+use super::{EventBus, TopicKey};
+use super::state::BusState;
+use super::topic::{topic_from_str, topic_str};
+
+pub(crate) type Normalizer = Rc<dyn Fn(&str) -> String>;
+
+/// Returns the event name rewritten by the normalizer, when the event bus uses `String` event names and has one.
+pub(crate) fn normalized<K: TopicKey>(normalizer: Option<&Normalizer>, event_name: K) -> K {
+    match normalizer {
+        Some(normalizer) => topic_str(&event_name)
+            .and_then(|name| topic_from_str(&normalizer(name)))
+            .unwrap_or(event_name),
+        None => event_name,
+    }
+}
+
+impl<K: TopicKey> BusState<K> {
+    /// Returns the event name rewritten by the normalizer of the event bus.
+    pub(crate) fn normalize(&self, event_name: K) -> K {
+        normalized(self.normalizer.as_ref(), event_name)
+    }
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// Returns the event name passed to the event bus, rewritten by its normalizer.
+    /// Each public method normalizes its event names once, before the state of the event bus is borrowed.
+    pub(crate) fn topic_key(&self, event_name: impl Into<K>) -> K {
+        self.state.borrow().normalize(event_name.into())
+    }
+}
+
+impl EventBus {
+    /// # Set Topic Normalizer
+    ///
+    /// Rewrites every event name passed to the event bus before it is used, so `"Orders"` and `" orders"`
+    /// can name the same event name. It applies to registering, subscribing, wildcard patterns, the settings
+    /// of event names, the introspection methods, sinks and remote sources, and runs once per call,
+    /// before the event name is resolved any further. The event names already in use are not rewritten,
+    /// so set it before subscribing. See `normalizers` for the normalizers that ship with the event bus.
+    /// The normalizer must not use the event bus.
+    pub fn set_topic_normalizer(&self, normalizer: impl Fn(&str) -> String + 'static) -> &Self {
+        self.state.borrow_mut().normalizer = Some(Rc::new(normalizer));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use crate::{EventBus, Subscriber};
+    use crate::normalizers::lowercase_trim;
+    use crate::subscribers::CollectingSubscriber;
+
+    #[test]
+    fn test_normalizer_matches_different_casings() {
+        let event_bus = EventBus::new();
+        let (collector, received) = CollectingSubscriber::<u32>::new();
+        let (pattern_collector, matched) = CollectingSubscriber::<u32>::new();
+        event_bus
+            .set_topic_normalizer(lowercase_trim)
+            .subscribe_listener("Orders", collector)
+            .subscribe_pattern("SENSORS/#", pattern_collector)
+            .unwrap()
+            .register(" orders ", 1u32)
+            .register("ORDERS", 2u32)
+            .register("Sensors/Kitchen", 3u32);
+
+        assert_eq!(2, event_bus.pending(&"Orders".to_string()));
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![1, 2], *received.borrow());
+        assert_eq!(vec![3], *matched.borrow());
+    }
+
+    #[test]
+    fn test_normalizer_runs_once_per_call() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let counted = calls.clone();
+        let event_bus = EventBus::new();
+        event_bus
+            .set_topic_normalizer(move |topic| {
+                counted.borrow_mut().push(topic.to_string());
+                format!("{}!", topic)
+            })
+            .subscribe_listener("ping", <dyn Subscriber>::builder().on_event(|_| Ok(())).build())
+            .register("ping", ());
+
+        assert_eq!(vec!["ping", "ping"], *calls.borrow());
+        assert_eq!(1, event_bus.pending(&"ping".to_string()));
+    }
+}
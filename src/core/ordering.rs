@@ -0,0 +1,95 @@
+use super::subscription::Subscription;
+
+/// Returns the order the subscriptions run in, so each runs after the subscribers it depends on,
+/// keeping the subscription order where no dependency decides.
+/// Dependencies on names of subscribers that did not subscribe are ignored.
+/// Returns the names of the subscribers that depend on each other in a cycle, when there is one.
+pub(crate) fn dependency_order(nodes: &[(&str, &[String])]) -> Result<Vec<usize>, Vec<String>> {
+    let depends_on = |node: usize, other: usize| node != other && nodes[node].1.iter().any(|name| name == nodes[other].0);
+    let mut order = Vec::with_capacity(nodes.len());
+    let mut placed = vec![false; nodes.len()];
+    while order.len() < nodes.len() {
+        let next = (0..nodes.len())
+            .find(|&node| !placed[node] && (0..nodes.len()).all(|other| placed[other] || !depends_on(node, other)));
+        match next {
+            Some(node) => {
+                placed[node] = true;
+                order.push(node);
+            }
+            None => {
+                return Err((0..nodes.len()).filter(|&node| !placed[node]).map(|node| nodes[node].0.to_string()).collect());
+            }
+        }
+    }
+    Ok(order)
+}
+
+/// Sorts the subscriptions of an event name by their dependencies,
+/// or leaves them in subscription order when they depend on each other in a cycle.
+pub(crate) fn sort_subscriptions(subscriptions: &mut Vec<Subscription>) -> Result<(), Vec<String>> {
+    if subscriptions.iter().all(|subscription| subscription.after.is_empty()) {
+        return Ok(());
+    }
+    let nodes: Vec<(&str, &[String])> = subscriptions.iter()
+        .map(|subscription| (subscription.listener.name(), subscription.after.as_slice()))
+        .collect();
+    let order = dependency_order(&nodes)?;
+    let mut taken: Vec<Option<Subscription>> = subscriptions.drain(..).map(Some).collect();
+    subscriptions.extend(order.into_iter().map(|index| taken[index].take().unwrap()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use crate::{Event, EventBus, Subscriber};
+
+    struct NamedSubscriber {
+        name: &'static str,
+        log: Rc<RefCell<Vec<&'static str>>>,
+    }
+
+    impl Subscriber for NamedSubscriber {
+        fn on_event(&mut self, _event: &mut Event) -> Result<(), String> {
+            self.log.borrow_mut().push(self.name);
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            self.name
+        }
+    }
+
+    #[test]
+    fn test_chain_runs_in_dependency_order() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let named = |name| NamedSubscriber { name, log: log.clone() };
+        let event_bus = EventBus::new();
+        event_bus
+            .subscribe_listener_after("order_saved", named("CacheInvalidator"), &["DbWriter"]).unwrap()
+            .subscribe_listener("order_saved", named("Metrics"))
+            .subscribe_listener_after("order_saved", named("DbWriter"), &["Validator"]).unwrap()
+            .subscribe_listener("order_saved", named("Validator"))
+            .register("order_saved", 1u32);
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec!["Metrics", "Validator", "DbWriter", "CacheInvalidator"], *log.borrow());
+    }
+
+    #[test]
+    fn test_cycle_is_reported() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let named = |name| NamedSubscriber { name, log: log.clone() };
+        let event_bus = EventBus::new();
+        event_bus.subscribe_listener_after("order_saved", named("DbWriter"), &["CacheInvalidator"]).unwrap();
+
+        let result = event_bus.subscribe_listener_after("order_saved", named("CacheInvalidator"), &["DbWriter"]);
+        assert_eq!(
+            Some("Subscribing CacheInvalidator to \"order_saved\" creates a dependency cycle among DbWriter, CacheInvalidator".to_string()),
+            result.err()
+        );
+        event_bus.register("order_saved", 1u32).publish().unwrap();
+        assert_eq!(vec!["DbWriter"], *log.borrow());
+    }
+}
@@ -0,0 +1,50 @@
+/// # Outcome
+///
+/// What happened when a subscriber received an event, returned by `Subscriber::on_event_outcome`.
+///
+/// ## Variants
+///
+/// * `Ack` - The subscriber handled the event.
+///
+/// * `AckAndUnsubscribe` - The subscriber handled the event, and does not want to receive
+///   any more events. The subscription is removed once every subscriber received the event.
+///
+/// * `Stop` - The subscriber handled the event, and consumed it. The remaining subscribers
+///   do not receive the on_event, nor the on_after of the event.
+///
+/// * `Ignored` - The subscriber received the event, but had nothing to do with it.
+///   An event ignored by all of its subscribers is dead-lettered as unhandled.
+///
+/// * `Nack` - The subscriber cannot handle the event. With `requeue` the event is queued again
+///   for a future publish, without counting as an error. Without it, the event is dead-lettered.
+///
+/// * `Defer` - The subscriber cannot handle the event yet. The event is queued again at the back of its event name
+///   for the next publish, without counting as an error or a redelivery, see `EventBus::set_defer_mode`.
+///
+/// * `Error` - The subscriber failed, handled like an `Err` of the other methods.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The subscriber handled the event.
+    Ack,
+    /// The subscriber handled the event, and unsubscribes itself.
+    AckAndUnsubscribe,
+    /// The subscriber handled the event, and stops its propagation to the remaining subscribers.
+    Stop,
+    /// The subscriber had nothing to do with the event.
+    Ignored,
+    /// The subscriber cannot handle the event.
+    Nack { requeue: bool },
+    /// The subscriber cannot handle the event yet.
+    Defer,
+    /// The subscriber failed.
+    Error(String),
+}
+
+impl From<Result<(), String>> for Outcome {
+    fn from(result: Result<(), String>) -> Self {
+        match result {
+            Ok(()) => Outcome::Ack,
+            Err(message) => Outcome::Error(message),
+        }
+    }
+}
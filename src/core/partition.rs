@@ -0,0 +1,304 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use super::{ErrorPolicy, Event, EventBus, Phase, Subscriber, TopicKey};
+
+/// Returns the key an event is partitioned by.
+pub(crate) type Partitioner = Box<dyn Fn(&Event) -> u64>;
+
+/// A failure of a partitioned subscriber, reported once its workers finished.
+pub(crate) struct PartitionFailure {
+    subscriber: String,
+    phase: Phase,
+    message: String,
+    event_id: u64,
+}
+
+/// A subscriber that is cloned onto each worker of a partitioned publish.
+pub(crate) trait PartitionedListener {
+    /// Delivers the events of each shard on its own worker, in the order of the shard.
+    fn deliver(&self, shards: &[Vec<&Event>]) -> Vec<PartitionFailure>;
+}
+
+/// Clones the payloads of type `T` out of the events, so only the payloads are shared with the workers.
+struct Partitioned<T, R> {
+    listener: R,
+    /// The number of clones of the listener that are called at the same time at most.
+    max_in_flight: Option<usize>,
+    payload: std::marker::PhantomData<fn() -> T>,
+}
+
+/// Limits the number of calls that are in flight at the same time, over the workers.
+struct InFlight {
+    max: usize,
+    calls: Mutex<usize>,
+    finished: Condvar,
+}
+
+impl InFlight {
+    fn new(max: usize) -> InFlight {
+        InFlight { max: max.max(1), calls: Mutex::new(0), finished: Condvar::new() }
+    }
+
+    /// Waits until fewer calls than the limit are in flight, and counts the call until the guard is dropped,
+    /// also when the listener panics.
+    fn acquire(&self) -> InFlightCall<'_> {
+        let calls = self.calls.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut calls = self.finished.wait_while(calls, |calls| *calls >= self.max)
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        *calls += 1;
+        InFlightCall(self)
+    }
+}
+
+struct InFlightCall<'a>(&'a InFlight);
+
+impl Drop for InFlightCall<'_> {
+    fn drop(&mut self) {
+        *self.0.calls.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) -= 1;
+        self.0.finished.notify_one();
+    }
+}
+
+impl<T, R> PartitionedListener for Partitioned<T, R>
+where
+    T: Clone + Send + 'static,
+    R: Subscriber + Clone + Send + 'static,
+{
+    fn deliver(&self, shards: &[Vec<&Event>]) -> Vec<PartitionFailure> {
+        let shards: Vec<Vec<(T, u64)>> = shards.iter()
+            .map(|shard| shard.iter()
+                .filter_map(|event| event.get_data::<T>().map(|data| (data.clone(), event.id())))
+                .collect())
+            .collect();
+        let in_flight = self.max_in_flight.map(InFlight::new);
+        thread::scope(|scope| {
+            let workers: Vec<_> = shards.into_iter()
+                .filter(|shard| !shard.is_empty())
+                .map(|shard| {
+                    let listener = self.listener.clone();
+                    let in_flight = in_flight.as_ref();
+                    scope.spawn(move || run_shard(listener, shard, in_flight))
+                })
+                .collect();
+            workers.into_iter()
+                .flat_map(|worker| worker.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+                .collect()
+        })
+    }
+}
+
+/// Delivers the payloads of a shard to a clone of the listener, one after the other,
+/// waiting for the in-flight limit of the listener before each event.
+fn run_shard<T: 'static, R: Subscriber>(mut listener: R, shard: Vec<(T, u64)>, in_flight: Option<&InFlight>) -> Vec<PartitionFailure> {
+    let mut failures = Vec::new();
+    for (data, event_id) in shard {
+        let mut event = Event::new(data);
+        let call = in_flight.map(InFlight::acquire);
+        let result = listener.on_before(&mut event).map_err(|e| (Phase::Before, e))
+            .and_then(|_| listener.on_event(&mut event).map_err(|e| (Phase::Event, e)))
+            .and_then(|_| listener.on_after(&event).map_err(|e| (Phase::After, e)));
+        drop(call);
+        if let Err((phase, message)) = result {
+            failures.push(PartitionFailure { subscriber: listener.name().to_string(), phase, message, event_id });
+        }
+    }
+    failures
+}
+
+/// Returns the worker the events with the key are delivered on.
+fn shard_of(key: u64, num_workers: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    (hasher.finish() % num_workers as u64) as usize
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Set Partitioner
+    ///
+    /// Sets the key the events of the event name are partitioned by in `publish_partitioned`,
+    /// like the id of the player an event is about. Events with the same key are delivered in order
+    /// on the same worker, events with different keys can be delivered in parallel.
+    /// Without a partitioner all events of the event name are delivered on a single worker.
+    pub fn set_partitioner(&self, event_name: impl Into<K>, partitioner: impl Fn(&Event) -> u64 + 'static) -> &Self {
+        let event_name = self.topic_key(event_name);
+        self.state.borrow_mut().partitioners.insert(event_name, Box::new(partitioner));
+        self
+    }
+
+    /// # Subscribe Partitioned
+    ///
+    /// Subscribes a listener that `publish_partitioned` delivers the events with a payload of type `T` to,
+    /// in parallel. Each worker receives its own clone of the listener, so the clones do not share state
+    /// unless the listener holds it behind an `Arc<Mutex<_>>`. A worker receives a new event holding
+    /// a clone of the payload. `publish` does not deliver events to partitioned subscribers.
+    pub fn subscribe_partitioned<T, R>(&self, event_name: impl Into<K>, listener: R) -> &Self
+    where
+        T: Clone + Send + 'static,
+        R: Subscriber + Clone + Send + 'static,
+    {
+        self.subscribe_partitioned_listener::<T, R>(event_name, listener, None)
+    }
+
+    /// # Subscribe Partitioned With Limit
+    ///
+    /// Subscribes a listener like `subscribe_partitioned`, of which at most `max_in_flight` clones
+    /// handle an event at the same time, like a subscriber calling an external API. The workers wait
+    /// for the limit before each event, so their events stay in order, while the other partitioned
+    /// subscribers of the event name keep their own parallelism. A limit of 0 is taken as 1.
+    pub fn subscribe_partitioned_with_limit<T, R>(&self, event_name: impl Into<K>, listener: R, max_in_flight: usize) -> &Self
+    where
+        T: Clone + Send + 'static,
+        R: Subscriber + Clone + Send + 'static,
+    {
+        self.subscribe_partitioned_listener::<T, R>(event_name, listener, Some(max_in_flight))
+    }
+
+    fn subscribe_partitioned_listener<T, R>(&self, event_name: impl Into<K>, listener: R, max_in_flight: Option<usize>) -> &Self
+    where
+        T: Clone + Send + 'static,
+        R: Subscriber + Clone + Send + 'static,
+    {
+        let listener = Partitioned::<T, R> { listener, max_in_flight, payload: std::marker::PhantomData };
+        let event_name = self.topic_key(event_name);
+        self.state.borrow_mut().partitioned.entry(event_name).or_default().push(Box::new(listener));
+        self
+    }
+
+    /// # Publish Partitioned
+    ///
+    /// Delivers the queued events to the partitioned subscribers of their event name, sharding them
+    /// by the key of their partitioner over the workers, and waits for the workers to finish.
+    /// Then publishes the events to the other subscribers like `publish`.
+    ///
+    /// The failures of the partitioned subscribers are logged and routed once their workers finished.
+    /// Returns the first failure, without publishing, when the error policy of its event name aborts.
+    pub fn publish_partitioned(&self, num_workers: usize) -> Result<(), String> {
+        let num_workers = num_workers.max(1);
+        let (partitioned, partitioners, events) = {
+            let mut state = self.state.borrow_mut();
+            state.register_due();
+            (std::mem::take(&mut state.partitioned), std::mem::take(&mut state.partitioners), state.take_events())
+        };
+
+        let mut failures = Vec::new();
+        for (event_name, listeners) in &partitioned {
+            let Some(messages) = events.get(event_name) else {
+                continue;
+            };
+            let mut shards = vec![Vec::new(); num_workers];
+            for message in messages {
+                let key = partitioners.get(event_name).map_or(0, |partitioner| partitioner(message));
+                shards[shard_of(key, num_workers)].push(message);
+            }
+            for listener in listeners {
+                failures.extend(listener.deliver(&shards).into_iter().map(|failure| (event_name.clone(), failure)));
+            }
+        }
+
+        let mut state = self.state.borrow_mut();
+        state.partitioned = partitioned;
+        state.partitioners = partitioners;
+        state.requeue_unpublished(events.into_iter());
+        let mut result = Ok(());
+        for (event_name, failure) in failures {
+            state.logger.subscriber_error(&event_name, &failure.subscriber, failure.phase, &failure.message);
+            state.route_error(&event_name, &failure.subscriber, failure.phase, &failure.message, failure.event_id);
+            if result.is_ok() && state.error_policy_for(&event_name) == ErrorPolicy::Abort {
+                result = Err(failure.message);
+            }
+        }
+        drop(state);
+        result?;
+        self.publish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    use crate::{Event, EventBus, Subscriber};
+
+    #[derive(Clone)]
+    struct SlowSubscriber {
+        received: Arc<Mutex<Vec<(u32, u32)>>>,
+    }
+
+    impl Subscriber for SlowSubscriber {
+        fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+            let &(player, move_number) = event.try_get_data::<(u32, u32)>()?;
+            std::thread::sleep(Duration::from_millis(20));
+            self.received.lock().unwrap().push((player, move_number));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_partitioned_keeps_order_per_key() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .set_partitioner("moves", |event| event.get_data::<(u32, u32)>().map_or(0, |(player, _)| *player as u64))
+            .subscribe_partitioned::<(u32, u32), _>("moves", SlowSubscriber { received: received.clone() });
+        for move_number in 0..3u32 {
+            for player in 0..8u32 {
+                event_bus.register("moves", (player, move_number));
+            }
+        }
+
+        let started = Instant::now();
+        assert_eq!(Ok(()), event_bus.publish_partitioned(8));
+        assert!(started.elapsed() < Duration::from_millis(20 * 24));
+
+        let received = received.lock().unwrap();
+        assert_eq!(24, received.len());
+        for player in 0..8 {
+            let moves: Vec<u32> = received.iter().filter(|(p, _)| *p == player).map(|(_, n)| *n).collect();
+            assert_eq!(vec![0, 1, 2], moves);
+        }
+    }
+
+    /// Records the highest number of its clones handling an event at the same time.
+    #[derive(Clone)]
+    struct OverlapSubscriber {
+        running: Arc<AtomicUsize>,
+        most: Arc<AtomicUsize>,
+    }
+
+    impl OverlapSubscriber {
+        fn new() -> OverlapSubscriber {
+            OverlapSubscriber { running: Arc::new(AtomicUsize::new(0)), most: Arc::new(AtomicUsize::new(0)) }
+        }
+    }
+
+    impl Subscriber for OverlapSubscriber {
+        fn on_event(&mut self, _event: &mut Event) -> Result<(), String> {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.most.fetch_max(running, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(30));
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_in_flight_limit_serializes_only_its_subscriber() {
+        let limited = OverlapSubscriber::new();
+        let unlimited = OverlapSubscriber::new();
+        let event_bus = EventBus::new();
+        event_bus
+            .set_partitioner("api.calls", |event| event.get_data::<u64>().copied().unwrap_or_default())
+            .subscribe_partitioned_with_limit::<u64, _>("api.calls", limited.clone(), 1)
+            .subscribe_partitioned::<u64, _>("api.calls", unlimited.clone());
+        for key in 0..3u64 {
+            event_bus.register("api.calls", key);
+        }
+
+        assert_eq!(Ok(()), event_bus.publish_partitioned(3));
+        assert_eq!(1, limited.most.load(Ordering::SeqCst));
+        assert!(unlimited.most.load(Ordering::SeqCst) > 1);
+    }
+}
@@ -0,0 +1,134 @@
+use std::any::{type_name, Any};
+use super::{Event, PayloadTypeError};
+
+/// The data of an event holding several parts, each of its own type.
+struct Parts(Vec<Part>);
+
+struct Part {
+    value: Box<dyn Any>,
+    type_name: &'static str,
+}
+
+impl Part {
+    fn new<T: 'static>(value: T) -> Part {
+        Part { value: Box::new(value), type_name: type_name::<T>() }
+    }
+}
+
+impl Event {
+    /// # New 2
+    ///
+    /// Creates a new event holding two parts, like an entity id and its new position,
+    /// without declaring a struct for them. The parts are read with `get_part` or `get_parts2`,
+    /// not with `get_data`, and the payload type name is the type name of the tuple.
+    pub fn new2<A: 'static, B: 'static>(a: A, b: B) -> Event {
+        Event::from_boxed(Box::new(Parts(vec![Part::new(a), Part::new(b)])), type_name::<(A, B)>())
+    }
+
+    /// # New 3
+    ///
+    /// Creates a new event holding three parts, see `new2`.
+    pub fn new3<A: 'static, B: 'static, C: 'static>(a: A, b: B, c: C) -> Event {
+        Event::from_boxed(Box::new(Parts(vec![Part::new(a), Part::new(b), Part::new(c)])), type_name::<(A, B, C)>())
+    }
+
+    /// # Get Part
+    ///
+    /// Returns the part at the index, or an error naming the type of the part
+    /// when it is not of type `T`. Asking an event without that many parts names the type of its data.
+    pub fn get_part<T: 'static>(&self, index: usize) -> Result<&T, PayloadTypeError> {
+        let part = self.part(index).ok_or_else(|| self.part_error::<T>(self.payload_type_name()))?;
+        part.value.downcast_ref::<T>().ok_or_else(|| self.part_error::<T>(part.type_name))
+    }
+
+    /// # Get Parts 2
+    ///
+    /// Returns the first two parts, when they are of types `A` and `B`.
+    pub fn get_parts2<A: 'static, B: 'static>(&self) -> Option<(&A, &B)> {
+        Some((self.get_part(0).ok()?, self.get_part(1).ok()?))
+    }
+
+    /// # Get Parts 3
+    ///
+    /// Returns the first three parts, when they are of types `A`, `B` and `C`.
+    pub fn get_parts3<A: 'static, B: 'static, C: 'static>(&self) -> Option<(&A, &B, &C)> {
+        Some((self.get_part(0).ok()?, self.get_part(1).ok()?, self.get_part(2).ok()?))
+    }
+
+    /// # Set Part
+    ///
+    /// Replaces the part at the index with a value of the same type,
+    /// or returns an error naming the type of the part when it is not of type `T`.
+    pub fn set_part<T: 'static>(&mut self, index: usize, value: T) -> Result<(), PayloadTypeError> {
+        let part = self.get_part_mut::<T>(index)?;
+        *part = value;
+        Ok(())
+    }
+
+    fn part(&self, index: usize) -> Option<&Part> {
+        self.get_data::<Parts>().and_then(|parts| parts.0.get(index))
+    }
+
+    fn get_part_mut<T: 'static>(&mut self, index: usize) -> Result<&mut T, PayloadTypeError> {
+        self.get_part::<T>(index)?;
+        let part = self.data.downcast_mut::<Parts>().and_then(|parts| parts.0.get_mut(index)).unwrap();
+        Ok(part.value.downcast_mut::<T>().unwrap())
+    }
+
+    fn part_error<T: 'static>(&self, found: &'static str) -> PayloadTypeError {
+        PayloadTypeError { expected: type_name::<T>(), found }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Event, EventBus, PayloadTypeError, Subscriber};
+
+    #[test]
+    fn test_mixed_type_parts() {
+        let mut event = Event::new3(7u64, (1.5f32, 2.0f32), "spawn".to_string());
+        assert_eq!("(u64, (f32, f32), alloc::string::String)", event.payload_type_name());
+        assert_eq!(Ok(&7u64), event.get_part::<u64>(0));
+        assert_eq!(Some((&7u64, &(1.5f32, 2.0f32))), event.get_parts2::<u64, (f32, f32)>());
+        assert_eq!(Some("spawn"), event.get_parts3::<u64, (f32, f32), String>().map(|(_, _, name)| name.as_str()));
+
+        assert_eq!(Ok(()), event.set_part(1, (3.0f32, 4.0f32)));
+        assert_eq!(Ok(&(3.0f32, 4.0f32)), event.get_part::<(f32, f32)>(1));
+    }
+
+    #[test]
+    fn test_part_type_mismatch() {
+        let mut event = Event::new2(7u64, "spawn".to_string());
+        assert_eq!(Err(PayloadTypeError { expected: "u32", found: "u64" }), event.get_part::<u32>(0));
+        assert_eq!(Err(PayloadTypeError { expected: "u32", found: "u64" }), event.set_part(0, 1u32));
+        assert_eq!(Err(PayloadTypeError { expected: "u64", found: "(u64, alloc::string::String)" }), event.get_part::<u64>(2));
+        assert_eq!(None, event.get_parts2::<u64, u64>());
+        assert_eq!(Err(PayloadTypeError { expected: "u64", found: "&str" }), Event::new("spawn").get_part::<u64>(0));
+    }
+
+    struct MoveSubscriber;
+
+    impl Subscriber for MoveSubscriber {
+        fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+            let x = event.get_part::<(f32, f32)>(1)?.0;
+            event.set_part(1, (x + 1.0, 0.0f32))?;
+            Ok(())
+        }
+
+        fn on_after(&self, event: &Event) -> Result<(), String> {
+            match event.get_parts2::<u64, (f32, f32)>() {
+                Some((7, position)) if *position == (2.5, 0.0) => Ok(()),
+                parts => Err(format!("Unexpected parts {:?}", parts)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_subscriber_reads_parts() {
+        let event_bus = EventBus::new();
+        event_bus
+            .subscribe_listener("moved", MoveSubscriber)
+            .register("moved", Event::new2(7u64, (1.5f32, 2.0f32)));
+        assert_eq!(Ok(()), event_bus.publish());
+    }
+}
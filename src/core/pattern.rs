@@ -0,0 +1,183 @@
+use std::error::Error;
+use std::fmt;
+use super::subscription::Subscription;
+
+/// A parsed MQTT-style topic pattern, with `/` separating the levels of a topic.
+/// A `+` level matches exactly one level, a final `#` level matches any number of remaining levels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TopicPattern {
+    levels: Vec<Level>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Level {
+    Exact(String),
+    Single,
+    Multi,
+}
+
+impl TopicPattern {
+    /// Returns whether the event name uses wildcards, and should be parsed as a pattern:
+    /// whether one of its levels is exactly `+` or `#`. Event names like `c++` are no patterns.
+    pub(crate) fn is_pattern(topic: &str) -> bool {
+        topic.split('/').any(|segment| segment == "+" || segment == "#")
+    }
+
+    pub(crate) fn parse(pattern: &str) -> Result<TopicPattern, InvalidPattern> {
+        let invalid = |reason: &str| InvalidPattern { pattern: pattern.to_string(), reason: reason.to_string() };
+        if pattern.is_empty() {
+            return Err(invalid("A pattern cannot be empty"));
+        }
+        let segments: Vec<&str> = pattern.split('/').collect();
+        let mut levels = Vec::with_capacity(segments.len());
+        for (index, segment) in segments.iter().enumerate() {
+            let level = match *segment {
+                "+" => Level::Single,
+                "#" if index + 1 == segments.len() => Level::Multi,
+                "#" => return Err(invalid("'#' is only allowed as the last level")),
+                _ if segment.contains(['+', '#']) => {
+                    return Err(invalid("Wildcards must occupy an entire level"));
+                }
+                _ => Level::Exact(segment.to_string()),
+            };
+            levels.push(level);
+        }
+        Ok(TopicPattern { levels })
+    }
+
+    /// Returns a pattern matching only the event name, even when it contains wildcards.
+    pub(crate) fn exact(topic: &str) -> TopicPattern {
+        TopicPattern { levels: topic.split('/').map(|segment| Level::Exact(segment.to_string())).collect() }
+    }
+
+    pub(crate) fn matches(&self, topic: &str) -> bool {
+        // Like MQTT, wildcards at the first level do not match topics starting with '$'.
+        if topic.starts_with('$') && !matches!(self.levels.first(), Some(Level::Exact(_))) {
+            return false;
+        }
+        let mut segments = topic.split('/');
+        for level in &self.levels {
+            match level {
+                Level::Multi => return true,
+                Level::Single => {
+                    if segments.next().is_none() {
+                        return false;
+                    }
+                }
+                Level::Exact(expected) => {
+                    if segments.next() != Some(expected.as_str()) {
+                        return false;
+                    }
+                }
+            }
+        }
+        segments.next().is_none()
+    }
+}
+
+impl fmt::Display for TopicPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, level) in self.levels.iter().enumerate() {
+            if index > 0 {
+                f.write_str("/")?;
+            }
+            match level {
+                Level::Exact(segment) => f.write_str(segment)?,
+                Level::Single => f.write_str("+")?,
+                Level::Multi => f.write_str("#")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A subscription to every event name matching a pattern.
+pub(crate) struct PatternSubscription {
+    pub(crate) pattern: TopicPattern,
+    pub(crate) subscription: Subscription,
+}
+
+impl PatternSubscription {
+    /// Returns whether the pattern matches the event name, only `String` event names can match.
+    pub(crate) fn matches(&self, topic: Option<&str>) -> bool {
+        topic.is_some_and(|topic| self.pattern.matches(topic))
+    }
+}
+
+/// # Invalid Pattern
+///
+/// The error returned when subscribing with a malformed wildcard pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidPattern {
+    /// The malformed pattern.
+    pub pattern: String,
+    /// Why the pattern is malformed.
+    pub reason: String,
+}
+
+impl fmt::Display for InvalidPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid pattern '{}': {}", self.pattern, self.reason)
+    }
+}
+
+impl Error for InvalidPattern {}
+
+#[cfg(test)]
+mod tests {
+    use super::TopicPattern;
+    use crate::EventBus;
+    use crate::subscribers::CollectingSubscriber;
+
+    #[test]
+    fn test_matches() {
+        let cases = [
+            ("sensors/+/temperature", "sensors/kitchen/temperature", true),
+            ("sensors/+/temperature", "sensors/kitchen/humidity", false),
+            ("sensors/+/temperature", "sensors/kitchen/oven/temperature", false),
+            ("sensors/+/temperature", "sensors//temperature", true),
+            ("sensors/#", "sensors", true),
+            ("sensors/#", "sensors/kitchen", true),
+            ("sensors/#", "sensors/kitchen/temperature", true),
+            ("sensors/#", "sensorsx/kitchen", false),
+            ("#", "sensors/kitchen/temperature", true),
+            ("#", "", true),
+            ("#", "$SYS/uptime", false),
+            ("$SYS/#", "$SYS/uptime", true),
+            ("+", "sensors", true),
+            ("+", "sensors/kitchen", false),
+            ("sensors/+", "sensors/", true),
+            ("sensors/+", "sensors", false),
+            ("+/+", "/kitchen", true),
+            ("sensors/+/#", "sensors/kitchen", true),
+            ("sensors/+/#", "sensors", false),
+        ];
+        for (pattern, topic, expected) in cases {
+            let parsed = TopicPattern::parse(pattern).unwrap();
+            assert_eq!(expected, parsed.matches(topic), "Pattern '{}' on topic '{}'", pattern, topic);
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_patterns() {
+        for pattern in ["", "sensors/#/temperature", "sensors/kitchen+", "sensors/#a", "#/#"] {
+            assert!(TopicPattern::parse(pattern).is_err(), "Pattern '{}' should be rejected", pattern);
+        }
+    }
+
+    #[test]
+    fn test_event_names_with_wildcard_characters_are_exact_subscriptions() {
+        assert!(!TopicPattern::is_pattern("c++"));
+        assert!(!TopicPattern::is_pattern("issue#1"));
+        assert!(TopicPattern::is_pattern("languages/+"));
+
+        let event_bus = EventBus::new();
+        let (collector, received) = CollectingSubscriber::<u32>::new();
+        event_bus.subscribe_listener("c++", collector).register("c++", 1u32).register("c", 2u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![1], *received.borrow());
+
+        let (collector, _) = CollectingSubscriber::<u32>::new();
+        assert!(event_bus.subscribe_pattern("c++", collector).is_err());
+    }
+}
@@ -0,0 +1,189 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use super::Event;
+#[cfg(feature = "serde")]
+use std::collections::BTreeMap;
+#[cfg(feature = "serde")]
+use super::json::{JsonDecode, JsonEncode, Migration};
+
+type Encode = Box<dyn Fn(&dyn Any) -> Vec<u8>>;
+type Decode = Box<dyn Fn(&[u8]) -> Result<Event, String>>;
+
+/// # Payload Registry
+///
+/// Knows how to turn the payloads of events into bytes and back,
+/// so events can leave the process, for example over the network.
+/// Every payload type is registered under a name that is shared by both sides.
+///
+/// ## Methods
+///
+/// * `register` - Registers the encoding and decoding of a payload type.
+///
+/// * `encode` - Encodes the payload of an event.
+///
+/// * `decode` - Decodes bytes into an event.
+///
+/// * `decode_versioned` - Decodes bytes of a schema version into an event.
+///
+/// * `register_json` - Registers a payload type encoded as JSON, with the `serde` feature.
+///
+/// * `register_versioned` - Registers a historical representation of a payload, with the `serde` feature.
+#[derive(Default)]
+pub struct PayloadRegistry {
+    encoders: HashMap<TypeId, (String, Encode)>,
+    decoders: HashMap<String, Decode>,
+    #[cfg(feature = "serde")]
+    pub(crate) json_encoders: HashMap<TypeId, JsonEncode>,
+    #[cfg(feature = "serde")]
+    pub(crate) json_decoders: HashMap<String, JsonDecode>,
+    #[cfg(feature = "serde")]
+    pub(crate) migrations: HashMap<String, BTreeMap<u32, Migration>>,
+}
+
+impl PayloadRegistry {
+    /// # New
+    ///
+    /// Creates an empty payload registry.
+    pub fn new() -> PayloadRegistry {
+        PayloadRegistry::default()
+    }
+
+    /// # Register
+    ///
+    /// Registers the encoding and decoding of payloads of type `T` under a name.
+    pub fn register<T: 'static>(
+        &mut self,
+        name: &str,
+        encode: impl Fn(&T) -> Vec<u8> + 'static,
+        decode: impl Fn(&[u8]) -> Result<T, String> + 'static,
+    ) -> &mut Self {
+        let encode: Encode = Box::new(move |data| encode(data.downcast_ref::<T>().unwrap()));
+        let decode: Decode = Box::new(move |bytes| decode(bytes).map(Event::new));
+        self.encoders.insert(TypeId::of::<T>(), (name.to_string(), encode));
+        self.decoders.insert(name.to_string(), decode);
+        self
+    }
+
+    /// # Encode
+    ///
+    /// Returns the registered name and the bytes of the payload of the event,
+    /// or `None` if its payload type is not registered.
+    pub fn encode(&self, event: &Event) -> Option<(&str, Vec<u8>)> {
+        self.encoders.get(&(*event.data).type_id())
+            .map(|(name, encode)| (name.as_str(), encode(&*event.data)))
+    }
+
+    /// Returns the name the payload type of the event is registered under.
+    #[cfg(feature = "serde")]
+    pub(crate) fn name_of(&self, event: &Event) -> Option<&str> {
+        self.encoders.get(&(*event.data).type_id()).map(|(name, _)| name.as_str())
+    }
+
+    /// # Decode
+    ///
+    /// Decodes the bytes of a payload registered under the name into an event.
+    pub fn decode(&self, name: &str, bytes: &[u8]) -> Result<Event, PayloadError> {
+        let decode = self.decoders.get(name)
+            .ok_or_else(|| PayloadError::Unknown { name: name.to_string() })?;
+        decode(bytes).map_err(|message| PayloadError::Invalid { name: name.to_string(), message })
+    }
+
+    /// # Decode Versioned
+    ///
+    /// Decodes the bytes of a payload registered under the name at a schema version into an event
+    /// holding that version. With the `serde` feature, payloads of a version registered with `register_versioned`
+    /// are decoded as JSON and migrated into the current version first.
+    pub fn decode_versioned(&self, name: &str, version: Option<u32>, bytes: &[u8]) -> Result<Event, PayloadError> {
+        let Some(version) = version else {
+            return self.decode(name, bytes);
+        };
+        #[cfg(feature = "serde")]
+        if let Some(migrated) = self.migrate(name, version, || serde_json::from_slice(bytes).map_err(|e| e.to_string())) {
+            return migrated;
+        }
+        let mut event = self.decode(name, bytes)?;
+        event.set_schema_version(version);
+        Ok(event)
+    }
+}
+
+/// # Payload Error
+///
+/// The error returned when bytes cannot be decoded into an event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PayloadError {
+    /// No payload type is registered under the name.
+    Unknown { name: String },
+    /// The bytes are not a valid payload of the registered type.
+    Invalid { name: String, message: String },
+    /// The JSON of an event is not an object made by `Event::to_json`.
+    Malformed { message: String },
+    /// The payload is of a schema version the registered migrations cannot bring to the current version.
+    UnsupportedVersion { name: String, found: u32, supported: Vec<u32> },
+}
+
+impl fmt::Display for PayloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PayloadError::Unknown { name } => write!(f, "Unknown payload type '{}'", name),
+            PayloadError::Invalid { name, message } => write!(f, "Invalid '{}' payload: {}", name, message),
+            PayloadError::Malformed { message } => write!(f, "Malformed event: {}", message),
+            PayloadError::UnsupportedVersion { name, found, supported } => {
+                let supported: Vec<String> = supported.iter().map(u32::to_string).collect();
+                write!(f, "No migration of '{}' payload from version {}, supported versions: {}", name, found, supported.join(", "))
+            }
+        }
+    }
+}
+
+impl Error for PayloadError {}
+
+/// # Raw Payload
+///
+/// The payload of an event that could not be decoded,
+/// holding the name and bytes as they were received.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawPayload {
+    /// The name the payload was encoded under.
+    pub name: String,
+    /// The encoded payload.
+    pub bytes: Vec<u8>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PayloadError, PayloadRegistry};
+    use crate::Event;
+
+    fn registry() -> PayloadRegistry {
+        let mut registry = PayloadRegistry::new();
+        registry.register::<u32>(
+            "u32",
+            |value| value.to_be_bytes().to_vec(),
+            |bytes| bytes.try_into().map(u32::from_be_bytes).map_err(|_| "Expected 4 bytes".to_string()),
+        );
+        registry
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let registry = registry();
+        let (name, bytes) = registry.encode(&Event::new(42u32)).unwrap();
+        assert_eq!("u32", name);
+        let event = registry.decode(name, &bytes).unwrap();
+        assert_eq!(Some(&42u32), event.get_data::<u32>());
+        assert!(registry.encode(&Event::new("hello".to_string())).is_none());
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let registry = registry();
+        assert_eq!(Some(PayloadError::Unknown { name: "string".to_string() }), registry.decode("string", &[]).err());
+        assert_eq!(
+            Some(PayloadError::Invalid { name: "u32".to_string(), message: "Expected 4 bytes".to_string() }),
+            registry.decode("u32", &[1]).err()
+        );
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+use super::{DispatchOrder, Event, EventBus, OverflowAction, TopicKey};
+use super::dispatch::{planned_targets, PlannedTarget};
+use super::state::BusState;
+
+/// # Routing Plan
+///
+/// The subscribers each queued event would be delivered to by the next publish,
+/// as returned by `publish_dry_run`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoutingPlan<K: TopicKey = String> {
+    /// The planned deliveries, in the order the events would be published.
+    pub deliveries: Vec<PlannedDelivery<K>>,
+}
+
+/// # Planned Delivery
+///
+/// The subscribers a single queued event would be delivered to.
+///
+/// ## Fields
+///
+/// * `topic` - The event name the event would be published on, the target of a route that forwards it.
+///
+/// * `event_id` - The id of the event. A copy made by a route has the id of the event it is copied from,
+///   as the copy only gets its own id when the publish makes it.
+///
+/// * `subscribers` - The names of the subscribers that would receive the event, in order.
+///
+/// * `unhandled` - Whether no subscriber would receive the event.
+///
+/// * `skipped` - Why the event would not be delivered by the next publish at all.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedDelivery<K: TopicKey = String> {
+    /// The event name the event would be published on.
+    pub topic: K,
+    /// The id of the event, or of the event a copy is made from.
+    pub event_id: u64,
+    /// The names of the subscribers that would receive the event, in order.
+    pub subscribers: Vec<String>,
+    /// Whether no subscriber would receive the event.
+    pub unhandled: bool,
+    /// Why the event would not be delivered by the next publish at all.
+    pub skipped: Option<SkipReason>,
+}
+
+/// # Skip Reason
+///
+/// Why a queued event would not be delivered by the next publish.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The ttl of the event passed, it would be dead-lettered.
+    Expired,
+    /// The event is over the limit of its event name, it would be published by a later publish.
+    Deferred,
+    /// The event is over the limit of its event name, it would be dropped.
+    Dropped,
+    /// The window of its event name in `TopicMode::WindowedBatch` is still open,
+    /// it would be published by a later publish.
+    Held,
+}
+
+impl<K: TopicKey> PlannedDelivery<K> {
+    fn skipped(topic: K, message: &Event, reason: SkipReason) -> PlannedDelivery<K> {
+        PlannedDelivery { topic, event_id: message.id(), subscribers: Vec::new(), unhandled: true, skipped: Some(reason) }
+    }
+}
+
+/// Returns why the message at the index of a run of the event name would be skipped for the limit of the event name.
+fn over_limit<K: TopicKey>(state: &BusState<K>, event_name: &K, index: usize) -> Option<SkipReason> {
+    let limit = state.topic_limits.get(event_name).filter(|limit| index >= limit.max_per_publish)?;
+    Some(match limit.overflow {
+        OverflowAction::Defer => SkipReason::Deferred,
+        OverflowAction::Drop => SkipReason::Dropped,
+    })
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Publish Dry Run
+    ///
+    /// Returns the subscribers each queued event would be delivered to by the next publish,
+    /// without calling any subscriber. The queued events stay queued.
+    /// The events are ordered, routed and limited like the publish would, and skip the subscribers
+    /// whose circuit breaker is open. The events held by their window are listed last.
+    /// Debounced subscribers are left out, as they only receive the latest event afterwards.
+    /// The outcomes of subscribers, schema upgrades and delayed events that become due are not known up front,
+    /// so the events after the trial of a half-open circuit breaker skip its subscriber.
+    pub fn publish_dry_run(&self) -> RoutingPlan<K> {
+        let state = self.state.borrow();
+        let now = state.clock.now();
+        let mut deliveries = Vec::new();
+        let mut held = Vec::new();
+        let mut queued: Vec<(K, Vec<&Event>)> = Vec::new();
+        for (event_name, messages) in &state.events {
+            match state.is_held(event_name, messages, now) {
+                true => held.push((event_name.clone(), messages.iter().collect())),
+                false => queued.push((event_name.clone(), messages.iter().collect())),
+            }
+        }
+        let events = match state.dispatch_order {
+            DispatchOrder::PerTopic => state.order_by_priority(queued),
+            DispatchOrder::GlobalFifo => {
+                for (event_name, messages) in &mut queued {
+                    let kept = (0..messages.len()).take_while(|index| over_limit(&state, event_name, *index).is_none()).count();
+                    for (index, message) in messages.drain(kept..).enumerate() {
+                        let reason = over_limit(&state, event_name, kept + index).unwrap();
+                        deliveries.push(PlannedDelivery::skipped(event_name.clone(), message, reason));
+                    }
+                }
+                state.order_by_registration(queued)
+            }
+        };
+
+        let mut targets: HashMap<K, Vec<PlannedTarget>> = HashMap::new();
+        for (event_name, messages) in state.plan_routes(events) {
+            let targets = targets.entry(event_name.clone()).or_insert_with(|| planned_targets(&state, &event_name));
+            for (index, message) in messages.into_iter().enumerate() {
+                let skipped = over_limit(&state, &event_name, index)
+                    .or_else(|| message.is_expired(now).then_some(SkipReason::Expired));
+                if let Some(reason) = skipped {
+                    deliveries.push(PlannedDelivery::skipped(event_name.clone(), message, reason));
+                    continue;
+                }
+                let subscribers: Vec<String> = targets.iter_mut()
+                    .filter_map(|target| target.admits(now).then(|| target.name.clone()))
+                    .collect();
+                deliveries.push(PlannedDelivery {
+                    topic: event_name.clone(),
+                    event_id: message.id(),
+                    unhandled: subscribers.is_empty(),
+                    subscribers,
+                    skipped: None,
+                });
+            }
+        }
+
+        for (event_name, messages) in state.order_by_priority(held) {
+            deliveries.extend(messages.into_iter().map(|message| PlannedDelivery::skipped(event_name.clone(), message, SkipReason::Held)));
+        }
+        RoutingPlan { deliveries }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+    use super::{PlannedDelivery, SkipReason};
+    use crate::{CircuitBreaker, DispatchOrder, ErrorPolicy, Event, EventBus, OverflowAction, RouteMode, Subscriber, TopicMode};
+    use crate::subscribers::CollectingSubscriber;
+    use crate::testing::ManualClock;
+
+    struct RecordingSubscriber {
+        name: &'static str,
+        deliveries: Rc<RefCell<Vec<(String, u64)>>>,
+    }
+
+    impl Subscriber for RecordingSubscriber {
+        fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+            self.deliveries.borrow_mut().push((self.name.to_string(), event.id()));
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            self.name
+        }
+    }
+
+    fn planned(plan: &[PlannedDelivery]) -> Vec<(String, u64)> {
+        plan.iter()
+            .flat_map(|delivery| delivery.subscribers.iter().map(|name| (name.clone(), delivery.event_id)))
+            .collect()
+    }
+
+    #[test]
+    fn test_dry_run_matches_delivery() {
+        let deliveries = Rc::new(RefCell::new(Vec::new()));
+        let recording = |name| RecordingSubscriber { name, deliveries: deliveries.clone() };
+        let event_bus = EventBus::new();
+        event_bus
+            .subscribe_listener("sensors/kitchen", recording("kitchen"))
+            .subscribe_pattern("sensors/+", recording("all sensors")).unwrap()
+            .subscribe_debounced("sensors/kitchen", recording("debounced"), Duration::from_secs(1))
+            .subscribe_listener("alarms", recording("alarm"))
+            .limit_topic("alarms", 1, OverflowAction::Defer)
+            .register("sensors/kitchen", 1u32)
+            .register("sensors/garage", 2u32)
+            .register("alarms", 3u32)
+            .register("alarms", 4u32)
+            .register("unknown", 5u32);
+
+        let plan = event_bus.publish_dry_run();
+        assert_eq!(5, plan.deliveries.len());
+        assert_eq!(1, event_bus.pending(&"sensors/kitchen".to_string()));
+        assert_eq!(2, event_bus.pending(&"alarms".to_string()));
+
+        let unknown: Vec<&PlannedDelivery> = plan.deliveries.iter().filter(|delivery| delivery.topic == "unknown").collect();
+        assert!(unknown[0].unhandled);
+        let deferred: Vec<&PlannedDelivery> = plan.deliveries.iter().filter(|delivery| delivery.skipped.is_some()).collect();
+        assert_eq!(1, deferred.len());
+        assert_eq!(Some(SkipReason::Deferred), deferred[0].skipped);
+
+        let planned = planned(&plan.deliveries);
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(planned, *deliveries.borrow());
+    }
+
+    #[test]
+    fn test_dry_run_matches_global_fifo_delivery_by_event_priority() {
+        let deliveries = Rc::new(RefCell::new(Vec::new()));
+        let recording = |name| RecordingSubscriber { name, deliveries: deliveries.clone() };
+        let event_bus = EventBus::new();
+        event_bus
+            .set_dispatch_order(DispatchOrder::GlobalFifo)
+            .subscribe_listener("orders", recording("orders"))
+            .subscribe_listener("payments", recording("payments"))
+            .limit_topic("payments", 1, OverflowAction::Drop)
+            .register("orders", 1u32)
+            .register("payments", 2u32)
+            .register("orders", Event::new(3u32).with_priority(5))
+            .register("payments", 4u32);
+
+        let plan = event_bus.publish_dry_run();
+        assert_eq!(Some(SkipReason::Dropped), plan.deliveries[0].skipped);
+        let planned = planned(&plan.deliveries);
+        assert_eq!(vec!["orders", "orders", "payments"], planned.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>());
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(planned, *deliveries.borrow());
+    }
+
+    #[test]
+    fn test_dry_run_skips_subscribers_with_an_open_breaker() {
+        let clock = ManualClock::new();
+        let deliveries = Rc::new(RefCell::new(Vec::new()));
+        let event_bus: EventBus = EventBus::with_clock(clock.clone());
+        event_bus
+            .set_error_policy(ErrorPolicy::Continue)
+            .subscribe_with_breaker("payments", <dyn Subscriber>::builder().name("gateway").on_event(|_| Err("gateway down".to_string())).build(), CircuitBreaker::new(1, Duration::from_secs(10), Duration::from_secs(5)))
+            .subscribe_listener("payments", RecordingSubscriber { name: "ledger", deliveries: deliveries.clone() })
+            .register("payments", 1u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        deliveries.borrow_mut().clear();
+
+        event_bus.register("payments", 2u32);
+        let plan = event_bus.publish_dry_run();
+        assert_eq!(vec!["ledger".to_string()], plan.deliveries[0].subscribers);
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(planned(&plan.deliveries), *deliveries.borrow());
+
+        clock.advance(Duration::from_secs(5));
+        event_bus.register("payments", 3u32).register("payments", 4u32);
+        let plan = event_bus.publish_dry_run();
+        assert_eq!(vec!["gateway".to_string(), "ledger".to_string()], plan.deliveries[0].subscribers);
+        assert_eq!(vec!["ledger".to_string()], plan.deliveries[1].subscribers);
+    }
+
+    #[test]
+    fn test_dry_run_reports_events_held_by_their_window() {
+        let clock = ManualClock::new();
+        let event_bus: EventBus = EventBus::with_clock(clock.clone());
+        let (collector, received) = CollectingSubscriber::<u32>::new();
+        event_bus
+            .set_topic_mode("db.writes", TopicMode::WindowedBatch { window: Duration::from_millis(100) })
+            .subscribe_listener("db.writes", collector)
+            .register("db.writes", 1u32);
+
+        let plan = event_bus.publish_dry_run();
+        assert_eq!(Some(SkipReason::Held), plan.deliveries[0].skipped);
+        assert!(plan.deliveries[0].subscribers.is_empty());
+        assert_eq!(Ok(()), event_bus.publish());
+        assert!(received.borrow().is_empty());
+
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(None, event_bus.publish_dry_run().deliveries[0].skipped);
+    }
+
+    #[test]
+    fn test_dry_run_matches_routed_delivery() {
+        let deliveries = Rc::new(RefCell::new(Vec::new()));
+        let recording = |name| RecordingSubscriber { name, deliveries: deliveries.clone() };
+        let event_bus = EventBus::new();
+        event_bus
+            .register_cloneable::<u32>()
+            .subscribe_listener("orders.v1", recording("legacy"))
+            .subscribe_listener("orders.v2", recording("orders"))
+            .subscribe_listener("audit", recording("audit"));
+        event_bus.add_route("orders.v1", "orders.v2", RouteMode::Move).unwrap();
+        event_bus.add_route("orders.v2", "audit", RouteMode::Copy).unwrap();
+        event_bus.register("orders.v1", 1u32).register("orders.v2", 2u32).register("orders.v2", "not cloneable");
+
+        let plan = event_bus.publish_dry_run();
+        assert!(plan.deliveries.iter().all(|delivery| delivery.topic != "orders.v1"));
+        let planned: Vec<(String, String)> = plan.deliveries.iter()
+            .flat_map(|delivery| delivery.subscribers.iter().map(|name| (delivery.topic.clone(), name.clone())))
+            .collect();
+        assert_eq!(Ok(()), event_bus.publish());
+        let delivered: Vec<String> = deliveries.borrow().iter().map(|(name, _)| name.clone()).collect();
+        assert_eq!(planned.iter().map(|(_, name)| name.clone()).collect::<Vec<_>>(), delivered);
+        assert_eq!(2, planned.iter().filter(|(topic, _)| topic == "audit").count());
+    }
+}
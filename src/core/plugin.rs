@@ -0,0 +1,265 @@
+use std::cell::{Cell, RefCell};
+use std::error::Error;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::fmt;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::rc::{Rc, Weak};
+use super::{Event, EventBus, Outcome, RawPayload, Subscriber, SubscriptionHandle};
+use super::state::BusState;
+use super::subscription::Subscription;
+
+/// The version of `PluginVTable` this event bus loads.
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+/// The symbol a plugin exports, returning its vtable.
+const PLUGIN_ENTRY: &CStr = c"plugin_create";
+
+/// The function a plugin exports as `plugin_create`.
+pub type PluginEntry = unsafe extern "C" fn() -> *const PluginVTable;
+
+/// # Plugin VTable
+///
+/// The interface of a subscriber compiled into a shared library, returned by the `plugin_create` function
+/// the library exports. The vtable and its strings are owned by the library and must stay valid while it is loaded.
+///
+/// ## Fields
+///
+/// * `abi_version` - The version of this layout, `PLUGIN_ABI_VERSION`.
+///
+/// * `name` - The nul-terminated name of the subscriber.
+///
+/// * `topic` - The nul-terminated event name the subscriber is subscribed to.
+///
+/// * `create` - Creates an instance of the subscriber, passed to the other functions.
+///
+/// * `destroy` - Frees an instance, before the library is unloaded.
+///
+/// * `on_event` - Handles the encoded payload of an event, with the nul-terminated name of its payload type.
+///   Returns 0 when the event was handled, an error code otherwise.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct PluginVTable {
+    /// The version of this layout, `PLUGIN_ABI_VERSION`.
+    pub abi_version: u32,
+    /// The nul-terminated name of the subscriber.
+    pub name: *const c_char,
+    /// The nul-terminated event name the subscriber is subscribed to.
+    pub topic: *const c_char,
+    /// Creates an instance of the subscriber, passed to the other functions.
+    pub create: extern "C" fn() -> *mut c_void,
+    /// Frees an instance, before the library is unloaded.
+    pub destroy: extern "C" fn(instance: *mut c_void),
+    /// Handles the encoded payload of an event, returns 0 when it was handled.
+    pub on_event: extern "C" fn(instance: *mut c_void, payload_type: *const c_char, payload: *const u8, len: usize) -> i32,
+}
+
+/// # Plugin Error
+///
+/// The error returned by `load_plugin` when a shared library cannot be loaded as a plugin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PluginError {
+    /// The shared library could not be opened.
+    Load(String),
+    /// The shared library does not export `plugin_create`.
+    MissingEntry,
+    /// The vtable has a different layout version.
+    AbiMismatch { expected: u32, found: u32 },
+    /// The vtable is null, or its name or event name is null or not UTF-8.
+    InvalidVTable,
+}
+
+impl fmt::Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PluginError::Load(e) => write!(f, "Cannot load the plugin: {}", e),
+            PluginError::MissingEntry => write!(f, "The plugin does not export plugin_create"),
+            PluginError::AbiMismatch { expected, found } => {
+                write!(f, "The plugin has ABI version {}, expected {}", found, expected)
+            }
+            PluginError::InvalidVTable => write!(f, "The plugin returned an invalid vtable"),
+        }
+    }
+}
+
+impl Error for PluginError {}
+
+/// An opened shared library, closed when dropped.
+struct Library(*mut c_void);
+
+impl Library {
+    fn open(path: &Path) -> Result<Self, PluginError> {
+        let path = CString::new(path.as_os_str().as_bytes()).map_err(|e| PluginError::Load(e.to_string()))?;
+        // SAFETY: the path is nul-terminated; the initializers of the library run here.
+        let handle = unsafe { libc::dlopen(path.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+        if handle.is_null() {
+            return Err(PluginError::Load(last_error()));
+        }
+        Ok(Library(handle))
+    }
+
+    fn symbol(&self, name: &CStr) -> Option<*mut c_void> {
+        // SAFETY: the handle is open and the name is nul-terminated.
+        let symbol = unsafe { libc::dlsym(self.0, name.as_ptr()) };
+        (!symbol.is_null()).then_some(symbol)
+    }
+}
+
+impl Drop for Library {
+    fn drop(&mut self) {
+        // SAFETY: the handle is open and nothing of the library is used anymore.
+        unsafe { libc::dlclose(self.0) };
+    }
+}
+
+/// Returns the message of the last failed dynamic loading call.
+fn last_error() -> String {
+    // SAFETY: `dlerror` returns null or a nul-terminated string, valid until the next call.
+    let error = unsafe { libc::dlerror() };
+    match error.is_null() {
+        true => "Unknown error".to_string(),
+        false => unsafe { CStr::from_ptr(error) }.to_string_lossy().into_owned(),
+    }
+}
+
+/// Reads a string of the vtable.
+///
+/// # Safety
+///
+/// A non-null pointer must point to a nul-terminated string.
+unsafe fn vtable_str(value: *const c_char) -> Result<String, PluginError> {
+    if value.is_null() {
+        return Err(PluginError::InvalidVTable);
+    }
+    CStr::from_ptr(value).to_str().map(str::to_string).map_err(|_| PluginError::InvalidVTable)
+}
+
+/// Hands the encoded payloads to an instance of a plugin. The library stays loaded as long as the subscriber lives.
+struct PluginSubscriber {
+    name: String,
+    vtable: PluginVTable,
+    instance: *mut c_void,
+    /// Set by `unload_plugin` when the subscriber could not be unsubscribed because it was being published.
+    unloaded: Rc<Cell<bool>>,
+    /// The state of the event bus, for its payload registry.
+    /// It is not borrowed mutably while a subscriber is called.
+    state: Weak<RefCell<BusState<String>>>,
+    /// Dropped after the instance was destroyed.
+    _library: Library,
+}
+
+impl Subscriber for PluginSubscriber {
+    fn on_event_outcome(&mut self, event: &mut Event) -> Outcome {
+        if self.unloaded.get() {
+            return Outcome::AckAndUnsubscribe;
+        }
+        let (payload_type, bytes) = match event.get_data::<RawPayload>() {
+            Some(raw) => (raw.name.clone(), raw.bytes.clone()),
+            None => {
+                let Some(state) = self.state.upgrade() else {
+                    return Outcome::AckAndUnsubscribe;
+                };
+                let encoded = state.borrow().payloads.encode(event).map(|(name, bytes)| (name.to_string(), bytes));
+                match encoded {
+                    Some(encoded) => encoded,
+                    None => return Outcome::Error(format!("Payloads of type {} are not registered", event.payload_type_name())),
+                }
+            }
+        };
+        let Ok(payload_type) = CString::new(payload_type) else {
+            return Outcome::Error("The payload type name contains a nul byte".to_string());
+        };
+        match (self.vtable.on_event)(self.instance, payload_type.as_ptr(), bytes.as_ptr(), bytes.len()) {
+            0 => Outcome::Ack,
+            code => Outcome::Error(format!("Plugin {} failed with {}", self.name, code)),
+        }
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Drop for PluginSubscriber {
+    fn drop(&mut self) {
+        (self.vtable.destroy)(self.instance);
+    }
+}
+
+/// # Plugin Handle
+///
+/// A plugin loaded with `load_plugin`, to unload it with `unload_plugin`.
+#[derive(Debug)]
+pub struct PluginHandle {
+    name: String,
+    subscription: SubscriptionHandle,
+    unloaded: Rc<Cell<bool>>,
+}
+
+impl PluginHandle {
+    /// # Name
+    ///
+    /// Returns the name of the subscriber of the plugin.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// # Topic
+    ///
+    /// Returns the event name the subscriber of the plugin is subscribed to.
+    pub fn topic(&self) -> &String {
+        self.subscription.topic()
+    }
+}
+
+impl EventBus {
+    /// # Load Plugin
+    ///
+    /// Loads a shared library exporting `plugin_create`, and subscribes an instance of its subscriber,
+    /// see `PluginVTable`, to the event name of its vtable. The payloads are handed to the plugin encoded,
+    /// raw payloads as they are and others with the payload registry. The library stays loaded
+    /// until the subscriber is unsubscribed and dropped, also when the plugin handle is dropped earlier.
+    ///
+    /// Loading a library runs its code, so only libraries that are trusted should be loaded.
+    pub fn load_plugin(&self, path: impl AsRef<Path>) -> Result<PluginHandle, PluginError> {
+        let library = Library::open(path.as_ref())?;
+        let entry = library.symbol(PLUGIN_ENTRY).ok_or(PluginError::MissingEntry)?;
+        // SAFETY: a plugin exports `plugin_create` as a `PluginEntry`.
+        let vtable = unsafe { std::mem::transmute::<*mut c_void, PluginEntry>(entry)() };
+        if vtable.is_null() {
+            return Err(PluginError::InvalidVTable);
+        }
+        // SAFETY: the vtable is owned by the library, which is loaded.
+        let vtable = unsafe { *vtable };
+        if vtable.abi_version != PLUGIN_ABI_VERSION {
+            return Err(PluginError::AbiMismatch { expected: PLUGIN_ABI_VERSION, found: vtable.abi_version });
+        }
+        // SAFETY: the strings are owned by the library, which is loaded.
+        let (name, topic) = unsafe { (vtable_str(vtable.name)?, vtable_str(vtable.topic)?) };
+        let unloaded = Rc::new(Cell::new(false));
+        let subscriber = PluginSubscriber {
+            name: name.clone(),
+            vtable,
+            instance: (vtable.create)(),
+            unloaded: unloaded.clone(),
+            state: Rc::downgrade(&self.state),
+            _library: library,
+        };
+        let topic = self.topic_key(topic);
+        let subscription = Subscription::new(subscriber);
+        let handle = SubscriptionHandle { topic: topic.clone(), id: subscription.id };
+        self.subscribe(topic, subscription);
+        Ok(PluginHandle { name, subscription: handle, unloaded })
+    }
+
+    /// # Unload Plugin
+    ///
+    /// Unsubscribes the subscriber of the plugin, after which its instance is destroyed and the library unloaded.
+    /// When its event name is being published, the subscriber unsubscribes itself the next time it is called instead,
+    /// so the library is never unloaded while its code runs.
+    pub fn unload_plugin(&self, plugin: PluginHandle) -> &Self {
+        plugin.unloaded.set(true);
+        self.unsubscribe(&plugin.subscription);
+        self
+    }
+}
@@ -0,0 +1,93 @@
+/// # Before Failure
+///
+/// Controls what happens when the on_before of a subscriber returns an error.
+///
+/// ## Variants
+///
+/// * `AbortMessage` - The message is not delivered to any subscriber. The next messages are delivered as usual.
+///
+/// * `SkipThisSubscriber` - Only the failing subscriber is skipped for the message,
+///   its on_event and on_after are not called. The other subscribers proceed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BeforeFailure {
+    /// The message is not delivered to any subscriber.
+    #[default]
+    AbortMessage,
+    /// Only the failing subscriber opts out of the message.
+    SkipThisSubscriber,
+}
+
+/// # Defer Mode
+///
+/// Controls which subscribers receive an event after one of them deferred it with `Outcome::Defer`.
+///
+/// ## Variants
+///
+/// * `RunRemaining` - The remaining subscribers receive the event as usual, and receive it again when it is redelivered.
+///
+/// * `SkipRemaining` - The remaining subscribers do not receive the event, nor its on_after, until it is redelivered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeferMode {
+    /// The remaining subscribers receive the event.
+    #[default]
+    RunRemaining,
+    /// The remaining subscribers wait for the redelivery.
+    SkipRemaining,
+}
+
+/// # Error Policy
+///
+/// Controls what happens when a subscriber returns an error.
+/// A policy set on a subscription takes precedence over the policy of its event name,
+/// which takes precedence over the policy of the event bus.
+///
+/// ## Variants
+///
+/// * `Abort` - Publishing stops and the error is returned.
+///
+/// * `Continue` - The error is logged and publishing continues with the next subscriber.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorPolicy {
+    /// Publishing stops and the error is returned.
+    #[default]
+    Abort,
+    /// The error is logged and publishing continues.
+    Continue,
+}
+
+/// # Validation Mode
+///
+/// Controls what happens to an event rejected by the validator of its event name.
+///
+/// ## Variants
+///
+/// * `Reject` - The event is dropped, and `try_register` returns the error of the validator.
+///
+/// * `DeadLetter` - The event is moved to the dead-letter queue with the error of the validator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValidationMode {
+    /// The event is dropped, and `try_register` returns the error.
+    #[default]
+    Reject,
+    /// The event is moved to the dead-letter queue.
+    DeadLetter,
+}
+
+/// # Dispatch Order
+///
+/// Controls the order a publish delivers the queued events in.
+///
+/// ## Variants
+///
+/// * `PerTopic` - The events are delivered event name by event name,
+///   in registration order within an event name.
+///
+/// * `GlobalFifo` - The events are delivered strictly in registration order, across event names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DispatchOrder {
+    /// The events are delivered event name by event name.
+    #[default]
+    PerTopic,
+    /// The events are delivered in the order they were registered, interleaving event names.
+    GlobalFifo,
+}
@@ -0,0 +1,117 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use super::{Event, EventBus, TopicKey};
+
+type PriorityOf = fn(&dyn Any) -> u8;
+
+/// # Prioritized
+///
+/// A payload type that declares the priority level of its events, so control events like a shutdown request
+/// outrank the others wherever they are registered. The payload type is registered as a priority source
+/// with `EventBus::register_priority_source`.
+pub trait Prioritized {
+    /// Returns the priority level of the event holding the payload.
+    fn priority(&self) -> u8;
+}
+
+/// The payload types that declare the priority level of their events.
+#[derive(Default)]
+pub(crate) struct PrioritySources {
+    sources: HashMap<TypeId, PriorityOf>,
+}
+
+impl PrioritySources {
+    /// Returns the priority level the payload of the event declares, or `None` if its type is not registered.
+    pub(crate) fn priority_of(&self, message: &Event) -> Option<u8> {
+        let data: &dyn Any = &*message.data;
+        self.sources.get(&data.type_id()).map(|priority| priority(data))
+    }
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Register Priority Source
+    ///
+    /// Registers payloads of type `T` as declaring the priority level of their events.
+    /// Events holding them are queued with the level of the payload, unless the event has a level of its own,
+    /// see `Event::with_priority`. The level of an event takes precedence over the level of its event name.
+    pub fn register_priority_source<T: Prioritized + 'static>(&self) -> &Self {
+        self.state.borrow_mut().priority_sources.sources
+            .insert(TypeId::of::<T>(), |data| data.downcast_ref::<T>().unwrap().priority());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use super::Prioritized;
+    use crate::{Event, EventBus, Subscriber};
+
+    struct TelemetrySample;
+
+    impl Prioritized for TelemetrySample {
+        fn priority(&self) -> u8 {
+            1
+        }
+    }
+
+    struct ShutdownRequested;
+
+    impl Prioritized for ShutdownRequested {
+        fn priority(&self) -> u8 {
+            10
+        }
+    }
+
+    fn recording(name: &'static str, order: &Rc<RefCell<Vec<&'static str>>>) -> Box<dyn Subscriber> {
+        let order = order.clone();
+        <dyn Subscriber>::builder().on_event(move |_| {
+            order.borrow_mut().push(name);
+            Ok(())
+        }).build()
+    }
+
+    #[test]
+    fn test_payload_priority_decides_dispatch_order() {
+        let event_bus = EventBus::new();
+        let order = Rc::new(RefCell::new(Vec::new()));
+        event_bus
+            .register_priority_source::<TelemetrySample>()
+            .register_priority_source::<ShutdownRequested>()
+            .subscribe_listener("telemetry", recording("telemetry", &order))
+            .subscribe_listener("shutdown", recording("shutdown", &order))
+            .register("telemetry", TelemetrySample)
+            .register("shutdown", ShutdownRequested);
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec!["shutdown", "telemetry"], *order.borrow());
+
+        order.borrow_mut().clear();
+        event_bus
+            .register("shutdown", ShutdownRequested)
+            .register("telemetry", Event::new(TelemetrySample).with_priority(20));
+        assert_eq!(20, event_bus.effective_priority(&"telemetry".to_string()));
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec!["telemetry", "shutdown"], *order.borrow());
+    }
+
+    #[test]
+    fn test_events_of_one_topic_are_ordered_by_their_priority() {
+        let event_bus = EventBus::new();
+        let order = Rc::new(RefCell::new(Vec::new()));
+        let recorded = order.clone();
+        event_bus
+            .register_priority_source::<ShutdownRequested>()
+            .subscribe_listener("control", <dyn Subscriber>::builder().on_event(move |event| {
+                recorded.borrow_mut().push(event.payload_type_name());
+                Ok(())
+            }).build())
+            .register("control", TelemetrySample)
+            .register("control", ShutdownRequested);
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert!(order.borrow()[0].ends_with("ShutdownRequested"));
+        assert_eq!(2, order.borrow().len());
+    }
+}
@@ -0,0 +1,220 @@
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
+use std::rc::Rc;
+use super::{EventBus, IntoEvent, TopicKey, TopicMetrics};
+use super::access::lists;
+use super::state::BusState;
+use super::topic::topic_str;
+
+/// # Permission Denied
+///
+/// The error returned by a `Publisher` or `TopicInspector` for an event name it has no permission for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionDenied<K: TopicKey = String> {
+    /// The event name the handle has no permission for.
+    pub topic: K,
+}
+
+impl<K: TopicKey> fmt::Display for PermissionDenied<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "No permission for topic {:?}", self.topic)
+    }
+}
+
+impl<K: TopicKey> Error for PermissionDenied<K> {}
+
+/// The event names a handle is permitted to use, as they were passed.
+struct Permissions<K: TopicKey> {
+    state: Rc<RefCell<BusState<K>>>,
+    topics: Vec<String>,
+}
+
+impl<K: TopicKey> Permissions<K> {
+    fn new(state: Rc<RefCell<BusState<K>>>, topics: &[&str]) -> Permissions<K> {
+        Permissions { state, topics: topics.iter().map(|topic| topic.to_string()).collect() }
+    }
+
+    /// Returns the normalized event name, or an error when it is not permitted.
+    /// The event name and the permitted topics are both normalized with the current normalizer of the event bus.
+    /// Event names that are not strings are never permitted.
+    fn check(&self, event_name: K) -> Result<K, PermissionDenied<K>> {
+        let state = self.state.borrow();
+        let event_name = state.normalize(event_name);
+        let mut topics = self.topics.clone();
+        state.normalize_entries(&mut topics);
+        match topic_str(&event_name).is_some_and(|topic| lists(&topics, topic)) {
+            true => Ok(event_name),
+            false => Err(PermissionDenied { topic: event_name }),
+        }
+    }
+}
+
+/// # Publisher
+///
+/// A handle to an event bus that can only register events on the event names it is permitted to,
+/// created with `EventBus::publisher_for_topics` to hand to code that is not trusted with the whole event bus.
+/// Events on other event names are rejected by the handle, and never reach the event bus.
+///
+/// ## Methods
+///
+/// * `send` - Registers an event with the event bus, when the event name is permitted.
+///
+/// * `permits` - Returns whether the event name is permitted.
+pub struct Publisher<K: TopicKey = String> {
+    permissions: Rc<Permissions<K>>,
+}
+
+impl<K: TopicKey> Clone for Publisher<K> {
+    fn clone(&self) -> Self {
+        Publisher { permissions: self.permissions.clone() }
+    }
+}
+
+impl<K: TopicKey> Publisher<K> {
+    /// # Send
+    ///
+    /// Registers an event with the event bus, see `EventBus::register`,
+    /// or returns an error when the publisher has no permission for the event name.
+    pub fn send(&self, event_name: impl Into<K>, message: impl IntoEvent) -> Result<&Self, PermissionDenied<K>> {
+        let event_name = self.permissions.check(event_name.into())?;
+        self.permissions.state.borrow_mut().register(event_name, message.into_event());
+        Ok(self)
+    }
+
+    /// # Permits
+    ///
+    /// Returns whether the publisher can send events on the event name.
+    pub fn permits(&self, event_name: impl Into<K>) -> bool {
+        self.permissions.check(event_name.into()).is_ok()
+    }
+}
+
+/// # Topic Inspector
+///
+/// A read-only handle to an event bus that can only look at the event names it is permitted to,
+/// created with `EventBus::inspector_for_topics`.
+///
+/// ## Methods
+///
+/// * `pending` - Returns the number of queued events of an event name.
+///
+/// * `subscriber_names` - Returns the names of the subscribers of an event name.
+///
+/// * `metrics` - Returns the counters of an event name.
+pub struct TopicInspector<K: TopicKey = String> {
+    permissions: Rc<Permissions<K>>,
+}
+
+impl<K: TopicKey> Clone for TopicInspector<K> {
+    fn clone(&self) -> Self {
+        TopicInspector { permissions: self.permissions.clone() }
+    }
+}
+
+impl<K: TopicKey> TopicInspector<K> {
+    /// # Pending
+    ///
+    /// Returns the number of queued events of an event name, see `EventBus::pending`.
+    pub fn pending(&self, event_name: impl Into<K>) -> Result<usize, PermissionDenied<K>> {
+        let event_name = self.permissions.check(event_name.into())?;
+        Ok(self.permissions.state.borrow().events.get(&event_name).map_or(0, Vec::len))
+    }
+
+    /// # Subscriber Names
+    ///
+    /// Returns the names of the subscribers of an event name, in subscription order.
+    /// The subscribers are not known while the event name is being published.
+    pub fn subscriber_names(&self, event_name: impl Into<K>) -> Result<Vec<String>, PermissionDenied<K>> {
+        let event_name = self.permissions.check(event_name.into())?;
+        let state = self.permissions.state.borrow();
+        Ok(state.subscribers.get(&event_name).into_iter().flatten()
+            .map(|subscription| subscription.listener.name().to_string())
+            .collect())
+    }
+
+    /// # Metrics
+    ///
+    /// Returns the counters of an event name, see `EventBus::metrics`.
+    pub fn metrics(&self, event_name: impl Into<K>) -> Result<TopicMetrics, PermissionDenied<K>> {
+        let event_name = self.permissions.check(event_name.into())?;
+        Ok(self.permissions.state.borrow().metrics.topic(&event_name))
+    }
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Publisher For Topics
+    ///
+    /// Returns a handle that can only register events on the event names the topics match, see `Publisher`.
+    /// A topic is an event name, or a prefix ending with `*` like `"plugin_x.*"`, which matches every
+    /// event name starting with it. The topics are normalized like the event names, with the normalizer
+    /// the event bus has when the handle is used.
+    pub fn publisher_for_topics(&self, topics: &[&str]) -> Publisher<K> {
+        Publisher { permissions: Rc::new(Permissions::new(self.state.clone(), topics)) }
+    }
+
+    /// # Inspector For Topics
+    ///
+    /// Returns a read-only handle that can only look at the event names the topics match, see `TopicInspector`.
+    /// The topics are matched like those of `publisher_for_topics`.
+    pub fn inspector_for_topics(&self, topics: &[&str]) -> TopicInspector<K> {
+        TopicInspector { permissions: Rc::new(Permissions::new(self.state.clone(), topics)) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PermissionDenied;
+    use crate::EventBus;
+    use crate::subscribers::CollectingSubscriber;
+
+    #[test]
+    fn test_scoped_publisher_rejects_other_topics() {
+        let event_bus = EventBus::new();
+        let (collector, received) = CollectingSubscriber::<u32>::new();
+        let (other, others) = CollectingSubscriber::<u32>::new();
+        event_bus
+            .subscribe_listener("plugin_x.loaded", collector)
+            .subscribe_listener("orders.created", other);
+        let publisher = event_bus.publisher_for_topics(&["plugin_x.*"]);
+
+        assert!(publisher.send("plugin_x.loaded", 1u32).is_ok());
+        let result = publisher.send("orders.created", 2u32);
+        assert_eq!(Some(PermissionDenied { topic: "orders.created".to_string() }), result.err());
+        assert!(!publisher.clone().permits("orders.created"));
+        assert_eq!(0, event_bus.pending(&"orders.created".to_string()));
+        assert_eq!(0, event_bus.metrics().topic(&"orders.created".to_string()).registered);
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![1], *received.borrow());
+        assert!(others.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_inspector_only_looks_at_permitted_topics() {
+        let event_bus = EventBus::new();
+        let (collector, _) = CollectingSubscriber::<u32>::new();
+        event_bus
+            .subscribe_listener("plugin_x.loaded", collector)
+            .register("plugin_x.loaded", 1u32)
+            .register("orders.created", 2u32);
+        let inspector = event_bus.inspector_for_topics(&["plugin_x.*"]);
+
+        assert_eq!(Ok(1), inspector.pending("plugin_x.loaded"));
+        assert_eq!(1, inspector.subscriber_names("plugin_x.loaded").unwrap().len());
+        assert_eq!(1, inspector.metrics("plugin_x.loaded").unwrap().registered);
+        assert_eq!(Err(PermissionDenied { topic: "orders.created".to_string() }), inspector.pending("orders.created"));
+    }
+
+    #[test]
+    fn test_permissions_follow_a_later_normalizer() {
+        let event_bus = EventBus::new();
+        let publisher = event_bus.publisher_for_topics(&["Plugin_X.*"]);
+        assert!(!publisher.permits("plugin_x.loaded"));
+
+        event_bus.set_topic_normalizer(|topic| topic.to_lowercase());
+        assert!(publisher.send("PLUGIN_X.loaded", 1u32).is_ok());
+        assert_eq!(1, event_bus.pending(&"plugin_x.loaded".to_string()));
+        assert!(!publisher.permits("orders.created"));
+    }
+}
@@ -0,0 +1,257 @@
+use std::time::Duration;
+use super::{EventBus, Instant, TopicKey};
+use super::cancel::Halt;
+
+/// # Budget
+///
+/// How much a `pump` may deliver. A pump stops at whichever limit it reaches first.
+///
+/// ## Fields
+///
+/// * `max_events` - The number of events the pump delivers at most, or `None` for no limit.
+///
+/// * `max_duration` - How long the pump delivers events at most, on the clock of the event bus, or `None` for no limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Budget {
+    /// The number of events the pump delivers at most.
+    pub max_events: Option<usize>,
+    /// How long the pump delivers events at most.
+    pub max_duration: Option<Duration>,
+}
+
+/// # Pump Result
+///
+/// What a `pump` delivered, and what it left for the next one.
+///
+/// ## Fields
+///
+/// * `delivered` - The number of events the pump delivered.
+///
+/// * `remaining` - The number of events that stay queued for the next pump.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PumpResult {
+    /// The number of events the pump delivered.
+    pub delivered: usize,
+    /// The number of events that stay queued for the next pump.
+    pub remaining: usize,
+}
+
+/// # Remaining Work
+///
+/// The events a `publish_some` left queued.
+///
+/// ## Fields
+///
+/// * `total_pending` - The number of queued events, over all event names.
+///
+/// * `per_topic` - The number of queued events per event name, in the order they are dispatched,
+///   the event names without queued events left out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemainingWork<K: TopicKey = String> {
+    /// The number of queued events, over all event names.
+    pub total_pending: usize,
+    /// The number of queued events per event name, in the order they are dispatched.
+    pub per_topic: Vec<(K, usize)>,
+}
+
+/// # Publish Outcome
+///
+/// Whether a `publish_with_deadline` published every queued event before its deadline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PublishOutcome {
+    /// Every queued event was published.
+    Completed,
+    /// The deadline passed, the events that were not published yet stay queued.
+    DeadlineExceeded { remaining: usize },
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Publish Some
+    ///
+    /// Publishes like `publish`, delivering at most `max_messages` events, and returns the events left queued
+    /// so the caller can decide to publish again or yield. Like `pump`, the next call continues
+    /// with the event name the previous call stopped at.
+    pub fn publish_some(&self, max_messages: usize) -> Result<RemainingWork<K>, String> {
+        self.publish_cycle(&Halt::budget(Some(max_messages), None))?;
+        let state = self.state.borrow();
+        let mut per_topic: Vec<(K, usize)> = state.events.iter()
+            .filter(|(_, messages)| !messages.is_empty())
+            .map(|(event_name, messages)| (event_name.clone(), messages.len()))
+            .collect();
+        per_topic.sort_by_cached_key(|(event_name, _)| state.dispatch_rank(event_name, &state.events[event_name]));
+        Ok(RemainingWork { total_pending: per_topic.iter().map(|(_, count)| count).sum(), per_topic })
+    }
+
+    /// # Pump
+    ///
+    /// Publishes like `publish`, until the budget is spent. The events that were not delivered stay queued,
+    /// and the next pump continues with the event name the previous pump stopped at, before the event names
+    /// that are dispatched earlier, also those of a higher priority level. So event names that are dispatched later
+    /// are not starved by a small budget. With `DispatchOrder::GlobalFifo`, the next pump continues with the oldest event.
+    /// The budget is checked before each event, so a subscriber is never interrupted,
+    /// and debounced deliveries wait for a pump that does not run out of budget.
+    pub fn pump(&self, budget: Budget) -> Result<PumpResult, String> {
+        let clock = self.state.borrow().clock.clone();
+        let deadline = budget.max_duration.map(|max_duration| (clock.now() + max_duration, clock));
+        let halt = Halt::budget(budget.max_events, deadline);
+        self.publish_cycle(&halt)?;
+        let remaining = self.state.borrow().events.values().map(Vec::len).sum();
+        Ok(PumpResult { delivered: halt.delivered(), remaining })
+    }
+
+    /// # Publish With Deadline
+    ///
+    /// Publishes like `publish`, until the deadline passes on the clock of the event bus.
+    /// The clock is checked before each event, so a subscriber is never interrupted, and a cycle
+    /// that runs past its deadline is not an error. Like `pump`, the events that were not delivered stay queued,
+    /// and the next call continues with the event name the previous call stopped at.
+    pub fn publish_with_deadline(&self, deadline: Instant) -> Result<PublishOutcome, String> {
+        let clock = self.state.borrow().clock.clone();
+        let halt = Halt::budget(None, Some((deadline, clock)));
+        self.publish_cycle(&halt)?;
+        if !halt.is_expired() {
+            return Ok(PublishOutcome::Completed);
+        }
+        let remaining = self.state.borrow().events.values().map(Vec::len).sum();
+        Ok(PublishOutcome::DeadlineExceeded { remaining })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use super::{Budget, PublishOutcome, PumpResult, RemainingWork};
+    use crate::{Clock, DispatchOrder, EventBus, Subscriber};
+    use crate::subscribers::CollectingSubscriber;
+    use crate::testing::ManualClock;
+
+    #[test]
+    fn test_pump_spreads_queue_over_frames() {
+        let event_bus = EventBus::new();
+        let (collector, received) = CollectingSubscriber::<u32>::new();
+        event_bus.subscribe_listener("tick", collector);
+        for tick in 0..10u32 {
+            event_bus.register("tick", tick);
+        }
+
+        let budget = Budget { max_events: Some(4), ..Budget::default() };
+        assert_eq!(Ok(PumpResult { delivered: 4, remaining: 6 }), event_bus.pump(budget));
+        assert_eq!(Ok(PumpResult { delivered: 4, remaining: 2 }), event_bus.pump(budget));
+        assert_eq!(Ok(PumpResult { delivered: 2, remaining: 0 }), event_bus.pump(budget));
+        assert_eq!((0..10).collect::<Vec<u32>>(), *received.borrow());
+    }
+
+    #[test]
+    fn test_pump_continues_at_the_event_name_it_stopped() {
+        let event_bus = EventBus::new();
+        let (collector, received) = CollectingSubscriber::<&str>::new();
+        event_bus
+            .set_topic_priority("input", 1)
+            .subscribe_pattern("#", collector)
+            .unwrap()
+            .register("input", "input 1")
+            .register("physics", "physics 1")
+            .register("physics", "physics 2")
+            .register("render", "render 1");
+
+        let budget = Budget { max_events: Some(2), ..Budget::default() };
+        assert_eq!(Ok(PumpResult { delivered: 2, remaining: 2 }), event_bus.pump(budget));
+        event_bus.register("input", "input 2");
+        assert_eq!(Ok(PumpResult { delivered: 2, remaining: 1 }), event_bus.pump(budget));
+        assert_eq!(Ok(PumpResult { delivered: 1, remaining: 0 }), event_bus.pump(budget));
+
+        assert_eq!(vec!["input 1", "physics 1", "physics 2", "render 1", "input 2"], *received.borrow());
+    }
+
+    #[test]
+    fn test_pump_stops_when_time_is_spent() {
+        let clock = ManualClock::new();
+        let event_bus = EventBus::new();
+        let slow = clock.clone();
+        event_bus
+            .set_clock(clock)
+            .subscribe_listener("job", <dyn Subscriber>::builder().on_event(move |_| {
+                slow.advance(Duration::from_millis(5));
+                Ok(())
+            }).build());
+        for job in 0..5u32 {
+            event_bus.register("job", job);
+        }
+
+        let budget = Budget { max_duration: Some(Duration::from_millis(12)), ..Budget::default() };
+        assert_eq!(Ok(PumpResult { delivered: 3, remaining: 2 }), event_bus.pump(budget));
+        assert_eq!(Ok(PumpResult { delivered: 2, remaining: 0 }), event_bus.pump(Budget::default()));
+    }
+
+    #[test]
+    fn test_publish_stops_at_the_deadline_and_resumes() {
+        let clock = ManualClock::new();
+        let event_bus: EventBus = EventBus::with_clock(clock.clone());
+        let (collector, received) = CollectingSubscriber::<u32>::new();
+        let slow = clock.clone();
+        event_bus
+            .subscribe_listener("render", <dyn Subscriber>::builder().on_event(move |_| {
+                slow.advance(Duration::from_millis(4));
+                Ok(())
+            }).build())
+            .subscribe_pattern("#", collector)
+            .unwrap();
+        for frame in 0..4u32 {
+            event_bus.register("render", frame);
+        }
+        event_bus.register("input", 10u32);
+
+        let deadline = clock.now() + Duration::from_millis(10);
+        assert_eq!(Ok(PublishOutcome::DeadlineExceeded { remaining: 2 }), event_bus.publish_with_deadline(deadline));
+        assert_eq!(3, received.borrow().len());
+
+        let deadline = clock.now() + Duration::from_millis(10);
+        assert_eq!(Ok(PublishOutcome::Completed), event_bus.publish_with_deadline(deadline));
+        assert_eq!(5, received.borrow().len());
+    }
+
+    #[test]
+    fn test_aged_event_is_not_starved_by_higher_levels() {
+        let event_bus = EventBus::new();
+        let (collector, received) = CollectingSubscriber::<&str>::new();
+        event_bus
+            .set_dispatch_order(DispatchOrder::GlobalFifo)
+            .set_topic_priority("alerts", 5)
+            .set_priority_aging(2)
+            .subscribe_pattern("#", collector)
+            .unwrap()
+            .register("reports", "report");
+
+        let mut cycles = 0;
+        while !received.borrow().contains(&"report") {
+            assert!(cycles < 5, "The report starved");
+            event_bus.register("alerts", "alert");
+            event_bus.publish_some(1).unwrap();
+            cycles += 1;
+        }
+        assert_eq!(4, cycles);
+        assert_eq!(0, event_bus.topic_priority(&"reports".to_string()));
+        assert_eq!((5, 7), (event_bus.topic_priority(&"alerts".to_string()), event_bus.effective_priority(&"alerts".to_string())));
+    }
+
+    #[test]
+    fn test_publish_some_reports_remaining_work() {
+        let event_bus = EventBus::new();
+        let (collector, received) = CollectingSubscriber::<u32>::new();
+        event_bus
+            .subscribe_pattern("#", collector)
+            .unwrap()
+            .register("a", 1u32)
+            .register("a", 2u32)
+            .register("b", 3u32)
+            .register("b", 4u32)
+            .register("c", 5u32);
+
+        let remaining = RemainingWork { total_pending: 2, per_topic: vec![("b".to_string(), 1), ("c".to_string(), 1)] };
+        assert_eq!(Ok(remaining), event_bus.publish_some(3));
+        assert_eq!(vec![1, 2, 3], *received.borrow());
+
+        assert_eq!(Ok(RemainingWork { total_pending: 0, per_topic: Vec::new() }), event_bus.publish_some(3));
+        assert_eq!(vec![1, 2, 3, 4, 5], *received.borrow());
+    }
+}
@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+use super::{LatencyStats, TopicKey};
+
+/// # Publish Report
+///
+/// What happened to the events of a single publish, grouped by event name.
+#[derive(Debug, Clone)]
+pub struct PublishReport<K: TopicKey = String> {
+    pub(crate) topics: HashMap<K, TopicReport>,
+}
+
+impl<K: TopicKey> Default for PublishReport<K> {
+    fn default() -> Self {
+        PublishReport { topics: HashMap::new() }
+    }
+}
+
+impl<K: TopicKey> PublishReport<K> {
+    /// # Topic
+    ///
+    /// Returns the counts of an event name, all zero if it was not published.
+    pub fn topic(&self, event_name: &K) -> TopicReport {
+        self.topics.get(event_name).copied().unwrap_or_default()
+    }
+
+    /// # Topics
+    ///
+    /// Returns the counts of every published event name.
+    pub fn topics(&self) -> impl Iterator<Item = (&K, &TopicReport)> {
+        self.topics.iter()
+    }
+
+    pub(crate) fn topic_mut(&mut self, event_name: &K) -> &mut TopicReport {
+        self.topics.entry(event_name.clone()).or_default()
+    }
+
+    /// Adds the counts of a run of events of an event name, unless no event was dispatched.
+    pub(crate) fn add(&mut self, event_name: &K, counts: TopicReport) {
+        if counts.latency.count == 0 {
+            return;
+        }
+        let topic = self.topic_mut(event_name);
+        topic.handled += counts.handled;
+        topic.ignored += counts.ignored;
+        topic.failed += counts.failed;
+        if counts.last_sequence.is_some() {
+            topic.last_sequence = counts.last_sequence;
+        }
+        topic.latency.merge(&counts.latency);
+    }
+}
+
+/// # Topic Report
+///
+/// The counts of a single event name in a publish.
+///
+/// ## Fields
+///
+/// * `handled` - The number of events handled by at least one subscriber.
+///
+/// * `ignored` - The number of events ignored by all of their subscribers.
+///
+/// * `failed` - The number of events a subscriber returned an error for, and no other subscriber handled.
+///
+/// * `last_sequence` - The sequence number of the last handled event.
+///
+/// * `latency` - How long the dispatched events were queued.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TopicReport {
+    /// The number of events handled by at least one subscriber.
+    pub handled: u64,
+    /// The number of events ignored by all of their subscribers.
+    pub ignored: u64,
+    /// The number of events a subscriber returned an error for, and no other subscriber handled.
+    pub failed: u64,
+    /// The sequence number of the last handled event.
+    pub last_sequence: Option<u64>,
+    /// How long the dispatched events were queued.
+    pub latency: LatencyStats,
+}
@@ -0,0 +1,291 @@
+use std::error::Error;
+use std::fmt;
+use super::{CloneRegistry, Event, EventBus, InvalidPattern, TopicKey};
+use super::access::TopicAccess;
+use super::pattern::TopicPattern;
+use super::state::BusState;
+use super::topic::{topic_from_str, topic_str};
+
+/// # Route Mode
+///
+/// What a route does with the events of the event names it matches, see `EventBus::add_route`.
+///
+/// ## Variants
+///
+/// * `Copy` - A clone of each event is delivered on the target event name as well.
+///   Events whose payload type is not registered as cloneable are not copied, which is logged.
+///
+/// * `Move` - The events are delivered on the target event name instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteMode {
+    /// A clone of each event is delivered on the target event name as well.
+    Copy,
+    /// The events are delivered on the target event name instead.
+    Move,
+}
+
+/// # Topic Route
+///
+/// A forwarding rule between event names, returned by `EventBus::routes`.
+///
+/// ## Fields
+///
+/// * `from` - The event name or wildcard pattern the route matches.
+///
+/// * `to` - The event name the events are forwarded to.
+///
+/// * `mode` - Whether the events are copied or moved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopicRoute {
+    /// The event name or wildcard pattern the route matches.
+    pub from: String,
+    /// The event name the events are forwarded to.
+    pub to: String,
+    /// Whether the events are copied or moved.
+    pub mode: RouteMode,
+}
+
+/// A route with its parsed pattern.
+pub(crate) struct Route {
+    pub(crate) route: TopicRoute,
+    pattern: TopicPattern,
+}
+
+/// # Route Error
+///
+/// The error returned when a route cannot be added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouteError {
+    /// The pattern the route matches is malformed.
+    InvalidPattern(InvalidPattern),
+    /// The target of a route must be an event name, not a pattern.
+    PatternTarget { to: String },
+    /// The route would forward events back to an event name it matches, through the event names of the path.
+    Cycle { path: Vec<String> },
+    /// The topic policy denies an event name of the route.
+    Denied { topic: String },
+}
+
+impl fmt::Display for RouteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RouteError::InvalidPattern(e) => e.fmt(f),
+            RouteError::PatternTarget { to } => write!(f, "Cannot route to the pattern {:?}", to),
+            RouteError::Cycle { path } => write!(f, "Routing cycle: {}", path.join(" -> ")),
+            RouteError::Denied { topic } => write!(f, "Topic {:?} is denied by the topic policy", topic),
+        }
+    }
+}
+
+impl Error for RouteError {}
+
+impl<K: TopicKey> BusState<K> {
+    /// Returns the path of event names through which events routed to `to` reach an event name `from` matches.
+    fn route_cycle(&self, from: &TopicPattern, to: &str) -> Option<Vec<String>> {
+        if from.matches(to) {
+            return Some(vec![to.to_string()]);
+        }
+        self.routes.iter()
+            .filter(|route| route.pattern.matches(to))
+            .find_map(|route| self.route_cycle(from, &route.route.to))
+            .map(|mut path| {
+                path.insert(0, to.to_string());
+                path
+            })
+    }
+
+    /// Applies the routes to the events taken out for a publish: moved events are delivered on their target
+    /// instead, copies are delivered right after the events they were copied from.
+    pub(crate) fn apply_routes(&mut self, events: Vec<(K, Vec<Event>)>) -> Vec<(K, Vec<Event>)> {
+        if self.routes.is_empty() {
+            return events;
+        }
+        let mut routed = Vec::with_capacity(events.len());
+        let mut hits = Vec::new();
+        for (event_name, messages) in events {
+            self.route(event_name, messages, &mut routed, &mut hits);
+        }
+        for hit in hits {
+            match hit {
+                RouteHit::Forwarded { route, count } => self.metrics.record_route_hits(&route.from, &route.to, count),
+                RouteHit::NotCopied { route, payload_type } => self.logger.route_not_copied(&route.from, &route.to, payload_type),
+            }
+        }
+        routed
+    }
+
+    /// Applies the routes to the queued events a dry run plans, like `apply_routes` would,
+    /// without counting the hits of the routes.
+    pub(crate) fn plan_routes<'a>(&self, events: Vec<(K, Vec<&'a Event>)>) -> Vec<(K, Vec<&'a Event>)> {
+        let mut routed = Vec::with_capacity(events.len());
+        for (event_name, messages) in events {
+            self.route(event_name, messages, &mut routed, &mut Vec::new());
+        }
+        routed
+    }
+
+    fn route<M: Routable>(&self, event_name: K, mut messages: Vec<M>, routed: &mut Vec<(K, Vec<M>)>, hits: &mut Vec<RouteHit>) {
+        let Some(topic) = topic_str(&event_name).map(str::to_string) else {
+            routed.push((event_name, messages));
+            return;
+        };
+        let matching: Vec<TopicRoute> = self.routes.iter()
+            .filter(|route| route.pattern.matches(&topic))
+            .map(|route| route.route.clone())
+            .collect();
+        let mut forwarded = Vec::new();
+        let mut moved = false;
+        for route in matching {
+            let Some(to) = topic_from_str::<K>(&route.to) else {
+                continue;
+            };
+            match route.mode {
+                RouteMode::Copy => {
+                    let mut copies = Vec::with_capacity(messages.len());
+                    for message in &messages {
+                        match message.copy(&self.cloneables) {
+                            Some(copy) => copies.push(copy),
+                            None => hits.push(RouteHit::NotCopied { route: route.clone(), payload_type: message.event().payload_type_name() }),
+                        }
+                    }
+                    hits.push(RouteHit::Forwarded { route, count: copies.len() });
+                    forwarded.push((to, copies));
+                }
+                RouteMode::Move if !moved => {
+                    moved = true;
+                    hits.push(RouteHit::Forwarded { route, count: messages.len() });
+                    forwarded.push((to, std::mem::take(&mut messages)));
+                }
+                RouteMode::Move => {}
+            }
+        }
+        if !moved {
+            routed.push((event_name, messages));
+        }
+        for (to, messages) in forwarded {
+            if !messages.is_empty() {
+                self.route(to, messages, routed, hits);
+            }
+        }
+    }
+}
+
+/// An event the routes apply to: taken out for a publish, or queued and planned by a dry run.
+trait Routable: Sized {
+    fn event(&self) -> &Event;
+
+    /// Returns the copy a `RouteMode::Copy` route forwards, `None` when the payload cannot be cloned.
+    fn copy(&self, cloneables: &CloneRegistry) -> Option<Self>;
+}
+
+impl Routable for Event {
+    fn event(&self) -> &Event {
+        self
+    }
+
+    fn copy(&self, cloneables: &CloneRegistry) -> Option<Event> {
+        self.try_clone(cloneables).ok()
+    }
+}
+
+impl Routable for &Event {
+    fn event(&self) -> &Event {
+        self
+    }
+
+    /// A planned copy is the queued event itself, as the copy is only made by the publish.
+    fn copy(&self, cloneables: &CloneRegistry) -> Option<Self> {
+        cloneables.can_clone(self).then_some(*self)
+    }
+}
+
+/// What a route did to the events of a publish, counted once the routes are applied.
+enum RouteHit {
+    Forwarded { route: TopicRoute, count: usize },
+    NotCopied { route: TopicRoute, payload_type: &'static str },
+}
+
+impl EventBus {
+    /// # Add Route
+    ///
+    /// Forwards the events of the event names matching `from`, an event name or an MQTT-style wildcard pattern,
+    /// to the event name `to`. Each publish applies the routes to the queued events before it delivers them:
+    /// `RouteMode::Copy` delivers a clone of each event on `to` as well, which needs the payload type
+    /// registered with `register_cloneable`, and `RouteMode::Move` delivers the events on `to` instead.
+    /// Routes are followed from event name to event name; when several move routes match, the first one added wins.
+    ///
+    /// Returns an error when the pattern is malformed, `to` is a pattern, the topic policy denies `to`
+    /// or the event name `from`, or the routes would forward events back to an event name they came from.
+    pub fn add_route(&self, from: &str, to: &str, mode: RouteMode) -> Result<&Self, RouteError> {
+        let from = self.topic_key(from);
+        let to = self.topic_key(to);
+        if TopicPattern::is_pattern(&to) {
+            return Err(RouteError::PatternTarget { to });
+        }
+        let pattern = match TopicPattern::is_pattern(&from) {
+            true => TopicPattern::parse(&from).map_err(RouteError::InvalidPattern)?,
+            false => TopicPattern::exact(&from),
+        };
+        let mut state = self.state.borrow_mut();
+        if let Some(topic) = [&to, &from].into_iter().find(|topic| state.topic_access(*topic) == TopicAccess::Denied) {
+            return Err(RouteError::Denied { topic: topic.clone() });
+        }
+        if let Some(mut path) = state.route_cycle(&pattern, &to) {
+            path.insert(0, from);
+            return Err(RouteError::Cycle { path });
+        }
+        state.routes.push(Route { route: TopicRoute { from, to, mode }, pattern });
+        Ok(self)
+    }
+
+    /// # Routes
+    ///
+    /// Returns the routes between event names, in the order they were added.
+    pub fn routes(&self) -> Vec<TopicRoute> {
+        self.state.borrow().routes.iter().map(|route| route.route.clone()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RouteError, RouteMode};
+    use crate::EventBus;
+    use crate::subscribers::CollectingSubscriber;
+
+    #[test]
+    fn test_copy_route_delivers_on_both_topics() {
+        let event_bus = EventBus::new();
+        let (orders, ordered) = CollectingSubscriber::<String>::new();
+        let (audit, audited) = CollectingSubscriber::<String>::new();
+        let (legacy, legacy_received) = CollectingSubscriber::<String>::new();
+        event_bus
+            .register_cloneable::<String>()
+            .subscribe_listener("shop/orders", orders)
+            .subscribe_listener("audit", audit)
+            .subscribe_listener("orders", legacy)
+            .add_route("shop/+", "audit", RouteMode::Copy).unwrap()
+            .add_route("legacy/orders", "shop/orders", RouteMode::Move).unwrap();
+
+        event_bus.register("shop/orders", "tea".to_string()).register("legacy/orders", "milk".to_string());
+        assert_eq!(Ok(()), event_bus.publish());
+
+        assert_eq!(vec!["tea".to_string(), "milk".to_string()], *ordered.borrow());
+        assert_eq!(vec!["tea".to_string(), "milk".to_string()], *audited.borrow());
+        assert!(legacy_received.borrow().is_empty());
+        assert_eq!(2, event_bus.metrics().route_hits("shop/+", "audit"));
+        assert_eq!(2, event_bus.routes().len());
+    }
+
+    #[test]
+    fn test_cyclic_route_is_rejected() {
+        let event_bus = EventBus::new();
+        event_bus
+            .add_route("a", "b", RouteMode::Copy).unwrap()
+            .add_route("b", "c/d", RouteMode::Move).unwrap();
+
+        let path = vec!["c/#".to_string(), "a".to_string(), "b".to_string(), "c/d".to_string()];
+        assert_eq!(Some(RouteError::Cycle { path }), event_bus.add_route("c/#", "a", RouteMode::Copy).err());
+        assert_eq!(Some(RouteError::PatternTarget { to: "x/+".to_string() }), event_bus.add_route("a", "x/+", RouteMode::Copy).err());
+        assert_eq!(2, event_bus.routes().len());
+    }
+}
@@ -0,0 +1,157 @@
+use std::any::{Any, TypeId};
+use std::error::Error;
+use std::fmt;
+use super::{Event, EventSink, IntoEvent};
+
+/// A routing rule of a bus router, with the sink of the event bus the matching events go to.
+enum Route {
+    Type(TypeId, EventSink),
+    TopicPrefix(String, EventSink),
+}
+
+/// # Bus Router
+///
+/// Forwards registered events to one of several event buses, by the type of their payload or their event name.
+/// The rules are tried in the order they were added, and the first matching rule wins;
+/// events no rule matches go to the default sink, or are returned as unroutable when there is none.
+///
+/// ## Methods
+///
+/// * `new` - Creates a router without rules.
+///
+/// * `route_type` - Routes the events with a payload type to a sink.
+///
+/// * `route_topic_prefix` - Routes the events of the event names starting with a prefix to a sink.
+///
+/// * `default_sink` - Sets the sink of the events no rule matches.
+///
+/// * `register` - Registers an event with the event bus of the first matching rule.
+#[derive(Default)]
+pub struct BusRouter {
+    routes: Vec<Route>,
+    default_sink: Option<EventSink>,
+}
+
+impl BusRouter {
+    /// # New
+    ///
+    /// Creates a router without rules or default sink.
+    pub fn new() -> BusRouter {
+        BusRouter::default()
+    }
+
+    /// # Route Type
+    ///
+    /// Routes the events with a payload of type `T` to the sink.
+    pub fn route_type<T: Any>(mut self, sink: EventSink) -> BusRouter {
+        self.routes.push(Route::Type(TypeId::of::<T>(), sink));
+        self
+    }
+
+    /// # Route Topic Prefix
+    ///
+    /// Routes the events of the event names starting with the prefix to the sink.
+    pub fn route_topic_prefix(mut self, prefix: &str, sink: EventSink) -> BusRouter {
+        self.routes.push(Route::TopicPrefix(prefix.to_string(), sink));
+        self
+    }
+
+    /// # Default Sink
+    ///
+    /// Sets the sink of the events no rule matches.
+    pub fn default_sink(mut self, sink: EventSink) -> BusRouter {
+        self.default_sink = Some(sink);
+        self
+    }
+
+    /// # Register
+    ///
+    /// Registers the event with the event bus of the first rule matching its payload type or event name,
+    /// or with the default sink. Returns the event as unroutable when nothing matches.
+    pub fn register(&self, event_name: &str, message: impl IntoEvent) -> Result<(), UnroutableEvent> {
+        let message = message.into_event();
+        let type_id = (*message.data).type_id();
+        let sink = self.routes.iter()
+            .find_map(|route| match route {
+                Route::Type(routed, sink) if *routed == type_id => Some(sink),
+                Route::TopicPrefix(prefix, sink) if event_name.starts_with(prefix.as_str()) => Some(sink),
+                _ => None,
+            })
+            .or(self.default_sink.as_ref());
+        match sink {
+            Some(sink) => {
+                sink.register(event_name, message);
+                Ok(())
+            }
+            None => Err(UnroutableEvent { topic: event_name.to_string(), event: Box::new(message) }),
+        }
+    }
+}
+
+/// # Unroutable Event
+///
+/// The error returned by `BusRouter::register` when no rule matches an event, and the router has no default sink.
+/// It holds the event, so it can be handled otherwise.
+#[derive(Debug)]
+pub struct UnroutableEvent {
+    /// The event name the event was registered on.
+    pub topic: String,
+    /// The event that could not be routed.
+    pub event: Box<Event>,
+}
+
+impl fmt::Display for UnroutableEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "No route for {:?} with a payload of type {}", self.topic, self.event.payload_type_name())
+    }
+}
+
+impl Error for UnroutableEvent {}
+
+#[cfg(test)]
+mod tests {
+    use super::BusRouter;
+    use crate::EventBus;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct NetworkPacket(Vec<u8>);
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Click {
+        x: i32,
+        y: i32,
+    }
+
+    fn pending(event_bus: &EventBus, topics: &[&str]) -> Vec<usize> {
+        topics.iter().map(|topic| event_bus.pending(&topic.to_string())).collect()
+    }
+
+    #[test]
+    fn test_events_are_routed_by_first_matching_rule() {
+        let (net_bus, ui_bus, default_bus) = (EventBus::new(), EventBus::new(), EventBus::new());
+        let router = BusRouter::new()
+            .route_type::<NetworkPacket>(net_bus.sink())
+            .route_topic_prefix("ui.", ui_bus.sink())
+            .default_sink(default_bus.sink());
+        let topics = ["ui.packet", "ui.click", "audit"];
+
+        router.register("ui.packet", NetworkPacket(vec![1, 2])).unwrap();
+        router.register("ui.click", Click { x: 3, y: 4 }).unwrap();
+        router.register("audit", "logged in".to_string()).unwrap();
+
+        assert_eq!(vec![1, 0, 0], pending(&net_bus, &topics));
+        assert_eq!(vec![0, 1, 0], pending(&ui_bus, &topics));
+        assert_eq!(vec![0, 0, 1], pending(&default_bus, &topics));
+    }
+
+    #[test]
+    fn test_unroutable_event_is_returned() {
+        let net_bus = EventBus::new();
+        let router = BusRouter::new().route_type::<NetworkPacket>(net_bus.sink());
+
+        let unroutable = router.register("ui.click", Click { x: 1, y: 2 }).unwrap_err();
+        assert_eq!("ui.click", unroutable.topic);
+        assert_eq!(Some(&Click { x: 1, y: 2 }), unroutable.event.get_data::<Click>());
+        assert_eq!(0, net_bus.pending(&"ui.click".to_string()));
+    }
+}
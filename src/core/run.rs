@@ -0,0 +1,232 @@
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::Duration;
+use super::{EventBus, TopicKey};
+#[cfg(feature = "signals")]
+use super::Signal;
+
+/// # Shutdown Signal
+///
+/// Stops a `run_loop`, also from another thread.
+/// Clones share the same state, so a subscriber or another thread can keep a clone to stop the loop with.
+///
+/// ## Methods
+///
+/// * `new` - Creates a signal that is not triggered.
+///
+/// * `trigger` - Stops the loop after its final drain, waking it when it is parked.
+///
+/// * `is_triggered` - Returns whether the signal was triggered.
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownSignal {
+    shared: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl ShutdownSignal {
+    /// # New
+    ///
+    /// Creates a signal that is not triggered.
+    pub fn new() -> ShutdownSignal {
+        ShutdownSignal::default()
+    }
+
+    /// # Trigger
+    ///
+    /// Stops the loop that checks the signal after its final drain, waking it when it is parked.
+    pub fn trigger(&self) {
+        *self.lock() = true;
+        self.shared.1.notify_all();
+    }
+
+    /// # Is Triggered
+    ///
+    /// Returns whether the signal was triggered.
+    pub fn is_triggered(&self) -> bool {
+        *self.lock()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, bool> {
+        self.shared.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Waits until the signal is triggered, or the timeout passed.
+    fn wait(&self, timeout: Duration) {
+        let triggered = self.lock();
+        let _ = self.shared.1.wait_timeout_while(triggered, timeout, |triggered| !*triggered);
+    }
+}
+
+/// # Run Options
+///
+/// How `run_loop` runs.
+///
+/// ## Fields
+///
+/// * `tick` - How long the loop parks at most when there is no work, before it checks its remote sources again.
+///
+/// * `shutdown` - The signal that stops the loop.
+///
+/// * `signals` - The OS signals that trigger the shutdown signal, set with `stop_on_signals`.
+#[derive(Debug, Clone)]
+pub struct RunOptions {
+    /// How long the loop parks at most when there is no work, before it checks its remote sources again.
+    pub tick: Duration,
+    /// The signal that stops the loop.
+    pub shutdown: ShutdownSignal,
+    /// The OS signals that trigger the shutdown signal, set with `stop_on_signals`.
+    #[cfg(feature = "signals")]
+    pub signals: Vec<Signal>,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        RunOptions {
+            tick: Duration::from_millis(10),
+            shutdown: ShutdownSignal::new(),
+            #[cfg(feature = "signals")]
+            signals: Vec::new(),
+        }
+    }
+}
+
+/// # Run Summary
+///
+/// What happened while `run_loop` ran.
+///
+/// ## Fields
+///
+/// * `cycles` - The number of publishes.
+///
+/// * `handled` - The number of events handled by at least one subscriber.
+///
+/// * `ignored` - The number of events ignored by all of their subscribers.
+///
+/// * `errors` - The number of publishes that stopped on a subscriber error.
+///
+/// * `signal` - The OS signal that stopped the loop, if one did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunSummary {
+    /// The number of publishes.
+    pub cycles: u64,
+    /// The number of events handled by at least one subscriber.
+    pub handled: u64,
+    /// The number of events ignored by all of their subscribers.
+    pub ignored: u64,
+    /// The number of publishes that stopped on a subscriber error.
+    pub errors: u64,
+    /// The OS signal that stopped the loop, if one did.
+    #[cfg(feature = "signals")]
+    pub signal: Option<Signal>,
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Run Loop
+    ///
+    /// Publishes until the shutdown signal of the options is triggered. Each publish reads the remote sources,
+    /// registers the delayed events that are due and publishes the queued events. When no work is left,
+    /// the loop parks for the tick of the options, or until the next delayed event is due, or the signal is triggered.
+    ///
+    /// A publish stopped by a subscriber error does not stop the loop: the error is routed like any
+    /// subscriber error and counted in the summary, and the event names that were not published yet
+    /// are published next. Like `publish`, the events of the failing event name after the failed event are dropped,
+    /// use `ErrorPolicy::Continue` to deliver them.
+    /// Once the signal is triggered, the loop publishes until no work is left before it returns,
+    /// so the events registered before the signal are not lost.
+    /// With the `signals` feature, the OS signals of the options trigger the signal as well, see `RunOptions::stop_on_signals`.
+    pub fn run_loop(&self, options: RunOptions) -> RunSummary {
+        let mut summary = RunSummary::default();
+        loop {
+            #[cfg(feature = "signals")]
+            if let Some(signal) = super::signal::take_signal(&options.signals) {
+                summary.signal.get_or_insert(signal);
+                options.shutdown.trigger();
+            }
+            let shutting_down = options.shutdown.is_triggered();
+            summary.cycles += 1;
+            match self.publish_report() {
+                Ok(report) => {
+                    for (_, topic) in report.topics() {
+                        summary.handled += topic.handled;
+                        summary.ignored += topic.ignored;
+                    }
+                }
+                Err(_) => summary.errors += 1,
+            }
+            if self.state.borrow().has_work() {
+                continue;
+            }
+            if shutting_down {
+                return summary;
+            }
+            options.shutdown.wait(self.park_time(options.tick));
+        }
+    }
+
+    /// Returns how long the loop can park, at most the tick and at most until the next delayed or recurring event
+    /// or heartbeat tick is due, or the window of held events closes.
+    fn park_time(&self, tick: Duration) -> Duration {
+        let state = self.state.borrow();
+        let now = state.clock.now();
+        state.next_scheduled().into_iter()
+            .chain(state.next_heartbeat())
+            .chain(state.next_window())
+            .map(|due| due.saturating_duration_since(now))
+            .fold(tick, Duration::min)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use std::time::Duration;
+    use super::RunOptions;
+    use crate::{ErrorPolicy, EventBus, Subscriber, SubscriberFailure};
+    use crate::subscribers::CollectingSubscriber;
+
+    #[test]
+    fn test_shutdown_drains_registered_events() {
+        let event_bus = Rc::new(EventBus::new());
+        let options = RunOptions { tick: Duration::from_millis(1), ..RunOptions::default() };
+        let (collector, received) = CollectingSubscriber::<u32>::new();
+        let (registering, shutdown) = (Rc::downgrade(&event_bus), options.shutdown.clone());
+        event_bus
+            .subscribe_listener("count", collector)
+            .subscribe_listener("count", <dyn Subscriber>::builder().on_event(move |event| {
+                let count = *event.try_get_data::<u32>()?;
+                if count == 5 {
+                    shutdown.trigger();
+                }
+                if count < 10 {
+                    registering.upgrade().unwrap().register("count", count + 1);
+                }
+                Ok(())
+            }).build())
+            .register_after("count", 0u32, Duration::from_millis(5));
+
+        let summary = event_bus.run_loop(options);
+        assert_eq!((0..=10).collect::<Vec<u32>>(), *received.borrow());
+        assert_eq!((11, 0), (summary.handled, summary.errors));
+    }
+
+    #[test]
+    fn test_subscriber_error_does_not_stop_the_loop() {
+        let event_bus = EventBus::new();
+        let options = RunOptions::default();
+        let shutdown = options.shutdown.clone();
+        let (collector, failures) = CollectingSubscriber::<SubscriberFailure>::new();
+        event_bus
+            .set_error_policy(ErrorPolicy::Abort)
+            .route_errors_to("errors")
+            .subscribe_listener("errors", collector)
+            .subscribe_listener("job", <dyn Subscriber>::builder().on_event(|_| Err("job failed".to_string())).build())
+            .subscribe_listener("stop", <dyn Subscriber>::builder().on_event(move |_| {
+                shutdown.trigger();
+                Ok(())
+            }).build())
+            .register("job", 1u32)
+            .register("stop", ());
+
+        let summary = event_bus.run_loop(options);
+        assert_eq!(1, summary.errors);
+        assert_eq!(vec!["job failed".to_string()], failures.borrow().iter().map(|failure| failure.message.clone()).collect::<Vec<_>>());
+    }
+}
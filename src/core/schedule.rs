@@ -0,0 +1,222 @@
+use std::time::Duration;
+use super::{Event, EventBus, Instant, IntoEvent, TopicKey};
+use super::state::BusState;
+
+/// # Schedule Id
+///
+/// Identifies a delayed or recurring event of an event bus, returned by `schedule` and `schedule_every`,
+/// to cancel or reschedule it with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ScheduleId(u64);
+
+/// # Schedule Info
+///
+/// A delayed or recurring event that has not been registered yet, returned by `pending_schedules`.
+///
+/// ## Fields
+///
+/// * `id` - The id of the schedule.
+///
+/// * `topic` - The event name the event is registered on.
+///
+/// * `due` - When the event is due next, on the clock of the event bus.
+///
+/// * `interval` - The interval of a recurring event, `None` for a delayed event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduleInfo<K: TopicKey = String> {
+    /// The id of the schedule.
+    pub id: ScheduleId,
+    /// The event name the event is registered on.
+    pub topic: K,
+    /// When the event is due next, on the clock of the event bus.
+    pub due: Instant,
+    /// The interval of a recurring event, `None` for a delayed event.
+    pub interval: Option<Duration>,
+}
+
+/// The event of a schedule: a delayed event is registered once, a recurring event is made anew every interval.
+enum Scheduled {
+    Once(Event),
+    Every(Duration, Box<dyn Fn() -> Event>),
+}
+
+/// A delayed or recurring event, with the time it is due next.
+pub(crate) struct ScheduledEvent<K: TopicKey> {
+    id: ScheduleId,
+    topic: K,
+    due: Instant,
+    event: Scheduled,
+}
+
+impl<K: TopicKey> BusState<K> {
+    fn add_schedule(&mut self, topic: K, delay: Duration, event: Scheduled) -> ScheduleId {
+        self.next_schedule_id += 1;
+        let id = ScheduleId(self.next_schedule_id);
+        let due = self.clock.now() + delay;
+        self.scheduled.push(ScheduledEvent { id, topic, due, event });
+        id
+    }
+
+    /// Registers the delayed events that are due, and every interval of the recurring events that passed.
+    pub(crate) fn register_scheduled(&mut self) {
+        let now = self.clock.now();
+        let mut due = Vec::new();
+        for mut scheduled in std::mem::take(&mut self.scheduled) {
+            if scheduled.due > now {
+                self.scheduled.push(scheduled);
+                continue;
+            }
+            match scheduled.event {
+                Scheduled::Once(event) => due.push((scheduled.due, scheduled.topic, event)),
+                Scheduled::Every(interval, ref make) => {
+                    while scheduled.due <= now {
+                        due.push((scheduled.due, scheduled.topic.clone(), make()));
+                        scheduled.due += interval;
+                    }
+                    self.scheduled.push(scheduled);
+                }
+            }
+        }
+        due.sort_by_key(|(due, _, _)| *due);
+        for (_, topic, event) in due {
+            self.register(topic, event);
+        }
+    }
+
+    /// Returns when the next delayed or recurring event is due.
+    pub(crate) fn next_scheduled(&self) -> Option<Instant> {
+        self.scheduled.iter().map(|scheduled| scheduled.due).min()
+    }
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Schedule
+    ///
+    /// Registers an event once the delay has passed, according to the clock of the event bus,
+    /// like `register_after`. Returns the id to cancel or reschedule it with.
+    pub fn schedule(&self, event_name: impl Into<K>, message: impl IntoEvent, delay: Duration) -> ScheduleId {
+        let event_name = self.topic_key(event_name);
+        self.state.borrow_mut().add_schedule(event_name, delay, Scheduled::Once(message.into_event()))
+    }
+
+    /// # Schedule Every
+    ///
+    /// Registers an event holding a clone of the payload every interval, on the clock of the event bus,
+    /// until it is cancelled. The first event is due one interval from now. Each publish registers the events
+    /// that became due since the previous publish, and `run_loop` wakes up for them.
+    /// Returns the id to cancel or reschedule it with. Panics when the interval is zero.
+    pub fn schedule_every<T: Clone + 'static>(&self, event_name: impl Into<K>, payload: T, interval: Duration) -> ScheduleId {
+        assert!(!interval.is_zero(), "The interval of a recurring event cannot be zero");
+        let event_name = self.topic_key(event_name);
+        let make = Box::new(move || Event::new(payload.clone()));
+        self.state.borrow_mut().add_schedule(event_name, interval, Scheduled::Every(interval, make))
+    }
+
+    /// # Cancel Schedule
+    ///
+    /// Cancels a delayed or recurring event, returns whether it was still pending.
+    /// A delayed event that is due but not registered by a publish yet is cancelled as well;
+    /// events that are already registered are still published.
+    pub fn cancel_schedule(&self, id: ScheduleId) -> bool {
+        let mut state = self.state.borrow_mut();
+        let before = state.scheduled.len();
+        state.scheduled.retain(|scheduled| scheduled.id != id);
+        state.scheduled.len() < before
+    }
+
+    /// # Reschedule
+    ///
+    /// Makes a delayed or recurring event due once the new delay has passed from now, returns whether it was still pending.
+    /// A recurring event keeps its interval from then on.
+    pub fn reschedule(&self, id: ScheduleId, delay: Duration) -> bool {
+        let mut state = self.state.borrow_mut();
+        let due = state.clock.now() + delay;
+        match state.scheduled.iter_mut().find(|scheduled| scheduled.id == id) {
+            Some(scheduled) => {
+                scheduled.due = due;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// # Pending Schedules
+    ///
+    /// Returns the delayed and recurring events that are pending, ordered by when they are due.
+    pub fn pending_schedules(&self) -> Vec<ScheduleInfo<K>> {
+        let state = self.state.borrow();
+        let mut schedules: Vec<ScheduleInfo<K>> = state.scheduled.iter()
+            .map(|scheduled| ScheduleInfo {
+                id: scheduled.id,
+                topic: scheduled.topic.clone(),
+                due: scheduled.due,
+                interval: match &scheduled.event {
+                    Scheduled::Once(_) => None,
+                    Scheduled::Every(interval, _) => Some(*interval),
+                },
+            })
+            .collect();
+        schedules.sort_by_key(|schedule| (schedule.due, schedule.id));
+        schedules
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::{Clock, EventBus};
+    use crate::subscribers::CollectingSubscriber;
+    use crate::testing::ManualClock;
+
+    fn timeouts() -> (ManualClock, EventBus, std::rc::Rc<std::cell::RefCell<Vec<u32>>>) {
+        let clock = ManualClock::new();
+        let event_bus: EventBus = EventBus::with_clock(clock.clone());
+        let (collector, received) = CollectingSubscriber::<u32>::new();
+        event_bus.subscribe_listener("timeout", collector);
+        (clock, event_bus, received)
+    }
+
+    #[test]
+    fn test_cancelled_timeout_is_not_delivered() {
+        let (clock, event_bus, received) = timeouts();
+        let before_due = event_bus.schedule("timeout", 1u32, Duration::from_secs(5));
+        let after_due = event_bus.schedule("timeout", 2u32, Duration::from_secs(5));
+        let kept = event_bus.schedule("timeout", 3u32, Duration::from_secs(5));
+
+        clock.advance(Duration::from_secs(2));
+        assert!(event_bus.cancel_schedule(before_due));
+        assert_eq!(Ok(()), event_bus.publish());
+        clock.advance(Duration::from_secs(4));
+        assert!(event_bus.cancel_schedule(after_due));
+        assert_eq!(Ok(()), event_bus.publish());
+
+        assert_eq!(vec![3], *received.borrow());
+        assert!(!event_bus.cancel_schedule(before_due));
+        assert!(!event_bus.cancel_schedule(kept));
+        assert!(event_bus.pending_schedules().is_empty());
+    }
+
+    #[test]
+    fn test_rescheduled_timeout_is_due_later() {
+        let (clock, event_bus, received) = timeouts();
+        let start = clock.now();
+        let timeout = event_bus.schedule("timeout", 1u32, Duration::from_secs(5));
+        let poll = event_bus.schedule_every("poll", 0u32, Duration::from_secs(3));
+
+        clock.advance(Duration::from_secs(4));
+        assert!(event_bus.reschedule(timeout, Duration::from_secs(10)));
+        let pending = event_bus.pending_schedules();
+        assert_eq!(vec![poll, timeout], pending.iter().map(|schedule| schedule.id).collect::<Vec<_>>());
+        assert_eq!((start + Duration::from_secs(3), Some(Duration::from_secs(3))), (pending[0].due, pending[0].interval));
+        assert_eq!((start + Duration::from_secs(14), None), (pending[1].due, pending[1].interval));
+
+        clock.advance(Duration::from_secs(2));
+        assert_eq!(Ok(()), event_bus.publish());
+        assert!(received.borrow().is_empty());
+        clock.advance(Duration::from_secs(8));
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![1], *received.borrow());
+        assert_eq!(vec![poll], event_bus.pending_schedules().iter().map(|schedule| schedule.id).collect::<Vec<_>>());
+        assert_eq!(4, event_bus.metrics().topic(&"poll".to_string()).registered);
+    }
+}
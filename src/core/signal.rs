@@ -0,0 +1,123 @@
+use std::error::Error;
+use std::fmt;
+#[cfg(unix)]
+use std::sync::atomic::{AtomicI32, Ordering};
+use super::RunOptions;
+
+/// The number of the last watched signal that was received and not taken by a loop yet, 0 when none is.
+#[cfg(unix)]
+static RECEIVED: AtomicI32 = AtomicI32::new(0);
+
+/// # Signal
+///
+/// An OS signal that stops a `run_loop`, see `RunOptions::stop_on_signals`.
+///
+/// ## Variants
+///
+/// * `Interrupt` - `SIGINT`, sent by Ctrl-C.
+///
+/// * `Terminate` - `SIGTERM`, sent by service managers to stop a process.
+///
+/// * `Hangup` - `SIGHUP`, sent when the terminal is closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Signal {
+    /// `SIGINT`, sent by Ctrl-C.
+    Interrupt,
+    /// `SIGTERM`, sent by service managers to stop a process.
+    Terminate,
+    /// `SIGHUP`, sent when the terminal is closed.
+    Hangup,
+}
+
+impl Signal {
+    #[cfg(unix)]
+    fn number(self) -> i32 {
+        match self {
+            Signal::Interrupt => libc::SIGINT,
+            Signal::Terminate => libc::SIGTERM,
+            Signal::Hangup => libc::SIGHUP,
+        }
+    }
+}
+
+/// # Signal Error
+///
+/// The error returned by `stop_on_signals` when the handler of a signal cannot be installed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignalError {
+    /// The signal whose handler was not installed.
+    pub signal: Signal,
+    /// Why the handler was not installed.
+    pub message: String,
+}
+
+impl fmt::Display for SignalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Cannot handle {:?}: {}", self.signal, self.message)
+    }
+}
+
+impl Error for SignalError {}
+
+#[cfg(unix)]
+extern "C" fn handle_signal(signal: libc::c_int) {
+    RECEIVED.store(signal, Ordering::SeqCst);
+}
+
+#[cfg(unix)]
+fn install(signal: Signal) -> Result<(), SignalError> {
+    // SAFETY: the action is fully initialized, and the handler only stores to an atomic, which is async-signal-safe.
+    let installed = unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handle_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        action.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(signal.number(), &action, std::ptr::null_mut())
+    };
+    match installed {
+        0 => Ok(()),
+        _ => Err(SignalError { signal, message: std::io::Error::last_os_error().to_string() }),
+    }
+}
+
+/// Signals are not supported on this target, so the loop is only stopped by its shutdown signal.
+#[cfg(not(unix))]
+fn install(_signal: Signal) -> Result<(), SignalError> {
+    Ok(())
+}
+
+/// Takes the last received signal, when it is one of the signals.
+pub(crate) fn take_signal(signals: &[Signal]) -> Option<Signal> {
+    #[cfg(unix)]
+    {
+        let received = RECEIVED.load(Ordering::SeqCst);
+        let signal = signals.iter().copied().find(|signal| signal.number() == received)?;
+        RECEIVED.compare_exchange(received, 0, Ordering::SeqCst, Ordering::SeqCst).ok()?;
+        Some(signal)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = signals;
+        None
+    }
+}
+
+impl RunOptions {
+    /// # Stop On Signals
+    ///
+    /// Installs handlers for the signals that trigger the shutdown of the loop, which then drains
+    /// the registered events and returns the signal in its summary. A signal is noticed within a tick.
+    /// The handlers are installed for the whole process and replace the default action of the signals,
+    /// so the process is no longer ended by them. On targets without signals nothing is installed.
+    ///
+    /// Returns an error when a handler cannot be installed.
+    pub fn stop_on_signals(mut self, signals: &[Signal]) -> Result<Self, SignalError> {
+        for &signal in signals {
+            install(signal)?;
+            if !self.signals.contains(&signal) {
+                self.signals.push(signal);
+            }
+        }
+        Ok(self)
+    }
+}
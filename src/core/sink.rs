@@ -0,0 +1,64 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use super::{IntoEvent, TopicKey};
+use super::state::BusState;
+
+/// # Event Sink
+///
+/// A handle to an event bus that can only register events, created with `EventBus::sink`.
+/// The events are queued on the event bus, and published by its next publish.
+/// Cloning a sink is cheap, every clone feeds the same event bus.
+///
+/// ## Methods
+///
+/// * `register` - Registers an event with the event bus.
+///
+/// * `register_all` - Registers each event with the event bus.
+pub struct EventSink<K: TopicKey = String> {
+    pub(crate) state: Rc<RefCell<BusState<K>>>,
+}
+
+impl<K: TopicKey> Clone for EventSink<K> {
+    fn clone(&self) -> Self {
+        EventSink { state: self.state.clone() }
+    }
+}
+
+impl<K: TopicKey> EventSink<K> {
+    /// # Register
+    ///
+    /// Registers an event with the event bus, see `EventBus::register`.
+    pub fn register(&self, event_name: impl Into<K>, message: impl IntoEvent) -> &Self {
+        let mut state = self.state.borrow_mut();
+        let event_name = state.normalize(event_name.into());
+        state.register(event_name, message.into_event());
+        self
+    }
+
+    /// # Register All
+    ///
+    /// Registers each event with the event bus, see `EventBus::register_all`.
+    pub fn register_all<E: IntoEvent>(&self, events: impl IntoIterator<Item = (K, E)>) -> &Self {
+        self.state.borrow_mut().register_all(events);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::EventBus;
+
+    #[test]
+    fn test_sink_feeds_event_bus() {
+        let event_bus = EventBus::new();
+        let sink = event_bus.sink();
+        sink.register("startup", 1u32)
+            .register_all(vec![("startup".to_string(), 2u32), ("shutdown".to_string(), 3u32)]);
+        sink.clone().register("shutdown", 4u32);
+        assert_eq!(2, event_bus.pending(&"startup".to_string()));
+        assert_eq!(2, event_bus.pending(&"shutdown".to_string()));
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(0, event_bus.pending(&"startup".to_string()));
+    }
+}
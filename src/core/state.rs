@@ -0,0 +1,883 @@
+use std::any::{Any, TypeId};
+use std::borrow::Borrow;
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::sync::Arc;
+use super::{BeforeFailure, BusMetrics, Clock, DeadLetter, DeadLetterReason, DispatchOrder, ErrorPolicy, Event, Instant, IntoEvent, PayloadRegistry, SystemClock};
+use super::{DeadLettered, Phase, SubscriberAdded, SubscriberFailure, SubscriberRemoved, TopicFirstEvent};
+use super::{BusLogger, CapacityOverflow, CloneRegistry, DeferMode, OverflowAction, Subscriber, TopicKey, TopicMode, TopicPolicy, ValidationMode};
+use super::collect::Response;
+use super::convert::ConverterRegistry;
+use super::dedupe::Dedupe;
+use super::executor::Executor;
+use super::heartbeat::HeartbeatSource;
+use super::idle::IdleWatch;
+use super::depth::QueueDepthWatch;
+use super::journal::Journal;
+use super::normalize::{normalized, Normalizer};
+use super::ordering::sort_subscriptions;
+use super::pattern::{PatternSubscription, TopicPattern};
+use super::priority::PrioritySources;
+use super::route::Route;
+use super::schedule::ScheduledEvent;
+use super::topic::{topic_from_str, topic_str, TopicLimit};
+use super::subscription::{Debounced, Subscription};
+use super::upgrade::UpgradeRegistry;
+use super::validate::Validator;
+use super::watchdog::TopicWatchdog;
+use super::worker::Worker;
+#[cfg(feature = "net")]
+use super::net::{AttachedSource, RemoteExport};
+#[cfg(feature = "threaded")]
+use super::dedicated::Forward;
+#[cfg(feature = "threaded")]
+use super::ingest::Ingest;
+#[cfg(feature = "threaded")]
+use super::partition::{PartitionedListener, Partitioner};
+
+/// The state of an event bus, kept behind a `RefCell` by the event bus.
+/// It is only borrowed while no subscriber is called, so subscribers can use the event bus.
+pub(crate) struct BusState<K: TopicKey> {
+    /// A vec of events grouped by an event name that have been published to the event bus.
+    pub(crate) events: HashMap<K, Vec<Event>>,
+    /// A vec of all subscribers that are linked to the event bus.
+    pub(crate) subscribers: HashMap<K, Vec<Subscription>>,
+
+    /// What happens when a subscriber fails, unless its subscription or event name overrides it.
+    pub(crate) error_policy: ErrorPolicy,
+
+    /// Overrides of the error policy per event name.
+    pub(crate) topic_error_policies: HashMap<K, ErrorPolicy>,
+
+    /// What happens when the on_before of a subscriber fails.
+    pub(crate) before_failure: BeforeFailure,
+
+    /// Overrides of the before failure behavior per event name.
+    pub(crate) topic_before_failures: HashMap<K, BeforeFailure>,
+
+    /// The schema upgrades applied to events before they are published.
+    pub(crate) upgrades: UpgradeRegistry,
+
+    /// The converters between payload types, shared with the registered events.
+    pub(crate) converters: Rc<ConverterRegistry>,
+
+    /// The validators of the events registered per event name.
+    pub(crate) validators: HashMap<K, Validator>,
+
+    /// What happens to the events rejected by a validator.
+    pub(crate) validation_mode: ValidationMode,
+
+    /// Rewrites the `String` event names passed to the event bus, before they are used.
+    pub(crate) normalizer: Option<Normalizer>,
+
+    /// Events that could not be delivered.
+    pub(crate) dead_letters: Vec<DeadLetter<K>>,
+
+    /// How many times an event can be queued again after a nack, unless the event overrides it.
+    pub(crate) max_redeliveries: u32,
+
+    /// Which subscribers receive an event after one of them deferred it.
+    pub(crate) defer_mode: DeferMode,
+
+    /// How many times an event can be queued again after it was deferred.
+    pub(crate) max_deferrals: u32,
+
+    /// The only event names that can be subscribed to, when restricted.
+    pub(crate) restricted_topics: Option<HashSet<K>>,
+
+    /// Which event names can be registered, subscribed to and routed to.
+    pub(crate) topic_policy: TopicPolicy,
+
+    /// How the queued events are deduplicated per event name.
+    pub(crate) dedupes: HashMap<K, Dedupe>,
+
+    /// How registered events are queued per event name.
+    pub(crate) topic_modes: HashMap<K, TopicMode>,
+
+    /// The event names in `TopicMode::WindowedBatch` whose events are released by the next publish.
+    pub(crate) flushed_topics: HashSet<K>,
+
+    /// The subscriptions to wildcard patterns of event names.
+    pub(crate) pattern_subscriptions: Vec<PatternSubscription>,
+
+    /// The listeners passed to `replace_listener` while their subscription was taken out for publishing.
+    pub(crate) replacements: Vec<Replacement<K>>,
+
+    /// The order a publish delivers the queued events in.
+    pub(crate) dispatch_order: DispatchOrder,
+
+    /// The priority levels of event names and of prefixes ending in `*`, in the order they were set.
+    pub(crate) topic_priorities: Vec<(K, u8)>,
+
+    /// The event names a publish dispatches first, in this order, within a priority level.
+    pub(crate) topic_order: Vec<K>,
+
+    /// The event name a budgeted publish stopped at, where the next budgeted publish continues.
+    pub(crate) resume_from: Option<K>,
+
+    /// The maximum number of events published per cycle, per event name.
+    pub(crate) topic_limits: HashMap<K, TopicLimit>,
+
+    /// The number of queued events, over all event names.
+    pub(crate) queued: usize,
+
+    /// Alerts when the number of queued events crosses a threshold.
+    pub(crate) queue_depth_watch: Option<QueueDepthWatch<K>>,
+
+    /// Reports the event names without subscribers and the subscriptions without events, when set.
+    pub(crate) topic_watchdog: Option<TopicWatchdog<K>>,
+
+    /// Tells when the event bus runs out of work, and when work arrives again.
+    pub(crate) idle_watch: IdleWatch,
+
+    /// The maximum number of queued events, per event name.
+    pub(crate) topic_capacities: HashMap<K, usize>,
+
+    /// What happens to the events registered for an event name at its capacity.
+    pub(crate) capacity_overflows: HashMap<K, CapacityOverflow>,
+
+    /// The latest events of event names with debounced subscriptions.
+    pub(crate) debounced: HashMap<K, Debounced>,
+
+    /// The source of time of the event bus.
+    pub(crate) clock: Arc<dyn Clock>,
+
+    /// The delayed and recurring events, with the time they are due next.
+    pub(crate) scheduled: Vec<ScheduledEvent<K>>,
+
+    /// The id of the last delayed or recurring event.
+    pub(crate) next_schedule_id: u64,
+
+    /// The heartbeats, registering an event on their event name every interval.
+    pub(crate) heartbeats: Vec<HeartbeatSource<K>>,
+
+    /// The file registered events are appended to, when journaling.
+    pub(crate) journal: Option<Journal>,
+
+    /// The worker threads owning the subscribers that run on their own thread.
+    pub(crate) workers: Vec<Worker<K>>,
+
+    /// Runs the worker threads: on threads of their own, or on a simulator.
+    pub(crate) executor: Executor,
+
+    /// The sequence number of the last registered event.
+    pub(crate) sequence: u64,
+
+    /// Whether the calls of the subscribers are recorded in the traces of the events.
+    pub(crate) delivery_tracing: bool,
+
+    /// The priority levels an event gains for every publish cycle that stopped before delivering it.
+    pub(crate) priority_aging: u8,
+
+    /// The application context handed to every subscriber, when set.
+    pub(crate) context: Option<Rc<dyn Any>>,
+
+    /// The responses of the subscribers, with their name, while `publish_collect` delivers its event.
+    pub(crate) responses: Option<Vec<Response>>,
+
+    /// Whether each publish ends by pruning and shrinking the collections.
+    pub(crate) auto_prune: bool,
+
+    /// The state of the parent event bus, for a child event bus.
+    pub(crate) parent: Option<Rc<RefCell<BusState<K>>>>,
+
+    /// The counters of the event bus.
+    pub(crate) metrics: BusMetrics<K>,
+
+    /// Receives the notifications of the event bus.
+    pub(crate) logger: Rc<dyn BusLogger>,
+
+    /// The encoding of payloads that leave the process.
+    pub(crate) payloads: PayloadRegistry,
+
+    /// The payload types events can be cloned with.
+    pub(crate) cloneables: CloneRegistry,
+
+    /// The payload types that declare the priority level of their events.
+    pub(crate) priority_sources: PrioritySources,
+
+    /// The routes forwarding events between event names, in the order they were added.
+    pub(crate) routes: Vec<Route>,
+
+    /// The event name subscriber failures are registered on, when errors are routed.
+    pub(crate) error_topic: Option<K>,
+
+    /// The meta events waiting for the end of the publish cycle, when meta events are enabled.
+    pub(crate) meta_events: Option<Vec<(K, Event)>>,
+
+    /// The subscribers that `publish_partitioned` delivers the events to on parallel workers.
+    #[cfg(feature = "threaded")]
+    pub(crate) partitioned: HashMap<K, Vec<Box<dyn PartitionedListener>>>,
+
+    /// The keys the events are partitioned by, per event name.
+    #[cfg(feature = "threaded")]
+    pub(crate) partitioners: HashMap<K, Partitioner>,
+
+    /// The event names whose events are moved to a dedicated thread when they are registered.
+    #[cfg(feature = "threaded")]
+    pub(crate) dedicated: HashMap<K, Forward>,
+
+    /// The queue the thread sinks push their events onto, once a thread sink was created.
+    #[cfg(feature = "threaded")]
+    pub(crate) ingest: Option<Ingest<K>>,
+
+    /// The remote publishers that events are exported to.
+    #[cfg(feature = "net")]
+    pub(crate) remote_exports: Vec<RemoteExport<K>>,
+
+    /// The remote sources that events are read from.
+    #[cfg(feature = "net")]
+    pub(crate) remote_sources: Vec<AttachedSource<K>>,
+}
+
+impl<K: TopicKey> Default for BusState<K> {
+    fn default() -> Self {
+        BusState {
+            events: HashMap::new(),
+            subscribers: HashMap::new(),
+            error_policy: ErrorPolicy::default(),
+            topic_error_policies: HashMap::new(),
+            before_failure: BeforeFailure::default(),
+            topic_before_failures: HashMap::new(),
+            upgrades: UpgradeRegistry::default(),
+            converters: Rc::default(),
+            validators: HashMap::new(),
+            validation_mode: ValidationMode::default(),
+            normalizer: None,
+            dead_letters: Vec::new(),
+            max_redeliveries: 3,
+            defer_mode: DeferMode::default(),
+            max_deferrals: 10,
+            restricted_topics: None,
+            topic_policy: TopicPolicy::default(),
+            dedupes: HashMap::new(),
+            topic_modes: HashMap::new(),
+            flushed_topics: HashSet::new(),
+            pattern_subscriptions: Vec::new(),
+            replacements: Vec::new(),
+            dispatch_order: DispatchOrder::default(),
+            topic_priorities: Vec::new(),
+            topic_order: Vec::new(),
+            resume_from: None,
+            topic_limits: HashMap::new(),
+            queued: 0,
+            queue_depth_watch: None,
+            topic_watchdog: None,
+            idle_watch: IdleWatch::default(),
+            topic_capacities: HashMap::new(),
+            capacity_overflows: HashMap::new(),
+            debounced: HashMap::new(),
+            clock: Arc::new(SystemClock),
+            scheduled: Vec::new(),
+            next_schedule_id: 0,
+            heartbeats: Vec::new(),
+            journal: None,
+            workers: Vec::new(),
+            executor: Executor::default(),
+            sequence: 0,
+            delivery_tracing: false,
+            priority_aging: 0,
+            context: None,
+            responses: None,
+            auto_prune: false,
+            parent: None,
+            metrics: BusMetrics::default(),
+            logger: default_logger(),
+            payloads: PayloadRegistry::new(),
+            cloneables: CloneRegistry::new(),
+            priority_sources: PrioritySources::default(),
+            routes: Vec::new(),
+            error_topic: None,
+            meta_events: None,
+            #[cfg(feature = "threaded")]
+            partitioned: HashMap::new(),
+            #[cfg(feature = "threaded")]
+            partitioners: HashMap::new(),
+            #[cfg(feature = "threaded")]
+            dedicated: HashMap::new(),
+            #[cfg(feature = "threaded")]
+            ingest: None,
+            #[cfg(feature = "net")]
+            remote_exports: Vec::new(),
+            #[cfg(feature = "net")]
+            remote_sources: Vec::new(),
+        }
+    }
+}
+
+impl<K: TopicKey> Drop for BusState<K> {
+    /// Tells the remaining subscriptions they end, when the last handle of the event bus is dropped.
+    /// A panicking listener does not keep the others from being told.
+    fn drop(&mut self) {
+        for (topic, mut subscription) in self.take_all_subscriptions() {
+            let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| subscription.listener.on_unsubscribe(&topic)));
+        }
+    }
+}
+
+impl<K: TopicKey> BusState<K> {
+    /// Queues an event, see `EventBus::register`. Only the events that are queued get a sequence number
+    /// and are journaled, after the events that are forwarded, deduplicated or dropped are taken out.
+    pub(crate) fn register(&mut self, event_name: K, message: Event) {
+        if !self.admits_topic(&event_name) {
+            return;
+        }
+        if let Some(reason) = self.validation_error(&event_name, &message) {
+            self.logger.event_invalid(&event_name, &reason);
+            if self.validation_mode == ValidationMode::DeadLetter {
+                self.dead_letter(&event_name, message, DeadLetterReason::Invalid { message: reason });
+            }
+            return;
+        }
+        self.logger.event_registered(&event_name, &message);
+        let now = self.clock.now();
+        let full = self.full_capacity(&event_name, &message).is_some();
+        let mut metrics = self.metrics.topic_mut(&event_name);
+        metrics.registered += 1;
+        if metrics.registered == 1 && self.meta_events.is_some() {
+            if !self.metrics.registered_before_reset(&event_name) {
+                self.emit_meta(TopicFirstEvent::TOPIC, TopicFirstEvent { topic: meta_topic(&event_name) });
+            }
+            metrics = self.metrics.topic_mut(&event_name);
+        }
+        #[cfg(feature = "threaded")]
+        let message = match self.dedicated.get(&event_name) {
+            Some(forward) => match forward(message) {
+                Some(message) => message,
+                None => return,
+            },
+            None => message,
+        };
+        if let Some(dedupe) = self.dedupes.get(&event_name) {
+            let queued = self.events.get(&event_name).map_or(&[][..], Vec::as_slice);
+            if dedupe.is_duplicate(queued, &message) {
+                metrics.deduplicated += 1;
+                return;
+            }
+        }
+        let mut queued = self.queued + 1;
+        if full {
+            metrics.dropped += 1;
+            let overflow = self.capacity_overflows.get(&event_name).copied().unwrap_or_default();
+            match (overflow, self.events.get_mut(&event_name)) {
+                (CapacityOverflow::DropOldest, Some(queue)) if !queue.is_empty() => {
+                    queue.remove(0);
+                    queued -= 1;
+                }
+                _ => return,
+            }
+        }
+        let mut message = message;
+        self.stamp(&mut message, now);
+        self.write_journal(&event_name, &message);
+        if let Some(debounced) = self.debounced.get_mut(&event_name) {
+            debounced.last_registered = now;
+            for subscription in self.subscribers.get_mut(&event_name).into_iter().flatten() {
+                if let Some(debounce) = &mut subscription.debounce {
+                    debounce.pending = true;
+                }
+            }
+        }
+
+        let coalesce = self.topic_modes.get(&event_name) == Some(&TopicMode::CoalesceLatest);
+        let metrics = self.metrics.topic_mut(&event_name);
+        let queue = self.events.entry(event_name).or_default();
+        if coalesce {
+            metrics.coalesced += queue.len() as u64;
+            queued -= queue.len();
+            queue.clear();
+        }
+        queue.push(message);
+        self.set_queued(queued);
+    }
+
+    /// Queues each event, registering consecutive events of the same event name together.
+    pub(crate) fn register_all<E: IntoEvent>(&mut self, events: impl IntoIterator<Item = (K, E)>) {
+        let normalizer = self.normalizer.clone();
+        let mut events = events.into_iter()
+            .map(|(event_name, message)| (normalized(normalizer.as_ref(), event_name), message))
+            .peekable();
+        while let Some((event_name, message)) = events.next() {
+            let mut messages = vec![message.into_event()];
+            while let Some((_, message)) = events.next_if(|(next, _)| *next == event_name) {
+                messages.push(message.into_event());
+            }
+            self.register_run(event_name, messages);
+        }
+    }
+
+    /// Registers consecutive events of the same event name with a single lookup,
+    /// unless the event name needs the per-event handling of `register`.
+    fn register_run(&mut self, event_name: K, mut messages: Vec<Event>) {
+        if !self.admits_topic(&event_name) {
+            return;
+        }
+        let per_event = self.dedupes.contains_key(&event_name)
+            || self.debounced.contains_key(&event_name)
+            || self.topic_capacities.contains_key(&event_name)
+            || self.validators.contains_key(&event_name)
+            || self.topic_modes.get(&event_name) == Some(&TopicMode::CoalesceLatest);
+        #[cfg(feature = "threaded")]
+        let per_event = per_event || self.dedicated.contains_key(&event_name);
+        if per_event {
+            for message in messages {
+                self.register(event_name.clone(), message);
+            }
+            return;
+        }
+        self.logger.events_registered(&event_name, messages.len());
+        let now = self.clock.now();
+        for message in messages.iter_mut() {
+            self.stamp(message, now);
+            self.write_journal(&event_name, message);
+        }
+        let metrics = self.metrics.topic_mut(&event_name);
+        let first = metrics.registered == 0;
+        metrics.registered += messages.len() as u64;
+        if first && !self.metrics.registered_before_reset(&event_name) {
+            self.emit_meta(TopicFirstEvent::TOPIC, TopicFirstEvent { topic: meta_topic(&event_name) });
+        }
+        self.set_queued(self.queued + messages.len());
+        self.events.entry(event_name).or_default().append(&mut messages);
+    }
+
+    /// Stamps a registered event with the time and its sequence number.
+    fn stamp(&mut self, message: &mut Event, now: Instant) {
+        self.sequence += 1;
+        message.set_registered_at(now);
+        message.set_sequence(self.sequence);
+        if message.priority().is_none() {
+            if let Some(level) = self.priority_sources.priority_of(message) {
+                message.set_priority(level);
+            }
+        }
+        if !self.converters.is_empty() {
+            message.set_converters(self.converters.clone());
+        }
+    }
+
+    /// Returns the error of the validator of the event name when it rejects the event.
+    pub(crate) fn validation_error(&self, event_name: &K, message: &Event) -> Option<String> {
+        self.validators.get(event_name)?(message).err()
+    }
+
+    /// Returns the capacity of the event name when registering the event would grow its queue beyond it.
+    /// An event replacing the queued event, or dropped as a duplicate, does not grow the queue.
+    pub(crate) fn full_capacity(&self, event_name: &K, message: &Event) -> Option<usize> {
+        let capacity = *self.topic_capacities.get(event_name)?;
+        let queued = self.events.get(event_name).map_or(&[][..], Vec::as_slice);
+        let replaces = self.topic_modes.get(event_name) == Some(&TopicMode::CoalesceLatest)
+            || self.dedupes.get(event_name).is_some_and(|dedupe| dedupe.is_duplicate(queued, message));
+        (!replaces && queued.len() >= capacity).then_some(capacity)
+    }
+
+    /// Takes all queued events out.
+    pub(crate) fn take_events(&mut self) -> HashMap<K, Vec<Event>> {
+        let mut events = std::mem::take(&mut self.events);
+        self.hold_windows(&mut events);
+        let held = self.events.values().map(Vec::len).sum();
+        self.set_queued(held);
+        events
+    }
+
+    /// Returns the error policy of the event name, unless a subscription overrides it.
+    pub(crate) fn error_policy_for(&self, event_name: &K) -> ErrorPolicy {
+        self.topic_error_policies.get(event_name).copied().unwrap_or(self.error_policy)
+    }
+
+    /// Queues events that were taken out to be published again, ahead of the events registered since.
+    /// Runs of the same event name keep their order.
+    pub(crate) fn requeue_unpublished(&mut self, events: impl Iterator<Item = (K, Vec<Event>)>) {
+        let mut unpublished: HashMap<K, Vec<Event>> = HashMap::new();
+        let mut queued = self.queued;
+        for (event_name, mut messages) in events {
+            queued += messages.len();
+            messages.iter_mut().for_each(Event::wait_cycle);
+            unpublished.entry(event_name).or_default().extend(messages);
+        }
+        for (event_name, mut messages) in unpublished {
+            messages.append(self.events.entry(event_name.clone()).or_default());
+            self.events.insert(event_name, messages);
+        }
+        self.set_queued(queued);
+    }
+
+    /// Takes the events beyond the limit of the event name out of the messages,
+    /// queuing them for a later publish or dropping them.
+    pub(crate) fn apply_limit(&mut self, event_name: &K, messages: &mut Vec<Event>) {
+        let Some(limit) = self.topic_limits.get(event_name).copied() else {
+            return;
+        };
+        if messages.len() > limit.max_per_publish {
+            let overflow = messages.split_off(limit.max_per_publish);
+            match limit.overflow {
+                OverflowAction::Defer => self.requeue_unpublished(std::iter::once((event_name.clone(), overflow))),
+                OverflowAction::Drop => self.metrics.topic_mut(event_name).dropped += overflow.len() as u64,
+            }
+        }
+    }
+
+    /// Returns the priority level of the event name: the level set for the event name itself,
+    /// or else the level of the longest matching prefix, or else 0.
+    pub(crate) fn topic_priority(&self, event_name: &K) -> u8 {
+        if let Some((_, level)) = self.topic_priorities.iter().find(|(topic, _)| topic == event_name) {
+            return *level;
+        }
+        let Some(topic) = topic_str(event_name) else {
+            return 0;
+        };
+        self.topic_priorities.iter()
+            .filter_map(|(prefix, level)| Some((topic_str(prefix)?.strip_suffix('*')?, *level)))
+            .filter(|(prefix, _)| topic.starts_with(prefix))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(0, |(_, level)| level)
+    }
+
+    /// Returns the priority level the message of the event name is dispatched by: the level of the message,
+    /// or else the level of the event name, raised by the priority aging for every publish cycle
+    /// that stopped before delivering the message.
+    pub(crate) fn effective_priority(&self, event_name: &K, message: Option<&Event>) -> u8 {
+        let level = message.and_then(Event::priority).unwrap_or_else(|| self.topic_priority(event_name));
+        let waited = message.map_or(0, Event::cycles_waited);
+        let boost = u32::from(self.priority_aging).saturating_mul(waited);
+        u8::try_from(u32::from(level).saturating_add(boost)).unwrap_or(u8::MAX)
+    }
+
+    /// Returns the key the queued events of the event names are dispatched by: higher priority levels first,
+    /// then the event names in the topic order, then the event name whose first queued event was registered first.
+    pub(crate) fn dispatch_rank<M: Borrow<Event>>(&self, event_name: &K, messages: &[M]) -> (Reverse<u8>, usize, Option<u64>) {
+        let position = self.topic_order.iter().position(|topic| topic == event_name).unwrap_or(usize::MAX);
+        let first = messages.first().map(Borrow::borrow);
+        (Reverse(self.effective_priority(event_name, first)), position, first.and_then(Event::sequence))
+    }
+
+    /// Takes the queued events out event name by event name, see `order_by_priority`.
+    pub(crate) fn take_by_priority(&mut self) -> Vec<(K, Vec<Event>)> {
+        let events = self.take_events().into_iter().collect();
+        self.order_by_priority(events)
+    }
+
+    /// Orders the events event name by event name, in the order of their dispatch rank.
+    /// The events of an event name with a priority level of their own are ordered by their level.
+    /// A dry run orders the queued events the same way.
+    pub(crate) fn order_by_priority<M: Borrow<Event>>(&self, mut events: Vec<(K, Vec<M>)>) -> Vec<(K, Vec<M>)> {
+        for (event_name, messages) in &mut events {
+            if messages.iter().any(|message| message.borrow().priority().is_some()) {
+                messages.sort_by_cached_key(|message| Reverse(self.effective_priority(event_name, Some(message.borrow()))));
+            }
+        }
+        events.sort_by_cached_key(|(event_name, messages)| self.dispatch_rank(event_name, messages));
+        events
+    }
+
+    /// Takes the queued events out in registration order, applying the limits of their event names,
+    /// see `order_by_registration`.
+    pub(crate) fn take_in_registration_order(&mut self) -> Vec<(K, Vec<Event>)> {
+        let mut events = Vec::new();
+        for (event_name, mut messages) in self.take_events() {
+            self.apply_limit(&event_name, &mut messages);
+            events.push((event_name, messages));
+        }
+        self.order_by_registration(events)
+    }
+
+    /// Orders the events in registration order, as runs of consecutive events of the same event name.
+    /// The events of event names with a higher priority level come first. A dry run orders the queued events the same way.
+    pub(crate) fn order_by_registration<M: Borrow<Event>>(&self, events: Vec<(K, Vec<M>)>) -> Vec<(K, Vec<M>)> {
+        let mut ordered: Vec<(u8, K, M)> = Vec::new();
+        for (event_name, messages) in events {
+            ordered.extend(messages.into_iter()
+                .map(|message| (self.effective_priority(&event_name, Some(message.borrow())), event_name.clone(), message)));
+        }
+        ordered.sort_by_key(|(level, _, message)| (Reverse(*level), message.borrow().sequence()));
+        let mut runs: Vec<(K, Vec<M>)> = Vec::new();
+        for (_, event_name, message) in ordered {
+            match runs.last_mut() {
+                Some((last, messages)) if *last == event_name => messages.push(message),
+                _ => runs.push((event_name, vec![message])),
+            }
+        }
+        runs
+    }
+
+    /// Registers the delayed and recurring events and the heartbeat ticks that are due.
+    pub(crate) fn register_due(&mut self) {
+        self.register_heartbeats();
+        self.register_scheduled();
+    }
+
+    pub(crate) fn is_allowed_topic(&self, event_name: &K) -> bool {
+        self.restricted_topics.as_ref()
+            .is_none_or(|topics| topics.contains(event_name))
+    }
+
+    /// Returns where a subscription to the event name goes,
+    /// or `None` when the event name is denied or an invalid pattern, which is logged.
+    pub(crate) fn subscription_target(&self, event_name: K) -> Option<SubscriptionTarget<K>> {
+        if !self.admits_topic(&event_name) {
+            return None;
+        }
+        let Some(pattern) = topic_str(&event_name).filter(|topic| TopicPattern::is_pattern(topic)) else {
+            return Some(SubscriptionTarget::Topic(event_name));
+        };
+        match TopicPattern::parse(pattern) {
+            Ok(pattern) => Some(SubscriptionTarget::Pattern(pattern)),
+            Err(e) => {
+                self.logger.invalid_pattern(&e);
+                None
+            }
+        }
+    }
+
+    /// Adds a subscription whose listener was told what it is subscribed to.
+    pub(crate) fn add_subscription(&mut self, target: SubscriptionTarget<K>, subscription: Subscription) {
+        self.emit_meta(SubscriberAdded::TOPIC, SubscriberAdded { topic: target.topic() });
+        let event_name = match target {
+            SubscriptionTarget::Pattern(pattern) => {
+                self.pattern_subscriptions.push(PatternSubscription { pattern, subscription });
+                return;
+            }
+            SubscriptionTarget::Topic(event_name) => event_name,
+        };
+        if cfg!(debug_assertions) && !self.is_allowed_topic(&event_name) {
+            self.logger.undeclared_topic(&event_name);
+        }
+        let subscriptions = self.subscribers.entry(event_name.clone()).or_default();
+        subscriptions.push(subscription);
+        if let Err(cycle) = sort_subscriptions(subscriptions) {
+            self.logger.dependency_cycle(&event_name, &cycle);
+        }
+    }
+
+    /// Subscribes a listener of the event bus itself, whose `on_subscribe` does not use the event bus.
+    /// Other listeners are subscribed with `EventBus::subscribe`.
+    pub(crate) fn subscribe(&mut self, event_name: K, mut subscription: Subscription) {
+        if let Some(target) = self.subscription_target(event_name) {
+            subscription.attach(target.topic());
+            self.add_subscription(target, subscription);
+        }
+    }
+
+    /// Puts the subscriptions taken out for publishing back,
+    /// before the subscriptions that were added while publishing.
+    pub(crate) fn restore_subscriptions(
+        &mut self,
+        event_name: K,
+        mut subscriptions: Vec<Subscription>,
+        mut patterns: Vec<PatternSubscription>,
+    ) {
+        if let Some(added) = self.subscribers.remove(&event_name) {
+            subscriptions.extend(added);
+            if let Err(cycle) = sort_subscriptions(&mut subscriptions) {
+                self.logger.dependency_cycle(&event_name, &cycle);
+            }
+        }
+        if !subscriptions.is_empty() {
+            self.subscribers.insert(event_name.clone(), subscriptions);
+        }
+        patterns.append(&mut self.pattern_subscriptions);
+        self.pattern_subscriptions = patterns;
+        if !self.replacements.is_empty() {
+            self.apply_replacements(&event_name);
+        }
+    }
+
+    /// Replaces the listener of the subscription, and returns the listener it replaced.
+    /// Hands the replacement back when the subscription is not subscribed, or taken out for publishing.
+    pub(crate) fn replace_listener(&mut self, replacement: Replacement<K>) -> Result<Box<dyn Subscriber>, Replacement<K>> {
+        let Some(subscription) = self.subscription_mut(&replacement.topic, replacement.id) else {
+            return Err(replacement);
+        };
+        subscription.type_id = replacement.type_id;
+        subscription.factory = None;
+        Ok(std::mem::replace(&mut subscription.listener, replacement.listener))
+    }
+
+    /// Replaces the listeners queued while their subscriptions were taken out for publishing,
+    /// now that the subscriptions of the event name are put back. The listeners they replaced are dropped,
+    /// and so are the replacements of subscriptions of the event name that unsubscribed meanwhile.
+    fn apply_replacements(&mut self, event_name: &K) {
+        for replacement in std::mem::take(&mut self.replacements) {
+            if let Err(replacement) = self.replace_listener(replacement) {
+                if replacement.topic != *event_name {
+                    self.replacements.push(replacement);
+                }
+            }
+        }
+    }
+
+    /// Returns whether a listener of the type is subscribed to the event name, or to the pattern.
+    pub(crate) fn has_subscriber(&self, event_name: &K, type_id: TypeId) -> bool {
+        let pattern = topic_str(event_name);
+        self.subscribers.get(event_name).into_iter().flatten().any(|subscription| subscription.type_id == type_id)
+            || self.pattern_subscriptions.iter().any(|subscription| {
+                subscription.subscription.type_id == type_id && pattern == Some(subscription.pattern.to_string().as_str())
+            })
+    }
+
+    /// Returns the subscription with the id, of the event name or of the pattern.
+    pub(crate) fn subscription_mut(&mut self, event_name: &K, id: u64) -> Option<&mut Subscription> {
+        self.subscribers.get_mut(event_name).into_iter().flatten()
+            .chain(self.pattern_subscriptions.iter_mut().map(|pattern| &mut pattern.subscription))
+            .find(|subscription| subscription.id == id)
+    }
+
+    /// Removes the subscription with the id, and returns it to be released, none when it was not subscribed.
+    pub(crate) fn unsubscribe(&mut self, event_name: &K, id: u64) -> Vec<(String, Subscription)> {
+        let mut subscriptions = self.subscribers.remove(event_name).unwrap_or_default();
+        let mut patterns = std::mem::take(&mut self.pattern_subscriptions);
+        let subscription = subscriptions.iter_mut()
+            .chain(patterns.iter_mut().map(|pattern| &mut pattern.subscription))
+            .find(|subscription| subscription.id == id);
+        if let Some(subscription) = subscription {
+            subscription.unsubscribed = true;
+        }
+        let removed = self.remove_unsubscribed(event_name, &mut subscriptions, &mut patterns);
+        self.restore_subscriptions(event_name.clone(), subscriptions, patterns);
+        removed
+    }
+
+    /// Removes the subscriptions whose listener unsubscribed itself, and returns them with the event name
+    /// or pattern they were subscribed to, to be released once the event bus is no longer borrowed.
+    pub(crate) fn remove_unsubscribed(
+        &mut self,
+        event: &K,
+        subscriptions: &mut Vec<Subscription>,
+        patterns: &mut Vec<PatternSubscription>,
+    ) -> Vec<(String, Subscription)> {
+        let (unsubscribed, kept): (Vec<Subscription>, Vec<Subscription>) = std::mem::take(subscriptions).into_iter()
+            .partition(|subscription| subscription.unsubscribed);
+        *subscriptions = kept;
+        let mut removed: Vec<(String, Subscription)> = unsubscribed.into_iter()
+            .map(|subscription| (meta_topic(event), subscription))
+            .collect();
+        let (unsubscribed, kept): (Vec<PatternSubscription>, Vec<PatternSubscription>) = std::mem::take(patterns).into_iter()
+            .partition(|pattern| pattern.subscription.unsubscribed);
+        *patterns = kept;
+        removed.extend(unsubscribed.into_iter().map(|pattern| (pattern.pattern.to_string(), pattern.subscription)));
+        self.announce_removed(removed.iter().map(|(topic, _)| topic.clone()).collect());
+        removed
+    }
+
+    /// Removes every subscription, in reverse subscription order per event name,
+    /// and returns them with the event name or pattern they were subscribed to.
+    pub(crate) fn take_all_subscriptions(&mut self) -> Vec<(String, Subscription)> {
+        let mut subscribers: Vec<(String, Vec<Subscription>)> = self.subscribers.drain()
+            .map(|(event_name, subscriptions)| (meta_topic(&event_name), subscriptions))
+            .collect();
+        subscribers.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let mut removed: Vec<(String, Subscription)> = subscribers.into_iter()
+            .flat_map(|(topic, subscriptions)| subscriptions.into_iter().rev().map(move |subscription| (topic.clone(), subscription)))
+            .collect();
+        removed.extend(std::mem::take(&mut self.pattern_subscriptions).into_iter().rev()
+            .map(|pattern| (pattern.pattern.to_string(), pattern.subscription)));
+        removed
+    }
+
+    /// Logs the removed subscriptions, and announces them with meta events.
+    pub(crate) fn announce_removed(&mut self, removed: Vec<String>) {
+        for topic in removed {
+            self.logger.subscriber_removed(&topic);
+            self.emit_meta(SubscriberRemoved::TOPIC, SubscriberRemoved { topic });
+        }
+    }
+
+    /// Queues a meta event stamped with the current time, when meta events are enabled and not being published.
+    pub(crate) fn emit_meta<T: 'static>(&mut self, event_name: &str, payload: T) {
+        if let (Some(meta_events), Some(event_name)) = (&mut self.meta_events, topic_from_str::<K>(event_name)) {
+            let mut message = Event::new(payload);
+            message.set_registered_at(self.clock.now());
+            meta_events.push((event_name, message));
+        }
+    }
+
+    /// Counts a failure of a subscriber, and registers it on the error topic when errors are routed.
+    /// Failures of the subscribers of the error topic itself are not routed.
+    pub(crate) fn route_error(&mut self, event: &K, subscriber: &str, phase: Phase, message: &str, event_id: u64) {
+        self.metrics.record_subscriber_error(event, subscriber);
+        let error_topic = match &self.error_topic {
+            Some(error_topic) if error_topic != event => error_topic.clone(),
+            _ => return,
+        };
+        let failure = SubscriberFailure {
+            topic: meta_topic(event),
+            subscriber: subscriber.to_string(),
+            phase,
+            message: message.to_string(),
+            event_id,
+        };
+        self.register(error_topic, Event::new(failure));
+    }
+
+    /// Moves an event to the dead-letter queue.
+    pub(crate) fn dead_letter(&mut self, event: &K, message: Event, reason: DeadLetterReason) {
+        self.emit_meta(DeadLettered::TOPIC, DeadLettered { topic: meta_topic(event), reason: reason.clone() });
+        self.dead_letters.push(DeadLetter { topic: event.clone(), event: message, reason });
+    }
+
+    /// Queues a nacked event again, or dead-letters it when it reached its maximum number of redeliveries.
+    pub(crate) fn requeue(&mut self, event: &K, mut message: Event) {
+        let max_redeliveries = message.max_redeliveries().unwrap_or(self.max_redeliveries);
+        if message.redeliveries() >= max_redeliveries {
+            let reason = DeadLetterReason::MaxRedeliveries { redeliveries: message.redeliveries() };
+            self.dead_letter(event, message, reason);
+            return;
+        }
+        message.redeliver();
+        self.events.entry(event.clone()).or_default().push(message);
+        self.set_queued(self.queued + 1);
+    }
+
+    /// Queues a deferred event again, or dead-letters it when it reached the maximum number of deferrals.
+    pub(crate) fn defer(&mut self, event: &K, mut message: Event) {
+        if message.deferral_count() >= self.max_deferrals {
+            let reason = DeadLetterReason::MaxDeferrals { deferrals: message.deferral_count() };
+            self.dead_letter(event, message, reason);
+            return;
+        }
+        message.defer();
+        self.events.entry(event.clone()).or_default().push(message);
+        self.set_queued(self.queued + 1);
+    }
+}
+
+#[cfg(feature = "log")]
+fn default_logger() -> Rc<dyn BusLogger> {
+    Rc::new(super::LogLogger)
+}
+
+#[cfg(not(feature = "log"))]
+fn default_logger() -> Rc<dyn BusLogger> {
+    Rc::new(super::NullLogger)
+}
+
+/// A listener that replaces the listener of the subscription with the id, see `EventBus::replace_listener`.
+pub(crate) struct Replacement<K: TopicKey> {
+    pub(crate) topic: K,
+    pub(crate) id: u64,
+    pub(crate) type_id: TypeId,
+    pub(crate) listener: Box<dyn Subscriber>,
+}
+
+/// Where a subscription goes: the subscriptions of an event name, or the pattern subscriptions.
+pub(crate) enum SubscriptionTarget<K: TopicKey> {
+    Topic(K),
+    Pattern(TopicPattern),
+}
+
+impl<K: TopicKey> SubscriptionTarget<K> {
+    /// Returns the event name or pattern the listener is subscribed to.
+    pub(crate) fn topic(&self) -> String {
+        match self {
+            SubscriptionTarget::Topic(event_name) => meta_topic(event_name),
+            SubscriptionTarget::Pattern(pattern) => pattern.to_string(),
+        }
+    }
+}
+
+/// Returns the event name for the payload of a meta event.
+pub(crate) fn meta_topic<K: TopicKey>(event_name: &K) -> String {
+    topic_str(event_name).map_or_else(|| format!("{:?}", event_name), str::to_string)
+}
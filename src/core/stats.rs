@@ -0,0 +1,152 @@
+use std::time::{Duration, SystemTime};
+use super::{EventBus, Instant, TopicKey, TopicMetrics};
+use super::state::meta_topic;
+
+/// # Bus Stats Snapshot
+///
+/// A copy of the counters of the event bus, taken by `stats_snapshot`. Two snapshots can be compared
+/// to find out what happened in between, for example the number of events per minute.
+///
+/// ## Fields
+///
+/// * `captured_at` - When the snapshot was taken, on the clock of the event bus.
+///
+/// * `timestamp` - When the snapshot was taken, on the system clock.
+///
+/// * `publishes` - The number of publish cycles.
+///
+/// * `publish_time` - The time spent publishing, on the clock of the event bus.
+///
+/// * `topics` - The counters of every event name something happened for, ordered by event name.
+///
+/// * `subscriber_errors` - The number of failures per event name and subscriber name, ordered by event name.
+///
+/// ## Methods
+///
+/// * `topic` - Returns the counters of an event name.
+///
+/// * `registered`, `delivered` - Return the number of events registered and delivered, over all event names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BusStatsSnapshot<K: TopicKey = String> {
+    /// When the snapshot was taken, on the clock of the event bus.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub captured_at: Instant,
+    /// When the snapshot was taken, on the system clock.
+    pub timestamp: SystemTime,
+    /// The number of publish cycles.
+    pub publishes: u64,
+    /// The time spent publishing, on the clock of the event bus.
+    pub publish_time: Duration,
+    /// The counters of every event name something happened for.
+    pub topics: Vec<(K, TopicMetrics)>,
+    /// The number of failures per event name and subscriber name.
+    pub subscriber_errors: Vec<(K, String, u64)>,
+}
+
+impl<K: TopicKey> BusStatsSnapshot<K> {
+    /// # Topic
+    ///
+    /// Returns the counters of an event name, all zero if nothing happened for it.
+    pub fn topic(&self, event_name: &K) -> TopicMetrics {
+        self.topics.iter().find(|(topic, _)| topic == event_name).map_or_else(TopicMetrics::default, |(_, metrics)| *metrics)
+    }
+
+    /// # Registered
+    ///
+    /// Returns the number of events registered, over all event names.
+    pub fn registered(&self) -> u64 {
+        self.topics.iter().map(|(_, metrics)| metrics.registered).sum()
+    }
+
+    /// # Delivered
+    ///
+    /// Returns the number of events handled by at least one subscriber, over all event names.
+    pub fn delivered(&self) -> u64 {
+        self.topics.iter().map(|(_, metrics)| metrics.delivered).sum()
+    }
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Stats Snapshot
+    ///
+    /// Returns a copy of the counters of the event bus, see `metrics`, with the time it was taken.
+    pub fn stats_snapshot(&self) -> BusStatsSnapshot<K> {
+        let state = self.state.borrow();
+        let metrics = &state.metrics;
+        let mut topics: Vec<(K, TopicMetrics)> = metrics.topics()
+            .map(|(event_name, metrics)| (event_name.clone(), *metrics))
+            .collect();
+        topics.sort_by_cached_key(|(event_name, _)| meta_topic(event_name));
+        let mut subscriber_errors: Vec<(K, String, u64)> = metrics.all_subscriber_errors()
+            .map(|(event_name, subscriber, errors)| (event_name.clone(), subscriber.to_string(), errors))
+            .collect();
+        subscriber_errors.sort_by_cached_key(|(event_name, subscriber, _)| (meta_topic(event_name), subscriber.clone()));
+        BusStatsSnapshot {
+            captured_at: state.clock.now(),
+            timestamp: SystemTime::now(),
+            publishes: metrics.publishes(),
+            publish_time: metrics.publish_time(),
+            topics,
+            subscriber_errors,
+        }
+    }
+
+    /// # Reset Stats
+    ///
+    /// Sets every counter of the event bus to zero. The queued events and the subscriptions are kept,
+    /// and event names that had events before are not announced again with a `TopicFirstEvent`.
+    pub fn reset_stats(&self) -> &Self {
+        self.state.borrow_mut().metrics.reset();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::{ErrorPolicy, EventBus, Subscriber};
+    use crate::testing::ManualClock;
+
+    #[test]
+    fn test_snapshot_after_reset_reflects_only_later_activity() {
+        let clock = ManualClock::new();
+        let event_bus: EventBus = EventBus::with_clock(clock.clone());
+        event_bus
+            .set_error_policy(ErrorPolicy::Continue)
+            .subscribe_listener("orders", <dyn Subscriber>::builder().name("billing").on_event(|_| Err("declined".to_string())).build())
+            .subscribe_listener("clicks", <dyn Subscriber>::builder().on_event(|_| Ok(())).build());
+        for click in 0..5u32 {
+            event_bus.register("clicks", click);
+        }
+        event_bus.register("orders", 1u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        event_bus.register("clicks", 5u32);
+
+        let before = event_bus.stats_snapshot();
+        event_bus.reset_stats();
+        clock.advance(Duration::from_secs(60));
+        event_bus.register("clicks", 6u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        let after = event_bus.stats_snapshot();
+
+        assert_eq!((6, 5, 1), (before.topic(&"clicks".to_string()).registered, before.delivered(), before.publishes));
+        assert_eq!(vec![("orders".to_string(), "billing".to_string(), 1)], before.subscriber_errors);
+        assert_eq!(vec!["clicks".to_string()], after.topics.iter().map(|(topic, _)| topic.clone()).collect::<Vec<_>>());
+        let clicks = after.topic(&"clicks".to_string());
+        assert_eq!((1, 2, 2), (clicks.registered, clicks.delivered, clicks.latency.count));
+        assert_eq!((1, 0), (after.publishes, after.topic(&"orders".to_string()).registered));
+        assert!(after.subscriber_errors.is_empty());
+        assert_eq!(Duration::from_secs(60), after.captured_at - before.captured_at);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_snapshot_serializes() {
+        let event_bus = EventBus::new();
+        event_bus.register("clicks", 1u32);
+        let json = serde_json::to_value(event_bus.stats_snapshot()).unwrap();
+        assert_eq!(1, json["topics"][0][1]["registered"]);
+        assert!(json.get("captured_at").is_none());
+    }
+}
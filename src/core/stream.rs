@@ -0,0 +1,236 @@
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+use futures_core::Stream;
+use super::{Event, EventBus, EventContext, Outcome, Subscriber, TopicKey};
+
+/// # Backpressure
+///
+/// Controls what the forwarder of an `EventStream` does with an event when the stream is full,
+/// because its consumer lags behind.
+///
+/// ## Variants
+///
+/// * `DropOldest` - The oldest event in the stream is dropped to make room.
+///
+/// * `Block` - The publish waits until the consumer makes room.
+///   Only use it when the stream is consumed on another thread, or the publish never ends.
+///
+/// * `Error` - The forwarder fails on the event, and the error policy applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backpressure {
+    /// The oldest event in the stream is dropped to make room.
+    #[default]
+    DropOldest,
+    /// The publish waits until the consumer makes room.
+    Block,
+    /// The forwarder fails on the event.
+    Error,
+}
+
+/// # Streamed Event
+///
+/// An event received from an `EventStream`.
+///
+/// ## Fields
+///
+/// * `topic` - The event name the event was published on, as text.
+///
+/// * `event_id` - The id of the event.
+///
+/// * `payload` - A clone of the payload of the event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamedEvent<T> {
+    /// The event name the event was published on, as text.
+    pub topic: String,
+    /// The id of the event.
+    pub event_id: u64,
+    /// A clone of the payload of the event.
+    pub payload: T,
+}
+
+/// The events shared by the forwarder and the stream.
+struct Channel<T> {
+    events: VecDeque<StreamedEvent<T>>,
+    capacity: usize,
+    waker: Option<Waker>,
+    /// Whether the stream is dropped, the forwarder unsubscribes on its next event.
+    stream_dropped: bool,
+    /// Whether the forwarder is dropped, the stream ends once it is empty.
+    forwarder_dropped: bool,
+}
+
+struct Shared<T> {
+    channel: Mutex<Channel<T>>,
+    /// Notified when the stream makes room, or is dropped.
+    room: Condvar,
+}
+
+impl<T> Shared<T> {
+    fn lock(&self) -> MutexGuard<'_, Channel<T>> {
+        self.channel.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// # Event Stream
+///
+/// The events of an event name holding a `T`, as a `futures_core::Stream`, created with `EventBus::stream`.
+/// The stream ends when its forwarder is dropped, like when the event bus is dropped.
+/// Dropping the stream unsubscribes its forwarder on the next event.
+pub struct EventStream<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Stream for EventStream<T> {
+    type Item = StreamedEvent<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut channel = self.shared.lock();
+        if let Some(event) = channel.events.pop_front() {
+            self.shared.room.notify_all();
+            return Poll::Ready(Some(event));
+        }
+        if channel.forwarder_dropped {
+            return Poll::Ready(None);
+        }
+        channel.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl<T> Drop for EventStream<T> {
+    fn drop(&mut self) {
+        self.shared.lock().stream_dropped = true;
+        self.shared.room.notify_all();
+    }
+}
+
+/// Forwards a clone of the payload of each event into an `EventStream`.
+struct Forwarder<T> {
+    shared: Arc<Shared<T>>,
+    backpressure: Backpressure,
+    payload: PhantomData<T>,
+}
+
+impl<T: Clone + 'static> Subscriber for Forwarder<T> {
+    fn on_event_with_context(&mut self, event: &mut Event, context: &mut EventContext) -> Outcome {
+        let Some(payload) = event.get_data::<T>() else {
+            return Outcome::Ignored;
+        };
+        let mut channel = self.shared.lock();
+        while channel.events.len() >= channel.capacity && !channel.stream_dropped {
+            match self.backpressure {
+                Backpressure::DropOldest => {
+                    channel.events.pop_front();
+                }
+                Backpressure::Block => {
+                    channel = self.shared.room.wait(channel).unwrap_or_else(|poisoned| poisoned.into_inner());
+                }
+                Backpressure::Error => {
+                    return Outcome::Error(format!("The stream of {} is full", context.topic()));
+                }
+            }
+        }
+        if channel.stream_dropped {
+            return Outcome::AckAndUnsubscribe;
+        }
+        channel.events.push_back(StreamedEvent { topic: context.topic().to_string(), event_id: event.id(), payload: payload.clone() });
+        if let Some(waker) = channel.waker.take() {
+            waker.wake();
+        }
+        Outcome::Ack
+    }
+}
+
+impl<T> Drop for Forwarder<T> {
+    fn drop(&mut self) {
+        let mut channel = self.shared.lock();
+        channel.forwarder_dropped = true;
+        if let Some(waker) = channel.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Stream
+    ///
+    /// Subscribes a forwarder to the event name, streaming a clone of the payload of each event holding a `T`.
+    /// The stream holds at most `capacity` events; what happens to the next event when the consumer lags behind
+    /// is decided by the backpressure. Events holding another type are ignored.
+    pub fn stream<T: Clone + 'static>(&self, event_name: impl Into<K>, capacity: usize, backpressure: Backpressure) -> EventStream<T> {
+        let shared = Arc::new(Shared {
+            channel: Mutex::new(Channel {
+                events: VecDeque::with_capacity(capacity),
+                capacity: capacity.max(1),
+                waker: None,
+                stream_dropped: false,
+                forwarder_dropped: false,
+            }),
+            room: Condvar::new(),
+        });
+        self.subscribe_listener(event_name, Forwarder { shared: shared.clone(), backpressure, payload: PhantomData });
+        EventStream { shared }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+    use std::pin::pin;
+    use std::task::{Context, Poll, Waker};
+    use futures_util::StreamExt;
+    use crate::{ErrorPolicy, EventBus};
+    use super::{Backpressure, EventStream, Forwarder};
+
+    /// Runs a future whose events are all published already.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        match future.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("The stream waits for events that are not published"),
+        }
+    }
+
+    #[test]
+    fn test_take_from_stream() {
+        let event_bus = EventBus::new();
+        let stream = event_bus.stream::<String>("chat", 8, Backpressure::Error);
+        for message in ["hi", "hello", "bye", "later"] {
+            event_bus.register("chat", message.to_string()).register("chat", 1u32);
+            assert_eq!(Ok(()), event_bus.publish());
+        }
+
+        let messages: Vec<String> = block_on(stream.take(3).map(|event| format!("{} {}", event.topic, event.payload)).collect());
+        assert_eq!(vec!["chat hi", "chat hello", "chat bye"], messages);
+    }
+
+    #[test]
+    fn test_lagging_consumer() {
+        let event_bus = EventBus::new();
+        event_bus.set_error_policy(ErrorPolicy::Continue);
+        let dropping = event_bus.stream::<u32>("tick", 2, Backpressure::DropOldest);
+        let failing = event_bus.stream::<u32>("tick", 2, Backpressure::Error);
+        for tick in 1..=3u32 {
+            event_bus.register("tick", tick);
+        }
+        assert_eq!(Ok(()), event_bus.publish());
+
+        let payloads = |stream: EventStream<u32>| block_on(stream.take(2).map(|event| event.payload).collect::<Vec<_>>());
+        assert_eq!(vec![2, 3], payloads(dropping));
+        assert_eq!(vec![1, 2], payloads(failing));
+        assert_eq!(1, event_bus.metrics().subscriber_errors(&"tick".to_string(), std::any::type_name::<Forwarder<u32>>()));
+    }
+
+    #[test]
+    fn test_dropping_the_stream_unsubscribes() {
+        let event_bus = EventBus::new();
+        drop(event_bus.stream::<u32>("tick", 2, Backpressure::Block));
+        assert!(event_bus.has_subscriber::<Forwarder<u32>>(&"tick".to_string()));
+
+        assert_eq!(Ok(()), event_bus.register("tick", 1u32).publish());
+        assert!(!event_bus.has_subscriber::<Forwarder<u32>>(&"tick".to_string()));
+    }
+}
@@ -1,5 +1,5 @@
 #![allow(unused_variables)]
-use super::Event;
+use super::{Event, EventContext, Outcome};
 
 /// # Subscriber
 ///
@@ -18,8 +18,31 @@ use super::Event;
 /// ## Methods
 ///
 /// * `on_event` - Called when the event bus is run.
+///
+/// * `name` - The name of the subscriber in errors, the type name by default.
+///
+/// * `on_subscribe` - Called when the subscriber is subscribed to an event name.
+///
+/// * `on_unsubscribe` - Called when the subscription ends, to release the resources of the subscriber.
+///
+/// * `on_event_outcome` - Called when the event bus is run, instead of `on_event`
+///   for subscribers that need to return more than success or failure.
+///
+/// * `on_event_with_context` - Called when the event bus is run, instead of `on_event_outcome`
+///   for subscribers that use the application context of the event bus.
 pub trait Subscriber {
 
+    /// Called when the subscriber is subscribed, with the event name or pattern it is subscribed to.
+    /// A lazy subscriber is told once it is constructed. It is called before the subscription is added,
+    /// so it can register events with the event bus, like replaying the last event to the new subscriber.
+    fn on_subscribe(&mut self, topic: &str) {}
+
+    /// Called once when the subscription ends, with the event name or pattern it was subscribed to:
+    /// when it is unsubscribed, unsubscribes itself, or the event bus shuts down or is dropped.
+    /// It is called once the subscription is removed, so it can register the events it still holds
+    /// with the event bus, except when the event bus is dropped.
+    fn on_unsubscribe(&mut self, topic: &str) {}
+
     /// Called before the on_event is run by the event bus
     fn on_before(&mut self, event: &mut Event) -> Result<(), String> {
         Ok(())
@@ -30,8 +53,79 @@ pub trait Subscriber {
         Ok(())
     }
 
+    /// Called when the event bus is run, calls on_event by default.
+    /// Override this to acknowledge or reject the event with an `Outcome`.
+    fn on_event_outcome(&mut self, event: &mut Event) -> Outcome {
+        self.on_event(event).into()
+    }
+
+    /// Called when the event bus is run, calls on_event_outcome by default.
+    /// Override this to use the application context set with `set_context`.
+    fn on_event_with_context(&mut self, event: &mut Event, context: &mut EventContext) -> Outcome {
+        self.on_event_outcome(event)
+    }
+
+    /// Called after the on_event is run by the event bus
+    fn on_after(&self, event: &Event) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// The name of the subscriber in errors, the type name by default.
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+/// # Read Only Subscriber
+///
+/// A subscriber that can only look at the events it receives, like a metrics collector or a logger.
+/// Subscribed with `subscribe_readonly`, it receives each event after the subscribers
+/// that can change the event, so it observes the final payload.
+///
+/// ## Methods
+///
+/// * `on_event` - Called when the event bus is run.
+///
+/// * `name` - The name of the subscriber in errors, the type name by default.
+pub trait ReadOnlySubscriber {
+
+    /// Called before the on_event is run by the event bus
+    fn on_before(&mut self, event: &Event) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Called when the event bus is run.
+    fn on_event(&mut self, event: &Event) -> Result<(), String> {
+        Ok(())
+    }
+
     /// Called after the on_event is run by the event bus
     fn on_after(&self, event: &Event) -> Result<(), String> {
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// The name of the subscriber in errors, the type name by default.
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+/// Subscribes a read-only subscriber, only handing it shared references to the events.
+pub(crate) struct ReadOnly<R>(pub(crate) R);
+
+impl<R: ReadOnlySubscriber> Subscriber for ReadOnly<R> {
+    fn on_before(&mut self, event: &mut Event) -> Result<(), String> {
+        self.0.on_before(event)
+    }
+
+    fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+        self.0.on_event(event)
+    }
+
+    fn on_after(&self, event: &Event) -> Result<(), String> {
+        self.0.on_after(event)
+    }
+
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+}
@@ -0,0 +1,103 @@
+use std::any::TypeId;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use super::{ErrorPolicy, Event, EventContext, Instant, Outcome, Phase, Subscriber};
+use super::breaker::Breaker;
+
+/// # Subscription
+///
+/// A subscriber linked to an event name, together with
+/// the settings that only apply to this subscription.
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+pub(crate) struct Subscription {
+    pub(crate) listener: Box<dyn Subscriber>,
+    /// A process-wide unique id of the subscription, held by its handle.
+    pub(crate) id: u64,
+    /// The type of the listener, as the boxed listener cannot tell.
+    pub(crate) type_id: TypeId,
+    /// Overrides the error policy of the event bus.
+    pub(crate) error_policy: Option<ErrorPolicy>,
+    /// Delays the delivery until the event name has been quiet for a while.
+    pub(crate) debounce: Option<Debounce>,
+    /// Whether the listener unsubscribed itself, the subscription is removed once it is safe.
+    pub(crate) unsubscribed: bool,
+    /// The names of the subscribers of the same event name this subscription runs after.
+    pub(crate) after: Vec<String>,
+    /// Whether the listener cannot change the events, it runs after the listeners that can.
+    pub(crate) read_only: bool,
+    /// Constructs the listener the first time an event is about to be delivered, for lazy subscriptions.
+    pub(crate) factory: Option<Box<dyn FnOnce() -> Box<dyn Subscriber>>>,
+    /// Skips the listener for a while when it keeps failing.
+    pub(crate) breaker: Option<Breaker>,
+}
+
+impl Subscription {
+    pub(crate) fn new<R: Subscriber + 'static>(listener: R) -> Subscription {
+        Subscription {
+            listener: Box::new(listener),
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            type_id: TypeId::of::<R>(),
+            error_policy: None,
+            debounce: None,
+            unsubscribed: false,
+            after: Vec::new(),
+            read_only: false,
+            factory: None,
+            breaker: None,
+        }
+    }
+
+    /// Tells the listener the event name or pattern it is subscribed to,
+    /// or the listener of a lazy subscription once it is constructed.
+    pub(crate) fn attach(&mut self, topic: String) {
+        match self.factory.take() {
+            Some(factory) => self.factory = Some(Box::new(move || {
+                let mut listener = factory();
+                listener.on_subscribe(&topic);
+                listener
+            })),
+            None => self.listener.on_subscribe(&topic),
+        }
+    }
+
+    /// Returns whether the subscription receives the events as they are published,
+    /// debounced subscriptions receive them afterwards.
+    pub(crate) fn receives_published(&self) -> bool {
+        self.debounce.is_none() && !self.unsubscribed
+    }
+
+    /// Calls each method of the listener for a single message.
+    /// A nack cannot requeue a message delivered to a single subscription, and is ignored.
+    pub(crate) fn deliver(&mut self, message: &mut Event, context: &mut EventContext) -> Result<(), (Phase, String)> {
+        self.listener.on_before(message).map_err(|e| (Phase::Before, e))?;
+        match self.listener.on_event_with_context(message, context) {
+            Outcome::Error(e) => return Err((Phase::Event, e)),
+            Outcome::AckAndUnsubscribe => self.unsubscribed = true,
+            _ => {}
+        }
+        self.listener.on_after(message).map_err(|e| (Phase::After, e))
+    }
+}
+
+/// Tells the listeners of the removed subscriptions that they ended, with the event name or pattern
+/// each was subscribed to. It is called once the event bus is no longer borrowed, so they can use it.
+pub(crate) fn release(removed: Vec<(String, Subscription)>) {
+    for (topic, mut subscription) in removed {
+        subscription.listener.on_unsubscribe(&topic);
+    }
+}
+
+/// The debounce state of a subscription.
+pub(crate) struct Debounce {
+    pub(crate) quiet_period: Duration,
+    /// Whether an event was registered that the subscription has not received yet.
+    pub(crate) pending: bool,
+}
+
+/// The debounce state of an event name with debounced subscriptions.
+pub(crate) struct Debounced {
+    /// The latest published event, kept until every debounced subscription received it.
+    pub(crate) latest: Option<Event>,
+    pub(crate) last_registered: Instant,
+}
@@ -0,0 +1,142 @@
+use std::any::Any;
+use std::error::Error;
+use std::fmt;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::time::Duration;
+
+/// # Topic Key
+///
+/// The type of the keys the events and subscribers of an event bus are grouped by.
+/// It is implemented for every type that can be hashed, compared, cloned and debug-printed,
+/// like `String` or a plain enum deriving `Debug, Clone, PartialEq, Eq, Hash`.
+pub trait TopicKey: Eq + Hash + Clone + Debug + 'static {}
+
+impl<T: Eq + Hash + Clone + Debug + 'static> TopicKey for T {}
+
+/// # Unknown Topic
+///
+/// The error returned when subscribing to an event name
+/// that is not one of the topics the event bus is restricted to, or that the topic policy denies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownTopic<K: TopicKey = String> {
+    /// The event name that is not declared.
+    pub topic: K,
+}
+
+impl<K: TopicKey> fmt::Display for UnknownTopic<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unknown topic {:?}", self.topic)
+    }
+}
+
+impl<K: TopicKey> Error for UnknownTopic<K> {}
+
+/// # Topic Mode
+///
+/// Controls how registered events are queued for an event name.
+///
+/// ## Variants
+///
+/// * `Queue` - Every registered event is queued.
+///
+/// * `CoalesceLatest` - A registered event replaces the queued event,
+///   so at most the latest event is published per cycle.
+///
+/// * `WindowedBatch` - The queued events are held until the window since the first of them was registered
+///   has passed, or the event name is flushed with `EventBus::flush_topic`, and are then published together.
+///   Events whose ttl passes while they are held are dead-lettered when they are released.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TopicMode {
+    /// Every registered event is queued.
+    #[default]
+    Queue,
+    /// Only the latest registered event is queued.
+    CoalesceLatest,
+    /// The queued events are held until the window since the first of them was registered has passed.
+    WindowedBatch { window: Duration },
+}
+
+/// # Overflow Action
+///
+/// Controls what happens to the events of a rate-limited event name
+/// that exceed the limit of a publish cycle.
+///
+/// ## Variants
+///
+/// * `Defer` - The events stay queued, in order, for the next publish.
+///
+/// * `Drop` - The events are dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowAction {
+    /// The events stay queued for the next publish.
+    #[default]
+    Defer,
+    /// The events are dropped.
+    Drop,
+}
+
+/// # Capacity Overflow
+///
+/// Controls what `register` does with an event for an event name whose queue is at its capacity.
+///
+/// ## Variants
+///
+/// * `DropNewest` - The registered event is dropped.
+///
+/// * `DropOldest` - The oldest queued event is dropped to make room for the registered event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CapacityOverflow {
+    /// The registered event is dropped.
+    #[default]
+    DropNewest,
+    /// The oldest queued event is dropped.
+    DropOldest,
+}
+
+/// # Register Error
+///
+/// The error returned by `try_register` when an event cannot be queued.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegisterError<K: TopicKey = String> {
+    /// The queue of the event name is at its capacity.
+    TopicFull { topic: K, capacity: usize },
+    /// The validator of the event name rejected the event.
+    Invalid { topic: K, reason: String },
+    /// The topic policy denies the event name.
+    Denied { topic: K },
+}
+
+impl<K: TopicKey> fmt::Display for RegisterError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegisterError::TopicFull { topic, capacity } => {
+                write!(f, "Topic {:?} is full, it holds at most {} events", topic, capacity)
+            }
+            RegisterError::Invalid { topic, reason } => {
+                write!(f, "Invalid event for topic {:?}: {}", topic, reason)
+            }
+            RegisterError::Denied { topic } => write!(f, "Topic {:?} is denied by the topic policy", topic),
+        }
+    }
+}
+
+impl<K: TopicKey> Error for RegisterError<K> {}
+
+/// The rate limit of an event name.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TopicLimit {
+    pub(crate) max_per_publish: usize,
+    pub(crate) overflow: OverflowAction,
+}
+
+/// Returns the event name of a string, when the event bus uses `String` event names.
+pub(crate) fn topic_from_str<K: TopicKey>(event_name: &str) -> Option<K> {
+    let event_name: Box<dyn Any> = Box::new(event_name.to_string());
+    event_name.downcast::<K>().ok().map(|event_name| *event_name)
+}
+
+/// Returns the event name as a string, when the event bus uses `String` event names.
+pub(crate) fn topic_str<K: TopicKey>(event_name: &K) -> Option<&str> {
+    (event_name as &dyn Any).downcast_ref::<String>().map(String::as_str)
+}
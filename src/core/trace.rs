@@ -0,0 +1,133 @@
+use std::sync::Arc;
+use std::time::Duration;
+use super::{Clock, Event, EventBus, Instant, Outcome, Phase, TopicKey};
+
+/// # Trace Entry
+///
+/// A call of a subscriber method with an event, recorded in the trace of the event
+/// when delivery tracing is enabled, see `EventBus::enable_delivery_tracing` and `Event::trace`.
+///
+/// ## Fields
+///
+/// * `subscriber` - The name of the subscriber.
+///
+/// * `phase` - The method of the subscriber that was called.
+///
+/// * `outcome` - What the method returned, an `Err` of `on_before` or `on_after` as `Outcome::Error`.
+///
+/// * `duration` - How long the method took, on the clock of the event bus.
+///
+/// * `attempt` - The delivery attempt of the event, 1 for its first delivery and one more for every redelivery.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEntry {
+    /// The name of the subscriber.
+    pub subscriber: String,
+    /// The method of the subscriber that was called.
+    pub phase: Phase,
+    /// What the method returned.
+    pub outcome: Outcome,
+    /// How long the method took, on the clock of the event bus.
+    pub duration: Duration,
+    /// The delivery attempt of the event, starting at 1.
+    pub attempt: u32,
+}
+
+/// Records the calls of the subscribers in the traces of the events, when delivery tracing is enabled.
+pub(crate) struct Tracer(Option<Arc<dyn Clock>>);
+
+impl Tracer {
+    pub(crate) fn new(clock: Option<Arc<dyn Clock>>) -> Tracer {
+        Tracer(clock)
+    }
+
+    /// Returns when a call starts, when tracing.
+    pub(crate) fn start(&self) -> Option<Instant> {
+        self.0.as_ref().map(|clock| clock.now())
+    }
+
+    /// Adds the call that started at `started` to the trace of the event, when tracing.
+    pub(crate) fn record(&self, event: &mut Event, started: Option<Instant>, subscriber: &str, phase: Phase, outcome: impl FnOnce() -> Outcome) {
+        let (Some(clock), Some(started)) = (&self.0, started) else {
+            return;
+        };
+        let entry = TraceEntry {
+            subscriber: subscriber.to_string(),
+            phase,
+            outcome: outcome(),
+            duration: clock.now().saturating_duration_since(started),
+            attempt: event.redeliveries() + 1,
+        };
+        event.trace.push(entry);
+    }
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Enable Delivery Tracing
+    ///
+    /// Makes every call of a subscriber method add an entry to the trace of the event, see `Event::trace`,
+    /// for debugging how an event was handled. The trace stays with the event when it is redelivered,
+    /// and is kept by its dead letter. Tracing is off by default, as it allocates for every call.
+    /// The deliveries to debounced subscriptions are not traced.
+    pub fn enable_delivery_tracing(&self, enabled: bool) -> &Self {
+        self.state.borrow_mut().delivery_tracing = enabled;
+        self
+    }
+
+    /// Returns the tracer of a delivery, tracing when delivery tracing is enabled.
+    pub(crate) fn tracer(&self) -> Tracer {
+        let state = self.state.borrow();
+        Tracer::new(state.delivery_tracing.then(|| state.clock.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DeadLetterReason, Event, EventBus, Outcome, Phase, Subscriber};
+
+    struct PoisonedSubscriber;
+
+    impl Subscriber for PoisonedSubscriber {
+        fn on_event_outcome(&mut self, _event: &mut Event) -> Outcome {
+            Outcome::Nack { requeue: true }
+        }
+
+        fn name(&self) -> &str {
+            "parser"
+        }
+    }
+
+    #[test]
+    fn test_trace_shows_every_attempt_of_a_dead_letter() {
+        let event_bus = EventBus::new();
+        event_bus
+            .enable_delivery_tracing(true)
+            .set_max_redeliveries(1)
+            .subscribe_listener("payments", PoisonedSubscriber)
+            .register("payments", "malformed".to_string());
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert!(event_bus.dead_letters().is_empty());
+        assert_eq!(Ok(()), event_bus.publish());
+
+        let dead_letters = event_bus.take_dead_letters();
+        assert_eq!(DeadLetterReason::MaxRedeliveries { redeliveries: 1 }, dead_letters[0].reason);
+        let calls: Vec<(&str, Phase, Outcome, u32)> = dead_letters[0].event.trace().iter()
+            .filter(|entry| entry.phase == Phase::Event)
+            .map(|entry| (entry.subscriber.as_str(), entry.phase, entry.outcome.clone(), entry.attempt))
+            .collect();
+        let nack = Outcome::Nack { requeue: true };
+        assert_eq!(vec![("parser", Phase::Event, nack.clone(), 1), ("parser", Phase::Event, nack, 2)], calls);
+        assert_eq!(6, dead_letters[0].event.trace().len());
+    }
+
+    #[test]
+    fn test_events_are_not_traced_by_default() {
+        let event_bus = EventBus::new();
+        event_bus
+            .set_max_redeliveries(0)
+            .subscribe_listener("payments", PoisonedSubscriber)
+            .register("payments", 1u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        assert!(event_bus.dead_letters()[0].event.trace().is_empty());
+    }
+}
@@ -0,0 +1,53 @@
+use std::any::type_name;
+use std::marker::PhantomData;
+use super::{Event, EventBus, Subscriber, TopicKey};
+
+/// # Bus Event
+///
+/// A payload type bound to its event name, usually declared with `define_event!`,
+/// so the event name is written down once instead of at every call site.
+///
+/// ## Methods
+///
+/// * `topic` - Returns the event name of the payload type.
+///
+/// * `register_on` - Registers the payload on its event name.
+pub trait BusEvent: 'static {
+    /// Returns the event name of the payload type.
+    fn topic() -> &'static str;
+
+    /// Registers the payload on its event name.
+    fn register_on<K: TopicKey + From<&'static str>>(self, event_bus: &EventBus<K>)
+    where
+        Self: Sized,
+    {
+        event_bus.register(Self::topic(), self);
+    }
+}
+
+/// Hands the payload of the events of a `BusEvent` to a closure.
+struct EventHandler<E, F> {
+    handler: F,
+    event: PhantomData<E>,
+}
+
+impl<E: BusEvent, F: FnMut(&mut E) -> Result<(), String>> Subscriber for EventHandler<E, F> {
+    fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+        (self.handler)(event.convert_data_mut::<E>()?)
+    }
+
+    fn name(&self) -> &str {
+        type_name::<E>()
+    }
+}
+
+impl<K: TopicKey + From<&'static str>> EventBus<K> {
+    /// # Subscribe Event
+    ///
+    /// Subscribes a closure to the event name of `E`, handing it the payload of each event.
+    /// An event holding another type on that event name fails the subscriber,
+    /// unless a converter to `E` is registered for it, see `register_converter`.
+    pub fn subscribe_event<E: BusEvent>(&self, handler: impl FnMut(&mut E) -> Result<(), String> + 'static) -> &Self {
+        self.subscribe_listener(E::topic(), EventHandler { handler, event: PhantomData })
+    }
+}
@@ -0,0 +1,89 @@
+use std::fs;
+use std::io::{self, ErrorKind, Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use super::{Event, EventBus, PayloadRegistry, RemoteSource};
+use super::net::{Accept, Frame};
+
+/// A listening socket, removed from the file system when it is dropped.
+struct UnixSocket {
+    listener: UnixListener,
+    path: PathBuf,
+}
+
+impl Accept for UnixSocket {
+    fn accept_nonblocking(&self) -> io::Result<Box<dyn Read>> {
+        let (stream, _) = self.listener.accept()?;
+        stream.set_nonblocking(true)?;
+        Ok(Box::new(stream))
+    }
+}
+
+impl Drop for UnixSocket {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+impl TryFrom<UnixStream> for RemoteSource {
+    type Error = io::Error;
+
+    fn try_from(stream: UnixStream) -> io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        Ok(RemoteSource::reading(stream))
+    }
+}
+
+/// # UDS Publisher
+///
+/// Writes events to a Unix domain socket, so an event bus listening on it with `listen_uds`
+/// registers them. The events are written in the frames of a `RemotePublisher`.
+///
+/// ## Methods
+///
+/// * `connect` - Connects to the socket of a listening event bus.
+///
+/// * `send` - Writes an event.
+pub struct UdsPublisher {
+    stream: UnixStream,
+}
+
+impl UdsPublisher {
+    /// # Connect
+    ///
+    /// Connects to the socket at the path, failing when nothing listens on it
+    /// or the socket cannot be accessed.
+    pub fn connect(path: impl AsRef<Path>) -> io::Result<UdsPublisher> {
+        Ok(UdsPublisher { stream: UnixStream::connect(path)? })
+    }
+
+    /// # Send
+    ///
+    /// Writes the event with the event name, encoded with the payload registry.
+    /// Fails when the payload type is not registered, the frame would be too long, or the listener is gone.
+    /// Blocks while the socket is full, until the listening event bus publishes and reads it.
+    pub fn send(&mut self, event_name: &str, event: &Event, payloads: &PayloadRegistry) -> io::Result<()> {
+        let (name, bytes) = payloads.encode(event).ok_or_else(|| {
+            io::Error::new(ErrorKind::InvalidInput, format!("Cannot send {:?}, its payload type is not registered", event))
+        })?;
+        let frame = Frame::encode(event_name, name, event.schema_version(), &bytes)
+            .map_err(|e| io::Error::new(ErrorKind::InvalidInput, e))?;
+        self.stream.write_all(&frame)
+    }
+}
+
+impl EventBus {
+    /// # Listen UDS
+    ///
+    /// Listens on a Unix domain socket at the path, and registers the events sent by each `UdsPublisher`
+    /// that connects each time the event bus publishes, see `attach_remote_source`.
+    /// Fails when the path is in use or cannot be created. The socket is removed when the event bus is dropped.
+    /// Errors of the connections, like a publisher disconnecting in the middle of a frame,
+    /// are reported to the logger of the event bus.
+    pub fn listen_uds(&self, path: impl AsRef<Path>) -> io::Result<&Self> {
+        let listener = UnixListener::bind(&path)?;
+        listener.set_nonblocking(true)?;
+        let socket = UnixSocket { listener, path: path.as_ref().to_path_buf() };
+        Ok(self.attach_remote_source(RemoteSource::listening(socket)))
+    }
+}
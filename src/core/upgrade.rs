@@ -0,0 +1,49 @@
+use std::any::TypeId;
+use std::collections::{BTreeMap, HashMap};
+use super::{DeadLetterReason, Event};
+
+type Upgrade = Box<dyn Fn(&mut Event) -> Result<(), String>>;
+
+/// # Upgrade Registry
+///
+/// Holds the schema upgrades per payload type.
+/// An upgrade registered for a payload type and version transforms
+/// an event into the next version, and may change the payload type.
+#[derive(Default)]
+pub(crate) struct UpgradeRegistry {
+    upgrades: HashMap<TypeId, BTreeMap<u32, Upgrade>>,
+}
+
+impl UpgradeRegistry {
+    pub(crate) fn register<T: 'static>(&mut self, from_version: u32, upgrade: Upgrade) {
+        self.upgrades.entry(TypeId::of::<T>())
+            .or_default()
+            .insert(from_version, upgrade);
+    }
+
+    /// Chains the upgrades of the event until it reaches the current version
+    /// registered for its payload type.
+    pub(crate) fn upgrade(&self, event: &mut Event) -> Result<(), DeadLetterReason> {
+        while let Some(version) = event.schema_version() {
+            let upgrades = match self.upgrades.get(&(*event.data).type_id()) {
+                Some(upgrades) => upgrades,
+                None => return Ok(()),
+            };
+            let current = upgrades.keys().next_back().map_or(0, |last| last + 1);
+            if version >= current {
+                return Ok(());
+            }
+            match upgrades.get(&version) {
+                Some(upgrade) => {
+                    upgrade(event).map_err(|message| DeadLetterReason::UpgradeFailed {
+                        from_version: version,
+                        message,
+                    })?;
+                    event.set_schema_version(version + 1);
+                }
+                None => return Err(DeadLetterReason::MissingUpgrade { version }),
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use super::{Event, EventBus, TopicKey, ValidationMode};
+
+pub(crate) type Validator = Box<dyn Fn(&Event) -> Result<(), String>>;
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Set Validator
+    ///
+    /// Validates each event registered on the event name, replacing its previous validator.
+    /// It runs for every event entering the queue: registered directly, through a sink,
+    /// or received from a remote source. The events it rejects are handled by the validation mode.
+    /// The validator must not use the event bus.
+    pub fn set_validator(&self, event_name: impl Into<K>, validator: impl Fn(&Event) -> Result<(), String> + 'static) -> &Self {
+        let event_name = self.topic_key(event_name);
+        self.state.borrow_mut().validators.insert(event_name, Box::new(validator));
+        self
+    }
+
+    /// # Set Validation Mode
+    ///
+    /// Sets what happens to the events rejected by a validator, see `ValidationMode`.
+    pub fn set_validation_mode(&self, validation_mode: ValidationMode) -> &Self {
+        self.state.borrow_mut().validation_mode = validation_mode;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Event, EventBus, RegisterError, ValidationMode};
+    use crate::subscribers::CollectingSubscriber;
+
+    fn below_100(event: &Event) -> Result<(), String> {
+        match event.try_get_data::<u32>()? {
+            value if *value < 100 => Ok(()),
+            value => Err(format!("{} is not below 100", value)),
+        }
+    }
+
+    #[test]
+    fn test_validator_rejects_out_of_range_event() {
+        let event_bus = EventBus::new();
+        let (collector, received) = CollectingSubscriber::<u32>::new();
+        event_bus.set_validator("score", below_100).subscribe_listener("score", collector);
+
+        assert!(event_bus.try_register("score", 42u32).is_ok());
+        assert_eq!(
+            Some(RegisterError::Invalid { topic: "score".to_string(), reason: "250 is not below 100".to_string() }),
+            event_bus.try_register("score", 250u32).err()
+        );
+        event_bus.register("score", 300u32).sink().register("score", 7u32);
+        assert_eq!(Ok(()), event_bus.publish());
+
+        assert_eq!(vec![42, 7], *received.borrow());
+        assert!(event_bus.take_dead_letters().is_empty());
+    }
+
+    #[test]
+    fn test_validator_dead_letters_in_dead_letter_mode() {
+        let event_bus = EventBus::new();
+        event_bus
+            .set_validation_mode(ValidationMode::DeadLetter)
+            .set_validator("score", below_100);
+
+        assert!(event_bus.try_register("score", 250u32).is_ok());
+        event_bus.register_all(vec![("score".to_string(), 1u32), ("score".to_string(), 150u32)]);
+        assert_eq!(1, event_bus.pending(&"score".to_string()));
+
+        let reasons: Vec<String> = event_bus.take_dead_letters().into_iter().map(|dead_letter| dead_letter.reason.to_string()).collect();
+        assert_eq!(vec!["Rejected by the validator: 250 is not below 100", "Rejected by the validator: 150 is not below 100"], reasons);
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn test_validator_runs_for_remote_events() {
+        use std::io::Cursor;
+        use crate::{DeadLetterReason, RemoteSource};
+        use crate::core::net::Frame;
+
+        let event_bus = EventBus::new();
+        let (collector, received) = CollectingSubscriber::<u32>::new();
+        event_bus
+            .register_payload::<u32>(
+                "u32",
+                |value| value.to_be_bytes().to_vec(),
+                |bytes| bytes.try_into().map(u32::from_be_bytes).map_err(|_| "Expected 4 bytes".to_string()),
+            )
+            .set_validation_mode(ValidationMode::DeadLetter)
+            .set_validator("score", below_100)
+            .subscribe_listener("score", collector);
+
+        let mut frames = Frame::encode("score", "u32", None, &5u32.to_be_bytes()).unwrap();
+        frames.extend(Frame::encode("score", "u32", None, &500u32.to_be_bytes()).unwrap());
+        event_bus.attach_remote_source(RemoteSource::reading(Cursor::new(frames)));
+        assert_eq!(Ok(()), event_bus.publish());
+
+        assert_eq!(vec![5], *received.borrow());
+        let dead_letters = event_bus.take_dead_letters();
+        assert_eq!(1, dead_letters.len());
+        assert_eq!(DeadLetterReason::Invalid { message: "500 is not below 100".to_string() }, dead_letters[0].reason);
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::HashSet;
+use std::time::Duration;
+use super::{EventBus, Instant, TopicKey};
+use super::state::{meta_topic, BusState};
+use super::topic::topic_str;
+
+/// # Watchdog Period
+///
+/// How often the topic watchdog of `on_unmatched_topics` checks the event bus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchdogPeriod {
+    /// Checks after every number of publish cycles.
+    Cycles(u32),
+    /// Checks at the end of the first publish cycle once the duration passed, on the clock of the event bus.
+    Duration(Duration),
+}
+
+/// # Topic Watch Report
+///
+/// Tells the callback of `on_unmatched_topics` about the event names and subscriptions that do not meet.
+///
+/// ## Fields
+///
+/// * `unsubscribed` - The event names that had events published during the period, without any subscriber.
+///
+/// * `silent` - The subscriptions that existed for a whole period, and never received an event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopicWatchReport<K: TopicKey = String> {
+    /// The event names that had events published during the period, without any subscriber.
+    pub unsubscribed: Vec<K>,
+    /// The subscriptions that existed for a whole period, and never received an event.
+    pub silent: Vec<SilentSubscription>,
+}
+
+/// # Silent Subscription
+///
+/// A subscription that never received an event, see `TopicWatchReport`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SilentSubscription {
+    /// The event name or pattern of the subscription.
+    pub topic: String,
+    /// The name of the subscriber.
+    pub subscriber: String,
+}
+
+/// The state of the topic watchdog between its checks.
+pub(crate) struct TopicWatchdog<K: TopicKey> {
+    period: WatchdogPeriod,
+    cycles: u32,
+    started: Instant,
+    /// The event names published without a subscriber since the last check.
+    unsubscribed: HashSet<K>,
+    /// The reported event names, reported again only after they had a subscriber.
+    reported: HashSet<K>,
+    /// The ids of the subscriptions that received an event.
+    received: HashSet<u64>,
+    /// The ids of the subscriptions that existed at the last check.
+    watched: HashSet<u64>,
+    /// The ids of the reported subscriptions.
+    reported_silent: HashSet<u64>,
+    callback: Box<dyn FnMut(TopicWatchReport<K>)>,
+}
+
+impl<K: TopicKey> BusState<K> {
+    /// Tells the topic watchdog that events of the event name were published without a subscriber.
+    pub(crate) fn watch_unsubscribed(&mut self, event_name: &K) {
+        if let Some(watchdog) = &mut self.topic_watchdog {
+            watchdog.unsubscribed.insert(event_name.clone());
+        }
+    }
+
+    /// Tells the topic watchdog that the subscriptions received an event.
+    pub(crate) fn watch_received(&mut self, ids: impl IntoIterator<Item = u64>) {
+        if let Some(watchdog) = &mut self.topic_watchdog {
+            watchdog.received.extend(ids);
+        }
+    }
+
+    /// Counts a publish cycle, and checks the event bus once the period of the topic watchdog passed.
+    pub(crate) fn check_topic_watchdog(&mut self) {
+        let now = self.clock.now();
+        let Some(mut watchdog) = self.topic_watchdog.take() else {
+            return;
+        };
+        watchdog.cycles += 1;
+        let due = match watchdog.period {
+            WatchdogPeriod::Cycles(cycles) => watchdog.cycles >= cycles,
+            WatchdogPeriod::Duration(duration) => now.saturating_duration_since(watchdog.started) >= duration,
+        };
+        if due {
+            self.check_topics(&mut watchdog, now);
+        }
+        self.topic_watchdog = Some(watchdog);
+    }
+
+    fn check_topics(&self, watchdog: &mut TopicWatchdog<K>, now: Instant) {
+        watchdog.reported.retain(|event_name| !self.is_subscribed(event_name));
+        let mut unsubscribed: Vec<K> = std::mem::take(&mut watchdog.unsubscribed).into_iter()
+            .filter(|event_name| !self.is_subscribed(event_name) && watchdog.reported.insert(event_name.clone()))
+            .collect();
+        unsubscribed.sort_by_cached_key(meta_topic);
+
+        let subscriptions = self.subscribers.iter()
+            .flat_map(|(event_name, subscriptions)| subscriptions.iter().map(move |subscription| (meta_topic(event_name), subscription)))
+            .chain(self.pattern_subscriptions.iter().map(|pattern| (pattern.pattern.to_string(), &pattern.subscription)));
+        let mut silent = Vec::new();
+        let mut watched = HashSet::new();
+        for (topic, subscription) in subscriptions {
+            watched.insert(subscription.id);
+            let id = subscription.id;
+            // Only subscriptions that existed for the whole period are reported, once.
+            if watchdog.watched.contains(&id) && !watchdog.received.contains(&id) && watchdog.reported_silent.insert(id) {
+                silent.push(SilentSubscription { topic, subscriber: subscription.listener.name().to_string() });
+            }
+        }
+        watchdog.reported_silent.retain(|id| watched.contains(id));
+        watchdog.received.retain(|id| watched.contains(id));
+        silent.sort_by(|a, b| (&a.topic, &a.subscriber).cmp(&(&b.topic, &b.subscriber)));
+        watchdog.watched = watched;
+        watchdog.cycles = 0;
+        watchdog.started = now;
+        if !unsubscribed.is_empty() || !silent.is_empty() {
+            (watchdog.callback)(TopicWatchReport { unsubscribed, silent });
+        }
+    }
+
+    /// Returns whether the event name has a subscription, or a pattern subscription matching it.
+    fn is_subscribed(&self, event_name: &K) -> bool {
+        let topic = topic_str(event_name);
+        self.subscribers.get(event_name).is_some_and(|subscriptions| !subscriptions.is_empty())
+            || self.pattern_subscriptions.iter().any(|pattern| pattern.matches(topic))
+    }
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # On Unmatched Topics
+    ///
+    /// Checks the event bus every period, and calls the callback with the event names whose events were published
+    /// without any subscriber during the period, and the subscriptions that existed for a whole period
+    /// without receiving a single event. An event name is reported once, and again only after it had a subscriber
+    /// in between; a subscription is reported once. The callback is called at the end of a publish cycle,
+    /// while the state of the event bus is borrowed, so it cannot use the event bus.
+    /// Replaces the callback that was set before, starting a new period.
+    pub fn on_unmatched_topics(&self, period: WatchdogPeriod, callback: impl FnMut(TopicWatchReport<K>) + 'static) -> &Self {
+        let mut state = self.state.borrow_mut();
+        let started = state.clock.now();
+        state.topic_watchdog = Some(TopicWatchdog {
+            period,
+            cycles: 0,
+            started,
+            unsubscribed: HashSet::new(),
+            reported: HashSet::new(),
+            received: HashSet::new(),
+            watched: HashSet::new(),
+            reported_silent: HashSet::new(),
+            callback: Box::new(callback),
+        });
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+    use super::{SilentSubscription, TopicWatchReport, WatchdogPeriod};
+    use crate::{EventBus, Subscriber};
+    use crate::testing::ManualClock;
+
+    fn watched(event_bus: &EventBus, period: WatchdogPeriod) -> Rc<RefCell<Vec<TopicWatchReport>>> {
+        let reports = Rc::new(RefCell::new(Vec::new()));
+        let recorded = reports.clone();
+        event_bus.on_unmatched_topics(period, move |report| recorded.borrow_mut().push(report));
+        reports
+    }
+
+    fn publish_cycles(event_bus: &EventBus, cycles: usize) {
+        for _ in 0..cycles {
+            assert_eq!(Ok(()), event_bus.publish());
+        }
+    }
+
+    #[test]
+    fn test_reports_unsubscribed_topic_once_until_it_recovers() {
+        let event_bus = EventBus::new();
+        let reports = watched(&event_bus, WatchdogPeriod::Cycles(2));
+        event_bus.register("orders", 1u32).register("audit", 2u32);
+        publish_cycles(&event_bus, 2);
+        assert_eq!(1, reports.borrow().len());
+        assert_eq!(vec!["audit".to_string(), "orders".to_string()], reports.borrow()[0].unsubscribed);
+
+        event_bus.register("orders", 3u32);
+        publish_cycles(&event_bus, 2);
+        assert_eq!(1, reports.borrow().len());
+
+        let handle = event_bus.subscribe_unique("orders", <dyn Subscriber>::builder().on_event(|_| Ok(())).build()).unwrap();
+        event_bus.register("orders", 4u32);
+        publish_cycles(&event_bus, 2);
+        assert!(event_bus.unsubscribe(&handle));
+        event_bus.register("orders", 5u32);
+        publish_cycles(&event_bus, 2);
+
+        let reports = reports.borrow();
+        assert_eq!(2, reports.len());
+        assert_eq!(vec!["orders".to_string()], reports[1].unsubscribed);
+        assert!(reports.iter().all(|report| report.silent.is_empty()));
+    }
+
+    #[test]
+    fn test_reports_subscription_that_never_received_an_event() {
+        let clock = ManualClock::new();
+        let event_bus: EventBus = EventBus::with_clock(clock.clone());
+        let reports = watched(&event_bus, WatchdogPeriod::Duration(Duration::from_secs(10)));
+        event_bus.subscribe_pattern("orders/+", <dyn Subscriber>::builder().name("tracing").on_event(|_| Ok(())).build()).unwrap();
+        event_bus
+            .subscribe_listener("orders", <dyn Subscriber>::builder().name("billing").on_event(|_| Ok(())).build())
+            .subscribe_listener("refunds", <dyn Subscriber>::builder().name("ledger").on_event(|_| Ok(())).build())
+            .register("orders", 1u32);
+        for _ in 0..3 {
+            clock.advance(Duration::from_secs(10));
+            publish_cycles(&event_bus, 1);
+        }
+
+        let expected = vec![
+            SilentSubscription { topic: "orders/+".to_string(), subscriber: "tracing".to_string() },
+            SilentSubscription { topic: "refunds".to_string(), subscriber: "ledger".to_string() },
+        ];
+        assert_eq!(vec![TopicWatchReport { unsubscribed: Vec::new(), silent: expected }], *reports.borrow());
+    }
+}
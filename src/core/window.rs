@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+use super::{Event, EventBus, Instant, TopicKey, TopicMode};
+use super::state::BusState;
+
+impl<K: TopicKey> BusState<K> {
+    /// Returns when the window of the queued events of the event name closes,
+    /// or `None` when the event name does not batch by windows or is flushed.
+    fn window_closes(&self, event_name: &K, messages: &[Event]) -> Option<Instant> {
+        let TopicMode::WindowedBatch { window } = *self.topic_modes.get(event_name)? else {
+            return None;
+        };
+        if self.flushed_topics.contains(event_name) {
+            return None;
+        }
+        Some(messages.first()?.registered_at()? + window)
+    }
+
+    /// Returns whether the queued events of the event name are held, as their window is still open.
+    pub(crate) fn is_held(&self, event_name: &K, messages: &[Event], now: Instant) -> bool {
+        self.window_closes(event_name, messages).is_some_and(|closes| closes > now)
+    }
+
+    /// Returns the queues of the event names that batch by windows.
+    fn windowed(&self) -> impl Iterator<Item = (&K, &Vec<Event>)> + '_ {
+        self.topic_modes.iter()
+            .filter(|(_, mode)| matches!(mode, TopicMode::WindowedBatch { .. }))
+            .filter_map(|(event_name, _)| self.events.get_key_value(event_name))
+    }
+
+    /// Returns the number of queued events that are held until their window closes.
+    pub(crate) fn held_count(&self, now: Instant) -> usize {
+        self.windowed()
+            .filter(|(event_name, messages)| self.is_held(event_name, messages, now))
+            .map(|(_, messages)| messages.len())
+            .sum()
+    }
+
+    /// Returns when the first window of the held events closes.
+    pub(crate) fn next_window(&self) -> Option<Instant> {
+        self.windowed()
+            .filter_map(|(event_name, messages)| self.window_closes(event_name, messages))
+            .min()
+    }
+
+    /// Puts the events whose window is still open back in the queue,
+    /// and forgets the flushed event names whose events are released.
+    pub(crate) fn hold_windows(&mut self, events: &mut HashMap<K, Vec<Event>>) {
+        let now = self.clock.now();
+        let held: Vec<K> = events.iter()
+            .filter(|(event_name, messages)| self.is_held(event_name, messages, now))
+            .map(|(event_name, _)| event_name.clone())
+            .collect();
+        for event_name in held {
+            let messages = events.remove(&event_name).unwrap();
+            self.events.insert(event_name, messages);
+        }
+        self.flushed_topics.retain(|event_name| !events.contains_key(event_name));
+    }
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Flush Topic
+    ///
+    /// Releases the events of an event name in `TopicMode::WindowedBatch` with the next publish,
+    /// before their window closes. When none are queued, the next events of the event name are released
+    /// by the first publish after they are registered.
+    pub fn flush_topic(&self, event_name: impl Into<K>) -> &Self {
+        let event_name = self.topic_key(event_name);
+        self.state.borrow_mut().flushed_topics.insert(event_name);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::{DeadLetterReason, Event, EventBus, TopicMode};
+    use crate::subscribers::CollectingSubscriber;
+    use crate::testing::ManualClock;
+
+    fn windowed(clock: &ManualClock) -> (EventBus, std::rc::Rc<std::cell::RefCell<Vec<u32>>>) {
+        let event_bus: EventBus = EventBus::with_clock(clock.clone());
+        let (collector, received) = CollectingSubscriber::<u32>::new();
+        event_bus
+            .set_topic_mode("db.writes", TopicMode::WindowedBatch { window: Duration::from_millis(100) })
+            .subscribe_listener("db.writes", collector);
+        (event_bus, received)
+    }
+
+    #[test]
+    fn test_window_elapsing_releases_one_batch() {
+        let clock = ManualClock::new();
+        let (event_bus, received) = windowed(&clock);
+        event_bus.register("db.writes", 1u32).register("db.writes", 2u32);
+        clock.advance(Duration::from_millis(60));
+        event_bus.register("db.writes", 3u32);
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert!(received.borrow().is_empty());
+        assert_eq!(3, event_bus.pending(&"db.writes".to_string()));
+
+        clock.advance(Duration::from_millis(40));
+        let report = event_bus.publish_report().unwrap();
+        assert_eq!(3, report.topic(&"db.writes".to_string()).handled);
+        assert_eq!(vec![1, 2, 3], *received.borrow());
+    }
+
+    #[test]
+    fn test_flush_topic_releases_early_and_expired_events_are_dead_lettered() {
+        let clock = ManualClock::new();
+        let (event_bus, received) = windowed(&clock);
+        event_bus
+            .register("db.writes", Event::new(1u32).with_ttl(Duration::from_millis(20)))
+            .register("db.writes", 2u32);
+        clock.advance(Duration::from_millis(30));
+
+        event_bus.flush_topic("db.writes");
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![2], *received.borrow());
+        let dead_letters = event_bus.take_dead_letters();
+        assert_eq!(vec![DeadLetterReason::Expired], dead_letters.iter().map(|dead| dead.reason.clone()).collect::<Vec<_>>());
+
+        event_bus.register("db.writes", 3u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![2], *received.borrow());
+    }
+
+    #[test]
+    fn test_flush_topic_waits_for_events_to_release() {
+        let clock = ManualClock::new();
+        let (event_bus, received) = windowed(&clock);
+        event_bus.flush_topic("db.writes").register("other", 1u32);
+        assert_eq!(Ok(()), event_bus.publish());
+
+        event_bus.register("db.writes", 2u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![2], *received.borrow());
+
+        event_bus.register("db.writes", 3u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![2], *received.borrow());
+    }
+}
@@ -0,0 +1,308 @@
+use std::sync::mpsc::{self, Receiver, TrySendError};
+use std::time::Duration;
+use super::{Event, EventBus, EventContext, Instant, Outcome, Phase, Subscriber, TopicKey};
+use super::executor::{Executor, MailSender, MailboxTask, TaskHandle};
+use super::state::{meta_topic, BusState};
+use super::subscription::Subscription;
+
+/// A message in the mailbox of a worker thread.
+pub(crate) enum Mail<T> {
+    /// The payload of an event, with the id of the event it was taken from.
+    Event(T, u64),
+    Stop,
+}
+
+/// A failure of a subscriber on a worker thread, sent back to the event bus.
+pub(crate) struct WorkerFailure {
+    subscriber: String,
+    phase: Phase,
+    message: String,
+    event_id: u64,
+}
+
+/// A worker thread owning a subscriber, as it is kept by the event bus.
+pub(crate) struct Worker<K: TopicKey> {
+    topic: K,
+    handle: TaskHandle,
+    /// Queues a stop in the mailbox, returns false while the mailbox is full.
+    stop: Box<dyn Fn() -> bool>,
+    failures: Receiver<WorkerFailure>,
+}
+
+/// The subscription on the event bus that forwards the payloads to the mailbox of a worker thread.
+struct Mailbox<T> {
+    name: String,
+    sender: MailSender<Mail<T>>,
+}
+
+impl<T: Clone + Send + 'static> Subscriber for Mailbox<T> {
+    /// Ignores events with another payload type, and unsubscribes once the worker has stopped.
+    fn on_event_outcome(&mut self, event: &mut Event) -> Outcome {
+        let data = match event.get_data::<T>() {
+            Some(data) => data.clone(),
+            None => return Outcome::Ignored,
+        };
+        match self.sender.send(Mail::Event(data, event.id())) {
+            Ok(()) => Outcome::Ack,
+            Err(_) => Outcome::AckAndUnsubscribe,
+        }
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Delivers a payload from the mailbox of a worker thread to its listener, returns whether the worker keeps running.
+fn run_worker<T: 'static>(listener: &mut dyn Subscriber, topic: &str, data: T, event_id: u64, failures: &mpsc::Sender<WorkerFailure>) -> bool {
+    let mut event = Event::new(data);
+    let mut context = EventContext::new(None, topic.to_string(), Instant::now());
+    !deliver_on_worker(listener, &mut event, &mut context, event_id, failures)
+}
+
+/// Delivers an event to a listener on a worker thread, sending its failure back to the event bus.
+/// Returns whether the listener unsubscribed.
+pub(crate) fn deliver_on_worker(
+    listener: &mut dyn Subscriber,
+    event: &mut Event,
+    context: &mut EventContext,
+    event_id: u64,
+    failures: &mpsc::Sender<WorkerFailure>,
+) -> bool {
+    let mut unsubscribe = false;
+    let result = listener.on_before(event).map_err(|e| (Phase::Before, e))
+        .and_then(|_| match listener.on_event_with_context(event, context) {
+            Outcome::Error(e) => Err((Phase::Event, e)),
+            outcome => {
+                unsubscribe = outcome == Outcome::AckAndUnsubscribe;
+                Ok(())
+            }
+        })
+        .and_then(|_| listener.on_after(event).map_err(|e| (Phase::After, e)));
+    if let Err((phase, message)) = result {
+        let failure = WorkerFailure { subscriber: listener.name().to_string(), phase, message, event_id };
+        let _ = failures.send(failure);
+    }
+    unsubscribe
+}
+
+impl<K: TopicKey> Worker<K> {
+    /// Spawns a worker thread of the event name on the executor, running on a mailbox that holds up to `capacity` mails.
+    /// The worker hands each payload to `run`, until it is stopped or `run` returns false.
+    /// Returns the worker, and the sender of its mailbox.
+    pub(crate) fn spawn<T: Send + 'static>(
+        executor: &Executor,
+        topic: K,
+        capacity: usize,
+        mut run: impl FnMut(T, u64, &mpsc::Sender<WorkerFailure>) -> bool + Send + 'static,
+    ) -> (Worker<K>, MailSender<Mail<T>>) {
+        let (sender, mailbox) = mpsc::sync_channel(capacity);
+        let (failure_sender, failures) = mpsc::channel();
+        let handle = executor.spawn(MailboxTask {
+            mailbox,
+            handle: move |mail| match mail {
+                Mail::Event(data, event_id) => run(data, event_id, &failure_sender),
+                Mail::Stop => false,
+            },
+        });
+        let stop_sender = sender.clone();
+        let stop = Box::new(move || !matches!(stop_sender.try_send(Mail::Stop), Err(TrySendError::Full(_))));
+        let sender = handle.sender(sender);
+        (Worker { topic, handle, stop, failures }, sender)
+    }
+}
+
+impl<K: TopicKey> EventBus<K> {
+    /// # Subscribe On Thread
+    ///
+    /// Subscribes a listener that runs on its own worker thread, receiving the events
+    /// with a payload of type `T` in the order they are published.
+    /// Publishing clones the payload into a mailbox of the worker that holds up to `capacity` payloads,
+    /// and only waits for the worker when its mailbox is full.
+    /// The worker receives a new event holding the payload, so only the payload is shared with it.
+    /// The application context of the event bus stays on its own thread, the listener receives an empty context.
+    ///
+    /// The failures of the listener are reported by the next publish, or by `join_workers`,
+    /// to the logger and to the error topic when errors are routed.
+    pub fn subscribe_on_thread<T, R>(&self, event_name: impl Into<K>, listener: R, capacity: usize) -> &Self
+    where
+        T: Clone + Send + 'static,
+        R: Subscriber + Send + 'static,
+    {
+        let event_name = self.topic_key(event_name);
+        let name = listener.name().to_string();
+        let topic = meta_topic(&event_name);
+        let executor = self.state.borrow().executor.clone();
+        let mut listener = listener;
+        let (worker, sender) = Worker::spawn(&executor, event_name.clone(), capacity, move |data: T, event_id, failures| {
+            run_worker(&mut listener, &topic, data, event_id, failures)
+        });
+        {
+            let mut state = self.state.borrow_mut();
+            state.workers.push(worker);
+            state.subscribe(event_name, Subscription::new(Mailbox { name, sender }));
+        }
+        self
+    }
+
+    /// # Join Workers
+    ///
+    /// Stops the worker threads once they received the events that are already in their mailbox,
+    /// and waits for them to finish for at most the timeout, on the clock of the event bus.
+    /// Returns an error when a worker did not finish in time, or panicked.
+    pub fn join_workers(&self, timeout: Duration) -> Result<&Self, String> {
+        let (workers, clock) = {
+            let mut state = self.state.borrow_mut();
+            (std::mem::take(&mut state.workers), state.clock.clone())
+        };
+        let deadline = clock.now() + timeout;
+        let mut unfinished = Vec::new();
+        let mut errors = Vec::new();
+        for worker in workers {
+            while !(worker.stop)() && clock.now() < deadline {
+                worker.handle.yield_to(&*clock);
+            }
+            while !worker.handle.is_finished() && clock.now() < deadline {
+                worker.handle.yield_to(&*clock);
+            }
+            if !worker.handle.is_finished() {
+                unfinished.push(worker);
+                continue;
+            }
+            let mut state = self.state.borrow_mut();
+            state.collect_worker_failures(&worker);
+            if !worker.handle.join() {
+                errors.push(format!("A worker of {:?} panicked", worker.topic));
+            }
+        }
+        if !unfinished.is_empty() {
+            errors.push(format!("{} workers did not finish within {:?}", unfinished.len(), timeout));
+        }
+        let mut state = self.state.borrow_mut();
+        state.workers.append(&mut unfinished);
+        if !errors.is_empty() {
+            return Err(errors.join(", "));
+        }
+        Ok(self)
+    }
+}
+
+impl<K: TopicKey> BusState<K> {
+    /// Logs and routes the failures the worker threads reported since the last publish.
+    pub(crate) fn collect_failures_of_workers(&mut self) {
+        let workers = std::mem::take(&mut self.workers);
+        for worker in &workers {
+            self.collect_worker_failures(worker);
+        }
+        self.workers = workers;
+    }
+
+    fn collect_worker_failures(&mut self, worker: &Worker<K>) {
+        for failure in worker.failures.try_iter() {
+            self.logger.subscriber_error(&worker.topic, &failure.subscriber, failure.phase, &failure.message);
+            self.route_error(&worker.topic, &failure.subscriber, failure.phase, &failure.message, failure.event_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::mpsc::{self, Receiver};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use crate::{Event, EventBus, Phase, Subscriber, SubscriberFailure};
+
+    struct GatedSubscriber {
+        gate: Receiver<()>,
+        received: Arc<Mutex<Vec<u32>>>,
+    }
+
+    impl Subscriber for GatedSubscriber {
+        fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+            self.gate.recv().map_err(|e| e.to_string())?;
+            self.received.lock().unwrap().push(*event.get_data::<u32>().unwrap());
+            Ok(())
+        }
+    }
+
+    struct MainSubscriber {
+        received: Rc<RefCell<Vec<u32>>>,
+    }
+
+    impl Subscriber for MainSubscriber {
+        fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+            self.received.borrow_mut().push(*event.get_data::<u32>().unwrap());
+            Ok(())
+        }
+    }
+
+    struct RejectingSubscriber;
+
+    impl Subscriber for RejectingSubscriber {
+        fn on_event(&mut self, _event: &mut Event) -> Result<(), String> {
+            Err("Rejected on the worker".to_string())
+        }
+
+        fn name(&self) -> &str {
+            "rejecting"
+        }
+    }
+
+    struct FailureCollector {
+        failures: Rc<RefCell<Vec<SubscriberFailure>>>,
+    }
+
+    impl Subscriber for FailureCollector {
+        fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+            self.failures.borrow_mut().push(event.get_data::<SubscriberFailure>().unwrap().clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_slow_worker_does_not_block_publish() {
+        let (gate, gate_receiver) = mpsc::channel();
+        let worker_received = Arc::new(Mutex::new(Vec::new()));
+        let main_received = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .subscribe_on_thread::<u32, _>("frames", GatedSubscriber { gate: gate_receiver, received: worker_received.clone() }, 8)
+            .subscribe_listener("frames", MainSubscriber { received: main_received.clone() })
+            .register("frames", 1u32)
+            .register("frames", 2u32)
+            .register("frames", 3u32);
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![1, 2, 3], *main_received.borrow());
+        assert!(worker_received.lock().unwrap().is_empty());
+
+        for _ in 0..3 {
+            gate.send(()).unwrap();
+        }
+        assert!(event_bus.join_workers(Duration::from_secs(5)).is_ok());
+        assert_eq!(vec![1, 2, 3], *worker_received.lock().unwrap());
+    }
+
+    #[test]
+    fn test_worker_failures_are_routed() {
+        let failures = Rc::new(RefCell::new(Vec::new()));
+        let event_bus = EventBus::new();
+        event_bus
+            .route_errors_to("errors")
+            .subscribe_listener("errors", FailureCollector { failures: failures.clone() })
+            .subscribe_on_thread::<u32, _>("frames", RejectingSubscriber, 8)
+            .register("frames", 1u32);
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert!(event_bus.join_workers(Duration::from_secs(5)).is_ok());
+        assert_eq!(Ok(()), event_bus.publish());
+
+        let failures = failures.borrow();
+        assert_eq!(1, failures.len());
+        assert_eq!("rejecting", failures[0].subscriber);
+        assert_eq!(Phase::Event, failures[0].phase);
+        assert_eq!("Rejected on the worker", failures[0].message);
+    }
+}
@@ -1,5 +1,130 @@
 mod core;
+mod macros;
+#[cfg(feature = "ffi")]
+pub use crate::core::ffi;
+pub mod normalizers;
+pub mod subscribers;
+pub mod testing;
 
+pub use crate::core::AlreadyPublishing;
+pub use crate::core::BeforeFailure;
+#[cfg(feature = "stream")]
+pub use crate::core::Backpressure;
+pub use crate::core::BreakerState;
+pub use crate::core::BreakerStateChanged;
+pub use crate::core::Budget;
+pub use crate::core::BusEvent;
+pub use crate::core::BusLogger;
+pub use crate::core::BusMetrics;
+pub use crate::core::BusRouter;
+pub use crate::core::BusStatsSnapshot;
+pub use crate::core::CancelToken;
+pub use crate::core::CapacityOverflow;
+pub use crate::core::CircuitBreaker;
+pub use crate::core::Clock;
+pub use crate::core::CloneRegistry;
+pub use crate::core::DeadLetter;
+pub use crate::core::DeadLetterReason;
+pub use crate::core::DebugSession;
+pub use crate::core::DeadLettered;
+pub use crate::core::DeferMode;
+pub use crate::core::DispatchOrder;
 pub use crate::core::Event;
+pub use crate::core::ErrorPolicy;
+pub use crate::core::DuplicateSubscriber;
 pub use crate::core::EventBus;
+pub use crate::core::EventContext;
+pub use crate::core::EventSink;
+#[cfg(feature = "stream")]
+pub use crate::core::EventStream;
+pub use crate::core::Heartbeat;
+pub use crate::core::Instant;
+pub use crate::core::IntoEvent;
+pub use crate::core::InvalidPattern;
+pub use crate::core::NotCloneable;
+pub use crate::core::LatencyStats;
+#[cfg(feature = "log")]
+pub use crate::core::LogLogger;
+pub use crate::core::MemoryFootprint;
+pub use crate::core::NullLogger;
+pub use crate::core::Outcome;
+pub use crate::core::OverflowAction;
+pub use crate::core::PayloadError;
+pub use crate::core::Phase;
+pub use crate::core::PlannedDelivery;
+#[cfg(feature = "plugins")]
+pub use crate::core::PluginEntry;
+#[cfg(feature = "plugins")]
+pub use crate::core::PluginError;
+#[cfg(feature = "plugins")]
+pub use crate::core::PluginHandle;
+#[cfg(feature = "plugins")]
+pub use crate::core::PluginVTable;
+#[cfg(feature = "plugins")]
+pub use crate::core::PLUGIN_ABI_VERSION;
+pub use crate::core::PayloadRegistry;
+pub use crate::core::PayloadTypeError;
+pub use crate::core::PermissionDenied;
+pub use crate::core::Prioritized;
+pub use crate::core::PublishCompleted;
+pub use crate::core::Publisher;
+pub use crate::core::PublishReport;
+pub use crate::core::PublishOutcome;
+pub use crate::core::PublishStatus;
+pub use crate::core::PumpResult;
+pub use crate::core::QueueDepthAlert;
+pub use crate::core::RawPayload;
+pub use crate::core::ReadOnlySubscriber;
+pub use crate::core::RegisterError;
+pub use crate::core::RemainingWork;
+#[cfg(feature = "net")]
+pub use crate::core::RemotePublisher;
+#[cfg(feature = "net")]
+pub use crate::core::RemoteSource;
+pub use crate::core::ReplayFilter;
+pub use crate::core::ReplayOptions;
+pub use crate::core::ReplayReport;
+pub use crate::core::RoutingPlan;
+pub use crate::core::RouteError;
+pub use crate::core::RouteMode;
+pub use crate::core::RunOptions;
+pub use crate::core::RunSummary;
+pub use crate::core::ScheduleId;
+pub use crate::core::ScheduleInfo;
+pub use crate::core::ShutdownSignal;
+#[cfg(feature = "signals")]
+pub use crate::core::Signal;
+#[cfg(feature = "signals")]
+pub use crate::core::SignalError;
+pub use crate::core::SkipReason;
+pub use crate::core::StepInfo;
 pub use crate::core::Subscriber;
+#[cfg(feature = "stream")]
+pub use crate::core::StreamedEvent;
+pub use crate::core::SilentSubscription;
+pub use crate::core::SubscriberAdded;
+pub use crate::core::SubscriberBuilder;
+pub use crate::core::SubscriberFailure;
+pub use crate::core::SubscriberRemoved;
+pub use crate::core::SubscriberState;
+pub use crate::core::SubscriptionHandle;
+pub use crate::core::SystemClock;
+#[cfg(feature = "threaded")]
+pub use crate::core::ThreadSink;
+pub use crate::core::TopicFirstEvent;
+pub use crate::core::TopicInspector;
+pub use crate::core::TopicKey;
+pub use crate::core::TopicMetrics;
+pub use crate::core::TopicMode;
+pub use crate::core::TopicPolicy;
+pub use crate::core::TopicReport;
+pub use crate::core::TopicRoute;
+pub use crate::core::TopicWatchReport;
+pub use crate::core::TraceEntry;
+#[cfg(feature = "uds")]
+pub use crate::core::UdsPublisher;
+pub use crate::core::UnknownHandle;
+pub use crate::core::UnknownTopic;
+pub use crate::core::UnroutableEvent;
+pub use crate::core::ValidationMode;
+pub use crate::core::WatchdogPeriod;
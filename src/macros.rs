@@ -0,0 +1,125 @@
+/// # Topics
+///
+/// Declares event names once, as `&'static str` constants.
+/// Next to the constants, it declares `ALL` with every declared event name
+/// and `is_declared` to check whether an event name was declared.
+///
+/// ```
+/// mod topics {
+///     simple_event_bus::topics! {
+///         PLAYER_MOVED = "player.moved",
+///         PLAYER_DIED = "player.died",
+///     }
+/// }
+///
+/// let mut event_bus = simple_event_bus::EventBus::new();
+/// event_bus.restrict_topics(topics::ALL);
+/// assert!(topics::is_declared(topics::PLAYER_DIED));
+/// ```
+#[macro_export]
+macro_rules! topics {
+    ($($name:ident = $topic:expr),* $(,)?) => {
+        $(pub const $name: &str = $topic;)*
+
+        /// All declared event names.
+        pub const ALL: &[&str] = &[$($name),*];
+
+        /// Returns whether the event name was declared.
+        pub fn is_declared(topic: &str) -> bool {
+            ALL.contains(&topic)
+        }
+    };
+}
+
+/// # Define Event
+///
+/// Declares a payload struct together with its event name, implementing `BusEvent` for it.
+/// Next to the struct, it declares the `TOPIC` constant of the struct with the event name.
+///
+/// ```
+/// use simple_event_bus::{BusEvent, EventBus};
+///
+/// simple_event_bus::define_event!(
+///     #[derive(Debug, Clone)]
+///     PlayerDied { id: u64, cause: String } => "player.died"
+/// );
+///
+/// let event_bus: EventBus = EventBus::new();
+/// event_bus.subscribe_event(|died: &mut PlayerDied| {
+///     assert_eq!((7, "lava"), (died.id, died.cause.as_str()));
+///     Ok(())
+/// });
+/// PlayerDied { id: 7, cause: "lava".to_string() }.register_on(&event_bus);
+/// assert_eq!(Ok(()), event_bus.publish());
+/// assert_eq!("player.died", PlayerDied::TOPIC);
+/// ```
+#[macro_export]
+macro_rules! define_event {
+    ($(#[$meta:meta])* $name:ident { $($field:ident : $type:ty),* $(,)? } => $topic:expr) => {
+        $(#[$meta])*
+        pub struct $name {
+            $(pub $field: $type,)*
+        }
+
+        impl $name {
+            /// The event name of the event.
+            pub const TOPIC: &'static str = $topic;
+        }
+
+        impl $crate::BusEvent for $name {
+            fn topic() -> &'static str {
+                Self::TOPIC
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use crate::{BusEvent, EventBus};
+    use events::{PlayerDied, PlayerMoved};
+
+    mod declared {
+        crate::topics! {
+            PLAYER_MOVED = "player.moved",
+            PLAYER_DIED = "player.died",
+        }
+    }
+
+    mod events {
+        crate::define_event!(PlayerMoved { id: u64, x: i32, y: i32 } => "player.moved");
+        crate::define_event!(PlayerDied { id: u64, cause: String } => "player.died");
+    }
+
+    #[test]
+    fn test_define_event_flows_through_generated_api() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let (moved, died) = (log.clone(), log.clone());
+        let event_bus: EventBus = EventBus::new();
+        event_bus
+            .subscribe_event(move |event: &mut PlayerMoved| {
+                moved.borrow_mut().push(format!("{} moved to {},{}", event.id, event.x, event.y));
+                Ok(())
+            })
+            .subscribe_event(move |event: &mut PlayerDied| {
+                died.borrow_mut().push(format!("{} died of {}", event.id, event.cause));
+                Ok(())
+            });
+        PlayerMoved { id: 1, x: 3, y: 4 }.register_on(&event_bus);
+        PlayerDied { id: 1, cause: "lava".to_string() }.register_on(&event_bus);
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec!["1 moved to 3,4", "1 died of lava"], *log.borrow());
+        assert_eq!(("player.moved", "player.died"), (PlayerMoved::topic(), PlayerDied::TOPIC));
+    }
+
+    #[test]
+    fn test_topics_expansion() {
+        assert_eq!("player.moved", declared::PLAYER_MOVED);
+        assert_eq!(&["player.moved", "player.died"], declared::ALL);
+        assert!(declared::is_declared("player.died"));
+        assert!(!declared::is_declared("player.dead"));
+    }
+}
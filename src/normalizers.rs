@@ -0,0 +1,8 @@
+//! Topic normalizers that ship with the event bus, see `EventBus::set_topic_normalizer`.
+
+/// # Lowercase Trim
+///
+/// Removes the leading and trailing whitespace of an event name, and lowercases it.
+pub fn lowercase_trim(topic: &str) -> String {
+    topic.trim().to_lowercase()
+}
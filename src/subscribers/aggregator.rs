@@ -0,0 +1,216 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use crate::{CloneRegistry, Event, EventBus, EventContext, Outcome, Subscriber};
+
+/// # Aggregator
+///
+/// Waits for an event of each of its input event names, and then emits a combined event on its output event name.
+/// It keeps the latest event of each input, cloned with the payload types added with `with_cloneable`;
+/// an event holding another type fails the subscriber. Once every input has an event, the combine function
+/// turns them into the output event, in the order of the inputs. By default the aggregator then forgets the events
+/// and waits for each input again, a latched aggregator keeps them and emits again on every later input event.
+///
+/// ```
+/// use simple_event_bus::{Event, EventBus};
+/// use simple_event_bus::subscribers::{Aggregator, CollectingSubscriber};
+///
+/// let aggregator = Aggregator::new(&["cfg.loaded", "db.connected"], "app.ready", |_| Event::new("ready"))
+///     .with_cloneable::<()>();
+/// let (subscriber, ready) = CollectingSubscriber::<&str>::new();
+/// let event_bus = EventBus::new();
+/// aggregator.subscribe_to(&event_bus);
+/// event_bus
+///     .subscribe_listener("app.ready", subscriber)
+///     .register("db.connected", ())
+///     .register("cfg.loaded", ());
+///
+/// assert_eq!(Ok(()), event_bus.publish());
+/// assert_eq!(Ok(()), event_bus.publish());
+/// assert_eq!(vec!["ready"], *ready.borrow());
+/// ```
+///
+/// ## Methods
+///
+/// * `new` - Creates an aggregator of the input event names.
+///
+/// * `latched` - Keeps the events after emitting, emitting again on every later input event.
+///
+/// * `with_cloneable` - Adds a payload type that can be kept.
+///
+/// * `subscribe_to` - Subscribes the aggregator to its input event names.
+///
+/// * `received`, `missing` - Return the input event names with and without an event.
+#[derive(Clone)]
+pub struct Aggregator {
+    aggregation: Rc<RefCell<Aggregation>>,
+}
+
+type Combine = Box<dyn Fn(&[&Event]) -> Event>;
+
+struct Aggregation {
+    inputs: Vec<String>,
+    latest: Vec<Option<Event>>,
+    output: String,
+    combine: Combine,
+    latched: bool,
+    cloneables: CloneRegistry,
+}
+
+impl Aggregator {
+    /// # New
+    ///
+    /// Creates an aggregator that emits the event made by the combine function on the output event name,
+    /// once each input event name has an event.
+    pub fn new(inputs: &[&str], output: &str, combine: impl Fn(&[&Event]) -> Event + 'static) -> Aggregator {
+        let aggregation = Aggregation {
+            inputs: inputs.iter().map(|input| input.to_string()).collect(),
+            latest: inputs.iter().map(|_| None).collect(),
+            output: output.to_string(),
+            combine: Box::new(combine),
+            latched: false,
+            cloneables: CloneRegistry::new(),
+        };
+        Aggregator { aggregation: Rc::new(RefCell::new(aggregation)) }
+    }
+
+    /// # Latched
+    ///
+    /// Keeps the events of the inputs after emitting, so every later event of an input emits a new combined event.
+    pub fn latched(self) -> Aggregator {
+        self.aggregation.borrow_mut().latched = true;
+        self
+    }
+
+    /// # With Cloneable
+    ///
+    /// Adds payloads of type `T` to the payloads the aggregator can keep.
+    pub fn with_cloneable<T: Clone + 'static>(self) -> Aggregator {
+        self.aggregation.borrow_mut().cloneables.register::<T>();
+        self
+    }
+
+    /// # Subscribe To
+    ///
+    /// Subscribes the aggregator to each of its input event names.
+    pub fn subscribe_to(&self, event_bus: &EventBus) {
+        let inputs = self.aggregation.borrow().inputs.clone();
+        for (index, input) in inputs.into_iter().enumerate() {
+            event_bus.subscribe_listener(input, AggregatorInput { aggregation: self.aggregation.clone(), index });
+        }
+    }
+
+    /// # Received
+    ///
+    /// Returns the input event names the aggregator keeps an event of.
+    pub fn received(&self) -> Vec<String> {
+        self.inputs_where(true)
+    }
+
+    /// # Missing
+    ///
+    /// Returns the input event names the aggregator still waits for.
+    pub fn missing(&self) -> Vec<String> {
+        self.inputs_where(false)
+    }
+
+    fn inputs_where(&self, received: bool) -> Vec<String> {
+        let aggregation = self.aggregation.borrow();
+        aggregation.inputs.iter().zip(&aggregation.latest)
+            .filter(|(_, latest)| latest.is_some() == received)
+            .map(|(input, _)| input.clone())
+            .collect()
+    }
+}
+
+/// The subscription of an aggregator to one of its inputs.
+struct AggregatorInput {
+    aggregation: Rc<RefCell<Aggregation>>,
+    index: usize,
+}
+
+impl Subscriber for AggregatorInput {
+    fn on_event_with_context(&mut self, event: &mut Event, context: &mut EventContext) -> Outcome {
+        let mut aggregation = self.aggregation.borrow_mut();
+        let kept = match event.try_clone(&aggregation.cloneables) {
+            Ok(kept) => kept,
+            Err(e) => return Outcome::Error(e.to_string()),
+        };
+        aggregation.latest[self.index] = Some(kept);
+        let Some(events) = aggregation.latest.iter().map(Option::as_ref).collect::<Option<Vec<&Event>>>() else {
+            return Outcome::Ack;
+        };
+        context.emit(aggregation.output.clone(), (aggregation.combine)(&events));
+        if !aggregation.latched {
+            aggregation.latest.iter_mut().for_each(|latest| *latest = None);
+        }
+        Outcome::Ack
+    }
+
+    fn name(&self) -> &str {
+        "aggregator"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use crate::{Event, EventBus};
+    use super::Aggregator;
+    use crate::subscribers::CollectingSubscriber;
+
+    const INPUTS: [&str; 3] = ["cfg.loaded", "db.connected", "cache.warm"];
+
+    fn app_ready(aggregator: Aggregator) -> (EventBus, Rc<RefCell<Vec<u32>>>) {
+        let (collector, ready) = CollectingSubscriber::<u32>::new();
+        let event_bus = EventBus::new();
+        aggregator.with_cloneable::<u32>().subscribe_to(&event_bus);
+        event_bus.subscribe_listener("app.ready", collector);
+        (event_bus, ready)
+    }
+
+    fn sum(events: &[&Event]) -> Event {
+        Event::new(events.iter().map(|event| *event.get_data::<u32>().unwrap()).sum::<u32>())
+    }
+
+    #[test]
+    fn test_all_inputs_in_any_order_emit_once() {
+        for order in [[0, 1, 2], [2, 0, 1], [1, 2, 0]] {
+            let aggregator = Aggregator::new(&INPUTS, "app.ready", sum);
+            let (event_bus, ready) = app_ready(aggregator.clone());
+            for (value, index) in order.into_iter().enumerate() {
+                event_bus.register(INPUTS[index], value as u32 + 1);
+                assert_eq!(Ok(()), event_bus.publish());
+            }
+            assert_eq!(Ok(()), event_bus.publish());
+            assert_eq!(vec![6], *ready.borrow());
+            assert_eq!(INPUTS.to_vec(), aggregator.missing());
+        }
+    }
+
+    #[test]
+    fn test_missing_input_emits_nothing() {
+        let aggregator = Aggregator::new(&INPUTS, "app.ready", sum);
+        let (event_bus, ready) = app_ready(aggregator.clone());
+        event_bus.register("cfg.loaded", 1u32).register("cache.warm", 2u32).register("cfg.loaded", 3u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(Ok(()), event_bus.publish());
+
+        assert!(ready.borrow().is_empty());
+        assert_eq!(vec!["cfg.loaded", "cache.warm"], aggregator.received());
+        assert_eq!(vec!["db.connected"], aggregator.missing());
+    }
+
+    #[test]
+    fn test_latched_aggregator_emits_on_every_later_input() {
+        let aggregator = Aggregator::new(&INPUTS, "app.ready", sum).latched();
+        let (event_bus, ready) = app_ready(aggregator);
+        event_bus.register("cfg.loaded", 1u32).register("db.connected", 2u32).register("cache.warm", 3u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        event_bus.register("db.connected", 10u32);
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(Ok(()), event_bus.publish());
+
+        assert_eq!(vec![6, 14], *ready.borrow());
+    }
+}
@@ -0,0 +1,143 @@
+use std::sync::mpsc::Sender;
+use crate::{CloneRegistry, Event, EventContext, Outcome, Subscriber};
+
+/// # Forwarded Event
+///
+/// An event forwarded into a channel by a `ChannelSubscriber`.
+/// The event keeps the properties of the original event like its schema version and ttl.
+///
+/// ## Fields
+///
+/// * `topic` - The event name the event was published on, as text.
+///
+/// * `event_id` - The id of the original event, a cloned event has an id of its own.
+///
+/// * `event` - The event holding the payload, cloned or moved out of the original event.
+#[derive(Debug)]
+pub struct ForwardedEvent {
+    /// The event name the event was published on, as text.
+    pub topic: String,
+    /// The id of the original event.
+    pub event_id: u64,
+    /// The event holding the payload.
+    pub event: Event,
+}
+
+/// # Channel Subscriber
+///
+/// Forwards the events it receives into a channel, for a part of the program
+/// that consumes them at its own pace.
+/// By default it forwards a clone of each event, for the payload types added with `with_cloneable`;
+/// an event holding another type fails the subscriber.
+/// A consuming channel subscriber, created with `consuming`, moves the payload out of the event instead
+/// and stops its propagation, as the remaining subscribers would only find `()` in it.
+///
+/// Once the receiver is dropped, each event fails the subscriber, so the error policy applies.
+///
+/// ## Methods
+///
+/// * `new` - Creates a channel subscriber forwarding clones of the events.
+///
+/// * `consuming` - Creates a channel subscriber moving the payloads out of the events.
+///
+/// * `with_cloneable` - Adds a payload type that can be cloned.
+pub struct ChannelSubscriber {
+    sender: Sender<ForwardedEvent>,
+    /// The payload types that can be cloned, `None` when the payloads are moved out.
+    cloneables: Option<CloneRegistry>,
+}
+
+impl ChannelSubscriber {
+    /// # New
+    ///
+    /// Creates a channel subscriber forwarding clones of the events into the channel.
+    pub fn new(sender: Sender<ForwardedEvent>) -> ChannelSubscriber {
+        ChannelSubscriber { sender, cloneables: Some(CloneRegistry::new()) }
+    }
+
+    /// # Consuming
+    ///
+    /// Creates a channel subscriber moving the payloads out of the events into the channel.
+    pub fn consuming(sender: Sender<ForwardedEvent>) -> ChannelSubscriber {
+        ChannelSubscriber { sender, cloneables: None }
+    }
+
+    /// # With Cloneable
+    ///
+    /// Adds payloads of type `T` to the payloads that can be cloned into the channel.
+    /// It has no effect on a consuming channel subscriber.
+    pub fn with_cloneable<T: Clone + 'static>(mut self) -> ChannelSubscriber {
+        if let Some(cloneables) = &mut self.cloneables {
+            cloneables.register::<T>();
+        }
+        self
+    }
+}
+
+impl Subscriber for ChannelSubscriber {
+    fn on_event_with_context(&mut self, event: &mut Event, context: &mut EventContext) -> Outcome {
+        let event_id = event.id();
+        let (forwarded, outcome) = match &self.cloneables {
+            Some(cloneables) => match event.try_clone(cloneables) {
+                Ok(clone) => (clone, Outcome::Ack),
+                Err(e) => return Outcome::Error(e.to_string()),
+            },
+            None => (event.take(), Outcome::Stop),
+        };
+        let forwarded = ForwardedEvent { topic: context.topic().to_string(), event_id, event: forwarded };
+        match self.sender.send(forwarded) {
+            Ok(()) => outcome,
+            Err(_) => Outcome::Error(format!("The receiver of event {} is dropped", event_id)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use crate::{DispatchOrder, ErrorPolicy, Event, EventBus};
+    use super::ChannelSubscriber;
+
+    #[test]
+    fn test_forwards_every_event_in_order() {
+        let (sender, receiver) = mpsc::channel();
+        let events = [Event::new(1u32), Event::new("hello".to_string()), Event::new(2u32)];
+        let ids: Vec<u64> = events.iter().map(Event::id).collect();
+        let mut event_bus = EventBus::new();
+        event_bus
+            .set_dispatch_order(DispatchOrder::GlobalFifo)
+            .subscribe_listener("score", ChannelSubscriber::new(sender.clone()).with_cloneable::<u32>())
+            .subscribe_listener("chat", ChannelSubscriber::consuming(sender));
+        event_bus.extend(["score", "chat", "score"].into_iter().map(String::from).zip(events));
+
+        assert_eq!(Ok(()), event_bus.publish());
+        let forwarded: Vec<(String, u64, String)> = receiver.try_iter()
+            .map(|forwarded| {
+                let payload = match forwarded.event.get_data::<u32>() {
+                    Some(score) => score.to_string(),
+                    None => forwarded.event.get_data::<String>().unwrap().clone(),
+                };
+                (forwarded.topic, forwarded.event_id, payload)
+            })
+            .collect();
+        assert_eq!(vec![
+            ("score".to_string(), ids[0], "1".to_string()),
+            ("chat".to_string(), ids[1], "hello".to_string()),
+            ("score".to_string(), ids[2], "2".to_string()),
+        ], forwarded);
+    }
+
+    #[test]
+    fn test_dropped_receiver_is_a_subscriber_error() {
+        let (sender, receiver) = mpsc::channel();
+        drop(receiver);
+        let event_bus = EventBus::new();
+        event_bus
+            .set_error_policy(ErrorPolicy::Continue)
+            .subscribe_listener("chat", ChannelSubscriber::consuming(sender))
+            .register("chat", "hello".to_string());
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(1, event_bus.metrics().subscriber_errors(&"chat".to_string(), std::any::type_name::<ChannelSubscriber>()));
+    }
+}
@@ -0,0 +1,70 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use crate::{Event, Outcome, Subscriber};
+
+/// # Collecting Subscriber
+///
+/// Collects a clone of the payload of every event holding a `T` into a shared list.
+/// The events holding another type are ignored.
+///
+/// ```
+/// use simple_event_bus::EventBus;
+/// use simple_event_bus::subscribers::CollectingSubscriber;
+///
+/// let (subscriber, scores) = CollectingSubscriber::<u32>::new();
+/// let event_bus = EventBus::new();
+/// event_bus
+///     .subscribe_listener("score", subscriber)
+///     .register("score", 10u32)
+///     .register("score", "not a score")
+///     .register("score", 20u32);
+///
+/// assert_eq!(Ok(()), event_bus.publish());
+/// assert_eq!(vec![10, 20], *scores.borrow());
+/// ```
+pub struct CollectingSubscriber<T> {
+    collected: Rc<RefCell<Vec<T>>>,
+}
+
+impl<T: Clone + 'static> CollectingSubscriber<T> {
+    /// # New
+    ///
+    /// Creates a collecting subscriber, and the list the payloads are collected into.
+    pub fn new() -> (CollectingSubscriber<T>, Rc<RefCell<Vec<T>>>) {
+        let collected = Rc::new(RefCell::new(Vec::new()));
+        (CollectingSubscriber { collected: collected.clone() }, collected)
+    }
+}
+
+impl<T: Clone + 'static> Subscriber for CollectingSubscriber<T> {
+    fn on_event_outcome(&mut self, event: &mut Event) -> Outcome {
+        match event.get_data::<T>() {
+            Some(data) => {
+                self.collected.borrow_mut().push(data.clone());
+                Outcome::Ack
+            }
+            None => Outcome::Ignored,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DeadLetterReason, EventBus};
+    use super::CollectingSubscriber;
+
+    #[test]
+    fn test_other_payload_types_are_unhandled() {
+        let (subscriber, names) = CollectingSubscriber::<String>::new();
+        let event_bus = EventBus::new();
+        event_bus
+            .subscribe_listener("join", subscriber)
+            .register("join", "alice".to_string())
+            .register("join", 7u32);
+
+        let report = event_bus.publish_report().unwrap();
+        assert_eq!(vec!["alice".to_string()], *names.borrow());
+        assert_eq!(1, report.topic(&"join".to_string()).ignored);
+        assert_eq!(DeadLetterReason::Unhandled, event_bus.dead_letters()[0].reason);
+    }
+}
@@ -0,0 +1,98 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use crate::{Event, Subscriber};
+
+/// # Invocation Counts
+///
+/// The number of times a `CountingSubscriber` was called in each phase.
+/// Clones share the same counts, so the handle returned by `CountingSubscriber::new`
+/// keeps counting after the subscriber is handed to the event bus.
+///
+/// ## Methods
+///
+/// * `before` - Returns the number of calls before the event.
+///
+/// * `event` - Returns the number of events received.
+///
+/// * `after` - Returns the number of calls after the event.
+#[derive(Debug, Clone, Default)]
+pub struct InvocationCounts {
+    counts: Rc<[Cell<usize>; 3]>,
+}
+
+impl InvocationCounts {
+    /// # Before
+    ///
+    /// Returns the number of times `on_before` was called.
+    pub fn before(&self) -> usize {
+        self.counts[0].get()
+    }
+
+    /// # Event
+    ///
+    /// Returns the number of times `on_event` was called.
+    pub fn event(&self) -> usize {
+        self.counts[1].get()
+    }
+
+    /// # After
+    ///
+    /// Returns the number of times `on_after` was called.
+    pub fn after(&self) -> usize {
+        self.counts[2].get()
+    }
+
+    fn increment(&self, phase: usize) {
+        self.counts[phase].set(self.counts[phase].get() + 1);
+    }
+}
+
+/// # Counting Subscriber
+///
+/// Counts the times it is called in each phase, whatever the payload of the event.
+///
+/// ```
+/// use simple_event_bus::EventBus;
+/// use simple_event_bus::subscribers::CountingSubscriber;
+///
+/// let (subscriber, counts) = CountingSubscriber::new();
+/// let event_bus = EventBus::new();
+/// event_bus
+///     .subscribe_listener("tick", subscriber)
+///     .register("tick", 1u32)
+///     .register("tick", "two");
+///
+/// assert_eq!(Ok(()), event_bus.publish());
+/// assert_eq!((2, 2, 2), (counts.before(), counts.event(), counts.after()));
+/// ```
+#[derive(Debug, Default)]
+pub struct CountingSubscriber {
+    counts: InvocationCounts,
+}
+
+impl CountingSubscriber {
+    /// # New
+    ///
+    /// Creates a counting subscriber, and the handle to read its counts.
+    pub fn new() -> (CountingSubscriber, InvocationCounts) {
+        let counts = InvocationCounts::default();
+        (CountingSubscriber { counts: counts.clone() }, counts)
+    }
+}
+
+impl Subscriber for CountingSubscriber {
+    fn on_before(&mut self, _event: &mut Event) -> Result<(), String> {
+        self.counts.increment(0);
+        Ok(())
+    }
+
+    fn on_event(&mut self, _event: &mut Event) -> Result<(), String> {
+        self.counts.increment(1);
+        Ok(())
+    }
+
+    fn on_after(&self, _event: &Event) -> Result<(), String> {
+        self.counts.increment(2);
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use log::Level;
+use crate::{Event, EventContext, Outcome, Subscriber};
+
+/// A formatter of the payload of one type, `None` when the event holds another type.
+type PayloadFormatter = Box<dyn Fn(&Event) -> Option<String>>;
+
+/// Where the lines go, with their level.
+type LineSink = Box<dyn Fn(Level, &str)>;
+
+/// # Logging Subscriber
+///
+/// Logs every event it receives, in all three phases: the payload type name and the event id
+/// before the event, the event name and the age of the event as well when it is delivered,
+/// and the event name again after the event.
+/// The payload is added to the lines of the types that have a formatter.
+///
+/// The lines go to the `log` crate at the level of the subscriber, or to the sink set with `with_sink`.
+///
+/// ## Methods
+///
+/// * `new` - Creates a logging subscriber logging at a level.
+///
+/// * `with_formatter` - Adds a formatter for the payloads of a type.
+///
+/// * `with_sink` - Sends the lines somewhere else than the `log` crate.
+pub struct LoggingSubscriber {
+    level: Level,
+    formatters: HashMap<&'static str, PayloadFormatter>,
+    sink: LineSink,
+    /// The event name of the event being delivered, for the line after the event.
+    topic: String,
+}
+
+impl LoggingSubscriber {
+    /// # New
+    ///
+    /// Creates a logging subscriber that logs every event at the level.
+    pub fn new(level: Level) -> LoggingSubscriber {
+        LoggingSubscriber {
+            level,
+            formatters: HashMap::new(),
+            sink: Box::new(|level, line| log::log!(level, "{}", line)),
+            topic: String::new(),
+        }
+    }
+
+    /// # With Formatter
+    ///
+    /// Adds the payload of the events holding a `T` to the lines, formatted by the formatter.
+    /// The payloads of types without a formatter are left out.
+    pub fn with_formatter<T: 'static>(mut self, formatter: impl Fn(&T) -> String + 'static) -> LoggingSubscriber {
+        let formatter = move |event: &Event| event.get_data::<T>().map(&formatter);
+        self.formatters.insert(std::any::type_name::<T>(), Box::new(formatter));
+        self
+    }
+
+    /// # With Sink
+    ///
+    /// Hands the lines and the level to the sink, instead of the `log` crate.
+    pub fn with_sink(mut self, sink: impl Fn(Level, &str) + 'static) -> LoggingSubscriber {
+        self.sink = Box::new(sink);
+        self
+    }
+
+    fn log(&self, event: &Event, line: String) {
+        match self.formatters.get(event.payload_type_name()).and_then(|formatter| formatter(event)) {
+            Some(payload) => (self.sink)(self.level, &format!("{}: {}", line, payload)),
+            None => (self.sink)(self.level, &line),
+        }
+    }
+}
+
+impl Subscriber for LoggingSubscriber {
+    fn on_before(&mut self, event: &mut Event) -> Result<(), String> {
+        self.log(event, format!("before {} #{}", event.payload_type_name(), event.id()));
+        Ok(())
+    }
+
+    fn on_event_with_context(&mut self, event: &mut Event, context: &mut EventContext) -> Outcome {
+        let age = event.registered_at().map_or(Duration::ZERO, |registered_at| context.now().saturating_duration_since(registered_at));
+        self.topic = context.topic().to_string();
+        self.log(event, format!("event {} {} #{} age {:?}", self.topic, event.payload_type_name(), event.id(), age));
+        Outcome::Ack
+    }
+
+    fn on_after(&self, event: &Event) -> Result<(), String> {
+        self.log(event, format!("after {} {} #{}", self.topic, event.payload_type_name(), event.id()));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+    use log::Level;
+    use crate::{Event, EventBus};
+    use crate::testing::ManualClock;
+    use super::LoggingSubscriber;
+
+    #[test]
+    fn test_logging_subscriber_lines() {
+        let clock = ManualClock::new();
+        let lines = Rc::new(RefCell::new(Vec::new()));
+        let sink = lines.clone();
+        let logger = LoggingSubscriber::new(Level::Debug)
+            .with_formatter(|position: &(i32, i32)| format!("({}, {})", position.0, position.1))
+            .with_sink(move |level, line| sink.borrow_mut().push(format!("{} {}", level, line)));
+        let (position, count) = (Event::new((3, 4)), Event::new(7u32));
+        let ids = [position.id(), count.id()];
+        let event_bus = EventBus::new();
+        event_bus
+            .set_clock(clock.clone())
+            .subscribe_listener("moved", logger)
+            .register("moved", position)
+            .register("moved", count);
+        clock.advance(Duration::from_millis(5));
+
+        assert_eq!(Ok(()), event_bus.publish());
+        assert_eq!(vec![
+            format!("DEBUG before (i32, i32) #{}: (3, 4)", ids[0]),
+            format!("DEBUG event moved (i32, i32) #{} age 5ms: (3, 4)", ids[0]),
+            format!("DEBUG after moved (i32, i32) #{}: (3, 4)", ids[0]),
+            format!("DEBUG before u32 #{}", ids[1]),
+            format!("DEBUG event moved u32 #{} age 5ms", ids[1]),
+            format!("DEBUG after moved u32 #{}", ids[1]),
+        ], *lines.borrow());
+    }
+}
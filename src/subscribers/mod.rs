@@ -0,0 +1,15 @@
+//! Subscribers that ship with the event bus.
+
+mod aggregator;
+mod channel;
+mod collecting;
+mod counting;
+#[cfg(feature = "log")]
+mod logging;
+
+pub use aggregator::Aggregator;
+pub use channel::{ChannelSubscriber, ForwardedEvent};
+pub use collecting::CollectingSubscriber;
+pub use counting::{CountingSubscriber, InvocationCounts};
+#[cfg(feature = "log")]
+pub use logging::LoggingSubscriber;
@@ -0,0 +1,49 @@
+//! Utilities to test code that uses an event bus.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use crate::{Clock, Instant};
+
+pub use crate::core::{SimulatedExecutor, TaskId};
+
+/// # Manual Clock
+///
+/// A clock that only moves when it is advanced.
+/// Clones share the same time, so a test can keep a clone
+/// to advance the clock that was handed to the event bus.
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now: Arc<Mutex<Instant>>,
+}
+
+impl ManualClock {
+    /// # New
+    ///
+    /// Creates a manual clock starting at the current time.
+    pub fn new() -> ManualClock {
+        ManualClock { now: Arc::new(Mutex::new(Instant::now())) }
+    }
+
+    /// # Advance
+    ///
+    /// Moves the clock forward.
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        ManualClock::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+    }
+}
@@ -0,0 +1,55 @@
+//! A plugin subscriber, compiled to a shared library by `tests/plugin.rs`.
+//! It accepts `u32` payloads holding an even number and fails on the others.
+
+use std::ffi::{c_char, c_void, CStr};
+
+#[repr(C)]
+pub struct PluginVTable {
+    abi_version: u32,
+    name: *const c_char,
+    topic: *const c_char,
+    create: extern "C" fn() -> *mut c_void,
+    destroy: extern "C" fn(instance: *mut c_void),
+    on_event: extern "C" fn(instance: *mut c_void, payload_type: *const c_char, payload: *const u8, len: usize) -> i32,
+}
+
+// SAFETY: the vtable is never written to.
+unsafe impl Sync for PluginVTable {}
+
+/// The number of events an instance received.
+struct Counter(u32);
+
+extern "C" fn create() -> *mut c_void {
+    Box::into_raw(Box::new(Counter(0))) as *mut c_void
+}
+
+extern "C" fn destroy(instance: *mut c_void) {
+    drop(unsafe { Box::from_raw(instance as *mut Counter) });
+}
+
+extern "C" fn on_event(instance: *mut c_void, payload_type: *const c_char, payload: *const u8, len: usize) -> i32 {
+    let counter = unsafe { &mut *(instance as *mut Counter) };
+    counter.0 += 1;
+    if unsafe { CStr::from_ptr(payload_type) }.to_bytes() != b"u32" || len != 4 {
+        return 1;
+    }
+    let bytes = unsafe { std::slice::from_raw_parts(payload, len) };
+    match u32::from_be_bytes(bytes.try_into().unwrap()) % 2 {
+        0 => 0,
+        _ => 2,
+    }
+}
+
+static VTABLE: PluginVTable = PluginVTable {
+    abi_version: 1,
+    name: c"even numbers".as_ptr(),
+    topic: c"numbers".as_ptr(),
+    create,
+    destroy,
+    on_event,
+};
+
+#[no_mangle]
+pub extern "C" fn plugin_create() -> *const PluginVTable {
+    &VTABLE
+}
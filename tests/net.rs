@@ -0,0 +1,97 @@
+#![cfg(feature = "net")]
+
+use std::cell::RefCell;
+use std::net::{TcpListener, TcpStream};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use simple_event_bus::{DeadLetterReason, Event, EventBus, PayloadError, RawPayload, RemoteSource, Subscriber};
+
+struct CollectingSubscriber {
+    received: Rc<RefCell<Vec<u32>>>,
+}
+
+impl Subscriber for CollectingSubscriber {
+    fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+        let value = event.get_data::<u32>().ok_or("Expected u32")?;
+        self.received.borrow_mut().push(*value);
+        Ok(())
+    }
+}
+
+fn register_u32(event_bus: &EventBus) {
+    event_bus.register_payload::<u32>(
+        "u32",
+        |value| value.to_be_bytes().to_vec(),
+        |bytes| bytes.try_into().map(u32::from_be_bytes).map_err(|_| "Expected 4 bytes".to_string()),
+    );
+}
+
+fn connect() -> (TcpStream, TcpListener) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    (stream, listener)
+}
+
+fn publish_until(event_bus: &EventBus, done: impl Fn(&EventBus) -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !done(event_bus) {
+        assert!(Instant::now() < deadline, "Timed out waiting for remote events");
+        event_bus.publish().unwrap();
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn test_remote_events_arrive_in_order() {
+    let (stream, listener) = connect();
+
+    let sender = EventBus::new();
+    register_u32(&sender);
+    sender.export_topics(&["metrics.*"], stream);
+
+    let received = Rc::new(RefCell::new(Vec::new()));
+    let receiver = EventBus::new();
+    register_u32(&receiver);
+    receiver
+        .attach_remote_source(RemoteSource::try_from(listener).unwrap())
+        .subscribe_listener("metrics.cpu", CollectingSubscriber { received: received.clone() });
+
+    for value in 0..300u32 {
+        sender
+            .register("metrics.cpu", Event::new(value))
+            .register("logs", Event::new(value));
+        if value % 50 == 0 {
+            sender.publish().unwrap();
+        }
+    }
+    sender.publish().unwrap();
+
+    let expected: Vec<u32> = (0..300).collect();
+    publish_until(&receiver, |_| received.borrow().len() >= expected.len());
+    assert_eq!(expected, *received.borrow());
+}
+
+#[test]
+fn test_unknown_remote_payload_is_dead_lettered() {
+    let (stream, listener) = connect();
+
+    let sender = EventBus::new();
+    register_u32(&sender);
+    sender
+        .export_topics(&["metrics.cpu"], stream)
+        .register("metrics.cpu", Event::new(7u32))
+        .publish()
+        .unwrap();
+
+    let receiver = EventBus::new();
+    receiver.attach_remote_source(RemoteSource::try_from(listener).unwrap());
+    publish_until(&receiver, |event_bus| !event_bus.dead_letters().is_empty());
+
+    let dead_letter = &receiver.dead_letters()[0];
+    assert_eq!("metrics.cpu", dead_letter.topic);
+    assert_eq!(DeadLetterReason::Payload(PayloadError::Unknown { name: "u32".to_string() }), dead_letter.reason);
+    assert_eq!(
+        Some(&RawPayload { name: "u32".to_string(), bytes: 7u32.to_be_bytes().to_vec() }),
+        dead_letter.event.get_data::<RawPayload>()
+    );
+}
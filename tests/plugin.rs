@@ -0,0 +1,43 @@
+#![cfg(feature = "plugins")]
+
+use std::path::PathBuf;
+use std::process::Command;
+use simple_event_bus::{EventBus, PluginError};
+
+/// Compiles the plugin fixture to a shared library, and returns its path.
+fn build_fixture() -> PathBuf {
+    let out_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    let library = out_dir.join(format!("{}plugin_fixture{}", std::env::consts::DLL_PREFIX, std::env::consts::DLL_SUFFIX));
+    let status = Command::new(std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string()))
+        .args(["--crate-type", "cdylib", "--edition", "2021", "--crate-name", "plugin_fixture", "-o"])
+        .arg(&library)
+        .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/plugin.rs"))
+        .status()
+        .expect("rustc should run");
+    assert!(status.success());
+    library
+}
+
+#[test]
+fn test_plugin_receives_encoded_events() {
+    let event_bus = EventBus::new();
+    event_bus.register_payload::<u32>("u32", |value| value.to_be_bytes().to_vec(), |_| Err("Not decoded".to_string()));
+    let plugin = event_bus.load_plugin(build_fixture()).unwrap();
+    assert_eq!(("even numbers", "numbers"), (plugin.name(), plugin.topic().as_str()));
+
+    event_bus.register("numbers", 4u32);
+    assert_eq!(Ok(()), event_bus.publish());
+    event_bus.register("numbers", 5u32);
+    assert_eq!(Err("Plugin even numbers failed with 2".to_string()), event_bus.publish());
+
+    event_bus.unload_plugin(plugin);
+    event_bus.register("numbers", 7u32);
+    assert_eq!(Ok(()), event_bus.publish());
+}
+
+#[test]
+fn test_missing_library_is_rejected() {
+    let event_bus = EventBus::new();
+    let missing = event_bus.load_plugin("/nonexistent/libplugin.so");
+    assert!(matches!(missing, Err(PluginError::Load(_))));
+}
@@ -0,0 +1,63 @@
+#![cfg(feature = "uds")]
+
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use simple_event_bus::{Event, EventBus, RunOptions, Subscriber, UdsPublisher};
+
+struct SharedCollector {
+    received: Arc<Mutex<Vec<u32>>>,
+}
+
+impl Subscriber for SharedCollector {
+    fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+        let value = event.get_data::<u32>().ok_or("Expected u32")?;
+        self.received.lock().unwrap().push(*value);
+        Ok(())
+    }
+}
+
+fn register_u32(event_bus: &EventBus) {
+    event_bus.register_payload::<u32>(
+        "u32",
+        |value| value.to_be_bytes().to_vec(),
+        |bytes| bytes.try_into().map(u32::from_be_bytes).map_err(|_| "Expected 4 bytes".to_string()),
+    );
+}
+
+#[test]
+fn test_run_loop_shutdown_loses_no_events() {
+    let path = std::env::temp_dir().join(format!("simple-event-bus-{}-run-loop.sock", std::process::id()));
+    let options = RunOptions { tick: Duration::from_millis(2), ..RunOptions::default() };
+    let shutdown = options.shutdown.clone();
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let (listening, listens) = mpsc::channel();
+
+    let collected = received.clone();
+    let socket = path.clone();
+    let service = std::thread::spawn(move || {
+        let event_bus = EventBus::new();
+        register_u32(&event_bus);
+        event_bus
+            .listen_uds(&socket)
+            .unwrap()
+            .subscribe_listener("jobs", SharedCollector { received: collected });
+        listening.send(()).unwrap();
+        event_bus.run_loop(options)
+    });
+
+    listens.recv().unwrap();
+    let sender = EventBus::new();
+    register_u32(&sender);
+    let mut publisher = UdsPublisher::connect(&path).unwrap();
+    for value in 0..500u32 {
+        publisher.send("jobs", &Event::new(value), &sender.payloads()).unwrap();
+    }
+    shutdown.trigger();
+
+    let summary = service.join().unwrap();
+    assert_eq!((0..500).collect::<Vec<u32>>(), *received.lock().unwrap());
+    assert_eq!(500, summary.handled);
+    assert_eq!(0, summary.errors);
+    assert!(!path.exists());
+}
@@ -0,0 +1,42 @@
+#![cfg(all(feature = "signals", unix))]
+
+use std::cell::RefCell;
+use std::process::Command;
+use std::rc::Rc;
+use std::time::Duration;
+use simple_event_bus::{Event, EventBus, RunOptions, Signal, Subscriber};
+
+struct CollectingSubscriber {
+    received: Rc<RefCell<Vec<u32>>>,
+}
+
+impl Subscriber for CollectingSubscriber {
+    fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+        let value = event.get_data::<u32>().ok_or("Expected u32")?;
+        self.received.borrow_mut().push(*value);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_sigterm_stops_the_loop_after_draining() {
+    let options = RunOptions { tick: Duration::from_millis(1), ..RunOptions::default() }
+        .stop_on_signals(&[Signal::Interrupt, Signal::Terminate])
+        .unwrap();
+    let received = Rc::new(RefCell::new(Vec::new()));
+    let event_bus = EventBus::new();
+    event_bus
+        .subscribe_listener("jobs", CollectingSubscriber { received: received.clone() })
+        .subscribe_listener("stop", <dyn Subscriber>::builder().on_event(|_| {
+            let status = Command::new("kill").args(["-TERM", &std::process::id().to_string()]).status();
+            status.map(|_| ()).map_err(|e| e.to_string())
+        }).build())
+        .register("stop", ())
+        .register("jobs", 0u32)
+        .register("jobs", 1u32);
+
+    let summary = event_bus.run_loop(options);
+    assert_eq!(Some(Signal::Terminate), summary.signal);
+    assert_eq!(vec![0, 1], *received.borrow());
+    assert_eq!(0, summary.errors);
+}
@@ -0,0 +1,112 @@
+#![cfg(feature = "uds")]
+
+use std::cell::RefCell;
+use std::io::Write;
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use simple_event_bus::{BusLogger, Event, EventBus, Subscriber, UdsPublisher};
+
+struct CollectingSubscriber {
+    received: Rc<RefCell<Vec<u32>>>,
+}
+
+impl Subscriber for CollectingSubscriber {
+    fn on_event(&mut self, event: &mut Event) -> Result<(), String> {
+        let value = event.get_data::<u32>().ok_or("Expected u32")?;
+        self.received.borrow_mut().push(*value);
+        Ok(())
+    }
+}
+
+struct RemoteErrors {
+    errors: Rc<RefCell<Vec<String>>>,
+}
+
+impl BusLogger for RemoteErrors {
+    fn remote_error(&self, message: &str) {
+        self.errors.borrow_mut().push(message.to_string());
+    }
+}
+
+fn register_u32(event_bus: &EventBus) {
+    event_bus.register_payload::<u32>(
+        "u32",
+        |value| value.to_be_bytes().to_vec(),
+        |bytes| bytes.try_into().map(u32::from_be_bytes).map_err(|_| "Expected 4 bytes".to_string()),
+    );
+}
+
+fn socket_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("simple-event-bus-{}-{}.sock", std::process::id(), name))
+}
+
+fn publish_until(event_bus: &EventBus, done: impl Fn(&EventBus) -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !done(event_bus) {
+        assert!(Instant::now() < deadline, "Timed out waiting for remote events");
+        event_bus.publish().unwrap();
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn test_uds_events_arrive_in_order() {
+    let path = socket_path("order");
+    let received = Rc::new(RefCell::new(Vec::new()));
+    let receiver = EventBus::new();
+    register_u32(&receiver);
+    receiver
+        .listen_uds(&path)
+        .unwrap()
+        .subscribe_listener("metrics.cpu", CollectingSubscriber { received: received.clone() });
+
+    let sender = EventBus::new();
+    register_u32(&sender);
+    let mut publisher = UdsPublisher::connect(&path).unwrap();
+    for value in 0..300u32 {
+        publisher.send("metrics.cpu", &Event::new(value), &sender.payloads()).unwrap();
+        if value % 50 == 0 {
+            receiver.publish().unwrap();
+        }
+    }
+    assert!(publisher.send("metrics.cpu", &Event::new("text"), &sender.payloads()).is_err());
+
+    let expected: Vec<u32> = (0..300).collect();
+    publish_until(&receiver, |_| received.borrow().len() >= expected.len());
+    assert_eq!(expected, *received.borrow());
+
+    drop(receiver);
+    assert!(!path.exists());
+    assert!(UdsPublisher::connect(&path).is_err());
+}
+
+#[test]
+fn test_uds_disconnect_mid_frame_is_logged() {
+    let path = socket_path("disconnect");
+    let errors = Rc::new(RefCell::new(Vec::new()));
+    let receiver = EventBus::new();
+    receiver
+        .set_logger(RemoteErrors { errors: errors.clone() })
+        .listen_uds(&path)
+        .unwrap();
+
+    let mut stream = UnixStream::connect(&path).unwrap();
+    stream.write_all(&[0, 0, 0, 20, 0, 3]).unwrap();
+    drop(stream);
+
+    publish_until(&receiver, |_| !errors.borrow().is_empty());
+    assert_eq!(vec!["Remote source connection closed in the middle of a frame"], *errors.borrow());
+}
+
+#[test]
+fn test_uds_bind_errors_are_returned() {
+    let path = socket_path("missing-dir").join("bus.sock");
+    assert!(EventBus::new().listen_uds(&path).is_err());
+
+    let path = socket_path("in-use");
+    let first = EventBus::new();
+    first.listen_uds(&path).unwrap();
+    assert!(EventBus::new().listen_uds(&path).is_err());
+}